@@ -12,6 +12,9 @@ pub struct Cli {
     verbose: bool,
     #[arg(short, long, global = true)]
     quiet: bool,
+    /// Serve @http requests from the response cache only; fail on a miss
+    #[arg(long, global = true)]
+    offline: bool,
     #[command(subcommand)]
     command: Commands,
 }
@@ -22,6 +25,8 @@ enum Commands {
     Add(cmd::add::AddArgs),
     /// Benchmark project
     Bench(cmd::bench::BenchArgs),
+    /// Build project, recompiling only what changed
+    Build(cmd::build::BuildArgs),
     /// Bundle project files
     Bundle(cmd::bundle::BundleArgs),
     /// Manage cache
@@ -56,6 +61,8 @@ enum Commands {
     Lint(cmd::lint::LintArgs),
     /// Optimize project or files
     Optimize(cmd::optimizer::OptimizeArgs),
+    /// List and describe operators
+    Ops(cmd::ops::OpsArgs),
     /// Publish project
     Publish(cmd::publish::PublishArgs),
     /// Remove files or entries
@@ -72,6 +79,8 @@ enum Commands {
     Sign(cmd::sign::SignArgs),
     /// Test project
     Test(cmd::test::TestArgs),
+    /// Upgrade compiled .hlxb files to the current format
+    Upgrade(cmd::upgrade::UpgradeArgs),
     /// Validate project or files
     Validate(cmd::validate::ValidateArgs),
     /// Watch files for changes
@@ -83,9 +92,13 @@ enum Commands {
 #[tokio::main]
 async fn main() {
     let cli = Cli::parse();
+    if cli.offline {
+        std::env::set_var(ops::http::OFFLINE_ENV, "1");
+    }
     let result = match cli.command {
         Commands::Add(args) => cmd::add::run(args).await,
         Commands::Bench(args) => cmd::bench::run(args),
+        Commands::Build(args) => cmd::build::run(args),
         Commands::Bundle(args) => cmd::bundle::run(args),
         Commands::Cache(args) => cmd::cache::run(args),
         Commands::Clean(args) => cmd::clean::run(args).await,
@@ -103,6 +116,7 @@ async fn main() {
         Commands::Init(args) => cmd::init::run(cmd::init::InitInstallArgs::Init(args)),
         Commands::Lint(args) => cmd::lint::run(args).await,
         Commands::Optimize(args) => cmd::optimizer::run(args),
+        Commands::Ops(args) => cmd::ops::run(args).await,
         Commands::Publish(args) => cmd::publish::run(args),
         Commands::Remove(args) => cmd::rm::run(args).await,
         Commands::Reset(args) => cmd::reset::run(args).await,
@@ -111,6 +125,7 @@ async fn main() {
         Commands::Serve(args) => cmd::serve::run(args),
         Commands::Sign(args) => cmd::sign::run(args),
         Commands::Test(args) => cmd::test::run(args).await,
+        Commands::Upgrade(args) => cmd::upgrade::run(args),
         Commands::Validate(args) => cmd::validate::run(args),
         Commands::Watch(args) => cmd::watch::run(args),
         Commands::Workflow(args) => cmd::workflow::run(args),
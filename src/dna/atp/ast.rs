@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use crate::atp::types::Value;
 use crate::atp::types::{TimeUnit, Duration, SecretRef};
+use crate::atp::quantity::{Dimension, Quantity};
 #[derive(Debug, Clone)]
 pub struct SectionDecl {
     pub name: String,
@@ -158,6 +159,7 @@ pub enum Expression {
     Bool(bool),
     Null,
     Duration(Duration),
+    Quantity(Quantity),
     Array(Vec<Expression>),
     Object(HashMap<String, Expression>),
     Variable(String),
@@ -294,7 +296,7 @@ impl AstPrettyPrinter {
         result
     }
     fn print_agent(&mut self, agent: &AgentDecl) -> String {
-        let mut result = format!("{}agent \"{}\" {{\n", self.write_indent(), agent.name);
+        let mut result = format!("{}agent {} {{\n", self.write_indent(), quote(&agent.name));
         self.indent += 1;
         let mut keys: Vec<_> = agent.properties.keys().collect();
         keys.sort();
@@ -312,7 +314,7 @@ impl AstPrettyPrinter {
             result.push_str(&format!("{}capabilities [\n", self.write_indent()));
             self.indent += 1;
             for cap in capabilities {
-                result.push_str(&format!("{}\"{}\"\n", self.write_indent(), cap));
+                result.push_str(&format!("{}{}\n", self.write_indent(), quote(cap)));
             }
             self.indent -= 1;
             result.push_str(&format!("{}]\n", self.write_indent()));
@@ -321,7 +323,7 @@ impl AstPrettyPrinter {
             result.push_str(&format!("{}backstory {{\n", self.write_indent()));
             self.indent += 1;
             for line in &backstory.lines {
-                result.push_str(&format!("{}{}\n", self.write_indent(), line));
+                result.push_str(&format!("{}{}\n", self.write_indent(), quote(line)));
             }
             self.indent -= 1;
             result.push_str(&format!("{}}}\n", self.write_indent()));
@@ -332,7 +334,7 @@ impl AstPrettyPrinter {
     }
     fn print_workflow(&mut self, workflow: &WorkflowDecl) -> String {
         let mut result = format!(
-            "{}workflow \"{}\" {{\n", self.write_indent(), workflow.name
+            "{}workflow {} {{\n", self.write_indent(), quote(&workflow.name)
         );
         self.indent += 1;
         if let Some(trigger) = &workflow.trigger {
@@ -350,49 +352,38 @@ impl AstPrettyPrinter {
         if let Some(pipeline) = &workflow.pipeline {
             result.push_str(&self.print_pipeline(pipeline));
         }
-        for (key, value) in &workflow.properties {
-            result
-                .push_str(
-                    &format!(
-                        "{}{} = {}\n", self.write_indent(), key, self
-                        .print_expression(value)
-                    ),
-                );
-        }
+        result.push_str(&self.print_properties(&workflow.properties));
         self.indent -= 1;
         result.push_str(&format!("{}}}\n", self.write_indent()));
         result
     }
     fn print_step(&mut self, step: &StepDecl) -> String {
-        let mut result = format!("{}step \"{}\" {{\n", self.write_indent(), step.name);
+        let mut result = format!("{}step {} {{\n", self.write_indent(), quote(&step.name));
         self.indent += 1;
         if let Some(agent) = &step.agent {
-            result.push_str(&format!("{}agent = \"{}\"\n", self.write_indent(), agent));
+            result.push_str(&format!("{}agent = {}\n", self.write_indent(), quote(agent)));
         }
         if let Some(crew) = &step.crew {
             result.push_str(&format!("{}crew = [", self.write_indent()));
             result
                 .push_str(
-                    &crew
-                        .iter()
-                        .map(|c| format!("\"{}\"", c))
-                        .collect::<Vec<_>>()
-                        .join(", "),
+                    &crew.iter().map(|c| quote(c)).collect::<Vec<_>>().join(", "),
                 );
             result.push_str("]\n");
         }
         if let Some(task) = &step.task {
-            result.push_str(&format!("{}task = \"{}\"\n", self.write_indent(), task));
+            result.push_str(&format!("{}task = {}\n", self.write_indent(), quote(task)));
         }
-        for (key, value) in &step.properties {
-            result
-                .push_str(
-                    &format!(
-                        "{}{} = {}\n", self.write_indent(), key, self
-                        .print_expression(value)
-                    ),
-                );
+        let mut properties = step.properties.clone();
+        // The parser only reads `retry` as a block
+        if let Some(Expression::Object(retry)) = properties.remove("retry") {
+            result.push_str(&format!("{}retry {{\n", self.write_indent()));
+            self.indent += 1;
+            result.push_str(&self.print_properties(&retry));
+            self.indent -= 1;
+            result.push_str(&format!("{}}}\n", self.write_indent()));
         }
+        result.push_str(&self.print_properties(&properties));
         self.indent -= 1;
         result.push_str(&format!("{}}}\n", self.write_indent()));
         result
@@ -454,13 +445,13 @@ impl AstPrettyPrinter {
     }
     fn print_context(&mut self, context: &ContextDecl) -> String {
         let mut result = format!(
-            "{}context \"{}\" {{\n", self.write_indent(), context.name
+            "{}context {} {{\n", self.write_indent(), quote(&context.name)
         );
         self.indent += 1;
         result
             .push_str(
                 &format!(
-                    "{}environment = \"{}\"\n", self.write_indent(), context.environment
+                    "{}environment = {}\n", self.write_indent(), quote(&context.environment)
                 ),
             );
         if let Some(secrets) = &context.secrets {
@@ -516,30 +507,22 @@ impl AstPrettyPrinter {
         result
     }
     fn print_crew(&mut self, crew: &CrewDecl) -> String {
-        let mut result = format!("{}crew \"{}\" {{\n", self.write_indent(), crew.name);
+        let mut result = format!("{}crew {} {{\n", self.write_indent(), quote(&crew.name));
         self.indent += 1;
         result.push_str(&format!("{}agents [\n", self.write_indent()));
         self.indent += 1;
         for agent in &crew.agents {
-            result.push_str(&format!("{}\"{}\"\n", self.write_indent(), agent));
+            result.push_str(&format!("{}{}\n", self.write_indent(), quote(agent)));
         }
         self.indent -= 1;
         result.push_str(&format!("{}]\n", self.write_indent()));
         if let Some(process_type) = &crew.process_type {
             result
                 .push_str(
-                    &format!("{}process = \"{}\"\n", self.write_indent(), process_type),
-                );
-        }
-        for (key, value) in &crew.properties {
-            result
-                .push_str(
-                    &format!(
-                        "{}{} = {}\n", self.write_indent(), key, self
-                        .print_expression(value)
-                    ),
+                    &format!("{}process = {}\n", self.write_indent(), quote(process_type)),
                 );
         }
+        result.push_str(&self.print_properties(&crew.properties));
         self.indent -= 1;
         result.push_str(&format!("{}}}\n", self.write_indent()));
         result
@@ -689,7 +672,12 @@ impl AstPrettyPrinter {
         result
     }
     fn print_section(&mut self, section: &SectionDecl) -> String {
-        let mut result = format!("{}section {} {{\n", self.write_indent(), section.name);
+        // `database main { }` parses as the section `database.main`
+        let header = match section.name.split_once('.') {
+            Some((kind, name)) => format!("{} {}", kind, quote(name)),
+            None => section.name.clone(),
+        };
+        let mut result = format!("{}{} {{\n", self.write_indent(), header);
         self.indent += 1;
         let mut keys: Vec<_> = section.properties.keys().collect();
         keys.sort();
@@ -724,16 +712,28 @@ impl AstPrettyPrinter {
         result.push_str(&format!("{}}}\n", self.write_indent()));
         result
     }
+    fn print_properties(&mut self, properties: &HashMap<String, Expression>) -> String {
+        let mut keys: Vec<_> = properties.keys().collect();
+        keys.sort();
+        keys.into_iter()
+            .map(|key| {
+                format!(
+                    "{}{} = {}\n", self.write_indent(), key, self
+                    .print_expression(&properties[key])
+                )
+            })
+            .collect()
+    }
     fn print_secret_ref(&mut self, secret_ref: &SecretRef) -> String {
         match secret_ref {
             SecretRef::Environment(var) => format!("${}", var),
-            SecretRef::Vault(path) => format!("vault:\"{}\"", path),
-            SecretRef::File(path) => format!("file:\"{}\"", path),
+            SecretRef::Vault(path) => quote(&format!("vault:{}", path)),
+            SecretRef::File(path) => quote(&format!("file:{}", path)),
         }
     }
     fn print_expression(&mut self, expr: &Expression) -> String {
         match expr {
-            Expression::String(s) => format!("\"{}\"", s),
+            Expression::String(s) => quote(s),
             Expression::Number(n) => format!("{}", n),
             Expression::Bool(b) => format!("{}", b),
             Expression::Duration(d) => {
@@ -743,6 +743,7 @@ impl AstPrettyPrinter {
                     "d", }
                 )
             }
+            Expression::Quantity(q) => q.to_string(),
             Expression::Variable(v) => format!("${}", v),
             Expression::Reference(r) => format!("@{}", r),
             Expression::IndexedReference(file, key) => format!("@{}[{}]", file, key),
@@ -789,10 +790,101 @@ impl AstPrettyPrinter {
         }
     }
 }
+/// A string literal the lexer reads back unchanged
+fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for ch in s.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            _ => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
 impl Expression {
     pub fn binary(left: Expression, op: BinaryOperator, right: Expression) -> Self {
         Expression::BinaryOp(Box::new(left), op, Box::new(right))
     }
+    /// Like `binary`, but evaluates `+ - * /` straight away when both sides
+    /// are numbers, durations or quantities, checking that units agree
+    pub fn arithmetic(
+        left: Expression,
+        op: BinaryOperator,
+        right: Expression,
+    ) -> Result<Self, String> {
+        enum Operand {
+            Number(f64),
+            Quantity(Quantity),
+        }
+        let operand = |expr: &Expression| match expr {
+            Expression::Number(n) => Some(Operand::Number(*n)),
+            Expression::Quantity(q) => Some(Operand::Quantity(*q)),
+            Expression::Duration(d) => Some(Operand::Quantity(Quantity::from(d))),
+            _ => None,
+        };
+        let arithmetic = matches!(
+            op, BinaryOperator::Add | BinaryOperator::Sub | BinaryOperator::Mul |
+            BinaryOperator::Div
+        );
+        let (l, r) = match (operand(&left), operand(&right)) {
+            (Some(l), Some(r)) if arithmetic => (l, r),
+            _ => return Ok(Expression::binary(left, op, right)),
+        };
+        let result = match (l, op, r) {
+            (Operand::Number(a), BinaryOperator::Add, Operand::Number(b)) => {
+                Expression::Number(a + b)
+            }
+            (Operand::Number(a), BinaryOperator::Sub, Operand::Number(b)) => {
+                Expression::Number(a - b)
+            }
+            (Operand::Number(a), BinaryOperator::Mul, Operand::Number(b)) => {
+                Expression::Number(a * b)
+            }
+            (Operand::Number(a), BinaryOperator::Div, Operand::Number(b)) => {
+                if b == 0.0 {
+                    return Err(format!("Cannot divide {} by zero", a));
+                }
+                Expression::Number(a / b)
+            }
+            (Operand::Quantity(a), BinaryOperator::Add, Operand::Quantity(b)) => {
+                Expression::Quantity(a.add(&b)?)
+            }
+            (Operand::Quantity(a), BinaryOperator::Sub, Operand::Quantity(b)) => {
+                Expression::Quantity(a.sub(&b)?)
+            }
+            (Operand::Quantity(a), BinaryOperator::Mul, Operand::Number(b))
+            | (Operand::Number(b), BinaryOperator::Mul, Operand::Quantity(a)) => {
+                Expression::Quantity(a.scale(b))
+            }
+            (Operand::Quantity(a), BinaryOperator::Div, Operand::Number(b)) => {
+                Expression::Quantity(a.divide(b)?)
+            }
+            (Operand::Quantity(a), BinaryOperator::Div, Operand::Quantity(b)) => {
+                Expression::Number(a.ratio(&b)?)
+            }
+            (Operand::Quantity(a), _, Operand::Number(b)) => {
+                return Err(
+                    format!("Cannot combine {} with {}: {} has no unit", a, b, b),
+                );
+            }
+            (Operand::Number(a), _, Operand::Quantity(b)) => {
+                return Err(
+                    format!("Cannot combine {} with {}: {} has no unit", a, b, a),
+                );
+            }
+            (Operand::Quantity(a), _, Operand::Quantity(b)) => {
+                return Err(format!("Cannot multiply {} by {}", a, b));
+            }
+            _ => unreachable!("only arithmetic operators reach here"),
+        };
+        Ok(result)
+    }
     pub fn as_string(&self) -> Option<String> {
         match self {
             Expression::String(s) => Some(s.clone()),
@@ -803,6 +895,9 @@ impl Expression {
     pub fn as_number(&self) -> Option<f64> {
         match self {
             Expression::Number(n) => Some(*n),
+            Expression::Quantity(q) if q.dimension() == Dimension::Count => {
+                Some(q.base_value())
+            }
             _ => None,
         }
     }
@@ -831,6 +926,7 @@ impl Expression {
             Expression::Bool(b) => Value::Bool(*b),
             Expression::Null => Value::Null,
             Expression::Duration(d) => Value::Duration(d.clone()),
+            Expression::Quantity(q) => Value::Quantity(*q),
             Expression::Array(arr) => {
                 Value::Array(arr.iter().map(|e| e.to_value()).collect())
             }
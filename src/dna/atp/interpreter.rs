@@ -3,30 +3,46 @@ use crate::hel::error::HlxError;
 use crate::ops::engine::OperatorEngine;
 use crate::ops::OperatorParser;
 use std::collections::HashMap;
+use std::path::PathBuf;
 pub struct HelixInterpreter {
     operator_engine: OperatorEngine,
     ops_parser: OperatorParser,
     variables: HashMap<String, Value>,
+    /// Directory of the config being run; relative plugin sources and
+    /// `@file` paths resolve against it
+    base_dir: Option<PathBuf>,
 }
 pub use crate::atp::types::Value;
 pub use crate::atp::ast::{BinaryOperator,LoadDecl};
 impl HelixInterpreter {
     pub async fn new() -> Result<Self, HlxError> {
         let operator_engine = OperatorEngine::new().await?;
-        let ops_parser = match OperatorParser::new().await {
+        let mut ops_parser = match OperatorParser::new().await {
             Ok(parser) => parser,
             Err(e) => return Err(HlxError::execution_error(
                 format!("Failed to create operator parser: {}", e),
                 "Check operator configuration"
             )),
         };
+        if let Some(engine) = ops_parser.operator_engine_mut() {
+            engine.share_registries(&operator_engine);
+        }
         Ok(Self {
             operator_engine,
             ops_parser,
             variables: HashMap::new(),
+            base_dir: None,
         })
     }
+    /// Directory that relative plugin sources and `@file` paths resolve against
+    pub fn set_base_dir(&mut self, dir: Option<PathBuf>) {
+        if let Some(engine) = self.ops_parser.operator_engine() {
+            engine.file_operators().set_base(dir.clone());
+        }
+        self.base_dir = dir;
+    }
     pub async fn execute_ast(&mut self, ast: &HelixAst) -> Result<Value, HlxError> {
+        self.load_runtime_declarations(ast)?;
         let mut result = Value::String("".to_string());
         for declaration in &ast.declarations {
             match declaration {
@@ -45,6 +61,30 @@ impl HelixInterpreter {
         }
         Ok(result)
     }
+    /// Apply `project` settings, register `database` blocks and load
+    /// `plugin` blocks before any section is evaluated, so their operators
+    /// are available everywhere.
+    fn load_runtime_declarations(&mut self, ast: &HelixAst) -> Result<(), HlxError> {
+        let mut runtime = HelixAst::new();
+        for declaration in &ast.declarations {
+            if matches!(
+                declaration,
+                Declaration::Project(_) | Declaration::Plugin(_) | Declaration::Database(_)
+            ) {
+                runtime.add_declaration(declaration.clone());
+            }
+        }
+        if runtime.declarations.is_empty() {
+            return Ok(());
+        }
+        let config = crate::atp::types::HelixLoader::new()
+            .ast_to_config(runtime)
+            .map_err(|e| HlxError::execution_error(
+                format!("Invalid project, plugin or database declaration: {}", e),
+                "Check the project, plugin and database blocks",
+            ))?;
+        self.operator_engine.apply_config(&config, self.base_dir.as_deref())
+    }
     async fn execute_section(
         &mut self,
         section: &crate::atp::ast::SectionDecl,
@@ -67,6 +107,7 @@ impl HelixInterpreter {
                                 Value::Duration(d) => {
                                     Value::String(format!("{} {:?}", d.value, d.unit))
                                 }
+                                Value::Quantity(q) => Value::Quantity(q),
                                 Value::Reference(r) => Value::String(r),
                                 Value::Array(_) => {
                                     Value::String(
@@ -97,6 +138,7 @@ impl HelixInterpreter {
                                     Value::Duration(d) => {
                                         Value::String(format!("{} {:?}", d.value, d.unit))
                                     }
+                                    Value::Quantity(q) => Value::Quantity(q),
                                     Value::Reference(r) => Value::String(r),
                                     Value::Array(_) => {
                                         Value::String(
@@ -118,6 +160,7 @@ impl HelixInterpreter {
                 Value::Duration(d) => {
                     Value::String(format!("{} {:?}", d.value, d.unit))
                 }
+                Value::Quantity(q) => Value::Quantity(q),
                 Value::Reference(r) => Value::String(r),
                 Value::Null => Value::Null,
                 Value::Identifier(i) => Value::String(i),
@@ -185,6 +228,7 @@ impl HelixInterpreter {
             Expression::Duration(d) => {
                 Ok(Value::String(format!("{} {:?}", d.value, d.unit)))
             }
+            Expression::Quantity(q) => Ok(Value::Quantity(*q)),
             Expression::Array(arr) => {
                 let mut values = Vec::new();
                 for item in arr {
@@ -331,6 +375,7 @@ impl HelixInterpreter {
             }
             Value::Null => serde_json::Value::Null,
             Value::Duration(d) => serde_json::Value::String(format!("{} {:?}", d.value, d.unit)),
+            Value::Quantity(q) => serde_json::Value::String(q.to_string()),
             Value::Reference(r) => serde_json::Value::String(format!("@{}", r)),
             Value::Identifier(i) => serde_json::Value::String(i.clone()),
         }
@@ -444,8 +489,88 @@ impl HelixInterpreter {
             }
             crate::atp::value::Value::Null => Value::Null,
             crate::atp::value::Value::Duration(d) => Value::Duration(d),
+            crate::atp::value::Value::Quantity(q) => Value::Quantity(q),
             crate::atp::value::Value::Reference(r) => Value::Reference(r),
             crate::atp::value::Value::Identifier(i) => Value::Identifier(i),
         }
     }
 }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::atp::ast::{AstBuilder, DatabaseDecl, PluginDecl};
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_declared_database_is_readable_from_operator_calls() {
+        let dir = TempDir::new().unwrap();
+        let ast = AstBuilder::new()
+            .add_database(DatabaseDecl {
+                name: "cache".to_string(),
+                path: Some(dir.path().join("cache").to_string_lossy().to_string()),
+                shards: None,
+                compression: None,
+                cache_size: None,
+                vector_index: None,
+                properties: HashMap::new(),
+            })
+            .build();
+        let mut interpreter = HelixInterpreter::new().await.unwrap();
+        interpreter.execute_ast(&ast).await.unwrap();
+
+        interpreter
+            .operator_engine()
+            .execute_operator("db.put", r#"{"db": "cache", "key": "step", "value": 7}"#)
+            .await
+            .unwrap();
+        let mut params = HashMap::new();
+        params.insert("db".to_string(), Expression::String("cache".to_string()));
+        params.insert("key".to_string(), Expression::String("step".to_string()));
+        let call = Expression::AtOperatorCall("db.get".to_string(), params);
+        let value = interpreter.evaluate_expression(&call).await.unwrap();
+        assert!(matches!(value, Value::Number(n) if n == 7.0), "got {:?}", value);
+    }
+
+    #[cfg(feature = "plugins")]
+    #[tokio::test]
+    async fn test_relative_plugin_source_resolves_against_base_dir() {
+        let dir = TempDir::new().unwrap();
+        let wasm = wat::parse_str(
+            r#"(module
+                (memory (export "memory") 1)
+                (func (export "hlx_alloc") (param i32) (result i32) (i32.const 1024))
+                (func (export "hlx_op_echo") (param $ptr i32) (param $len i32) (result i64)
+                  (i64.or
+                    (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+                    (i64.extend_i32_u (local.get $len)))))"#,
+        )
+        .unwrap();
+        std::fs::create_dir(dir.path().join("plugins")).unwrap();
+        std::fs::write(dir.path().join("plugins").join("echo.wasm"), wasm).unwrap();
+        let ast = AstBuilder::new()
+            .add_plugin(PluginDecl {
+                name: "echo".to_string(),
+                source: "plugins/echo.wasm".to_string(),
+                version: None,
+                config: HashMap::new(),
+            })
+            .build();
+
+        let mut interpreter = HelixInterpreter::new().await.unwrap();
+        interpreter.set_base_dir(Some(dir.path().to_path_buf()));
+        interpreter.execute_ast(&ast).await.unwrap();
+        assert!(interpreter.operator_engine().plugins().is_loaded("echo"));
+    }
+
+    #[tokio::test]
+    async fn test_project_http_allow_reaches_operator_calls() {
+        let source = "project \"demo\" {\n    http_allow = [\"registry.example.com\"]\n}\n";
+        let ast = crate::parse(source).unwrap();
+        let mut interpreter = HelixInterpreter::new().await.unwrap();
+        interpreter.execute_ast(&ast).await.unwrap();
+
+        let engine = interpreter.ops_parser.operator_engine().unwrap();
+        let policy = engine.http_operators().policy();
+        assert_eq!(policy.allow, vec!["registry.example.com".to_string()]);
+    }
+}
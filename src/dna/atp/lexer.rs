@@ -1,16 +1,20 @@
 use std::str::Chars;
 use std::iter::Peekable;
 use std::fmt;
+use crate::atp::quantity::Unit;
 #[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     String(String),
     Number(f64),
     Bool(bool),
     Duration(u64, TimeUnit),
+    Quantity(f64, Unit),
     Identifier(String),
     Keyword(Keyword),
     Assign,
     Plus,
+    Star,
+    Slash,
     Arrow,
     Tilde,
     Pipe,
@@ -306,24 +310,62 @@ impl<'a> Lexer<'a> {
             _ => None,
         }
     }
-    fn read_duration(&mut self, num: f64) -> Option<Token> {
-        let mut unit_str = String::new();
-        while let Some(ch) = self.current_char {
-            if ch.is_alphabetic() {
-                unit_str.push(ch);
-                self.advance();
-            } else {
+    /// The unit suffix at the cursor, without consuming it. A suffix must end
+    /// the word, so `3d_model` has none.
+    fn peek_unit_suffix(&self) -> String {
+        let mut suffix = String::new();
+        match self.current_char {
+            Some(ch) if ch.is_alphabetic() => suffix.push(ch),
+            _ => return suffix,
+        }
+        let mut rest = self.input.clone();
+        while let Some(&ch) = rest.peek() {
+            if !ch.is_alphabetic() {
                 break;
             }
+            suffix.push(ch);
+            rest.next();
         }
+        match rest.peek() {
+            Some(&ch) if ch.is_alphanumeric() || ch == '_' => String::new(),
+            _ => suffix,
+        }
+    }
+    fn consume_suffix(&mut self, suffix: &str) {
+        for _ in suffix.chars() {
+            self.advance();
+        }
+    }
+    fn read_duration(&mut self, num: f64) -> Option<Token> {
+        if num < 0.0 || num.fract() != 0.0 {
+            return None;
+        }
+        let unit_str = self.peek_unit_suffix();
         let unit = match unit_str.as_str() {
             "s" | "sec" | "seconds" => Some(TimeUnit::Seconds),
             "m" | "min" | "minutes" => Some(TimeUnit::Minutes),
             "h" | "hr" | "hours" => Some(TimeUnit::Hours),
             "d" | "days" => Some(TimeUnit::Days),
             _ => None,
-        };
-        if let Some(u) = unit { Some(Token::Duration(num as u64, u)) } else { None }
+        }?;
+        self.consume_suffix(&unit_str);
+        Some(Token::Duration(num as u64, unit))
+    }
+    /// Whole durations stay `Duration` tokens; any other unit written right
+    /// after the number (`16GiB`, `1.5h`, `500ms`) makes a quantity
+    fn read_number_with_unit(&mut self, num: f64) -> Token {
+        if let Some(duration) = self.read_duration(num) {
+            return duration;
+        }
+        let suffix = self.peek_unit_suffix();
+        if let Some(unit) = Unit::from_symbol(&suffix) {
+            self.consume_suffix(&suffix);
+            return Token::Quantity(num, unit);
+        }
+        while let Some(' ') | Some('\t') = self.current_char {
+            self.advance();
+        }
+        self.read_duration(num).unwrap_or(Token::Number(num))
     }
     pub fn next_token_with_location(&mut self) -> TokenWithLocation {
         self.skip_whitespace();
@@ -424,7 +466,7 @@ impl<'a> Lexer<'a> {
                     if let Some(ch) = self.current_char {
                         if ch.is_numeric() {
                             let num = -self.read_number();
-                            Token::Number(num)
+                            self.read_number_with_unit(num)
                         } else {
                             let mut ident = String::from("-");
                             ident.push_str(&self.read_identifier());
@@ -451,6 +493,14 @@ impl<'a> Lexer<'a> {
                 self.advance();
                 Token::Plus
             }
+            Some('*') => {
+                self.advance();
+                Token::Star
+            }
+            Some('/') => {
+                self.advance();
+                Token::Slash
+            }
             Some('!') => {
                 let mut var_name = String::new();
                 self.advance();
@@ -471,14 +521,7 @@ impl<'a> Lexer<'a> {
             }
             Some(ch) if ch.is_numeric() => {
                 let num = self.read_number();
-                while let Some(' ') | Some('\t') = self.current_char {
-                    self.advance();
-                }
-                if let Some(duration_token) = self.read_duration(num) {
-                    duration_token
-                } else {
-                    Token::Number(num)
-                }
+                self.read_number_with_unit(num)
             }
             Some(ch) if ch.is_alphabetic() || ch == '_' => {
                 let ident = self.read_identifier();
@@ -606,6 +649,18 @@ mod tests {
         assert_eq!(tokens[2], Token::Duration(30, TimeUnit::Minutes));
     }
     #[test]
+    fn test_quantities() {
+        let tokens = tokenize("16GiB 1.5h 8k 500ms 30 GiB 3d_model").unwrap();
+        assert_eq!(tokens[0], Token::Quantity(16.0, Unit::GiB));
+        assert_eq!(tokens[1], Token::Quantity(1.5, Unit::Hours));
+        assert_eq!(tokens[2], Token::Quantity(8.0, Unit::Thousand));
+        assert_eq!(tokens[3], Token::Quantity(500.0, Unit::Milliseconds));
+        assert_eq!(tokens[4], Token::Number(30.0));
+        assert_eq!(tokens[5], Token::Identifier("GiB".to_string()));
+        assert_eq!(tokens[6], Token::Number(3.0));
+        assert_eq!(tokens[7], Token::Identifier("d_model".to_string()));
+    }
+    #[test]
     fn test_section_keyword() {
         let input = "section test { }";
         let tokens = tokenize(input).unwrap();
@@ -2,6 +2,7 @@ pub mod ops;
 pub mod parser;
 pub mod lexer;
 pub mod types;
+pub mod quantity;
 pub mod ast;
 pub mod value;
 pub mod verify;
@@ -14,6 +14,9 @@ use std::path::{Path, PathBuf};
 use std::str::Chars;
 use thiserror::Error;
 pub use crate::ops::engine::OperatorEngine;
+use crate::ops::functions::{self, FunctionDef, FunctionRegistry, ImportDecl};
+use crate::ops::hermetic::Hermetic;
+use std::sync::Arc;
 
 // Add BoxFuture type alias
 type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
@@ -154,8 +157,11 @@ impl RegexCache {
             range: Regex::new(r"^(\d+)-(\d+)$").unwrap(),
             get: Regex::new(r#"^@([a-zA-Z0-9_-]+)\.hlx\.get\(["']([^"']+)["']\)$"#).unwrap(),
             set: Regex::new(r#"^@([a-zA-Z0-9_-]+)\.hlx\.set\(["']([^"']+)["'],\s*(.+)\)$"#).unwrap(),
-            query: Regex::new(r#"^@query\(["']([^"']+)["']\)$"#).unwrap(),
-            operator: Regex::new(r"^@([a-zA-Z_][a-zA-Z0-9_]*)\((.*)\)$").unwrap(),
+            query: Regex::new(
+                r#"^@query\(\s*(?:"([^"]*)"|'([^']*)')\s*(?:,\s*(.+?))?\s*\)$"#,
+            )
+            .unwrap(),
+            operator: Regex::new(r"^@([a-zA-Z_][a-zA-Z0-9_]*(?:\.[a-zA-Z_][a-zA-Z0-9_]*)*)\((.*)\)$").unwrap(),
             ternary: Regex::new(r"(.+?)\s*\?\s*(.+?)\s*:\s*(.+)").unwrap(),
             section: Regex::new(r"^\[([a-zA-Z_][a-zA-Z0-9_]*)\]$").unwrap(),
             angle_start: Regex::new(r"^([a-zA-Z_][a-zA-Z0-9_]*)\s*>$").unwrap(),
@@ -513,7 +519,7 @@ mod parser_components {
                 let var = cap.get(1).unwrap().as_str();
                 let def = cap.get(2).map_or("", |m| m.as_str());
                 return Some(Value::String(
-                    env::var(var).unwrap_or_else(|_| def.to_string())
+                    self.parser.env_var(var).unwrap_or_else(|| def.to_string())
                 ));
             }
             None
@@ -678,7 +684,6 @@ pub struct OperatorParser {
     data: HashMap<String, Value>,
     global_variables: HashMap<VariableName, Value>,
     section_variables: HashMap<String, Value>,
-    cache: HashMap<String, Value>,
     cross_file_cache: HashMap<CacheKey, Value>,
     current_section: Option<SectionName>,
     in_object: bool,
@@ -689,6 +694,7 @@ pub struct OperatorParser {
     errors: Vec<ParseError>,  // Collect errors for recovery
     current_line: usize,
     current_column: usize,
+    current_file: Option<PathBuf>,
 }
 
 /* ---------- helper directories ---------- */
@@ -780,7 +786,6 @@ impl OperatorParser {
             data: HashMap::new(),
             global_variables: HashMap::new(),
             section_variables: HashMap::new(),
-            cache: HashMap::new(),
             cross_file_cache: HashMap::new(),
             current_section: None,
             in_object: false,
@@ -802,9 +807,23 @@ impl OperatorParser {
             errors: Vec::new(),
             current_line: 0,
             current_column: 0,
+            current_file: None,
         })
     }
 
+    /// Parser whose operators evaluate reproducibly (see `ops::hermetic`).
+    /// Only project-relative `dna.hlx` files are loaded, never the user's.
+    pub async fn new_hermetic(hermetic: Hermetic) -> Result<Self> {
+        let mut parser = Self::new().await?;
+        parser.hlx_locations.retain(|loc| loc.starts_with("./") || loc.starts_with("../"));
+        parser.operator_engine = Some(
+            OperatorEngine::new_hermetic(hermetic)
+                .await
+                .map_err(|e| ParseError::OperatorError(e.to_string()))?,
+        );
+        Ok(parser)
+    }
+
     /* ---------- configuration loading ---------- */
     pub async fn load_hlx(&mut self) -> Result<()> {
         if self.hlx_loaded {
@@ -895,7 +914,7 @@ impl OperatorParser {
             if let Some(cap) = REGEX_CACHE.env.captures(v) {
                 let var = cap.get(1).unwrap().as_str();
                 let def = cap.get(2).map_or("", |m| m.as_str());
-                return Ok(Value::String(env::var(var).unwrap_or_else(|_| def.to_string())));
+                return Ok(Value::String(self.env_var(var).unwrap_or_else(|| def.to_string())));
             }
 
             // Continue with the rest of the function...
@@ -928,9 +947,8 @@ impl OperatorParser {
 
             // Query
             if let Some(cap) = REGEX_CACHE.query.captures(v) {
-                return Ok(Value::String(
-                    self.execute_query(cap.get(1).unwrap().as_str()).await
-                ));
+                let sql = cap.get(1).or_else(|| cap.get(2)).unwrap().as_str();
+                return self.execute_query(sql, cap.get(3).map(|m| m.as_str())).await;
             }
 
             // Operator
@@ -1150,8 +1168,19 @@ impl OperatorParser {
     }
 
     /* ---------- built-in functions ---------- */
+    /// Environment variable, read from the hermetic snapshot when the engine has one
+    fn env_var(&self, name: &str) -> Option<String> {
+        match self.operator_engine.as_ref().and_then(|e| e.hermetic()) {
+            Some(hermetic) => hermetic.env_var(name).map(String::from),
+            None => env::var(name).ok(),
+        }
+    }
+
     fn execute_date(&self, fmt: &str) -> String {
-        let now: DateTime<Utc> = Utc::now();
+        let now: DateTime<Utc> = match self.operator_engine.as_ref().and_then(|e| e.hermetic()) {
+            Some(hermetic) => hermetic.now(),
+            None => Utc::now(),
+        };
         match fmt {
             "Y" => now.format("%Y").to_string(),
             "Y-m-d" => now.format("%Y-%m-%d").to_string(),
@@ -1161,23 +1190,58 @@ impl OperatorParser {
         }
     }
 
-    async fn execute_query(&mut self, q: &str) -> String {
+    /// Run `@query` SQL against the SQLite file of a declared database.
+    /// `name:SQL` selects a database, otherwise `database.default` (or
+    /// `default`) is used; `params` is a JSON array or object of bindings.
+    async fn execute_query(&mut self, q: &str, params: Option<&str>) -> Result<Value> {
         let _ = self.load_hlx().await;
-        let db_type = self
-            .get("database.default")
-            .map(|v| v.to_string())
-            .unwrap_or_else(|| "sqlite".to_string());
-
-        if q.contains(':') {
-            let parts: Vec<&str> = q.splitn(2, ':').collect();
-            format!("[Cross-DB Query: {} on {}]", parts[1], parts[0])
-        } else if q.to_lowercase().contains("insert") && q.contains('{') {
-            format!("[Auto-Schema Insert: {} on {}]", q, db_type)
-        } else if q.to_lowercase().contains("sync:") {
-            format!("[Sync Operation: {} on {}]", q, db_type)
+        let (prefix, sql) = crate::ops::query::split_database_prefix(q);
+        let db_name = match prefix {
+            Some(name) => name.to_string(),
+            None => self
+                .get("database.default")
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "default".to_string()),
+        };
+
+        let target = if let Some(path) = self.get(&format!("database.{}.path", db_name)) {
+            crate::ops::query::SqlTarget {
+                path: PathBuf::from(path.to_string()),
+                writable: matches!(
+                    self.get(&format!("database.{}.writable", db_name)),
+                    Some(Value::Bool(true))
+                ),
+            }
         } else {
-            format!("[Query: {} on {}]", q, db_type)
+            match &self.operator_engine {
+                Some(engine) => engine.sql_operators().target(&db_name),
+                None => crate::ops::query::SqlOperators::new()
+                    .await
+                    .and_then(|ops| ops.target(&db_name)),
+            }
+            .map_err(|e| ParseError::OperatorError(e.to_string()))?
+        };
+
+        let bindings = match params {
+            Some(raw) => {
+                let json: serde_json::Value = serde_json::from_str(raw)?;
+                crate::ops::utils::json_to_value(&json)
+            }
+            None => Value::Null,
+        };
+        let run = || crate::ops::query::run_query(&target, sql, &bindings);
+        let cache = self.operator_engine.as_ref().and_then(|engine| engine.eval_cache());
+        match cache {
+            // Statements against a writable database may change it; only
+            // read-only queries are memoized
+            Some(cache) if !target.writable => {
+                let inputs = vec![target.path.canonicalize().unwrap_or_else(|_| target.path.clone())];
+                let args = format!("{}:{} {}", db_name, sql, params.unwrap_or(""));
+                cache.get_or_insert_with("query", args.trim_end(), &inputs, run)
+            }
+            _ => run(),
         }
+        .map_err(|e| ParseError::OperatorError(e.to_string()))
     }
 
     async fn execute_operator(
@@ -1185,6 +1249,14 @@ impl OperatorParser {
         operator: &str,
         params: &str,
     ) -> Result<Value> {
+        let scope = self.current_section.as_ref().map(|s| s.as_str().to_string());
+        let function = self
+            .operator_engine
+            .as_ref()
+            .and_then(|engine| engine.functions().lookup(operator, scope.as_deref()));
+        if let Some(def) = function {
+            return self.call_function(def, params).await;
+        }
         match &self.operator_engine {
             Some(engine) => {
                 engine
@@ -1196,6 +1268,44 @@ impl OperatorParser {
         }
     }
 
+    /// Evaluate the arguments in the caller's scope, then the bound body in
+    /// the scope the function was declared in.
+    async fn call_function(&mut self, def: FunctionDef, raw_args: &str) -> Result<Value> {
+        let mut args = Vec::new();
+        for raw in functions::split_args(raw_args) {
+            args.push(self.parse_value(raw).await?);
+        }
+        let registry = Arc::clone(self.functions()?);
+        let body = def
+            .bind(&args)
+            .map_err(|e| ParseError::OperatorError(e.to_string()))?;
+
+        let caller_section = std::mem::replace(
+            &mut self.current_section,
+            def.scope.map(SectionName::new),
+        );
+        let result = registry.enter(&def.name, self.parse_value(&body)).await;
+        self.current_section = caller_section;
+        result.map_err(|e| ParseError::OperatorError(e.to_string()))?
+    }
+
+    fn functions(&self) -> Result<&Arc<FunctionRegistry>> {
+        self.operator_engine
+            .as_ref()
+            .map(|engine| engine.functions())
+            .ok_or_else(|| ParseError::OperatorError(
+                "User functions require the operator engine".to_string(),
+            ))
+    }
+
+    pub fn operator_engine(&self) -> Option<&OperatorEngine> {
+        self.operator_engine.as_ref()
+    }
+
+    pub fn operator_engine_mut(&mut self) -> Option<&mut OperatorEngine> {
+        self.operator_engine.as_mut()
+    }
+
     /* ---------- line parser with error recovery (Improvement #10) ---------- */
     pub async fn parse_line(&mut self, raw: &str) -> Result<()> {
         self.current_column = 0;
@@ -1254,6 +1364,29 @@ impl OperatorParser {
             return Ok(());
         }
 
+        // User-defined function
+        let scope = self.current_section.as_ref().map(|s| s.as_str().to_string());
+        if let Some(def) = FunctionDef::parse(line, scope.as_deref()) {
+            let def = def.map_err(|e| ParseError::OperatorError(e.to_string()))?;
+            return self.functions()?
+                .define(def)
+                .map_err(|e| ParseError::OperatorError(e.to_string()));
+        }
+
+        // Function import
+        if let Some(import) = ImportDecl::parse(line) {
+            let base = self
+                .current_file
+                .as_ref()
+                .and_then(|f| f.parent())
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+            return self.functions()?
+                .import_file(&base.join(&import.path), import.names.as_deref())
+                .map(|_| ())
+                .map_err(|e| ParseError::OperatorError(e.to_string()));
+        }
+
         // Key-value pair handling
         if let Some(cap) = REGEX_CACHE.key_value.captures(line) {
             let key_raw = cap[1].trim();
@@ -1314,6 +1447,7 @@ impl OperatorParser {
                 }
             }
             Expression::Number(n) => Ok(Value::Number(*n)),
+            Expression::Quantity(q) => Ok(Value::Quantity(*q)),
             Expression::Bool(b) => Ok(Value::Bool(*b)),
             Expression::Array(arr) => {
                 // Concurrent evaluation for array elements (Improvement #9)
@@ -1389,6 +1523,7 @@ impl OperatorParser {
             Value::Duration(d) => {
                 serde_json::Value::String(format!("{} {:?}", d.value, d.unit))
             }
+            Value::Quantity(q) => serde_json::Value::String(q.to_string()),
             Value::Reference(r) => serde_json::Value::String(format!("@{}", r)),
             Value::Identifier(i) => serde_json::Value::String(i.clone()),
         }
@@ -1430,8 +1565,17 @@ impl OperatorParser {
     pub fn parse_file<'a>(&'a mut self, path: &'a str) -> BoxFuture<'a, Result<()>> {
         Box::pin(async move {
             let txt = fs::read_to_string(path)?;
-            self.parse(&txt).await?;
-            Ok(())
+            let previous = self.current_file.replace(PathBuf::from(path));
+            let previous_base = self.operator_engine.as_ref().map(|engine| {
+                let dir = Path::new(path).parent().map(Path::to_path_buf);
+                engine.file_operators().set_base(dir)
+            });
+            let result = self.parse(&txt).await;
+            if let (Some(engine), Some(base)) = (&self.operator_engine, previous_base) {
+                engine.file_operators().set_base(base);
+            }
+            self.current_file = previous;
+            result.map(|_| ())
         })
     }
 
@@ -1564,6 +1708,17 @@ mod tests {
         assert_eq!(parser.get("key"), Some(Value::String("value".into())));
     }
 
+    #[tokio::test]
+    async fn test_bare_unit_values_stay_strings() {
+        // Quantity literals come from the lexer; `key = value` lines keep
+        // `5m`-style values as the strings they always were
+        let mut parser = OperatorParser::new().await.unwrap();
+        for raw in ["5m", "8k", "10M", "512MiB"] {
+            parser.parse_line(&format!("key = {}", raw)).await.unwrap();
+            assert_eq!(parser.get("key"), Some(Value::String(raw.into())));
+        }
+    }
+
     #[tokio::test]
     async fn test_parser_sections() {
         let mut parser = OperatorParser::new().await.unwrap();
@@ -1615,6 +1770,61 @@ mod tests {
         assert_eq!(parser.get("key"), Some(Value::String("value".into())));
     }
 
+    #[cfg(feature = "sqlite")]
+    #[tokio::test]
+    async fn test_query_runs_against_declared_database() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("app.db");
+        let writable = crate::ops::query::SqlTarget { path: path.clone(), writable: true };
+        crate::ops::query::run_query(&writable, "CREATE TABLE users (name TEXT, age INTEGER)", &Value::Null).unwrap();
+        crate::ops::query::run_query(&writable, "INSERT INTO users VALUES ('ada', 36)", &Value::Null).unwrap();
+
+        let mut parser = OperatorParser::new().await.unwrap();
+        parser.set("database.app.path", Value::String(path.to_string_lossy().to_string()));
+        let rows = parser
+            .parse_value(r#"@query("app:SELECT name FROM users WHERE age > ?", [30])"#)
+            .await
+            .unwrap();
+        assert_eq!(rows.as_array().unwrap()[0].get("name"), Some(&Value::String("ada".into())));
+        assert!(parser.parse_value(r#"@query("app:DELETE FROM users")"#).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_user_functions() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::write(dir.path().join("lib.hlx"), "fn shout(s) = s + \"!\"\n").unwrap();
+        let main = dir.path().join("main.hlx");
+        fs::write(&main, r#"
+import "lib.hlx"
+fn greet(name) = "Hello " + name
+fn double(n) = n * 2
+
+[site]
+fn greet(name) = "Welcome " + name
+title = @greet("Ada")
+count = @double(21)
+loud = @shout("hey")
+
+[other]
+title = @greet("Ada")
+"#).unwrap();
+
+        let mut parser = OperatorParser::new().await.unwrap();
+        parser.parse_file(main.to_str().unwrap()).await.unwrap();
+        assert_eq!(parser.get("site.title"), Some(Value::String("Welcome Ada".into())));
+        assert_eq!(parser.get("site.count"), Some(Value::Number(42.0)));
+        assert_eq!(parser.get("site.loud"), Some(Value::String("hey!".into())));
+        assert_eq!(parser.get("other.title"), Some(Value::String("Hello Ada".into())));
+    }
+
+    #[tokio::test]
+    async fn test_user_function_recursion_limit() {
+        let mut parser = OperatorParser::new().await.unwrap();
+        parser.parse_line("fn forever(n) = @forever(n)").await.unwrap();
+        let err = parser.parse_value("@forever(1)").await.unwrap_err();
+        assert!(err.to_string().contains("Recursion limit"));
+    }
+
     #[tokio::test]
     async fn test_error_recovery() {
         let mut parser = OperatorParser::new().await.unwrap();
@@ -3,6 +3,7 @@ use std::path::PathBuf;
 use serde::{Serialize, Deserialize};
 use crate::hel::error::HlxError;
 use crate::atp::types::Value;
+use crate::out::parquet_format::ParquetDataWriter;
 use arrow::datatypes::{Schema, Field, DataType};
 use arrow::array::{Array, ArrayRef, StringArray, Float64Array, Int64Array};
 use arrow::record_batch::RecordBatch;
@@ -49,9 +50,14 @@ pub struct OutputConfig {
     pub formats: Vec<OutputFormat>,
     pub compression: CompressionConfig,
     pub batch_size: usize,
+    #[serde(default = "default_row_group_size")]
+    pub row_group_size: usize,
     pub include_preview: bool,
     pub preview_rows: usize,
 }
+fn default_row_group_size() -> usize {
+    1024 * 1024
+}
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CompressionConfig {
     pub enabled: bool,
@@ -80,6 +86,7 @@ impl Default for OutputConfig {
             formats: vec![OutputFormat::Helix, OutputFormat::Jsonl],
             compression: CompressionConfig::default(),
             batch_size: 1000,
+            row_group_size: default_row_group_size(),
             include_preview: true,
             preview_rows: 10,
         }
@@ -143,6 +150,9 @@ impl OutputManager {
         for format in &self.config.formats {
             let writer: Box<dyn DataWriter> = match format {
                 OutputFormat::Hlxc => Box::new(HlxcDataWriter::new(self.config.clone())),
+                OutputFormat::Parquet => {
+                    Box::new(ParquetDataWriter::new(self.config.clone()))
+                }
                 _ => {
                     continue;
                 }
@@ -157,10 +167,8 @@ impl OutputManager {
         batch: RecordBatch,
     ) -> Result<(), HlxError> {
         self.initialize_writers()?;
-        for (format, writer) in &mut self.writers {
-            if *format == OutputFormat::Hlxc {
-                writer.write_batch(batch.clone())?;
-            }
+        for writer in self.writers.values_mut() {
+            writer.write_batch(batch.clone())?;
         }
         Ok(())
     }
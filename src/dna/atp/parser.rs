@@ -3,6 +3,7 @@ use crate::atp::lexer::{
 };
 pub use crate::atp::types::SecretRef;
 use crate::atp::types::Duration;
+use crate::atp::quantity::Quantity;
 use crate::atp::ast::*;
 pub use crate::atp::types::Value;
 use crate::ops::engine::OperatorEngine;
@@ -33,6 +34,8 @@ pub struct Parser {
     recovery_points: Vec<usize>,
     operator_engine: Option<OperatorEngine>,
     runtime_context: HashMap<String, String>,
+    /// Fall back to the process environment for `$VAR` and `@env`
+    host_env: bool,
 }
 #[derive(Debug, Clone)]
 pub struct ParseError {
@@ -149,6 +152,7 @@ impl Parser {
             recovery_points: Vec::new(),
             operator_engine: None,
             runtime_context: HashMap::new(),
+            host_env: true,
         }
     }
     pub fn new_enhanced(tokens: Vec<TokenWithLocation>) -> Self {
@@ -160,6 +164,7 @@ impl Parser {
             recovery_points: Vec::new(),
             operator_engine: None,
             runtime_context: HashMap::new(),
+            host_env: true,
         }
     }
     pub fn new_with_source_map(source_map: SourceMap) -> Self {
@@ -172,6 +177,7 @@ impl Parser {
             recovery_points: Vec::new(),
             operator_engine: None,
             runtime_context: HashMap::new(),
+            host_env: true,
         }
     }
     fn add_error(&mut self, message: String, expected: Option<String>) {
@@ -364,6 +370,9 @@ impl Parser {
         if let Some(v) = self.runtime_context.get(name) {
             return v.clone();
         }
+        if !self.host_env {
+            return name.to_string();
+        }
         std::env::var(name).unwrap_or_else(|_| name.to_string())
     }
     fn expect_identifier_or_string(&mut self) -> Result<(String, bool), String> {
@@ -409,6 +418,11 @@ impl Parser {
     pub fn set_runtime_context(&mut self, context: HashMap<String, String>) {
         self.runtime_context = context;
     }
+    /// Resolve `$VAR` and `@env` only from `env`, never the process environment
+    pub fn set_hermetic_env(&mut self, env: HashMap<String, String>) {
+        self.runtime_context = env;
+        self.host_env = false;
+    }
     pub fn parse(&mut self) -> Result<HelixAst, String> {
         let mut ast = HelixAst::new();
         while self.current_token() != &Token::Eof {
@@ -642,6 +656,12 @@ impl Parser {
                             let timeout_value = self.parse_expression()?;
                             properties.insert("timeout".to_string(), timeout_value);
                         }
+                        Token::Keyword(Keyword::Context) => {
+                            self.advance();
+                            self.expect(Token::Assign)?;
+                            let context = self.parse_expression()?;
+                            properties.insert("context".to_string(), context);
+                        }
                         Token::Identifier(key) => {
                             let key = key.clone();
                             self.advance();
@@ -931,13 +951,19 @@ impl Parser {
                     }
                 }
                 Token::Keyword(keyword) => {
-                    let key = format!("{:?}", keyword).to_lowercase();
+                    let key = match keyword {
+                        Keyword::DependsOn => "depends_on".to_string(),
+                        _ => format!("{:?}", keyword).to_lowercase(),
+                    };
                     self.advance();
                     self.expect(Token::Assign)?;
                     match key.as_str() {
                         "agent" => {
                             agent = self.parse_expression()?.as_string();
                         }
+                        "task" => {
+                            task = self.parse_expression()?.as_string();
+                        }
                         _ => {
                             let value = self.parse_expression()?;
                             properties.insert(key, value);
@@ -1015,10 +1041,11 @@ impl Parser {
                 break;
             }
             match self.current_token() {
-                Token::Plus => {
-                    self.advance();
-                    let right = self.parse_expression_with_precedence(Precedence::Addition)?;
-                    left = Expression::BinaryOp(Box::new(left), BinaryOperator::Add, Box::new(right));
+                Token::Plus | Token::Star | Token::Slash => {
+                    left = self.parse_arithmetic(left, precedence)?;
+                }
+                Token::Identifier(s) if s == "-" => {
+                    left = self.parse_arithmetic(left, precedence)?;
                 }
                 Token::Arrow => {
                     self.advance();
@@ -1050,6 +1077,28 @@ impl Parser {
         }
         Ok(left)
     }
+    /// Parse `<op> right` after `left`. Operands bind tighter than the
+    /// operator, so `a - b - c` groups to the left.
+    fn parse_arithmetic(
+        &mut self,
+        left: Expression,
+        precedence: Precedence,
+    ) -> Result<Expression, String> {
+        let op = match self.current_token() {
+            Token::Plus => BinaryOperator::Add,
+            Token::Star => BinaryOperator::Mul,
+            Token::Slash => BinaryOperator::Div,
+            _ => BinaryOperator::Sub,
+        };
+        self.advance();
+        let next = if precedence == Precedence::Multiplication {
+            Precedence::Unary
+        } else {
+            Precedence::Multiplication
+        };
+        let right = self.parse_expression_with_precedence(next)?;
+        Expression::arithmetic(left, op, right)
+    }
     fn parse_primary_expression(&mut self) -> Result<Expression, String> {
         match self.current_token() {
             Token::String(s) => {
@@ -1086,6 +1135,11 @@ impl Parser {
                 self.advance();
                 Ok(Expression::Duration(duration))
             }
+            Token::Quantity(value, unit) => {
+                let quantity = Quantity::new(*value, *unit);
+                self.advance();
+                Ok(Expression::Quantity(quantity))
+            }
             Token::Variable(v) => {
                 let v = v.clone();
                 self.advance();
@@ -1224,6 +1278,8 @@ impl Parser {
     fn get_token_precedence(&self, token: &Token) -> Precedence {
         match token {
             Token::Plus => Precedence::Addition,
+            Token::Identifier(s) if s == "-" => Precedence::Addition,
+            Token::Star | Token::Slash => Precedence::Multiplication,
             Token::Arrow => Precedence::Pipeline,
             _ => Precedence::Lowest,
         }
@@ -1554,6 +1610,7 @@ impl Parser {
             }
             crate::atp::value::Value::Null => serde_json::Value::Null,
             crate::atp::value::Value::Duration(d) => serde_json::Value::String(format!("{} {:?}", d.value, d.unit)),
+            crate::atp::value::Value::Quantity(q) => serde_json::Value::String(q.to_string()),
             crate::atp::value::Value::Reference(r) => serde_json::Value::String(format!("@{}", r)),
             crate::atp::value::Value::Identifier(i) => serde_json::Value::String(i.clone()),
         }
@@ -1620,7 +1677,9 @@ impl Parser {
                             .runtime_context
                             .get(&var_name)
                             .cloned()
-                            .or_else(|| std::env::var(&var_name).ok())
+                            .or_else(|| {
+                                self.host_env.then(|| std::env::var(&var_name).ok()).flatten()
+                            })
                             .ok_or_else(|| HlxError::validation_error(
                                 &format!("Env var '{}' not set", var_name),
                                 "",
@@ -1859,6 +1918,7 @@ impl Parser {
             }
             crate::atp::value::Value::Null => Value::Null,
             crate::atp::value::Value::Duration(d) => Value::Duration(d),
+            crate::atp::value::Value::Quantity(q) => Value::Quantity(q),
             crate::atp::value::Value::Reference(r) => Value::Reference(r),
             crate::atp::value::Value::Identifier(i) => Value::Identifier(i),
         }
@@ -1877,3 +1937,36 @@ pub fn parse(tokens: Vec<Token>) -> Result<HelixAst, ParseError> {
             context: String::new(),
         })
 }
+#[cfg(test)]
+mod tests {
+    use crate::dna::atp::lexer::{tokenize, tokenize_with_locations};
+    use proptest::prelude::*;
+    /// Fragments of real syntax, so generated input gets past the first
+    /// character more often than random text would
+    fn token_soup() -> impl Strategy<Value = String> {
+        let fragment = proptest::sample::select(vec![
+            "agent", "workflow", "step", "section", "context", "crew", "project", "\"a\"",
+            "\"", "{", "}", "[", "]", "(", ")", "=", ":", ",", ".", "-", "+", "<", ">", "!",
+            "30m", "1.5h", "16GiB", "8k", "0", "-1", "1e309", "$X", "@a.b", "@", "$", "#",
+            "//", "/*", "*/", "\n", " ", "\t", "\\", "é", "\u{0}", "true", "null", "~",
+        ]);
+        proptest::collection::vec(fragment, 0..64).prop_map(|parts: Vec<&str>| parts.concat())
+    }
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(512))]
+        #[test]
+        fn fuzz_lexer_never_panics(input in any::<String>()) {
+            let _ = tokenize(&input);
+            let _ = tokenize_with_locations(&input);
+        }
+        #[test]
+        fn fuzz_lexer_never_panics_on_token_soup(input in token_soup()) {
+            let _ = tokenize(&input);
+            let _ = tokenize_with_locations(&input);
+        }
+        #[test]
+        fn fuzz_parser_never_panics(source in token_soup()) {
+            let _ = crate::parse(&source);
+        }
+    }
+}
@@ -0,0 +1,384 @@
+//! Unit-aware quantities: byte sizes, token counts and durations
+//!
+//! A quantity literal is a number immediately followed by a unit symbol,
+//! such as `16GiB`, `512MB`, `8k` or `1.5h`. Arithmetic is only allowed
+//! between quantities of the same dimension, and the result keeps the unit
+//! of the left operand.
+use std::fmt;
+use std::str::FromStr;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::atp::types::{Duration, TimeUnit};
+
+/// What a unit measures; only quantities of the same dimension mix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dimension {
+    Bytes,
+    Count,
+    Time,
+}
+impl fmt::Display for Dimension {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dimension::Bytes => write!(f, "bytes"),
+            Dimension::Count => write!(f, "count"),
+            Dimension::Time => write!(f, "time"),
+        }
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+pub enum Unit {
+    B,
+    KB,
+    MB,
+    GB,
+    TB,
+    PB,
+    KiB,
+    MiB,
+    GiB,
+    TiB,
+    PiB,
+    /// Thousands, as in `8k` tokens
+    #[serde(rename = "k")]
+    Thousand,
+    /// Millions, as in `2M` tokens
+    #[serde(rename = "M")]
+    Million,
+    #[serde(rename = "ms")]
+    Milliseconds,
+    #[serde(rename = "s")]
+    Seconds,
+    #[serde(rename = "m")]
+    Minutes,
+    #[serde(rename = "h")]
+    Hours,
+    #[serde(rename = "d")]
+    Days,
+}
+impl Unit {
+    pub const ALL: [Unit; 18] = [
+        Unit::B,
+        Unit::KB,
+        Unit::MB,
+        Unit::GB,
+        Unit::TB,
+        Unit::PB,
+        Unit::KiB,
+        Unit::MiB,
+        Unit::GiB,
+        Unit::TiB,
+        Unit::PiB,
+        Unit::Thousand,
+        Unit::Million,
+        Unit::Milliseconds,
+        Unit::Seconds,
+        Unit::Minutes,
+        Unit::Hours,
+        Unit::Days,
+    ];
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Unit::B => "B",
+            Unit::KB => "KB",
+            Unit::MB => "MB",
+            Unit::GB => "GB",
+            Unit::TB => "TB",
+            Unit::PB => "PB",
+            Unit::KiB => "KiB",
+            Unit::MiB => "MiB",
+            Unit::GiB => "GiB",
+            Unit::TiB => "TiB",
+            Unit::PiB => "PiB",
+            Unit::Thousand => "k",
+            Unit::Million => "M",
+            Unit::Milliseconds => "ms",
+            Unit::Seconds => "s",
+            Unit::Minutes => "m",
+            Unit::Hours => "h",
+            Unit::Days => "d",
+        }
+    }
+    /// Symbols are case-sensitive: `m` is minutes and `M` is millions
+    pub fn from_symbol(symbol: &str) -> Option<Unit> {
+        Unit::ALL.iter().copied().find(|unit| unit.symbol() == symbol)
+    }
+    pub fn dimension(&self) -> Dimension {
+        match self {
+            Unit::B
+            | Unit::KB
+            | Unit::MB
+            | Unit::GB
+            | Unit::TB
+            | Unit::PB
+            | Unit::KiB
+            | Unit::MiB
+            | Unit::GiB
+            | Unit::TiB
+            | Unit::PiB => Dimension::Bytes,
+            Unit::Thousand | Unit::Million => Dimension::Count,
+            Unit::Milliseconds | Unit::Seconds | Unit::Minutes | Unit::Hours
+            | Unit::Days => Dimension::Time,
+        }
+    }
+    /// Size of one unit in bytes, items or seconds
+    pub fn factor(&self) -> f64 {
+        match self {
+            Unit::B => 1.0,
+            Unit::KB => 1e3,
+            Unit::MB => 1e6,
+            Unit::GB => 1e9,
+            Unit::TB => 1e12,
+            Unit::PB => 1e15,
+            Unit::KiB => 1024.0,
+            Unit::MiB => 1024.0 * 1024.0,
+            Unit::GiB => 1024.0 * 1024.0 * 1024.0,
+            Unit::TiB => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+            Unit::PiB => 1024.0 * 1024.0 * 1024.0 * 1024.0 * 1024.0,
+            Unit::Thousand => 1e3,
+            Unit::Million => 1e6,
+            Unit::Milliseconds => 1e-3,
+            Unit::Seconds => 1.0,
+            Unit::Minutes => 60.0,
+            Unit::Hours => 3600.0,
+            Unit::Days => 86400.0,
+        }
+    }
+}
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+impl FromStr for Unit {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Unit::from_symbol(s.trim())
+            .ok_or_else(|| {
+                let known: Vec<&str> = Unit::ALL.iter().map(|u| u.symbol()).collect();
+                format!("Unknown unit '{}', expected one of: {}", s, known.join(", "))
+            })
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+pub struct Quantity {
+    pub value: f64,
+    pub unit: Unit,
+}
+impl Quantity {
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Quantity { value, unit }
+    }
+    pub fn dimension(&self) -> Dimension {
+        self.unit.dimension()
+    }
+    /// The value in bytes, items or seconds
+    pub fn base_value(&self) -> f64 {
+        self.value * self.unit.factor()
+    }
+    pub fn convert_to(&self, unit: Unit) -> Result<Quantity, String> {
+        if self.dimension() != unit.dimension() {
+            return Err(
+                format!(
+                    "Cannot convert {} to {}: {} is {} but {} is {}", self, unit, self,
+                    self.dimension(), unit, unit.dimension()
+                ),
+            );
+        }
+        if self.unit == unit {
+            return Ok(*self);
+        }
+        Ok(Quantity::new(self.base_value() / unit.factor(), unit))
+    }
+    pub fn add(&self, other: &Quantity) -> Result<Quantity, String> {
+        let rhs = self.same_dimension("add", other)?;
+        Ok(Quantity::new(self.value + rhs, self.unit))
+    }
+    pub fn sub(&self, other: &Quantity) -> Result<Quantity, String> {
+        let rhs = self.same_dimension("subtract", other)?;
+        Ok(Quantity::new(self.value - rhs, self.unit))
+    }
+    pub fn scale(&self, factor: f64) -> Quantity {
+        Quantity::new(self.value * factor, self.unit)
+    }
+    pub fn divide(&self, divisor: f64) -> Result<Quantity, String> {
+        if divisor == 0.0 {
+            return Err(format!("Cannot divide {} by zero", self));
+        }
+        Ok(Quantity::new(self.value / divisor, self.unit))
+    }
+    /// How many times `other` fits into this quantity
+    pub fn ratio(&self, other: &Quantity) -> Result<f64, String> {
+        let rhs = self.same_dimension("divide", other)?;
+        if rhs == 0.0 {
+            return Err(format!("Cannot divide {} by zero", self));
+        }
+        Ok(self.value / rhs)
+    }
+    /// `other` expressed in this quantity's unit
+    fn same_dimension(&self, verb: &str, other: &Quantity) -> Result<f64, String> {
+        other
+            .convert_to(self.unit)
+            .map(|q| q.value)
+            .map_err(|_| {
+                format!(
+                    "Cannot {} {} and {}: {} is {} but {} is {}", verb, self, other,
+                    self, self.dimension(), other, other.dimension()
+                )
+            })
+    }
+}
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.value, self.unit)
+    }
+}
+impl FromStr for Quantity {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| c.is_alphabetic())
+            .ok_or_else(|| format!("Quantity '{}' has no unit", s))?;
+        let (number, unit) = s.split_at(split);
+        let value: f64 = number
+            .trim()
+            .replace('_', "")
+            .parse()
+            .map_err(|_| format!("Invalid quantity '{}': '{}' is not a number", s, number))?;
+        Ok(Quantity::new(value, unit.parse()?))
+    }
+}
+impl From<&Duration> for Quantity {
+    fn from(duration: &Duration) -> Self {
+        let unit = match duration.unit {
+            TimeUnit::Seconds => Unit::Seconds,
+            TimeUnit::Minutes => Unit::Minutes,
+            TimeUnit::Hours => Unit::Hours,
+            TimeUnit::Days => Unit::Days,
+        };
+        Quantity::new(duration.value as f64, unit)
+    }
+}
+impl Quantity {
+    /// Whole seconds, for the places that still take a `Duration`
+    pub fn to_duration(&self) -> Option<Duration> {
+        if self.dimension() != Dimension::Time || self.value < 0.0 {
+            return None;
+        }
+        Some(Duration {
+            value: self.base_value().round() as u64,
+            unit: TimeUnit::Seconds,
+        })
+    }
+}
+/// Text formats get the literal (`"16GiB"`); binary formats such as `.hlxb`
+/// get the value and unit
+impl Serialize for Quantity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            (self.value, self.unit).serialize(serializer)
+        }
+    }
+}
+impl<'de> Deserialize<'de> for Quantity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let text = String::deserialize(deserializer)?;
+            text.parse().map_err(serde::de::Error::custom)
+        } else {
+            let (value, unit) = <(f64, Unit)>::deserialize(deserializer)?;
+            Ok(Quantity::new(value, unit))
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_parse_and_display() {
+        let q: Quantity = "16GiB".parse().unwrap();
+        assert_eq!(q, Quantity::new(16.0, Unit::GiB));
+        assert_eq!(q.to_string(), "16GiB");
+        assert_eq!("1.5h".parse::<Quantity>().unwrap().to_string(), "1.5h");
+        assert_eq!("8k".parse::<Quantity>().unwrap().unit, Unit::Thousand);
+        assert_eq!("2M".parse::<Quantity>().unwrap().unit, Unit::Million);
+        assert_eq!("5m".parse::<Quantity>().unwrap().unit, Unit::Minutes);
+        assert!("16Gb".parse::<Quantity>().is_err());
+        assert!("GiB".parse::<Quantity>().is_err());
+    }
+    #[test]
+    fn test_conversion() {
+        let q = Quantity::new(16.0, Unit::GiB);
+        assert_eq!(q.convert_to(Unit::MiB).unwrap().value, 16384.0);
+        assert_eq!(q.convert_to(Unit::MB).unwrap().value, 17179.869184);
+        assert_eq!(Quantity::new(1.5, Unit::Hours).convert_to(Unit::Minutes).unwrap().value, 90.0);
+        let err = q.convert_to(Unit::Hours).unwrap_err();
+        assert!(err.contains("16GiB") && err.contains("bytes") && err.contains("time"));
+    }
+    #[test]
+    fn test_arithmetic() {
+        let gib = Quantity::new(1.0, Unit::GiB);
+        let sum = gib.add(&Quantity::new(512.0, Unit::MiB)).unwrap();
+        assert_eq!(sum, Quantity::new(1.5, Unit::GiB));
+        assert_eq!(gib.scale(4.0), Quantity::new(4.0, Unit::GiB));
+        assert_eq!(gib.ratio(&Quantity::new(256.0, Unit::MiB)).unwrap(), 4.0);
+        let err = gib.add(&Quantity::new(2.0, Unit::Hours)).unwrap_err();
+        assert_eq!(err, "Cannot add 1GiB and 2h: 1GiB is bytes but 2h is time");
+        assert!(gib.divide(0.0).is_err());
+    }
+    #[test]
+    fn test_literals_and_arithmetic_in_config() {
+        use crate::atp::ast::{Declaration, Expression};
+        let source = r#"
+            project "demo" {
+                max_memory = 16GiB
+                cache = 512MiB + 1GiB
+                context_tokens = 8k * 4
+                window = 1.5h - 30m
+                shards = 1GiB / 256MiB
+            }
+        "#;
+        let ast = crate::parse(source).unwrap();
+        let project = match &ast.declarations[0] {
+            Declaration::Project(p) => p,
+            other => panic!("expected project, got {:?}", other),
+        };
+        let quantity = |key: &str| match &project.properties[key] {
+            Expression::Quantity(q) => q.to_string(),
+            other => panic!("{} is not a quantity: {:?}", key, other),
+        };
+        assert_eq!(quantity("max_memory"), "16GiB");
+        assert_eq!(quantity("cache"), "1536MiB");
+        assert_eq!(quantity("context_tokens"), "32k");
+        assert_eq!(quantity("window"), "1h");
+        assert_eq!(project.properties["context_tokens"].as_number(), Some(32000.0));
+        assert!(matches!(project.properties["shards"], Expression::Number(n) if n == 4.0));
+
+        let err = crate::parse("project \"demo\" {\n x = 16GiB + 2h\n}").unwrap_err();
+        assert!(err.to_string().contains("Cannot add 16GiB and 2h"), "{}", err);
+    }
+    #[tokio::test]
+    async fn test_convert_operator() {
+        let engine = crate::ops::engine::OperatorEngine::new().await.unwrap();
+        let mb = engine.execute_operator("@convert", r#"16GiB, "MB""#).await.unwrap();
+        assert_eq!(mb, crate::atp::value::Value::Quantity(Quantity::new(17179.869184, Unit::MB)));
+        let minutes = engine.execute_operator("@convert", r#""1.5h", unit = "m""#).await.unwrap();
+        assert_eq!(minutes.to_string(), "90m");
+        let err = engine.execute_operator("@convert", r#"16GiB, "h""#).await.unwrap_err();
+        assert!(err.to_string().contains("Cannot convert 16GiB to h"), "{}", err);
+        let err = engine.execute_operator("@convert", r#"16, "MB""#).await.unwrap_err();
+        assert!(err.to_string().contains("expects quantity, got number"), "{}", err);
+    }
+    #[test]
+    fn test_serde_formats() {
+        let q = Quantity::new(512.0, Unit::MB);
+        assert_eq!(serde_json::to_string(&q).unwrap(), "\"512MB\"");
+        assert_eq!(serde_json::from_str::<Quantity>("\"512MB\"").unwrap(), q);
+        let bytes = bincode::serialize(&q).unwrap();
+        assert_eq!(bincode::deserialize::<Quantity>(&bytes).unwrap(), q);
+    }
+}
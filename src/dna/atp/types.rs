@@ -3,6 +3,7 @@ use std::fs;
 use std::path::Path;
 use serde::{Deserialize, Serialize};
 use crate::atp::ast::*;
+use crate::atp::quantity::Quantity;
 use crate::atp::value::Value as AstValue;
 
 fn extract_string_value(
@@ -42,6 +43,11 @@ fn extract_duration_value(
                 unit: duration.unit.clone(),
             })
         }
+        Some(Expression::Quantity(quantity)) => {
+            quantity
+                .to_duration()
+                .ok_or_else(|| format!("Expected a duration, found {}", quantity))
+        }
         _ => {
             Ok(Duration {
                 value: 0,
@@ -89,6 +95,7 @@ fn extract_map_values(
     }
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct HelixConfig {
     pub projects: HashMap<String, ProjectConfig>,
     pub agents: HashMap<String, AgentConfig>,
@@ -118,6 +125,7 @@ impl Default for HelixConfig {
     }
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct ProjectConfig {
     pub name: String,
     pub version: String,
@@ -126,6 +134,7 @@ pub struct ProjectConfig {
     pub metadata: HashMap<String, Value>,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct AgentConfig {
     pub name: String,
     pub model: String,
@@ -138,6 +147,7 @@ pub struct AgentConfig {
     pub constraints: Vec<String>,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct WorkflowConfig {
     pub name: String,
     pub trigger: TriggerConfig,
@@ -147,6 +157,7 @@ pub struct WorkflowConfig {
     pub on_error: Option<String>,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct StepConfig {
     pub name: String,
     pub agent: Option<String>,
@@ -158,6 +169,7 @@ pub struct StepConfig {
     pub retry: Option<RetryConfig>,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct MemoryConfig {
     pub provider: String,
     pub connection: String,
@@ -166,12 +178,14 @@ pub struct MemoryConfig {
     pub persistence: bool,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct EmbeddingConfig {
     pub model: String,
     pub dimensions: u32,
     pub batch_size: Option<u32>,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct ContextConfig {
     pub name: String,
     pub environment: String,
@@ -181,6 +195,7 @@ pub struct ContextConfig {
     pub variables: HashMap<String, Value>,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct CrewConfig {
     pub name: String,
     pub agents: Vec<String>,
@@ -190,6 +205,7 @@ pub struct CrewConfig {
     pub verbose: bool,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct PluginConfig {
     pub name: String,
     pub source: String,
@@ -197,6 +213,7 @@ pub struct PluginConfig {
     pub config: HashMap<String, Value>,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct DatabaseConfig {
     pub name: String,
     pub path: Option<String>,
@@ -207,6 +224,7 @@ pub struct DatabaseConfig {
     pub properties: HashMap<String, Value>,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct VectorIndexConfig {
     pub index_type: String,
     pub dimensions: i64,
@@ -215,14 +233,38 @@ pub struct VectorIndexConfig {
     pub distance_metric: Option<String>,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
+#[cfg_attr(
+    feature = "rkyv",
+    rkyv(
+        serialize_bounds(
+            __S: rkyv::ser::Writer + rkyv::ser::Allocator,
+            __S::Error: rkyv::rancor::Source,
+        ),
+        deserialize_bounds(__D::Error: rkyv::rancor::Source),
+        bytecheck(bounds(
+            __C: rkyv::validation::ArchiveContext,
+            __C::Error: rkyv::rancor::Source,
+        )),
+    )
+)]
 pub enum Value {
     String(String),
     Number(f64),
     Bool(bool),
     Null,
-    Array(Vec<Value>),
-    Object(HashMap<String, Value>),
+    Array(
+        #[serde(deserialize_with = "crate::dna::hel::limits::nested")]
+        #[cfg_attr(feature = "rkyv", rkyv(omit_bounds))]
+        Vec<Value>,
+    ),
+    Object(
+        #[serde(deserialize_with = "crate::dna::hel::limits::nested")]
+        #[cfg_attr(feature = "rkyv", rkyv(omit_bounds))]
+        HashMap<String, Value>,
+    ),
     Duration(Duration),
+    Quantity(Quantity),
     Reference(String),
     Identifier(String),
 }
@@ -251,11 +293,13 @@ impl Value {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct Duration {
     pub value: u64,
     pub unit: TimeUnit,
 }
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub enum TimeUnit {
     Seconds,
     Minutes,
@@ -263,6 +307,7 @@ pub enum TimeUnit {
     Days,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub enum TriggerConfig {
     Manual,
     Schedule(String),
@@ -271,6 +316,7 @@ pub enum TriggerConfig {
     FileWatch(String),
 }
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub enum ProcessType {
     Sequential,
     Hierarchical,
@@ -278,24 +324,28 @@ pub enum ProcessType {
     Consensus,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct RetryConfig {
     pub max_attempts: u32,
     pub delay: Duration,
     pub backoff: BackoffStrategy,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub enum BackoffStrategy {
     Fixed,
     Linear,
     Exponential,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub struct PipelineConfig {
     pub name: String,
     pub stages: Vec<String>,
     pub flow: String,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "rkyv", derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize))]
 pub enum SecretRef {
     Environment(String),
     Vault(String),
@@ -791,6 +841,7 @@ impl HelixLoader {
     fn expression_to_duration(&self, expr: Expression) -> Option<Duration> {
         match expr {
             Expression::Duration(d) => Some(d),
+            Expression::Quantity(q) => q.to_duration(),
             _ => None,
         }
     }
@@ -1011,6 +1062,7 @@ impl std::fmt::Display for Value {
             }
             Value::Null => write!(f, "null"),
             Value::Duration(d) => write!(f, "{} {:?}", d.value, d.unit),
+            Value::Quantity(q) => write!(f, "{}", q),
             Value::Reference(r) => write!(f, "@{}", r),
             Value::Identifier(i) => write!(f, "{}", i),
         }
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use crate::atp::types::Duration;
+use crate::atp::quantity::Quantity;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ValueType {
     String,
@@ -19,6 +20,7 @@ pub enum Value {
     Object(HashMap<String, Value>),
     Null,
     Duration(Duration),
+    Quantity(Quantity),
     Reference(String),
     Identifier(String),
 }
@@ -40,6 +42,7 @@ impl Value {
             Value::Object(_) => ValueType::Object,
             Value::Null => ValueType::Null,
             Value::Duration(_) => ValueType::String, // Treat as string for now
+            Value::Quantity(_) => ValueType::String,
             Value::Reference(_) => ValueType::String,
             Value::Identifier(_) => ValueType::String,
         }
@@ -74,6 +77,12 @@ impl Value {
             _ => None,
         }
     }
+    pub fn as_quantity(&self) -> Option<Quantity> {
+        match self {
+            Value::Quantity(q) => Some(*q),
+            _ => None,
+        }
+    }
     pub fn as_f64(&self) -> Option<f64> {
         self.as_number()
     }
@@ -143,6 +152,7 @@ impl Value {
             }
             Value::Null => "null".to_string(),
             Value::Duration(d) => format!("{} {:?}", d.value, d.unit),
+            Value::Quantity(q) => q.to_string(),
             Value::Reference(r) => format!("@{}", r),
             Value::Identifier(i) => i.clone(),
         }
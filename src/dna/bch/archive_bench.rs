@@ -0,0 +1,103 @@
+use criterion::*;
+use helix::dna::out::hlxa_format::{self, HlxaFile};
+use helix::dna::out::hlxb_config_format::HlxbConfigHandler;
+use std::io::Cursor;
+use tempfile::TempDir;
+
+const AGENTS: usize = 10_000;
+const WORKFLOWS: usize = 1_000;
+
+fn generate_large_hlx(agents: usize, workflows: usize) -> String {
+    let mut hlx = String::from("project \"large\" { version = \"1.0.0\" }\n\n");
+    for i in 0..agents {
+        hlx.push_str(
+            &format!(
+                r#"
+agent "agent_{}" {{
+    model = "gpt-4"
+    role = "Agent {}"
+    temperature = 0.7
+    capabilities ["task-{}", "skill-{}"]
+}}
+"#,
+                i, i, i, i
+            ),
+        );
+    }
+    for i in 0..workflows {
+        hlx.push_str(
+            &format!(
+                r#"
+workflow "workflow_{}" {{
+    trigger = "manual"
+    step "step_{}" {{
+        agent = "agent_{}"
+        task = "Execute task {}"
+    }}
+}}
+"#,
+                i, i, i % agents, i
+            ),
+        );
+    }
+    hlx
+}
+fn benchmark_loading(c: &mut Criterion) {
+    let source = generate_large_hlx(AGENTS, WORKFLOWS);
+    let config = helix::parse_and_validate(&source).expect("benchmark config should parse");
+    assert_eq!(config.agents.len(), AGENTS);
+
+    let mut hlxb = Cursor::new(Vec::new());
+    HlxbConfigHandler::write_helix_config(&config, &mut hlxb).unwrap();
+    let hlxb = hlxb.into_inner();
+    let hlxa = hlxa_format::to_bytes(&config).unwrap();
+    let dir = TempDir::new().unwrap();
+    let path = dir.path().join("large.hlxa");
+    hlxa_format::write_file(&config, &path).unwrap();
+
+    let mut group = c.benchmark_group("load_10k_agents");
+    group.sample_size(10);
+    group
+        .bench_function(
+            "text",
+            |b| b.iter(|| helix::parse_and_validate(black_box(&source)).unwrap()),
+        );
+    group
+        .bench_function(
+            "hlxb",
+            |b| {
+                b.iter(|| {
+                    let mut reader = Cursor::new(black_box(&hlxb));
+                    HlxbConfigHandler::read_helix_config(&mut reader).unwrap()
+                })
+            },
+        );
+    group
+        .bench_function(
+            "hlxa_access",
+            |b| b.iter(|| hlxa_format::access(black_box(&hlxa)).unwrap().agents.len()),
+        );
+    group
+        .bench_function(
+            "hlxa_mmap",
+            // SAFETY: the benchmark file is not modified while it is mapped
+            |b| b.iter(|| unsafe { HlxaFile::open(black_box(&path)) }.unwrap().config().agents.len()),
+        );
+    group.finish();
+
+    // SAFETY: as above
+    let file = unsafe { HlxaFile::open(&path) }.unwrap();
+    let mut group = c.benchmark_group("lookup_agent");
+    group
+        .bench_function(
+            "hlxa_in_place",
+            |b| {
+                b.iter(|| {
+                    file.config().agents.get(black_box("agent_5000")).unwrap().model.len()
+                })
+            },
+        );
+    group.finish();
+}
+criterion_group!(benches, benchmark_loading);
+criterion_main!(benches);
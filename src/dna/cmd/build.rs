@@ -6,7 +6,7 @@ use crate::mds::build;
 
 #[derive(Args)]
 pub struct BuildArgs {
-    /// Input file path (defaults to current directory)
+    /// Input file or directory (defaults to src/main.hlx)
     #[arg(short, long)]
     pub input: Option<PathBuf>,
     #[arg(short, long)]
@@ -15,8 +15,12 @@ pub struct BuildArgs {
     pub optimize: u8,
     #[arg(short, long)]
     pub compress: bool,
+    /// Reuse outputs that are up to date since the last build
     #[arg(long)]
     pub cache: bool,
+    /// Say why each file was rebuilt or left alone
+    #[arg(long)]
+    pub explain: bool,
 }
 
 #[derive(clap::Subcommand)]
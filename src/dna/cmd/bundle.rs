@@ -1,5 +1,6 @@
 use clap::Args;
 use std::path::PathBuf;
+use crate::mds::bundle::{bundle_command, BundleOptions};
 
 #[derive(Args)]
 pub struct BundleArgs {
@@ -20,6 +21,10 @@ pub struct BundleArgs {
     #[arg(short = 'x', long)]
     exclude: Vec<String>,
 
+    /// Workflow, crew or agent to keep when tree shaking (defaults to every workflow)
+    #[arg(short, long)]
+    entry: Vec<String>,
+
     /// Tree shake (defaults to false)
     #[arg(long)]
     tree_shake: bool,
@@ -35,11 +40,15 @@ pub struct BundleArgs {
 pub fn run(args: BundleArgs) -> anyhow::Result<()> {
     let input = args.input.unwrap_or_else(|| PathBuf::from("."));
     let output = args.output.unwrap_or_else(|| PathBuf::from("bundle.hlxb"));
-    let include = args.include;
-    let exclude = args.exclude;
-    let tree_shake = args.tree_shake;
-    let optimize = args.optimize;
-    crate::dna::mds::bundle::bundle_command(input, output, include, exclude, tree_shake, optimize, false)
+    let options = BundleOptions {
+        include: args.include,
+        exclude: args.exclude,
+        entry: args.entry,
+        tree_shake: args.tree_shake,
+        optimize: args.optimize,
+        verbose: false,
+    };
+    bundle_command(input, output, options)
         .map_err(|e| anyhow::anyhow!("Bundle command failed: {}", e))?;
     Ok(())
 }
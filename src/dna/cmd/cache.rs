@@ -1,11 +1,19 @@
 use clap::Args;
+use crate::mds::cache::{self, CacheAction};
 
-#[derive(Args)]
+/// Arguments for the `cache` command.
+#[derive(Args, Debug)]
 pub struct CacheArgs {
-    action: String,
+    /// show, clear, clean (alias: prune) or size
+    #[arg(default_value = "show")]
+    pub action: String,
+
+    /// Enable verbose output
+    #[arg(short, long)]
+    pub verbose: bool,
 }
 
 pub fn run(args: CacheArgs) -> anyhow::Result<()> {
-    println!("Cache command: {}", args.action);
-    Ok(())
-}
\ No newline at end of file
+    let action: CacheAction = args.action.parse()?;
+    cache::manage_cache(action, args.verbose)
+}
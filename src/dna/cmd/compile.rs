@@ -1,6 +1,9 @@
 use clap::Args;
 use std::path::PathBuf;
 use crate::mds::compile::compile_command;
+use crate::ops::hermetic::Hermetic;
+use crate::hel::encryption::{SectionKey, KEY_ENV};
+use crate::mds::serializer::sealable_section;
 
 
 #[derive(Args)]
@@ -25,6 +28,26 @@ pub struct CompileArgs {
 
     #[arg(short, long)]
     quiet: bool,
+
+    /// Reproducible build: clock from SOURCE_DATE_EPOCH, no host environment or paths
+    #[arg(long)]
+    hermetic: bool,
+
+    /// Environment snapshot for --hermetic, as KEY=VALUE (repeatable)
+    #[arg(long = "env", value_name = "KEY=VALUE", requires = "hermetic")]
+    env: Vec<String>,
+
+    /// Encrypt contexts and declarations marked `sensitive = true` with the key from --key-file or HLX_SECTION_KEY
+    #[arg(long)]
+    encrypt: bool,
+
+    /// File holding a base64 256-bit section key (e.g. from `openssl rand -base64 32`)
+    #[arg(long, value_name = "FILE", requires = "encrypt")]
+    key_file: Option<PathBuf>,
+
+    /// Encrypt every declaration of this kind, marked or not: agents, workflows, crews or sections (repeatable)
+    #[arg(long, value_name = "SECTION", requires = "encrypt")]
+    sensitive: Vec<String>,
 }
 
 pub fn run(args: CompileArgs) -> anyhow::Result<()> {
@@ -33,7 +56,30 @@ pub fn run(args: CompileArgs) -> anyhow::Result<()> {
     let compress = args.compress;
     let optimize = args.optimize;
     let cache = args.cache;
-    compile_command(input, output, compress, optimize, cache, args.verbose, args.quiet)
+    let hermetic = if args.hermetic {
+        Some(Hermetic::from_source_date_epoch()?.with_env_pairs(&args.env)?)
+    } else {
+        None
+    };
+    let encryption = if args.encrypt {
+        let key = SectionKey::resolve(args.key_file.as_deref())?.ok_or_else(|| {
+            anyhow::anyhow!("--encrypt needs --key-file or the {} environment variable", KEY_ENV)
+        })?;
+        let sensitive = args
+            .sensitive
+            .iter()
+            .map(|name| {
+                sealable_section(name)
+                    .ok_or_else(|| anyhow::anyhow!("Cannot encrypt unknown section kind '{}'", name))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Some((key, sensitive))
+    } else {
+        None
+    };
+    compile_command(
+        input, output, compress, optimize, cache, args.verbose, args.quiet, hermetic, encryption,
+    )
         .map_err(|e| anyhow::anyhow!("Compilation failed: {}", e))?;
     println!("Compilation completed");
 
@@ -1,16 +1,24 @@
 use clap::Args;
 use std::path::PathBuf;
+use crate::mds::diff::{self, DiffFormat};
 
-
+/// Compare two configs (.hlx, .hlxb or JSON). Exits 0 when they match, 1
+/// when they differ and 2 on errors, like diff(1).
 #[derive(Args)]
 pub struct DiffArgs {
-    /// Target directory to analyze (defaults to current directory)
-    #[arg(short, long)]
-    file1: Option<PathBuf>,
+    /// Old config
+    #[arg(value_name = "FILE1")]
+    file1: PathBuf,
 
-    #[arg(short, long)]
-    file2: Option<PathBuf>,
+    /// New config
+    #[arg(value_name = "FILE2")]
+    file2: PathBuf,
 
+    /// Output format: text, json or unified
+    #[arg(long, default_value = "text")]
+    format: String,
+
+    /// Show full values of added and removed entries
     #[arg(short, long)]
     detailed: bool,
 }
@@ -18,10 +26,16 @@ pub struct DiffArgs {
 
 
 pub fn run(args: DiffArgs) -> anyhow::Result<()> {
-    let file1 = args.file1.unwrap_or_else(|| PathBuf::from("."));
-    let file2 = args.file2.unwrap_or_else(|| PathBuf::from("."));
-    let detailed = args.detailed;
-    crate::mds::diff::diff_command(file1, file2, detailed)
-        .map_err(|e| anyhow::anyhow!("Diff command failed: {}", e))?;
-    Ok(())
-}
\ No newline at end of file
+    let result = args
+        .format
+        .parse::<DiffFormat>()
+        .and_then(|format| diff::diff_command(args.file1, args.file2, format, args.detailed));
+    match result {
+        Ok(false) => Ok(()),
+        Ok(true) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("Error: Diff command failed: {}", e);
+            std::process::exit(2);
+        }
+    }
+}
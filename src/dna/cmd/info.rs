@@ -7,8 +7,8 @@ pub struct InfoArgs {
     #[arg(short, long)]
     input: Option<PathBuf>,
 
-    /// File to analyze (defaults to current directory)
-    #[arg(short, long)]
+    /// Compiled .hlxb file to analyze
+    #[arg(value_name = "FILE")]
     file: Option<PathBuf>,
 
     /// Output file path (defaults to stdout if not specified)
@@ -26,14 +26,19 @@ pub struct InfoArgs {
     /// Sections (defaults to false)
     #[arg(long)]
     sections: bool,
+
+    /// Section key for encrypted sections (defaults to HLX_SECTION_KEY)
+    #[arg(long, value_name = "FILE")]
+    key_file: Option<PathBuf>,
 }
 
 pub fn run(args: InfoArgs) -> anyhow::Result<()> {
-    let input = args.input.unwrap_or_else(|| PathBuf::from("."));
+    let input = args.file.or(args.input).unwrap_or_else(|| PathBuf::from("."));
     let format = args.format;
     let symbols = args.symbols;
     let sections = args.sections;
     let verbose = false; // Add default verbose parameter
-    crate::mds::info::info_command(input, format, symbols, sections, verbose)
+    let key = crate::hel::encryption::SectionKey::resolve(args.key_file.as_deref())?;
+    crate::mds::info::info_command(input, format, symbols, sections, verbose, key)
         .map_err(|e| anyhow::anyhow!("Info command failed: {}", e))
 }
\ No newline at end of file
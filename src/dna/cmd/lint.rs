@@ -1,6 +1,7 @@
 use clap::Args;
 use std::path::PathBuf;
 use crate::mds::lint;
+use crate::ops::engine::OperatorEngine;
 
 /// Arguments for the `lint` command.
 #[derive(Args, Debug)]
@@ -12,9 +13,17 @@ pub struct LintArgs {
     /// Enable verbose output
     #[arg(short, long)]
     pub verbose: bool,
+
+    /// Also report nondeterministic calls (@now, @uuid, @env, $VAR, ...)
+    #[arg(long)]
+    pub hermetic: bool,
 }
 
 pub async fn run(args: LintArgs) -> anyhow::Result<()> {
-    // Directly call the lint_files function from mds::lint
-    lint::lint_files(args.files, args.verbose)
+    if !args.hermetic {
+        return lint::lint_files(args.files, args.verbose);
+    }
+    let engine = OperatorEngine::new().await?;
+    let descriptors = engine.descriptors();
+    lint::lint_files_with(args.files, args.verbose, Some(&descriptors))
 }
\ No newline at end of file
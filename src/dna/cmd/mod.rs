@@ -24,6 +24,7 @@ pub mod map;
 pub mod lint;
 pub mod loader;
 pub mod migrate;
+pub mod ops;
 pub mod optimizer;
 pub mod preview;
 pub mod project;
@@ -39,6 +40,7 @@ pub mod sign;
 pub mod templates;
 pub mod test;
 pub mod tools;
+pub mod upgrade;
 pub mod validate;
 pub mod watch;
 pub mod workflow;
\ No newline at end of file
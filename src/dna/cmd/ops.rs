@@ -0,0 +1,43 @@
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct OpsArgs {
+    #[command(subcommand)]
+    action: OpsAction,
+
+    /// Also include functions declared in this Helix file
+    #[arg(short, long, global = true)]
+    file: Option<PathBuf>,
+
+    /// Print descriptors as JSON
+    #[arg(long, global = true)]
+    json: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum OpsAction {
+    /// List available operators
+    List {
+        /// Only show one category (e.g. string, math, data)
+        #[arg(short, long)]
+        category: Option<String>,
+    },
+    /// Show parameters, return type and examples for an operator
+    Describe {
+        /// Operator name, e.g. @regex
+        operator: String,
+    },
+}
+
+pub async fn run(args: OpsArgs) -> anyhow::Result<()> {
+    let file = args.file.as_deref();
+    match args.action {
+        OpsAction::List { category } => {
+            crate::mds::ops::list_operators(file, category.as_deref(), args.json).await
+        }
+        OpsAction::Describe { operator } => {
+            crate::mds::ops::describe_operator(file, &operator, args.json).await
+        }
+    }
+}
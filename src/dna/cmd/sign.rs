@@ -1,27 +1,46 @@
 use clap::Args;
 use std::path::PathBuf;
-use crate::mds::sign::sign_binary;
+use crate::mds::sign::{generate_key_command, sign_command, verify_command};
 
 
 #[derive(Args)]
 pub struct SignArgs {
-    input: PathBuf,
-    key: Option<String>,
+    /// Compiled .hlxb file to sign or verify
+    #[arg(required_unless_present = "generate_key")]
+    input: Option<PathBuf>,
+
+    /// PKCS#8 signing key (defaults to signing.key in the global config)
+    #[arg(short, long)]
+    key: Option<PathBuf>,
+
+    /// Write the signed binary here instead of in place
+    #[arg(short, long)]
     output: Option<PathBuf>,
+
+    /// Check the signature against the trusted keys instead of signing
+    #[arg(long, conflicts_with_all = ["key", "output"])]
     verify: bool,
+
+    /// Also trust this base64 public key when verifying
+    #[arg(long, value_name = "PUBLIC_KEY", requires = "verify")]
+    trust: Vec<String>,
+
+    /// Write a new Ed25519 key pair to this file and its public key to FILE.pub
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["input", "verify"])]
+    generate_key: Option<PathBuf>,
+
+    #[arg(short, long)]
     verbose: bool,
-    quiet: bool,
-    content: Option<String>,
 }
 
 pub fn run(args: SignArgs) -> anyhow::Result<()> {
-    let input = args.input;
-    let key = args.key;
-    let output = args.output;
-    let verify = args.verify;
-    let verbose = args.verbose;
-    let sign = sign_binary(input, key, output, verify, verbose);
-    sign?;
-    Ok(())
+    if let Some(path) = args.generate_key {
+        return generate_key_command(&path);
+    }
+    let input = args.input.expect("clap requires input unless generating a key");
+    if args.verify {
+        verify_command(&input, &args.trust, args.verbose)
+    } else {
+        sign_command(&input, args.key, args.output, args.verbose)
+    }
 }
-
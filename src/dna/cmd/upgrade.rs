@@ -0,0 +1,21 @@
+use clap::Args;
+use std::path::PathBuf;
+use crate::mds::upgrade::upgrade_command;
+
+#[derive(Args)]
+pub struct UpgradeArgs {
+    /// Compiled .hlxb files to rewrite in the current format
+    #[arg(value_name = "FILE", required = true)]
+    files: Vec<PathBuf>,
+
+    /// Write the upgraded binary here instead of in place (single file only)
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+pub fn run(args: UpgradeArgs) -> anyhow::Result<()> {
+    if args.output.is_some() && args.files.len() > 1 {
+        anyhow::bail!("--output can only be used with a single file");
+    }
+    upgrade_command(args.files, args.output)
+}
@@ -1,14 +1,27 @@
-use crate::dna::hel::binary::{HelixBinary, CompressionMethod};
+use crate::dna::hel::binary::{HelixBinary, CompressionMethod, SectionType};
+use crate::dna::hel::encryption::SectionKey;
 pub use crate::dna::mds::optimizer::{Optimizer, OptimizationLevel};
 use crate::dna::mds::serializer::BinarySerializer;
 use crate::dna::mds::bundle::Bundler;
 use crate::dna::mds::runtime::HelixVM;
-use crate::dna::atp::ast::HelixAst;
-use crate::dna::mds::codegen::{CodeGenerator, HelixIR};
+use crate::dna::atp::ast::{
+    AgentDecl, BackstoryBlock, ContextDecl, CrewDecl, Declaration, Expression, HelixAst,
+    PipelineDecl, PipelineNode, SectionDecl, StepDecl, WorkflowDecl,
+};
+use crate::dna::atp::types::{Duration, SecretRef, TimeUnit};
+use crate::dna::mds::codegen::{
+    AgentSymbol, BackoffStrategyIR, CodeGenerator, ConstantValue, ContextSymbol, CrewSymbol,
+    DurationIR, HelixIR, Instruction, PipelineNodeIR, ProcessTypeIR, SecretType,
+    StepDefinition, TimeUnitIR, TriggerType, WorkflowSymbol,
+};
+use crate::dna::ops::file::file_dependencies;
+use crate::dna::ops::hermetic::Hermetic;
 use crate::{parse, validate};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::fs;
-use std::time::Instant;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
 
 #[derive(Clone)]
 pub struct Compiler {
@@ -17,6 +30,8 @@ pub struct Compiler {
     enable_cache: bool,
     verbose: bool,
     cache_dir: Option<PathBuf>,
+    hermetic: Option<Arc<Hermetic>>,
+    encryption: Option<(SectionKey, Vec<SectionType>)>,
 }
 impl Compiler {
     pub fn new(optimization_level: OptimizationLevel) -> Self {
@@ -26,11 +41,27 @@ impl Compiler {
             enable_cache: true,
             verbose: false,
             cache_dir: None,
+            hermetic: None,
+            encryption: None,
         }
     }
     pub fn builder() -> CompilerBuilder {
         CompilerBuilder::default()
     }
+    /// Produce byte-identical output across machines: `$VAR` and `@env`
+    /// resolve from the hermetic snapshot, the build time is its clock and
+    /// no host details are recorded. The compile cache is bypassed.
+    pub fn with_hermetic(mut self, hermetic: Hermetic) -> Self {
+        self.hermetic = Some(Arc::new(hermetic));
+        self
+    }
+    /// Seal contexts and declarations marked `sensitive = true` with `key`,
+    /// plus every declaration of the `sensitive` kinds. The compile cache is
+    /// bypassed.
+    pub fn with_encryption(mut self, key: SectionKey, sensitive: Vec<SectionType>) -> Self {
+        self.encryption = Some((key, sensitive));
+        self
+    }
     pub fn compile_file<P: AsRef<Path>>(
         &self,
         input: P,
@@ -40,7 +71,9 @@ impl Compiler {
         if self.verbose {
             println!("Compiling: {}", path.display());
         }
-        if self.enable_cache {
+        let use_cache = self.enable_cache && self.hermetic.is_none()
+            && self.encryption.is_none();
+        if use_cache {
             if let Some(cached) = self.check_cache(path)? {
                 if self.verbose {
                     println!("  Using cached version");
@@ -51,7 +84,7 @@ impl Compiler {
         let source = fs::read_to_string(path)
             .map_err(|e| CompileError::IoError(format!("Failed to read file: {}", e)))?;
         let binary = self.compile_source(&source, Some(path))?;
-        if self.enable_cache {
+        if use_cache {
             self.cache_binary(path, &binary)?;
         }
         if self.verbose {
@@ -65,10 +98,23 @@ impl Compiler {
         source: &str,
         source_path: Option<&Path>,
     ) -> Result<HelixBinary, CompileError> {
-        let ast = parse(source).map_err(|e| CompileError::ParseError(e.to_string()))?;
-        validate(&ast).map_err(|e| CompileError::ValidationError(e.to_string()))?;
+        let ast = match &self.hermetic {
+            Some(hermetic) => crate::parse_hermetic(source, hermetic.env()).map_err(|e| e.to_string()),
+            None => parse(source).map_err(|e| e.to_string()),
+        }
+        .map_err(CompileError::ParseError)?;
+        self.compile_ast(&ast, source_path)
+    }
+    /// Compile an already parsed program, such as modules merged with the
+    /// files they load
+    pub fn compile_ast(
+        &self,
+        ast: &HelixAst,
+        source_path: Option<&Path>,
+    ) -> Result<HelixBinary, CompileError> {
+        validate(ast).map_err(|e| CompileError::ValidationError(e.to_string()))?;
         let mut generator = CodeGenerator::new();
-        let ir = generator.generate(&ast);
+        let ir = generator.generate(ast);
         let optimized_ir = self.optimize_ir(ir);
         let binary = self.ir_to_binary(optimized_ir, source_path)?;
         Ok(binary)
@@ -94,7 +140,7 @@ impl Compiler {
         ir: HelixIR,
         source_path: Option<&Path>,
     ) -> Result<HelixBinary, CompileError> {
-        let serializer = BinarySerializer::new(self.enable_compression)
+        let mut serializer = BinarySerializer::new(self.enable_compression)
             .with_compression_method(
                 if self.enable_compression {
                     CompressionMethod::Lz4
@@ -102,6 +148,12 @@ impl Compiler {
                     CompressionMethod::Lz4
                 },
             );
+        if let Some(hermetic) = &self.hermetic {
+            serializer = serializer.with_hermetic(hermetic.epoch().max(0) as u64);
+        }
+        if let Some((key, sensitive)) = &self.encryption {
+            serializer = serializer.with_encryption(key.clone(), sensitive.clone());
+        }
         let binary = serializer
             .serialize(ir, source_path)
             .map_err(|e| CompileError::SerializationError(e.to_string()))?;
@@ -127,7 +179,9 @@ impl Compiler {
                 let cache_modified = fs::metadata(&cache_path)
                     .and_then(|m| m.modified())
                     .map_err(|e| CompileError::IoError(e.to_string()))?;
-                if cache_modified > source_modified {
+                if cache_modified > source_modified
+                    && !self.dependency_changed(source_path, cache_modified)
+                {
                     let serializer = BinarySerializer::new(false);
                     return serializer
                         .read_from_file(&cache_path)
@@ -138,6 +192,21 @@ impl Compiler {
         }
         Ok(None)
     }
+    /// Whether a file the source reads through `@file.*` changed after the
+    /// cached binary was written. A dependency that can no longer be read
+    /// counts as changed.
+    fn dependency_changed(&self, source_path: &Path, cache_modified: SystemTime) -> bool {
+        let Ok(source) = fs::read_to_string(source_path) else {
+            return true;
+        };
+        let base_dir = source_path.parent().unwrap_or(Path::new(""));
+        file_dependencies(&source, base_dir).iter().any(|dep| {
+            fs::metadata(dep)
+                .and_then(|m| m.modified())
+                .map(|modified| modified >= cache_modified)
+                .unwrap_or(true)
+        })
+    }
     fn cache_binary(
         &self,
         source_path: &Path,
@@ -164,6 +233,7 @@ pub struct CompilerBuilder {
     enable_cache: bool,
     verbose: bool,
     cache_dir: Option<PathBuf>,
+    hermetic: Option<Hermetic>,
 }
 impl CompilerBuilder {
     pub fn optimization_level(mut self, level: OptimizationLevel) -> Self {
@@ -186,6 +256,10 @@ impl CompilerBuilder {
         self.verbose = enable;
         self
     }
+    pub fn hermetic(mut self, hermetic: Hermetic) -> Self {
+        self.hermetic = Some(hermetic);
+        self
+    }
     pub fn build(self) -> Compiler {
         Compiler {
             optimization_level: self.optimization_level,
@@ -193,6 +267,8 @@ impl CompilerBuilder {
             enable_cache: self.enable_cache,
             verbose: self.verbose,
             cache_dir: self.cache_dir,
+            hermetic: self.hermetic.map(Arc::new),
+            encryption: None,
         }
     }
 }
@@ -221,25 +297,357 @@ impl std::fmt::Display for CompileError {
 }
 impl std::error::Error for CompileError {}
 fn ir_to_ast(ir: HelixIR) -> Result<HelixAst, CompileError> {
-    let mut declarations = Vec::new();
-    for (_id, agent) in ir.symbol_table.agents {
-        let name = ir
+    AstRebuilder::new(&ir)?.rebuild()
+}
+/// Turns IR back into declarations. Each IR construct has one canonical
+/// spelling, so compiling the printed result yields the same IR.
+struct AstRebuilder<'a> {
+    ir: &'a HelixIR,
+    properties: HashMap<u32, HashMap<String, Expression>>,
+    steps: HashMap<u32, Vec<&'a StepDefinition>>,
+    pipelines: HashMap<u32, &'a [PipelineNodeIR]>,
+}
+impl<'a> AstRebuilder<'a> {
+    fn new(ir: &'a HelixIR) -> Result<Self, CompileError> {
+        let mut rebuilder = Self {
+            ir,
+            properties: HashMap::new(),
+            steps: HashMap::new(),
+            pipelines: HashMap::new(),
+        };
+        for instruction in &ir.instructions {
+            match instruction {
+                // `null` has no literal in the language; absent means null
+                Instruction::SetProperty { value: ConstantValue::Null, .. } => {}
+                Instruction::SetProperty { target, key, value } => {
+                    let key = rebuilder.string(*key)?;
+                    let value = rebuilder.expression(value)?;
+                    rebuilder.properties.entry(*target).or_default().insert(key, value);
+                }
+                Instruction::DefineStep { workflow, step } => {
+                    rebuilder.steps.entry(*workflow).or_default().push(step);
+                }
+                Instruction::DefinePipeline { workflow, nodes } => {
+                    rebuilder.pipelines.insert(*workflow, nodes);
+                }
+                _ => {}
+            }
+        }
+        Ok(rebuilder)
+    }
+    fn rebuild(&self) -> Result<HelixAst, CompileError> {
+        let symbols = &self.ir.symbol_table;
+        let mut ids: Vec<u32> = symbols
+            .agents
+            .keys()
+            .chain(symbols.workflows.keys())
+            .chain(symbols.contexts.keys())
+            .chain(symbols.crews.keys())
+            .chain(symbols.sections.keys())
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        let mut declarations = Vec::with_capacity(ids.len());
+        for id in ids {
+            let declaration = if let Some(agent) = symbols.agents.get(&id) {
+                Declaration::Agent(self.agent(agent)?)
+            } else if let Some(workflow) = symbols.workflows.get(&id) {
+                Declaration::Workflow(self.workflow(workflow)?)
+            } else if let Some(context) = symbols.contexts.get(&id) {
+                Declaration::Context(self.context(context)?)
+            } else if let Some(crew) = symbols.crews.get(&id) {
+                Declaration::Crew(self.crew(crew)?)
+            } else {
+                let section = &symbols.sections[&id];
+                Declaration::Section(SectionDecl {
+                    name: self.string(section.name_idx)?,
+                    properties: self.properties(id),
+                })
+            };
+            declarations.push(declaration);
+        }
+        Ok(HelixAst { declarations })
+    }
+    fn agent(&self, agent: &AgentSymbol) -> Result<AgentDecl, CompileError> {
+        let mut properties = HashMap::new();
+        properties.insert("model".to_string(), Expression::String(self.string(agent.model_idx)?));
+        properties.insert("role".to_string(), Expression::String(self.string(agent.role_idx)?));
+        if let Some(temperature) = agent.temperature {
+            // Print the f32 as written rather than its widened f64 digits
+            let temperature = temperature.to_string().parse().unwrap_or(temperature as f64);
+            properties.insert("temperature".to_string(), Expression::Number(temperature));
+        }
+        if let Some(max_tokens) = agent.max_tokens {
+            properties.insert("max_tokens".to_string(), Expression::Number(max_tokens as f64));
+        }
+        properties.extend(self.properties(agent.id));
+        let capabilities = agent
+            .capabilities
+            .iter()
+            .map(|&idx| self.string(idx))
+            .collect::<Result<Vec<_>, _>>()?;
+        let backstory = match agent.backstory_idx {
+            Some(idx) => {
+                Some(BackstoryBlock {
+                    lines: self.string(idx)?.split('\n').map(str::to_string).collect(),
+                })
+            }
+            None => None,
+        };
+        Ok(AgentDecl {
+            name: self.string(agent.name_idx)?,
+            properties,
+            capabilities: (!capabilities.is_empty()).then_some(capabilities),
+            backstory,
+            tools: None,
+        })
+    }
+    fn workflow(&self, workflow: &WorkflowSymbol) -> Result<WorkflowDecl, CompileError> {
+        let defined = self.steps.get(&workflow.id).map(Vec::as_slice).unwrap_or_default();
+        let step_names = defined
+            .iter()
+            .map(|step| Ok((step.id, self.string(step.name_idx)?)))
+            .collect::<Result<HashMap<u32, String>, CompileError>>()?;
+        let steps = defined
+            .iter()
+            .map(|step| self.step(step, &step_names))
+            .collect::<Result<Vec<_>, _>>()?;
+        let trigger = match &workflow.trigger_type {
+            TriggerType::Manual => None,
+            TriggerType::Schedule(idx) => Some(("schedule", *idx)),
+            TriggerType::Webhook(idx) => Some(("webhook", *idx)),
+            TriggerType::Event(idx) => Some(("event", *idx)),
+            TriggerType::FileWatch(idx) => Some(("file", *idx)),
+        };
+        let trigger = match trigger {
+            Some((kind, idx)) => {
+                Some(Expression::String(format!("{}:{}", kind, self.string(idx)?)))
+            }
+            None => None,
+        };
+        // Binaries from before pipelines were recorded only list the steps
+        let pipeline = match self.pipelines.get(&workflow.id) {
+            Some(nodes) => {
+                Some(PipelineDecl {
+                    flow: nodes
+                        .iter()
+                        .filter_map(|node| self.pipeline_node(node, &step_names))
+                        .collect(),
+                })
+            }
+            None => {
+                workflow
+                    .pipeline
+                    .as_ref()
+                    .map(|ids| PipelineDecl {
+                        flow: ids
+                            .iter()
+                            .filter_map(|id| step_names.get(id).cloned())
+                            .map(PipelineNode::Step)
+                            .collect(),
+                    })
+            }
+        };
+        Ok(WorkflowDecl {
+            name: self.string(workflow.name_idx)?,
+            trigger,
+            steps,
+            pipeline,
+            properties: self.properties(workflow.id),
+        })
+    }
+    fn pipeline_node(
+        &self,
+        node: &PipelineNodeIR,
+        step_names: &HashMap<u32, String>,
+    ) -> Option<PipelineNode> {
+        match node {
+            PipelineNodeIR::Step(id) => step_names.get(id).cloned().map(PipelineNode::Step),
+            PipelineNodeIR::Parallel(nodes) => {
+                Some(
+                    PipelineNode::Parallel(
+                        nodes
+                            .iter()
+                            .filter_map(|node| self.pipeline_node(node, step_names))
+                            .collect(),
+                    ),
+                )
+            }
+            PipelineNodeIR::Conditional { condition, then_branch, else_branch } => {
+                let else_branch = match else_branch {
+                    Some(branch) => Some(Box::new(self.pipeline_node(branch, step_names)?)),
+                    None => None,
+                };
+                Some(PipelineNode::Conditional {
+                    condition: Expression::String(self.string(*condition).ok()?),
+                    then_branch: Box::new(self.pipeline_node(then_branch, step_names)?),
+                    else_branch,
+                })
+            }
+        }
+    }
+    fn step(
+        &self,
+        step: &StepDefinition,
+        step_names: &HashMap<u32, String>,
+    ) -> Result<StepDecl, CompileError> {
+        let mut properties = HashMap::new();
+        if let Some(timeout) = &step.timeout {
+            properties.insert("timeout".to_string(), Expression::Duration(duration(timeout)));
+        }
+        if step.parallel {
+            properties.insert("parallel".to_string(), Expression::Bool(true));
+        }
+        if !step.depends_on.is_empty() {
+            let depends_on = step
+                .depends_on
+                .iter()
+                .filter_map(|id| step_names.get(id).cloned())
+                .map(Expression::String)
+                .collect();
+            properties.insert("depends_on".to_string(), Expression::Array(depends_on));
+        }
+        if let Some(retry) = &step.retry {
+            let backoff = match retry.backoff {
+                BackoffStrategyIR::Fixed => "fixed",
+                BackoffStrategyIR::Linear => "linear",
+                BackoffStrategyIR::Exponential => "exponential",
+            };
+            let retry = HashMap::from([
+                ("max_attempts".to_string(), Expression::Number(retry.max_attempts as f64)),
+                ("delay".to_string(), Expression::Duration(duration(&retry.delay))),
+                ("backoff".to_string(), Expression::String(backoff.to_string())),
+            ]);
+            properties.insert("retry".to_string(), Expression::Object(retry));
+        }
+        properties.extend(self.properties(step.id));
+        let crew = step
+            .crew_ids
+            .as_ref()
+            .map(|ids| ids.iter().filter_map(|&id| self.agent_name(id)).collect::<Vec<_>>());
+        Ok(StepDecl {
+            name: self.string(step.name_idx)?,
+            agent: step.agent_id.and_then(|id| self.agent_name(id)),
+            crew,
+            task: step.task_idx.map(|idx| self.string(idx)).transpose()?,
+            properties,
+        })
+    }
+    fn context(&self, context: &ContextSymbol) -> Result<ContextDecl, CompileError> {
+        let mut properties = self.properties(context.id);
+        let variables = match properties.remove("variables") {
+            Some(Expression::Object(variables)) => Some(variables),
+            Some(other) => {
+                properties.insert("variables".to_string(), other);
+                None
+            }
+            None => None,
+        };
+        if context.debug {
+            properties.insert("debug".to_string(), Expression::Bool(true));
+        }
+        if let Some(max_tokens) = context.max_tokens {
+            properties.insert("max_tokens".to_string(), Expression::Number(max_tokens as f64));
+        }
+        let mut secrets = HashMap::new();
+        for (&key, secret) in &context.secrets {
+            let secret = match secret {
+                SecretType::Environment(idx) => SecretRef::Environment(self.string(*idx)?),
+                SecretType::Vault(idx) => SecretRef::Vault(self.string(*idx)?),
+                SecretType::File(idx) => SecretRef::File(self.string(*idx)?),
+            };
+            secrets.insert(self.string(key)?, secret);
+        }
+        Ok(ContextDecl {
+            name: self.string(context.name_idx)?,
+            environment: self.string(context.environment_idx)?,
+            secrets: (!secrets.is_empty()).then_some(secrets),
+            variables,
+            properties,
+        })
+    }
+    fn crew(&self, crew: &CrewSymbol) -> Result<CrewDecl, CompileError> {
+        let process_type = match crew.process_type {
+            ProcessTypeIR::Sequential => "sequential",
+            ProcessTypeIR::Hierarchical => "hierarchical",
+            ProcessTypeIR::Parallel => "parallel",
+            ProcessTypeIR::Consensus => "consensus",
+        };
+        let mut properties = HashMap::new();
+        if let Some(manager) = crew.manager_id.and_then(|id| self.agent_name(id)) {
+            properties.insert("manager".to_string(), Expression::String(manager));
+        }
+        properties.extend(self.properties(crew.id));
+        Ok(CrewDecl {
+            name: self.string(crew.name_idx)?,
+            agents: crew.agent_ids.iter().filter_map(|&id| self.agent_name(id)).collect(),
+            process_type: Some(process_type.to_string()),
+            properties,
+        })
+    }
+    fn expression(&self, value: &ConstantValue) -> Result<Expression, CompileError> {
+        let expression = match value {
+            ConstantValue::String(idx) => Expression::String(self.string(*idx)?),
+            ConstantValue::Number(n) => Expression::Number(*n),
+            ConstantValue::Bool(b) => Expression::Bool(*b),
+            ConstantValue::Duration(d) => Expression::Duration(duration(d)),
+            ConstantValue::Quantity(q) => Expression::Quantity(*q),
+            ConstantValue::Null => Expression::Null,
+            ConstantValue::Array(items) => {
+                Expression::Array(
+                    items
+                        .iter()
+                        .map(|item| self.expression(item))
+                        .collect::<Result<_, _>>()?,
+                )
+            }
+            ConstantValue::Object(entries) => {
+                Expression::Object(
+                    entries
+                        .iter()
+                        .map(|(key, value)| Ok((self.string(*key)?, self.expression(value)?)))
+                        .collect::<Result<_, CompileError>>()?,
+                )
+            }
+            ConstantValue::Reference(idx) => {
+                let text = self.string(*idx)?;
+                match text.strip_prefix('$') {
+                    Some(name) => Expression::Variable(name.to_string()),
+                    None => Expression::Reference(text.trim_start_matches('@').to_string()),
+                }
+            }
+        };
+        Ok(expression)
+    }
+    fn properties(&self, target: u32) -> HashMap<String, Expression> {
+        self.properties.get(&target).cloned().unwrap_or_default()
+    }
+    fn agent_name(&self, id: u32) -> Option<String> {
+        let agent = self.ir.symbol_table.agents.get(&id)?;
+        self.ir.string_pool.get(agent.name_idx).cloned()
+    }
+    fn string(&self, idx: u32) -> Result<String, CompileError> {
+        self.ir
             .string_pool
-            .get(agent.name_idx)
-            .unwrap_or(&format!("agent_{}", agent.id))
-            .clone();
-        declarations
-            .push(
-                crate::dna::atp::ast::Declaration::Agent(crate::dna::atp::ast::AgentDecl {
-                    name,
-                    properties: std::collections::HashMap::new(),
-                    capabilities: None,
-                    backstory: None,
-                    tools: None,
-                }),
-            );
+            .get(idx)
+            .cloned()
+            .ok_or_else(|| {
+                CompileError::DeserializationError(
+                    format!("String index {} is out of range", idx),
+                )
+            })
+    }
+}
+fn duration(duration: &DurationIR) -> Duration {
+    Duration {
+        value: duration.value,
+        unit: match duration.unit {
+            TimeUnitIR::Seconds => TimeUnit::Seconds,
+            TimeUnitIR::Minutes => TimeUnit::Minutes,
+            TimeUnitIR::Hours => TimeUnit::Hours,
+            TimeUnitIR::Days => TimeUnit::Days,
+        },
     }
-    Ok(HelixAst { declarations })
 }
 fn cache_path_for(cache_dir: &Path, source_path: &Path) -> PathBuf {
     let mut hasher = std::collections::hash_map::DefaultHasher::new();
@@ -276,4 +684,365 @@ mod tests {
         let result = compiler.compile_source(source, None);
         assert!(result.is_ok());
     }
+    #[test]
+    fn test_hermetic_compile_is_byte_identical() {
+        let source = r#"
+            project "demo" {
+                version = "1.0.0"
+                home = $HOME
+            }
+            agent "test" {
+                model = "gpt-4"
+                temperature = 0.7
+            }
+        "#;
+        // Same source in two checkouts, written through the CLI path
+        let compile = || {
+            let dir = tempfile::TempDir::new().unwrap();
+            let input = dir.path().join("demo.hlx");
+            let output = dir.path().join("demo.hlxb");
+            fs::write(&input, source).unwrap();
+            let hermetic = Hermetic::new(1_700_000_000).unwrap().with_env_var("HOME", "/home/ci");
+            crate::mds::compile::compile_command(
+                input, Some(output.clone()), true, 2, false, false, true, Some(hermetic), None,
+            )
+            .unwrap();
+            fs::read(&output).unwrap()
+        };
+        assert_eq!(compile(), compile());
+    }
+    #[test]
+    fn test_deleted_dependency_invalidates_cache() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let dependency = dir.path().join("prompt.txt");
+        fs::write(&dependency, "Be brief").unwrap();
+        let source = dir.path().join("agent.hlx");
+        fs::write(&source, "agent \"a\" {\n    model = \"gpt-4\"\n    prompt = \"@file.read('prompt.txt')\"\n}\n").unwrap();
+        // Back-date the inputs so the cache is newer on coarse-grained clocks
+        let earlier = SystemTime::now() - std::time::Duration::from_secs(60);
+        for path in [&dependency, &source] {
+            fs::File::options().write(true).open(path).unwrap().set_modified(earlier).unwrap();
+        }
+
+        let compiler = Compiler::builder()
+            .cache(true)
+            .cache_dir(dir.path().join("cache"))
+            .build();
+        compiler.compile_file(&source).unwrap();
+        assert!(compiler.check_cache(&source).unwrap().is_some());
+
+        fs::remove_file(&dependency).unwrap();
+        assert!(compiler.check_cache(&source).unwrap().is_none());
+    }
+    fn recompile(compiler: &Compiler, source: &str) -> (HelixIR, String) {
+        let binary = compiler.compile_source(source, None).unwrap();
+        let ir = BinarySerializer::new(false).deserialize_to_ir(&binary).unwrap();
+        (ir, compiler.decompile(&binary).unwrap())
+    }
+    fn assert_round_trip(compiler: &Compiler, source: &str) {
+        let (first, printed) = recompile(compiler, source);
+        let (second, reprinted) = recompile(compiler, &printed);
+        assert_eq!(first.symbol_table, second.symbol_table, "{}", printed);
+        assert_eq!(first.instructions, second.instructions, "{}", printed);
+        assert_eq!(first.string_pool.strings, second.string_pool.strings, "{}", printed);
+        assert_eq!(printed, reprinted);
+    }
+    #[test]
+    fn test_decompile_round_trips() {
+        let source = r#"
+            agent "researcher" {
+                model = "gpt-4"
+                role = "Say \"hi\""
+                temperature = 0.7
+                max_tokens = 2000
+                region = "eu"
+                weights = [1, 2.5, true]
+                capabilities [
+                    "search"
+                    "summarize"
+                ]
+                backstory {
+                    First line
+                    Second line
+                }
+            }
+            agent "writer" {
+                model = "claude"
+            }
+            crew "team" {
+                agents [
+                    "researcher"
+                    "writer"
+                ]
+                process = "hierarchical"
+                manager = "writer"
+                budget = 12
+            }
+            workflow "flow" {
+                trigger = "schedule:0 * * * *"
+                step "gather" {
+                    agent = "researcher"
+                    task = "Gather sources"
+                    timeout = 5m
+                    retry {
+                        max_attempts = 3
+                        delay = 30s
+                        backoff = "exponential"
+                    }
+                }
+                step "draft" {
+                    crew = ["researcher", "writer"]
+                    depends_on = ["gather"]
+                    parallel = true
+                    note = $AUTHOR
+                }
+                pipeline {
+                    gather -> draft
+                }
+                owner = @env.USER
+            }
+            context "production" {
+                environment = "prod"
+                debug = true
+                max_tokens = 100
+                secrets {
+                    api_key = $API_KEY
+                    db = "vault:db/password"
+                }
+                variables {
+                    api_endpoint = "https://api.example.com"
+                    retries = 3
+                }
+            }
+            database "main" {
+                host = "localhost"
+                port = 5432
+                limits = { reads = 10, writes = 2 }
+            }
+        "#;
+        for level in [OptimizationLevel::Zero, OptimizationLevel::Two] {
+            assert_round_trip(&Compiler::new(level), source);
+        }
+    }
+    mod round_trip {
+        use super::*;
+        use proptest::collection::{hash_map, vec};
+        use proptest::prelude::*;
+        fn key() -> impl Strategy<Value = String> {
+            "[a-z][a-z0-9_]{0,6}".prop_map(|s| format!("p_{}", s))
+        }
+        fn text() -> impl Strategy<Value = String> {
+            "[a-zA-Z0-9 _.,:/\"\\\n-]{0,12}"
+        }
+        fn scalar() -> impl Strategy<Value = Expression> {
+            prop_oneof![
+                text().prop_map(Expression::String),
+                (0u32..100_000).prop_map(|n| Expression::Number(n as f64 / 8.0)),
+                any::<bool>().prop_map(Expression::Bool),
+                duration().prop_map(Expression::Duration),
+                "[A-Z][A-Z0-9_]{0,5}".prop_map(Expression::Variable),
+                "[A-Z][A-Z0-9_]{0,5}".prop_map(|v| Expression::Reference(format!("env.{}", v))),
+            ]
+        }
+        fn value() -> impl Strategy<Value = Expression> {
+            prop_oneof![
+                4 => scalar(),
+                1 => vec(scalar(), 0..3).prop_map(Expression::Array),
+                1 => hash_map(key(), scalar(), 0..3).prop_map(Expression::Object),
+            ]
+        }
+        fn properties() -> impl Strategy<Value = HashMap<String, Expression>> {
+            hash_map(key(), value(), 0..4)
+        }
+        fn duration() -> impl Strategy<Value = Duration> {
+            let unit = prop_oneof![
+                Just(TimeUnit::Seconds),
+                Just(TimeUnit::Minutes),
+                Just(TimeUnit::Hours),
+                Just(TimeUnit::Days),
+            ];
+            (1u64..1000, unit).prop_map(|(value, unit)| Duration { value, unit })
+        }
+        fn agent(name: String) -> impl Strategy<Value = Declaration> {
+            (
+                (proptest::option::of(text()), proptest::option::of(text())),
+                (proptest::option::of(0u32..9), proptest::option::of(1u32..100_000)),
+                properties(),
+                proptest::option::of(vec(text(), 1..3)),
+                proptest::option::of(vec("[a-z]{1,6}", 1..3)),
+            )
+                .prop_map(move |((model, role), (temperature, max_tokens), mut properties, capabilities, backstory)| {
+                    let typed = [
+                        ("model", model.map(Expression::String)),
+                        ("role", role.map(Expression::String)),
+                        ("temperature", temperature.map(|t| Expression::Number(t as f64 / 4.0))),
+                        ("max_tokens", max_tokens.map(|n| Expression::Number(n as f64))),
+                    ];
+                    for (key, value) in typed {
+                        if let Some(value) = value {
+                            properties.insert(key.to_string(), value);
+                        }
+                    }
+                    Declaration::Agent(AgentDecl {
+                        name: name.clone(),
+                        properties,
+                        capabilities,
+                        backstory: backstory.map(|lines| BackstoryBlock { lines }),
+                        tools: None,
+                    })
+                })
+        }
+        fn step(index: usize, agents: Vec<String>) -> impl Strategy<Value = StepDecl> {
+            let pick = proptest::sample::select(agents.clone());
+            let retry = (1u32..10, duration(), prop_oneof![Just("fixed"), Just("linear"), Just("exponential")])
+                .prop_map(|(attempts, delay, backoff)| {
+                    Expression::Object(HashMap::from([
+                        ("max_attempts".to_string(), Expression::Number(attempts as f64)),
+                        ("delay".to_string(), Expression::Duration(delay)),
+                        ("backoff".to_string(), Expression::String(backoff.to_string())),
+                    ]))
+                });
+            (
+                proptest::option::of(pick),
+                proptest::option::of(proptest::sample::subsequence(agents, 0..=1)),
+                proptest::option::of(text()),
+                (proptest::option::of(duration()), proptest::option::of(any::<bool>()), proptest::option::of(retry)),
+                proptest::sample::subsequence((0..index).collect::<Vec<_>>(), 0..=index),
+                properties(),
+            )
+                .prop_map(move |(agent, crew, task, (timeout, parallel, retry), depends_on, mut properties)| {
+                    if let Some(timeout) = timeout {
+                        properties.insert("timeout".to_string(), Expression::Duration(timeout));
+                    }
+                    if let Some(parallel) = parallel {
+                        properties.insert("parallel".to_string(), Expression::Bool(parallel));
+                    }
+                    if let Some(retry) = retry {
+                        properties.insert("retry".to_string(), retry);
+                    }
+                    if !depends_on.is_empty() {
+                        let names = depends_on.iter().map(|i| Expression::String(format!("s{}", i))).collect();
+                        properties.insert("depends_on".to_string(), Expression::Array(names));
+                    }
+                    StepDecl { name: format!("s{}", index), agent, crew, task, properties }
+                })
+        }
+        fn workflow(name: String, agents: Vec<String>) -> impl Strategy<Value = Declaration> {
+            let trigger = (prop_oneof![Just("schedule"), Just("webhook"), Just("event"), Just("file")], text())
+                .prop_map(|(kind, target)| Expression::String(format!("{}:{}", kind, target)))
+                .boxed();
+            (0usize..4)
+                .prop_flat_map(move |count| {
+                    let steps: Vec<_> = (0..count).map(|i| step(i, agents.clone())).collect();
+                    (steps, any::<bool>())
+                })
+                .prop_flat_map(move |(steps, piped)| {
+                    let name = name.clone();
+                    (proptest::option::of(trigger.clone()), properties()).prop_map(move |(trigger, properties)| {
+                        let pipeline = (piped && !steps.is_empty()).then(|| PipelineDecl {
+                            flow: steps.iter().map(|s| PipelineNode::Step(s.name.clone())).collect(),
+                        });
+                        Declaration::Workflow(WorkflowDecl {
+                            name: name.clone(),
+                            trigger,
+                            steps: steps.clone(),
+                            pipeline,
+                            properties,
+                        })
+                    })
+                })
+        }
+        fn context(name: String) -> impl Strategy<Value = Declaration> {
+            let secret = prop_oneof![
+                "[A-Z][A-Z0-9_]{0,5}".prop_map(SecretRef::Environment),
+                "[a-z][a-z/]{0,8}".prop_map(SecretRef::Vault),
+                "[a-z][a-z/.]{0,8}".prop_map(SecretRef::File),
+            ];
+            (
+                text(),
+                proptest::option::of(hash_map(key(), secret, 1..3)),
+                proptest::option::of(hash_map(key(), value(), 0..3)),
+                (proptest::option::of(any::<bool>()), proptest::option::of(1u32..100_000)),
+                properties(),
+            )
+                .prop_map(move |(environment, secrets, variables, (debug, max_tokens), mut properties)| {
+                    if let Some(debug) = debug {
+                        properties.insert("debug".to_string(), Expression::Bool(debug));
+                    }
+                    if let Some(max_tokens) = max_tokens {
+                        properties.insert("max_tokens".to_string(), Expression::Number(max_tokens as f64));
+                    }
+                    Declaration::Context(ContextDecl {
+                        name: name.clone(),
+                        environment,
+                        secrets,
+                        variables,
+                        properties,
+                    })
+                })
+        }
+        fn crew(name: String, agents: Vec<String>) -> impl Strategy<Value = Declaration> {
+            let process = prop_oneof![
+                Just("sequential"),
+                Just("hierarchical"),
+                Just("parallel"),
+                Just("consensus"),
+            ];
+            (
+                proptest::sample::subsequence(agents.clone(), 0..=agents.len()),
+                proptest::option::of(process),
+                proptest::option::of(proptest::sample::select(agents.clone())),
+                properties(),
+            )
+                .prop_map(move |(members, process, manager, mut properties)| {
+                    // Hierarchical crews must name a manager to validate
+                    let manager = match process {
+                        Some("hierarchical") => manager.or_else(|| agents.first().cloned()),
+                        _ => manager,
+                    };
+                    if let Some(manager) = manager {
+                        properties.insert("manager".to_string(), Expression::String(manager));
+                    }
+                    Declaration::Crew(CrewDecl {
+                        name: name.clone(),
+                        agents: members,
+                        process_type: process.map(str::to_string),
+                        properties,
+                    })
+                })
+        }
+        fn section(index: usize) -> impl Strategy<Value = Declaration> {
+            (proptest::option::of("[a-z]{1,6}"), properties()).prop_map(move |(sub, properties)| {
+                let name = match sub {
+                    Some(sub) => format!("sec{}.{}", index, sub),
+                    None => format!("sec{}", index),
+                };
+                Declaration::Section(SectionDecl { name, properties })
+            })
+        }
+        fn ast() -> impl Strategy<Value = HelixAst> {
+            (1usize..3, 0usize..3, 0usize..3, 0usize..3, 0usize..3)
+                .prop_flat_map(|(agents, workflows, contexts, crews, sections)| {
+                    let names: Vec<String> = (0..agents).map(|i| format!("a{}", i)).collect();
+                    let mut declarations: Vec<BoxedStrategy<Declaration>> = Vec::new();
+                    declarations.extend(names.iter().map(|n| agent(n.clone()).boxed()));
+                    declarations.extend((0..workflows).map(|i| workflow(format!("w{}", i), names.clone()).boxed()));
+                    declarations.extend((0..contexts).map(|i| context(format!("c{}", i)).boxed()));
+                    declarations.extend((0..crews).map(|i| crew(format!("r{}", i), names.clone()).boxed()));
+                    declarations.extend((0..sections).map(section).map(Strategy::boxed));
+                    declarations.prop_shuffle()
+                })
+                .prop_map(|declarations| HelixAst { declarations })
+        }
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(64))]
+            #[test]
+            fn decompile_then_compile_yields_identical_ir(ast in ast()) {
+                let source = crate::pretty_print(&ast);
+                for level in [OptimizationLevel::Zero, OptimizationLevel::Two] {
+                    assert_round_trip(&Compiler::new(level), &source);
+                }
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -1,9 +1,102 @@
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
+use crate::dna::atp::quantity::Quantity;
+use crate::dna::hel::encryption::{EncryptionError, SealedSection, SectionKey};
+use crate::dna::hel::limits::{LimitError, ReadError, ReadLimits};
+use crate::dna::hel::signing::{Signature, SigningKey, SIGNATURE_SECTION_LEN};
 #[cfg(feature = "zstd")]
 use zstd;
 pub const MAGIC_BYTES: [u8; 4] = *b"HLXB";
-pub const BINARY_VERSION: u32 = 1;
+/// Format written by this build. Version 1 files are a bare bincode dump of
+/// [`HelixBinary`]; from version 2 a fixed header is followed by tagged,
+/// length-prefixed sections.
+pub const BINARY_VERSION: u32 = 2;
+/// Magic bytes, format version and the oldest reader version that can read
+/// the file
+pub const HEADER_LEN: usize = 12;
+/// Section tags of the version 2 container. Readers skip tags they don't
+/// know, so a new optional section needs no version bump.
+pub mod tags {
+    /// Flags and metadata
+    pub const META: [u8; 4] = *b"META";
+    pub const SYMBOLS: [u8; 4] = *b"SYMS";
+    /// One data section; repeated in order
+    pub const DATA: [u8; 4] = *b"DATA";
+    /// Little-endian `u64` checksum
+    pub const CHECKSUM: [u8; 4] = *b"CSUM";
+    /// Ed25519 signature over everything before it; always the last section
+    pub const SIGNATURE: [u8; 4] = *b"SIGN";
+}
+/// Metadata entry listing the ids of the keys that sealed sections, comma
+/// separated
+pub const SEALED_KEYS: &str = "sealed_keys";
+fn encode<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, String> {
+    bincode::serialize(value).map_err(|e| e.to_string())
+}
+/// Why bytes could not be decoded as a [`HelixBinary`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatError {
+    Truncated,
+    BadMagic([u8; 4]),
+    /// The file needs a reader of at least this format version
+    UnsupportedVersion(u32),
+    MissingSection([u8; 4]),
+    Corrupt(String),
+    /// The file exceeds a [`ReadLimits`] bound
+    Limit(LimitError),
+}
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "data ends unexpectedly"),
+            Self::BadMagic(found) => write!(f, "invalid magic bytes {:?}", found),
+            Self::UnsupportedVersion(version) => {
+                write!(
+                    f, "format version {} is newer than supported version {}", version,
+                    BINARY_VERSION
+                )
+            }
+            Self::MissingSection(tag) => {
+                write!(f, "missing {} section", String::from_utf8_lossy(tag))
+            }
+            Self::Corrupt(message) => write!(f, "{}", message),
+            Self::Limit(e) => write!(f, "{}", e),
+        }
+    }
+}
+impl std::error::Error for FormatError {}
+impl From<LimitError> for FormatError {
+    fn from(e: LimitError) -> Self {
+        Self::Limit(e)
+    }
+}
+impl From<ReadError> for FormatError {
+    fn from(e: ReadError) -> Self {
+        match e {
+            ReadError::Limit(limit) => Self::Limit(limit),
+            ReadError::Io(io) if io.kind() == std::io::ErrorKind::UnexpectedEof => Self::Truncated,
+            other => Self::Corrupt(other.to_string()),
+        }
+    }
+}
+impl From<bincode::Error> for FormatError {
+    fn from(e: bincode::Error) -> Self {
+        match *e {
+            bincode::ErrorKind::Io(ref io) if io.kind()
+                == std::io::ErrorKind::UnexpectedEof => Self::Truncated,
+            other => Self::Corrupt(other.to_string()),
+        }
+    }
+}
+/// Serialize a map in key order so equal binaries encode to equal bytes
+pub(crate) fn sorted_map<K, V, S>(map: &HashMap<K, V>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    K: Serialize + Ord,
+    V: Serialize,
+    S: serde::Serializer,
+{
+    serializer.collect_map(map.iter().collect::<std::collections::BTreeMap<_, _>>())
+}
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HelixBinary {
     pub magic: [u8; 4],
@@ -13,6 +106,10 @@ pub struct HelixBinary {
     pub symbol_table: SymbolTable,
     pub data_sections: Vec<DataSection>,
     pub checksum: u64,
+    /// Carried in the container's signature section, never in the bincode
+    /// body
+    #[serde(skip)]
+    pub signature: Option<Signature>,
 }
 impl HelixBinary {
     pub fn new() -> Self {
@@ -24,19 +121,15 @@ impl HelixBinary {
             symbol_table: SymbolTable::default(),
             data_sections: Vec::new(),
             checksum: 0,
+            signature: None,
         }
     }
     pub fn validate(&self) -> Result<(), String> {
         if self.magic != MAGIC_BYTES {
             return Err(format!("Invalid magic bytes: {:?}", self.magic));
         }
-        if self.version > BINARY_VERSION {
-            return Err(
-                format!(
-                    "Binary version {} is newer than supported version {}", self.version,
-                    BINARY_VERSION
-                ),
-            );
+        if self.version == 0 {
+            return Err("Binary version 0 is invalid".to_string());
         }
         if false && self.checksum != 0 {
             let calculated = self.calculate_checksum();
@@ -62,6 +155,212 @@ impl HelixBinary {
             0
         }
     }
+    /// Encode in the current container format. Binaries read from older
+    /// files must be [upgraded](HelixBinary::upgrade) first.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        if self.version != BINARY_VERSION {
+            return Err(
+                format!(
+                    "Binary uses format version {}; upgrade it to {} before writing",
+                    self.version, BINARY_VERSION
+                ),
+            );
+        }
+        let mut bytes = Vec::with_capacity(self.size() + 64);
+        bytes.extend_from_slice(&MAGIC_BYTES);
+        bytes.extend_from_slice(&BINARY_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&BINARY_VERSION.to_le_bytes());
+        let mut push = |tag: [u8; 4], payload: Vec<u8>| {
+            bytes.extend_from_slice(&tag);
+            bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(&payload);
+        };
+        push(tags::META, encode(&(&self.flags, &self.metadata))?);
+        push(tags::SYMBOLS, encode(&self.symbol_table)?);
+        for section in &self.data_sections {
+            push(tags::DATA, encode(section)?);
+        }
+        push(tags::CHECKSUM, self.checksum.to_le_bytes().to_vec());
+        if let Some(signature) = &self.signature {
+            push(tags::SIGNATURE, signature.to_section());
+        }
+        Ok(bytes)
+    }
+    /// Encode in the current format with a signature section covering the
+    /// header and every other section. The binary is flagged as signed, so
+    /// readers refuse it if the signature is later stripped.
+    pub fn to_signed_bytes(&self, key: &SigningKey) -> Result<Vec<u8>, String> {
+        let mut signed = self.clone();
+        signed.flags.signed = true;
+        signed.checksum = signed.calculate_checksum();
+        signed.signature = None;
+        let mut bytes = signed.to_bytes()?;
+        let signature = key.sign(&bytes);
+        bytes.extend_from_slice(&tags::SIGNATURE);
+        bytes.extend_from_slice(&(SIGNATURE_SECTION_LEN as u64).to_le_bytes());
+        bytes.extend_from_slice(&signature.to_section());
+        Ok(bytes)
+    }
+    /// The bytes a signature section covers, or `None` when `bytes` does not
+    /// end in one
+    pub fn signed_message(bytes: &[u8]) -> Option<&[u8]> {
+        let start = bytes.len().checked_sub(12 + SIGNATURE_SECTION_LEN)?;
+        (start >= HEADER_LEN && bytes[start..start + 4] == tags::SIGNATURE)
+            .then(|| &bytes[..start])
+    }
+    /// Decode any supported format version within the default limits
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, FormatError> {
+        Self::from_bytes_within(bytes, &ReadLimits::default())
+    }
+    /// Decode any supported format version, refusing sections, nesting and
+    /// string tables beyond `limits`
+    pub fn from_bytes_within(bytes: &[u8], limits: &ReadLimits) -> Result<Self, FormatError> {
+        if bytes.len() < 8 {
+            return Err(FormatError::Truncated);
+        }
+        let mut magic = [0u8; 4];
+        magic.copy_from_slice(&bytes[..4]);
+        if magic != MAGIC_BYTES {
+            return Err(FormatError::BadMagic(magic));
+        }
+        let version = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+        match version {
+            0 => Err(FormatError::UnsupportedVersion(version)),
+            1 => {
+                let binary: Self = limits.decode(bytes)?;
+                binary.check_strings(limits)?;
+                Ok(binary)
+            }
+            _ => Self::from_container(version, bytes, limits),
+        }
+    }
+    fn check_strings(&self, limits: &ReadLimits) -> Result<(), LimitError> {
+        let table = &self.symbol_table;
+        limits.check_strings("symbol table", table.strings.len())?;
+        limits.check_strings("string map", table.string_map.len())
+    }
+    fn from_container(version: u32, bytes: &[u8], limits: &ReadLimits) -> Result<Self, FormatError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(FormatError::Truncated);
+        }
+        let readable_by = u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]]);
+        if readable_by > BINARY_VERSION {
+            return Err(FormatError::UnsupportedVersion(version));
+        }
+        let mut binary = HelixBinary::new();
+        binary.version = version;
+        let (mut meta, mut symbols, mut checksum) = (None, None, None);
+        let mut rest = &bytes[HEADER_LEN..];
+        while !rest.is_empty() {
+            if rest.len() < 12 {
+                return Err(FormatError::Truncated);
+            }
+            let tag = [rest[0], rest[1], rest[2], rest[3]];
+            let len = u64::from_le_bytes(rest[4..12].try_into().unwrap());
+            rest = &rest[12..];
+            let len = limits.check_section(&String::from_utf8_lossy(&tag), len)?;
+            if len > rest.len() {
+                return Err(FormatError::Truncated);
+            }
+            let (payload, tail) = rest.split_at(len);
+            rest = tail;
+            match tag {
+                tags::META => meta = Some(limits.decode(payload)?),
+                tags::SYMBOLS => symbols = Some(limits.decode(payload)?),
+                tags::DATA => binary.data_sections.push(limits.decode(payload)?),
+                tags::CHECKSUM => {
+                    let raw: [u8; 8] = payload
+                        .try_into()
+                        .map_err(|_| FormatError::Corrupt(
+                            "checksum section is not 8 bytes".to_string(),
+                        ))?;
+                    checksum = Some(u64::from_le_bytes(raw));
+                }
+                tags::SIGNATURE => {
+                    if !rest.is_empty() {
+                        return Err(
+                            FormatError::Corrupt(
+                                "sections follow the signature section".to_string(),
+                            ),
+                        );
+                    }
+                    binary.signature = Some(
+                        Signature::from_section(payload)
+                            .map_err(|e| FormatError::Corrupt(e.to_string()))?,
+                    );
+                }
+                _ => {}
+            }
+        }
+        let (flags, metadata) = meta.ok_or(FormatError::MissingSection(tags::META))?;
+        binary.flags = flags;
+        binary.metadata = metadata;
+        binary.symbol_table = symbols.ok_or(FormatError::MissingSection(tags::SYMBOLS))?;
+        binary.checksum = checksum.ok_or(FormatError::MissingSection(tags::CHECKSUM))?;
+        binary.check_strings(limits)?;
+        Ok(binary)
+    }
+    /// Move a binary read from an older format to the current one. Only the
+    /// version changes, so the checksum is recomputed; verify it first.
+    pub fn upgrade(&mut self) -> bool {
+        if self.version >= BINARY_VERSION {
+            return false;
+        }
+        self.version = BINARY_VERSION;
+        self.checksum = self.calculate_checksum();
+        true
+    }
+    /// Sections still encrypted, with their index
+    pub fn locked_sections(&self) -> Vec<(usize, SealedSection)> {
+        self.data_sections
+            .iter()
+            .enumerate()
+            .filter_map(|(index, section)| Some((index, section.sealed()?)))
+            .collect()
+    }
+    /// Decrypt the sections sealed with `key` in place, restoring the
+    /// symbols and strings they took with them. Returns how many were
+    /// opened. The result must not be written out again, or the sealed
+    /// contents would be stored in the clear.
+    pub fn unseal(&mut self, key: &SectionKey) -> Result<usize, EncryptionError> {
+        let mut opened = 0;
+        let mut index = 0;
+        while index < self.data_sections.len() {
+            let Some(sealed) = self.data_sections[index].sealed() else {
+                index += 1;
+                continue;
+            };
+            if sealed.key_id != key.id() {
+                index += 1;
+                continue;
+            }
+            let plaintext = key.open(&sealed)?;
+            let contents: SealedContents = bincode::deserialize(&plaintext)
+                .map_err(|e| EncryptionError::Failed(e.to_string()))?;
+            let table = &mut self.symbol_table;
+            for (idx, string) in contents.strings {
+                if let Some(slot) = table.strings.get_mut(idx as usize) {
+                    table.string_map.insert(string.clone(), idx);
+                    *slot = string;
+                }
+            }
+            for (kind, name, id) in contents.names {
+                let names = match kind {
+                    SectionType::Agents => &mut table.agents,
+                    SectionType::Workflows => &mut table.workflows,
+                    SectionType::Contexts => &mut table.contexts,
+                    SectionType::Crews => &mut table.crews,
+                    _ => continue,
+                };
+                names.insert(name, id);
+            }
+            let count = contents.sections.len();
+            self.data_sections.splice(index..=index, contents.sections);
+            index += count;
+            opened += 1;
+        }
+        Ok(opened)
+    }
     pub fn size(&self) -> usize {
         bincode::serialized_size(self).unwrap_or(0) as usize
     }
@@ -105,6 +404,7 @@ pub struct BinaryMetadata {
     pub optimization_level: u8,
     pub platform: String,
     pub source_path: Option<String>,
+    #[serde(serialize_with = "sorted_map")]
     pub extra: HashMap<String, String>,
 }
 impl Default for BinaryMetadata {
@@ -126,11 +426,17 @@ impl Default for BinaryMetadata {
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SymbolTable {
     pub strings: Vec<String>,
+    #[serde(serialize_with = "sorted_map")]
     pub string_map: HashMap<String, u32>,
+    #[serde(serialize_with = "sorted_map")]
     pub agents: HashMap<String, u32>,
+    #[serde(serialize_with = "sorted_map")]
     pub workflows: HashMap<String, u32>,
+    #[serde(serialize_with = "sorted_map")]
     pub contexts: HashMap<String, u32>,
+    #[serde(serialize_with = "sorted_map")]
     pub crews: HashMap<String, u32>,
+    #[serde(serialize_with = "sorted_map")]
     pub variables: HashMap<String, Reference>,
 }
 impl SymbolTable {
@@ -192,6 +498,13 @@ pub struct DataSection {
     pub compression: Option<CompressionMethod>,
 }
 impl DataSection {
+    /// The encrypted section this holds, if it is sealed
+    pub fn sealed(&self) -> Option<SealedSection> {
+        match self.section_type {
+            SectionType::Sealed => ReadLimits::default().decode(&self.data).ok(),
+            _ => None,
+        }
+    }
     pub fn new(section_type: SectionType, data: Vec<u8>) -> Self {
         let size = data.len() as u64;
         Self {
@@ -216,16 +529,33 @@ impl DataSection {
         Ok(())
     }
     pub fn decompress(&mut self) -> Result<(), String> {
+        self.decompress_within(&ReadLimits::default()).map_err(|e| e.to_string())
+    }
+    /// Decompress in place, refusing to expand beyond what `limits` allow
+    /// for the stored size
+    pub fn decompress_within(&mut self, limits: &ReadLimits) -> Result<(), FormatError> {
         if let Some(method) = &self.compression {
+            let name = format!("{:?}", self.section_type);
+            let compressed = self.data.len() as u64;
             let decompressed = match method {
                 CompressionMethod::None => self.data.clone(),
                 CompressionMethod::Lz4 => {
+                    // The output size is prepended; check it before allocating
+                    let declared = self.data.get(..4).map_or(0, |size| {
+                        u32::from_le_bytes([size[0], size[1], size[2], size[3]]) as u64
+                    });
+                    limits.check_decompressed(&name, compressed, declared)?;
                     lz4_flex::decompress_size_prepended(&self.data)
-                        .map_err(|e| e.to_string())?
+                        .map_err(|e| FormatError::Corrupt(e.to_string()))?
                 }
                 #[cfg(feature = "zstd")]
                 CompressionMethod::Zstd(_) => {
-                    zstd::decode_all(&self.data[..]).map_err(|e| e.to_string())?
+                    let decoder = zstd::Decoder::new(&self.data[..])
+                        .map_err(|e| FormatError::Corrupt(e.to_string()))?;
+                    limits.decompress(&name, decoder, compressed).map_err(|e| match e {
+                        ReadError::Io(e) => FormatError::Corrupt(e.to_string()),
+                        other => other.into(),
+                    })?
                 }
             };
             self.data = decompressed;
@@ -234,7 +564,15 @@ impl DataSection {
         Ok(())
     }
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What a sealed section decrypts to: string pool entries and symbol names
+/// only the sealed declarations use, and the sections holding them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SealedContents {
+    pub strings: Vec<(u32, String)>,
+    pub names: Vec<(SectionType, String, u32)>,
+    pub sections: Vec<DataSection>,
+}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SectionType {
     Project,
     Agents,
@@ -245,6 +583,8 @@ pub enum SectionType {
     Crews,
     Instructions,
     Custom(String),
+    /// A [`SealedSection`]; its kind is inside
+    Sealed,
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum CompressionMethod {
@@ -304,8 +644,12 @@ pub enum Value {
     String(u32),
     Reference(u32),
     Duration(u64),
-    Array(Vec<Value>),
-    Object(HashMap<u32, Value>),
+    Quantity(Quantity),
+    Array(#[serde(deserialize_with = "crate::dna::hel::limits::nested")] Vec<Value>),
+    Object(
+        #[serde(serialize_with = "sorted_map", deserialize_with = "crate::dna::hel::limits::nested")]
+        HashMap<u32, Value>,
+    ),
 }
 #[cfg(test)]
 mod tests {
@@ -338,4 +682,135 @@ mod tests {
         section.decompress().unwrap();
         assert_eq!(section.data, data);
     }
+    /// Binaries written by each format version, compiled from `fixture.hlx`
+    /// by the compiler that shipped it. They are never regenerated; every
+    /// build must keep reading them.
+    fn fixture(version: u32) -> Vec<u8> {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/hlxb");
+        std::fs::read(dir.join(format!("v{}.hlxb", version))).unwrap()
+    }
+    /// The declarations every compiler so far has kept, by name. Older
+    /// compilers dropped sections, context fields and secrets, and laid out
+    /// the string pool differently.
+    fn declarations(bytes: &[u8]) -> Vec<String> {
+        use crate::dna::mds::serializer::BinarySerializer;
+        let binary = HelixBinary::from_bytes(bytes).unwrap();
+        let ir = BinarySerializer::new(false).deserialize_to_ir(&binary).unwrap();
+        let text = |idx: u32| ir.string_pool.get(idx).cloned().unwrap_or_default();
+        let table = &ir.symbol_table;
+        let mut found: Vec<String> = table
+            .agents
+            .values()
+            .map(|a| {
+                format!(
+                    "agent {} model={} role={} temperature={:?}", text(a.name_idx),
+                    text(a.model_idx), text(a.role_idx), a.temperature
+                )
+            })
+            .collect();
+        found.extend(
+            table
+                .workflows
+                .values()
+                .map(|w| format!("workflow {} steps={}", text(w.name_idx), w.steps.len())),
+        );
+        found.extend(table.contexts.values().map(|c| format!("context {}", text(c.name_idx))));
+        found.sort();
+        found
+    }
+    #[test]
+    fn test_reads_fixtures_from_every_version() {
+        let expected = declarations(&fixture(BINARY_VERSION));
+        assert_eq!(
+            expected, vec![
+            "agent researcher model=gpt-4 role=Researcher temperature=Some(0.5)",
+            "agent writer model=claude role=Assistant temperature=None", "context production",
+            "workflow publish steps=2",
+        ]
+        );
+        for version in 1..=BINARY_VERSION {
+            let binary = HelixBinary::from_bytes(&fixture(version)).unwrap();
+            assert_eq!(binary.version, version);
+            // Version 1 checksummed its maps in hash order, so only later
+            // checksums can be reproduced
+            if version > 1 {
+                assert_eq!(binary.checksum, binary.calculate_checksum(), "v{}", version);
+            }
+            assert_eq!(declarations(&fixture(version)), expected, "v{}", version);
+        }
+    }
+    #[test]
+    fn test_upgrading_old_fixtures_preserves_declarations() {
+        for version in 1..BINARY_VERSION {
+            let mut binary = HelixBinary::from_bytes(&fixture(version)).unwrap();
+            assert!(binary.to_bytes().is_err());
+            assert!(binary.upgrade());
+            let bytes = binary.to_bytes().unwrap();
+            let upgraded = HelixBinary::from_bytes(&bytes).unwrap();
+            assert_eq!(upgraded.version, BINARY_VERSION);
+            assert_eq!(upgraded.checksum, upgraded.calculate_checksum(), "v{}", version);
+            assert_eq!(declarations(&bytes), declarations(&fixture(version)), "v{}", version);
+        }
+    }
+    #[test]
+    fn test_newer_writers_stay_readable_when_compatible() {
+        let mut bytes = fixture(BINARY_VERSION);
+        bytes[4..8].copy_from_slice(&(BINARY_VERSION + 1).to_le_bytes());
+        bytes.extend_from_slice(b"XTRA");
+        bytes.extend_from_slice(&3u64.to_le_bytes());
+        bytes.extend_from_slice(b"new");
+        let binary = HelixBinary::from_bytes(&bytes).unwrap();
+        assert_eq!(binary.version, BINARY_VERSION + 1);
+        assert_eq!(binary.data_sections.len(), 5);
+        let mut upgraded = binary.clone();
+        assert!(!upgraded.upgrade());
+
+        bytes[8..12].copy_from_slice(&(BINARY_VERSION + 1).to_le_bytes());
+        assert_eq!(
+            HelixBinary::from_bytes(&bytes).unwrap_err(),
+            FormatError::UnsupportedVersion(BINARY_VERSION + 1)
+        );
+        let truncated = &fixture(BINARY_VERSION)[..40];
+        assert_eq!(HelixBinary::from_bytes(truncated).unwrap_err(), FormatError::Truncated);
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn fuzz_binary_reader_never_panics(
+            bytes in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..512)
+        ) {
+            if let Ok(mut binary) = HelixBinary::from_bytes(&bytes) {
+                for section in &mut binary.data_sections {
+                    let _ = section.decompress_within(&ReadLimits::default());
+                }
+            }
+        }
+
+        #[test]
+        fn fuzz_binary_reader_never_panics_on_mutations(
+            edits in proptest::collection::vec(
+                (proptest::prelude::any::<proptest::sample::Index>(), proptest::prelude::any::<u8>()),
+                1..8,
+            )
+        ) {
+            let mut binary = HelixBinary::new();
+            binary.symbol_table.intern("assistant");
+            let mut section = DataSection::new(SectionType::Agents, b"agent assistant".repeat(8));
+            section.compress(CompressionMethod::Lz4).unwrap();
+            binary.data_sections.push(section);
+            binary.checksum = binary.calculate_checksum();
+            let mut bytes = binary.to_bytes().unwrap();
+            for (index, byte) in &edits {
+                let at = index.index(bytes.len());
+                bytes[at] = *byte;
+            }
+            if let Ok(mut binary) = HelixBinary::from_bytes(&bytes) {
+                for section in &mut binary.data_sections {
+                    let _ = section.decompress_within(&ReadLimits::default());
+                }
+            }
+        }
+    }
 }
\ No newline at end of file
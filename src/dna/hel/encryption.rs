@@ -0,0 +1,316 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::digest::{digest, SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use crate::dna::hel::binary::SectionType;
+/// Environment variable holding a base64 section key
+pub const KEY_ENV: &str = "HLX_SECTION_KEY";
+pub const KEY_LEN: usize = 32;
+/// An AES-256-GCM key for sealing sections. Its id, derived from the key,
+/// is recorded next to every section it seals so readers can tell which
+/// key a locked section needs.
+#[derive(Clone)]
+pub struct SectionKey {
+    id: String,
+    bytes: [u8; KEY_LEN],
+}
+impl SectionKey {
+    pub fn generate() -> Result<Self, EncryptionError> {
+        let mut bytes = [0u8; KEY_LEN];
+        SystemRandom::new()
+            .fill(&mut bytes)
+            .map_err(|_| EncryptionError::MalformedKey("failed to generate a key".to_string()))?;
+        Ok(Self::from_bytes(bytes))
+    }
+    pub fn from_bytes(bytes: [u8; KEY_LEN]) -> Self {
+        let hash = digest(&SHA256, &bytes);
+        let id = hash.as_ref()[..8].iter().map(|b| format!("{:02x}", b)).collect();
+        Self { id, bytes }
+    }
+    /// Parse a key written as base64, e.g. by `openssl rand -base64 32`
+    pub fn from_base64(encoded: &str) -> Result<Self, EncryptionError> {
+        let decoded = STANDARD
+            .decode(encoded.trim())
+            .map_err(|e| EncryptionError::MalformedKey(e.to_string()))?;
+        let bytes = decoded
+            .try_into()
+            .map_err(|decoded: Vec<u8>| EncryptionError::MalformedKey(
+                format!("expected {} bytes, found {}", KEY_LEN, decoded.len()),
+            ))?;
+        Ok(Self::from_bytes(bytes))
+    }
+    pub fn from_file(path: &Path) -> Result<Self, EncryptionError> {
+        let encoded = std::fs::read_to_string(path)
+            .map_err(|e| EncryptionError::MalformedKey(
+                format!("failed to read {}: {}", path.display(), e),
+            ))?;
+        Self::from_base64(&encoded)
+    }
+    /// The key in [`KEY_ENV`], if it is set
+    pub fn from_env() -> Result<Option<Self>, EncryptionError> {
+        match std::env::var(KEY_ENV) {
+            Ok(encoded) if !encoded.trim().is_empty() => Self::from_base64(&encoded).map(Some),
+            _ => Ok(None),
+        }
+    }
+    /// The key in `path` if one is given, otherwise the one in [`KEY_ENV`]
+    pub fn resolve(path: Option<&Path>) -> Result<Option<Self>, EncryptionError> {
+        match path {
+            Some(path) => Self::from_file(path).map(Some),
+            None => Self::from_env(),
+        }
+    }
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+    pub fn to_base64(&self) -> String {
+        STANDARD.encode(self.bytes)
+    }
+    fn aead(&self) -> LessSafeKey {
+        LessSafeKey::new(
+            UnboundKey::new(&AES_256_GCM, &self.bytes).expect("AES-256 keys are 32 bytes"),
+        )
+    }
+    /// Encrypt the contents of a `kind` section. The kind and key id are
+    /// authenticated, so a sealed section cannot be relabelled.
+    pub fn seal(
+        &self,
+        kind: SectionType,
+        plaintext: &[u8],
+    ) -> Result<SealedSection, EncryptionError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| EncryptionError::Failed("failed to generate a nonce".to_string()))?;
+        let aad = associated_data(&self.id, &kind);
+        let mut ciphertext = plaintext.to_vec();
+        self.aead()
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(aad),
+                &mut ciphertext,
+            )
+            .map_err(|_| EncryptionError::Failed("encryption failed".to_string()))?;
+        Ok(SealedSection {
+            key_id: self.id.clone(),
+            kind,
+            nonce,
+            ciphertext,
+        })
+    }
+    pub fn open(&self, sealed: &SealedSection) -> Result<Vec<u8>, EncryptionError> {
+        if sealed.key_id != self.id {
+            return Err(EncryptionError::WrongKey {
+                expected: sealed.key_id.clone(),
+                found: self.id.clone(),
+            });
+        }
+        let aad = associated_data(&sealed.key_id, &sealed.kind);
+        let mut plaintext = sealed.ciphertext.clone();
+        let len = self
+            .aead()
+            .open_in_place(
+                Nonce::assume_unique_for_key(sealed.nonce),
+                Aad::from(aad),
+                &mut plaintext,
+            )
+            .map_err(|_| EncryptionError::Tampered(sealed.kind.clone()))?
+            .len();
+        plaintext.truncate(len);
+        Ok(plaintext)
+    }
+}
+impl fmt::Debug for SectionKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SectionKey").field("id", &self.id).finish_non_exhaustive()
+    }
+}
+fn associated_data(key_id: &str, kind: &SectionType) -> Vec<u8> {
+    let mut aad = key_id.as_bytes().to_vec();
+    aad.extend(bincode::serialize(kind).unwrap_or_default());
+    aad
+}
+/// A section encrypted with a [`SectionKey`]. Only the kind of section and
+/// the key id are readable without the key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SealedSection {
+    pub key_id: String,
+    pub kind: SectionType,
+    pub nonce: [u8; NONCE_LEN],
+    pub ciphertext: Vec<u8>,
+}
+#[derive(Debug, Clone, PartialEq)]
+pub enum EncryptionError {
+    MalformedKey(String),
+    /// The section was sealed with a different key
+    WrongKey { expected: String, found: String },
+    /// Authentication failed: the ciphertext or its label was modified
+    Tampered(SectionType),
+    Failed(String),
+}
+impl fmt::Display for EncryptionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MalformedKey(message) => write!(f, "malformed section key: {}", message),
+            Self::WrongKey { expected, found } => {
+                write!(f, "section needs key {}, not {}", expected, found)
+            }
+            Self::Tampered(kind) => {
+                write!(f, "sealed {:?} section failed authentication", kind)
+            }
+            Self::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+impl std::error::Error for EncryptionError {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dna::compiler::{Compiler, OptimizationLevel};
+    use crate::dna::hel::binary::{HelixBinary, SEALED_KEYS};
+    use crate::dna::mds::codegen::HelixIR;
+    use crate::dna::mds::serializer::{sealable_section, BinarySerializer};
+    #[test]
+    fn test_sealed_sections_authenticate_contents_and_kind() {
+        let key = SectionKey::generate().unwrap();
+        let parsed = SectionKey::from_base64(&key.to_base64()).unwrap();
+        assert_eq!(parsed.id(), key.id());
+        let sealed = key.seal(SectionType::Contexts, b"api_key = sk-live").unwrap();
+        assert!(!sealed.ciphertext.windows(7).any(|w| w == b"sk-live"));
+        assert_eq!(parsed.open(&sealed).unwrap(), b"api_key = sk-live");
+
+        let mut relabelled = sealed.clone();
+        relabelled.kind = SectionType::Agents;
+        assert_eq!(key.open(&relabelled), Err(EncryptionError::Tampered(SectionType::Agents)));
+        let mut flipped = sealed.clone();
+        flipped.ciphertext[0] ^= 1;
+        assert!(matches!(key.open(&flipped), Err(EncryptionError::Tampered(_))));
+        let other = SectionKey::generate().unwrap();
+        assert!(matches!(other.open(&sealed), Err(EncryptionError::WrongKey { .. })));
+        assert!(SectionKey::from_base64("c2hvcnQ=").is_err());
+    }
+    #[test]
+    fn test_sealed_sections_round_trip_only_with_key() {
+        let source = r#"
+            agent "researcher" {
+                model = "gpt-4"
+                role = "Researcher"
+            }
+            workflow "publish" {
+                trigger = "manual"
+                step "gather" {
+                    agent = "researcher"
+                    task = "Collect sources"
+                }
+            }
+            context "production" {
+                environment = "prod"
+                secrets {
+                    api_key = $LIVE_API_KEY
+                }
+                variables {
+                    endpoint = "https://internal.example.com"
+                }
+            }
+            database "main" {
+                password = "hunter2-database"
+            }
+        "#;
+        let key = SectionKey::generate().unwrap();
+        let compiler = Compiler::new(OptimizationLevel::Two);
+        let plain = compiler.compile_source(source, None).unwrap();
+        let sealed = compiler
+            .clone()
+            .with_encryption(key.clone(), vec![sealable_section("sections").unwrap()])
+            .compile_source(source, None)
+            .unwrap();
+        let bytes = sealed.to_bytes().unwrap();
+        for secret in ["LIVE_API_KEY", "internal.example.com", "hunter2-database"] {
+            assert!(!bytes.windows(secret.len()).any(|w| w == secret.as_bytes()), "{}", secret);
+        }
+        assert!(sealed.flags.encrypted);
+        assert_eq!(sealed.metadata.extra.get(SEALED_KEYS).map(String::as_str), Some(key.id()));
+        let kinds: Vec<_> = sealed.locked_sections().into_iter().map(|(_, s)| s.kind).collect();
+        assert_eq!(kinds, vec![SectionType::Contexts, SectionType::Custom("sections".into())]);
+
+        let serializer = BinarySerializer::new(false);
+        let expected = serializer.deserialize_to_ir(&plain).unwrap();
+        let mut locked = HelixBinary::from_bytes(&bytes).unwrap();
+        let readable = serializer.deserialize_to_ir(&locked).unwrap();
+        assert!(readable.symbol_table.contexts.is_empty());
+        assert!(readable.symbol_table.sections.is_empty());
+        assert_eq!(readable.symbol_table.agents, expected.symbol_table.agents);
+        assert!(readable.instructions.len() < expected.instructions.len());
+        assert_eq!(locked.unseal(&SectionKey::generate().unwrap()), Ok(0));
+
+        let mut tampered = locked.clone();
+        let (index, mut section) = tampered.locked_sections().remove(0);
+        section.ciphertext[0] ^= 1;
+        tampered.data_sections[index].data = bincode::serialize(&section).unwrap();
+        assert_eq!(tampered.unseal(&key), Err(EncryptionError::Tampered(SectionType::Contexts)));
+
+        assert_eq!(locked.unseal(&key), Ok(2));
+        assert!(locked.locked_sections().is_empty());
+        assert_eq!(locked.symbol_table.strings, plain.symbol_table.strings);
+        assert_eq!(locked.symbol_table.string_map, plain.symbol_table.string_map);
+        assert_eq!(locked.symbol_table.contexts, plain.symbol_table.contexts);
+        let unsealed = serializer.deserialize_to_ir(&locked).unwrap();
+        assert_eq!(unsealed.symbol_table, expected.symbol_table);
+        assert_eq!(unsealed.instructions, expected.instructions);
+        assert_eq!(unsealed.string_pool.strings, expected.string_pool.strings);
+    }
+
+    #[test]
+    fn test_declarations_marked_sensitive_are_sealed() {
+        let source = r#"
+            agent "researcher" {
+                model = "gpt-4"
+                role = "Researcher"
+                sensitive = true
+            }
+            agent "writer" {
+                model = "claude"
+            }
+            database "main" {
+                password = "hunter2-database"
+                sensitive = true
+            }
+            cache "local" {
+                ttl = 60
+            }
+        "#;
+        let compiler = Compiler::new(OptimizationLevel::Two);
+        assert!(compiler.compile_source(source, None).is_err());
+        let key = SectionKey::generate().unwrap();
+        let sealed = compiler
+            .clone()
+            .with_encryption(key.clone(), Vec::new())
+            .compile_source(source, None)
+            .unwrap();
+        let bytes = sealed.to_bytes().unwrap();
+        assert!(!bytes.windows(16).any(|w| w == b"hunter2-database"));
+        let kinds: Vec<_> = sealed.locked_sections().into_iter().map(|(_, s)| s.kind).collect();
+        assert_eq!(kinds, vec![SectionType::Agents, SectionType::Custom("sections".into())]);
+
+        let serializer = BinarySerializer::new(false);
+        let mut locked = HelixBinary::from_bytes(&bytes).unwrap();
+        let readable = serializer.deserialize_to_ir(&locked).unwrap();
+        let names = |ir: &HelixIR, ids: Vec<u32>| -> Vec<String> {
+            ids.iter()
+                .filter_map(|&idx| ir.string_pool.get(idx).cloned())
+                .collect()
+        };
+        let agents = readable.symbol_table.agents.values().map(|a| a.name_idx).collect();
+        assert_eq!(names(&readable, agents), vec!["writer"]);
+        let sections = readable.symbol_table.sections.values().map(|s| s.name_idx).collect();
+        assert_eq!(names(&readable, sections), vec!["cache.local"]);
+
+        assert_eq!(locked.unseal(&key), Ok(2));
+        let unsealed = serializer.deserialize_to_ir(&locked).unwrap();
+        assert_eq!(unsealed.symbol_table.agents.len(), 2);
+        assert_eq!(unsealed.symbol_table.sections.len(), 2);
+    }
+}
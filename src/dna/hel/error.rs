@@ -54,6 +54,10 @@ pub enum HlxError {
     DecompressionError { message: String, suggestion: String },
     #[error("Feature not available: {feature}")]
     FeatureError { feature: String, message: String },
+    #[error("Resource limit exceeded: {0}")]
+    LimitExceeded(crate::dna::hel::limits::LimitError),
+    #[error("Database corrupted: {path} at offset {offset}: {message}")]
+    DatabaseCorrupted { path: PathBuf, offset: u64, message: String },
 }
 impl HlxError {
     pub fn config_conversion(
@@ -71,6 +75,20 @@ impl HlxError {
             suggestion,
         }
     }
+    pub fn config_validation(
+        field: impl Into<String>,
+        value: impl Into<String>,
+        suggestion: impl Into<String>,
+    ) -> Self {
+        let field = field.into();
+        let value = value.into();
+        let suggestion = suggestion.into();
+        Self::ConfigValidation {
+            field,
+            value,
+            suggestion,
+        }
+    }
     pub fn dataset_processing(message: impl Into<String>) -> Self {
         let message = message.into();
         let suggestion = "Try running 'forge hlx dataset validate' to check dataset compatibility"
@@ -292,6 +310,19 @@ impl HlxError {
             message,
         }
     }
+    pub fn database_corrupted(
+        path: impl Into<PathBuf>,
+        offset: u64,
+        message: impl Into<String>,
+    ) -> Self {
+        let path = path.into();
+        let message = message.into();
+        Self::DatabaseCorrupted {
+            path,
+            offset,
+            message,
+        }
+    }
     pub fn suggestions(&self) -> Vec<String> {
         match self {
             Self::ConfigConversion { suggestion, .. } => vec![suggestion.clone()],
@@ -321,6 +352,12 @@ impl HlxError {
             Self::CompressionError { suggestion, .. } => vec![suggestion.clone()],
             Self::DecompressionError { suggestion, .. } => vec![suggestion.clone()],
             Self::FeatureError { message, .. } => vec![message.clone()],
+            Self::LimitExceeded(_) => {
+                vec!["The file may be corrupted or hostile; raise the read limits only if it is trusted".to_string()]
+            }
+            Self::DatabaseCorrupted { .. } => {
+                vec!["The shard was left unchanged; restore it from a backup or delete the database".to_string()]
+            }
         }
     }
     pub fn is_recoverable(&self) -> bool {
@@ -350,6 +387,8 @@ impl HlxError {
             Self::CompressionError { .. } => true,
             Self::DecompressionError { .. } => true,
             Self::FeatureError { .. } => false,
+            Self::LimitExceeded(_) => false,
+            Self::DatabaseCorrupted { .. } => false,
         }
     }
 }
@@ -358,6 +397,21 @@ impl From<std::io::Error> for HlxError {
         Self::io_error(err.to_string(), "Check file permissions and paths")
     }
 }
+impl From<crate::dna::hel::limits::LimitError> for HlxError {
+    fn from(err: crate::dna::hel::limits::LimitError) -> Self {
+        Self::LimitExceeded(err)
+    }
+}
+impl From<crate::dna::hel::limits::ReadError> for HlxError {
+    fn from(err: crate::dna::hel::limits::ReadError) -> Self {
+        use crate::dna::hel::limits::ReadError;
+        match err {
+            ReadError::Limit(limit) => Self::LimitExceeded(limit),
+            ReadError::Io(io) => io.into(),
+            ReadError::Decode(message) => Self::deserialization_error(message, "File may be corrupted"),
+        }
+    }
+}
 impl From<serde_json::Error> for HlxError {
     fn from(err: serde_json::Error) -> Self {
         Self::json_error(err.to_string(), "Check JSON format and structure")
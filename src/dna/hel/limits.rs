@@ -0,0 +1,291 @@
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Deserializer};
+use std::cell::Cell;
+use std::fmt;
+use std::io::Read;
+use bincode::Options;
+pub const DEFAULT_MAX_SECTION_SIZE: u64 = 256 * 1024 * 1024;
+pub const DEFAULT_MAX_DECOMPRESSED_RATIO: u64 = 1024;
+pub const DEFAULT_MAX_NESTING_DEPTH: usize = 128;
+pub const DEFAULT_MAX_STRINGS: usize = 1_000_000;
+/// Bounds a reader enforces before trusting anything a file says about
+/// itself: length fields, compressed sizes, nesting and table sizes. They
+/// keep a malformed or hostile file from exhausting memory or the stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadLimits {
+    /// Largest section, compressed or not, a reader allocates for
+    pub max_section_size: u64,
+    /// Most bytes a section may decompress to per compressed byte
+    pub max_decompressed_ratio: u64,
+    /// Deepest nesting of arrays and objects inside a value
+    pub max_nesting_depth: usize,
+    /// Most entries in a string or symbol table
+    pub max_strings: usize,
+}
+impl Default for ReadLimits {
+    fn default() -> Self {
+        Self {
+            max_section_size: DEFAULT_MAX_SECTION_SIZE,
+            max_decompressed_ratio: DEFAULT_MAX_DECOMPRESSED_RATIO,
+            max_nesting_depth: DEFAULT_MAX_NESTING_DEPTH,
+            max_strings: DEFAULT_MAX_STRINGS,
+        }
+    }
+}
+impl ReadLimits {
+    /// Check a length read from `section` before allocating for it
+    pub fn check_section(&self, section: &str, size: u64) -> Result<usize, LimitError> {
+        if size > self.max_section_size {
+            return Err(LimitError::SectionTooLarge {
+                section: section.to_string(),
+                size,
+                limit: self.max_section_size,
+            });
+        }
+        usize::try_from(size).map_err(|_| LimitError::SectionTooLarge {
+            section: section.to_string(),
+            size,
+            limit: usize::MAX as u64,
+        })
+    }
+    /// The most `compressed` bytes of `section` may decompress to
+    pub fn decompressed_limit(&self, compressed: u64) -> u64 {
+        compressed.saturating_mul(self.max_decompressed_ratio).min(self.max_section_size)
+    }
+    /// Check a decompressed size, declared or actual
+    pub fn check_decompressed(
+        &self,
+        section: &str,
+        compressed: u64,
+        decompressed: u64,
+    ) -> Result<(), LimitError> {
+        self.check_section(section, decompressed)?;
+        if decompressed > self.decompressed_limit(compressed) {
+            return Err(LimitError::DecompressionRatio {
+                section: section.to_string(),
+                compressed,
+                decompressed,
+                limit: self.max_decompressed_ratio,
+            });
+        }
+        Ok(())
+    }
+    pub fn check_strings(&self, table: &str, count: usize) -> Result<(), LimitError> {
+        if count > self.max_strings {
+            return Err(LimitError::TooManyStrings {
+                table: table.to_string(),
+                count,
+                limit: self.max_strings,
+            });
+        }
+        Ok(())
+    }
+    /// Check the nesting of a JSON value that was parsed without the
+    /// [`nested`] hook
+    pub fn check_json_depth(&self, value: &serde_json::Value) -> Result<(), LimitError> {
+        fn depth(value: &serde_json::Value) -> usize {
+            match value {
+                serde_json::Value::Array(items) => 1 + items.iter().map(depth).max().unwrap_or(0),
+                serde_json::Value::Object(map) => 1 + map.values().map(depth).max().unwrap_or(0),
+                _ => 0,
+            }
+        }
+        if depth(value) > self.max_nesting_depth {
+            return Err(LimitError::TooDeep { limit: self.max_nesting_depth });
+        }
+        Ok(())
+    }
+    /// Read exactly `len` bytes of `section`, growing the buffer as data
+    /// arrives rather than trusting `len` up front
+    pub fn read_section<R: Read>(
+        &self,
+        section: &str,
+        reader: R,
+        len: u64,
+    ) -> Result<Vec<u8>, ReadError> {
+        let capacity = self.check_section(section, len)?;
+        let mut data = Vec::with_capacity(capacity.min(64 * 1024));
+        reader.take(len).read_to_end(&mut data)?;
+        if data.len() != capacity {
+            return Err(ReadError::Io(std::io::ErrorKind::UnexpectedEof.into()));
+        }
+        Ok(data)
+    }
+    /// Drain a decompressor for `section`, stopping as soon as the output
+    /// passes what `compressed` bytes are allowed to expand to
+    pub fn decompress<R: Read>(
+        &self,
+        section: &str,
+        decoder: R,
+        compressed: u64,
+    ) -> Result<Vec<u8>, ReadError> {
+        let limit = self.decompressed_limit(compressed);
+        let mut data = Vec::new();
+        decoder.take(limit.saturating_add(1)).read_to_end(&mut data)?;
+        self.check_decompressed(section, compressed, data.len() as u64)?;
+        Ok(data)
+    }
+    /// Decode bincode written by `bincode::serialize`, refusing input that
+    /// nests values deeper than [`ReadLimits::max_nesting_depth`]
+    pub fn decode<T: DeserializeOwned>(&self, data: &[u8]) -> Result<T, ReadError> {
+        let options = bincode::DefaultOptions::new()
+            .with_fixint_encoding()
+            .allow_trailing_bytes()
+            .with_limit(self.max_section_size);
+        self.nested(|| options.deserialize(data)).map_err(ReadError::from)
+    }
+    /// Run `f` with nesting tracked against this limit. A value nested too
+    /// deeply fails its deserializer and is reported as
+    /// [`LimitError::TooDeep`].
+    pub fn nested<T, E>(&self, f: impl FnOnce() -> Result<T, E>) -> Result<T, NestedError<E>> {
+        let saved = NESTING.with(|nesting| nesting.replace(Nesting::new(self.max_nesting_depth)));
+        let result = f();
+        let exceeded = NESTING.with(|nesting| nesting.replace(saved)).exceeded;
+        result.map_err(|e| match exceeded {
+            true => NestedError::TooDeep(self.max_nesting_depth),
+            false => NestedError::Other(e),
+        })
+    }
+}
+#[derive(Debug, Clone, Copy)]
+struct Nesting {
+    depth: usize,
+    limit: usize,
+    exceeded: bool,
+}
+impl Nesting {
+    const fn new(limit: usize) -> Self {
+        Self { depth: 0, limit, exceeded: false }
+    }
+}
+thread_local! {
+    static NESTING: Cell<Nesting> = const { Cell::new(Nesting::new(DEFAULT_MAX_NESTING_DEPTH)) };
+}
+/// `deserialize_with` for the recursive fields of value types; each one
+/// counts as a level of nesting
+pub fn nested<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    let nesting = NESTING.with(Cell::get);
+    if nesting.depth >= nesting.limit {
+        NESTING.with(|cell| cell.set(Nesting { exceeded: true, ..nesting }));
+        return Err(D::Error::custom(format!("values nested deeper than {} levels", nesting.limit)));
+    }
+    NESTING.with(|cell| cell.set(Nesting { depth: nesting.depth + 1, ..nesting }));
+    let result = T::deserialize(deserializer);
+    NESTING.with(|cell| {
+        let current = cell.get();
+        cell.set(Nesting { depth: current.depth - 1, ..current });
+    });
+    result
+}
+/// A limit a file exceeded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitError {
+    SectionTooLarge { section: String, size: u64, limit: u64 },
+    DecompressionRatio { section: String, compressed: u64, decompressed: u64, limit: u64 },
+    TooDeep { limit: usize },
+    TooManyStrings { table: String, count: usize, limit: usize },
+}
+impl fmt::Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::SectionTooLarge { section, size, limit } => {
+                write!(f, "{} section of {} bytes exceeds the {} byte limit", section, size, limit)
+            }
+            Self::DecompressionRatio { section, compressed, decompressed, limit } => {
+                write!(
+                    f, "{} section expands from {} to at least {} bytes, more than {}x",
+                    section, compressed, decompressed, limit
+                )
+            }
+            Self::TooDeep { limit } => write!(f, "values nested deeper than {} levels", limit),
+            Self::TooManyStrings { table, count, limit } => {
+                write!(f, "{} holds {} strings, more than the limit of {}", table, count, limit)
+            }
+        }
+    }
+}
+impl std::error::Error for LimitError {}
+/// Why a bounded read failed
+#[derive(Debug)]
+pub enum ReadError {
+    Limit(LimitError),
+    Io(std::io::Error),
+    Decode(String),
+}
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Limit(e) => write!(f, "{}", e),
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Decode(message) => write!(f, "{}", message),
+        }
+    }
+}
+impl std::error::Error for ReadError {}
+impl From<LimitError> for ReadError {
+    fn from(e: LimitError) -> Self {
+        Self::Limit(e)
+    }
+}
+impl From<std::io::Error> for ReadError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+impl From<NestedError<bincode::Error>> for ReadError {
+    fn from(e: NestedError<bincode::Error>) -> Self {
+        match e {
+            NestedError::TooDeep(limit) => Self::Limit(LimitError::TooDeep { limit }),
+            NestedError::Other(e) => match *e {
+                bincode::ErrorKind::Io(io) => Self::Io(io),
+                bincode::ErrorKind::SizeLimit => Self::Decode("encoded value exceeds the section size limit".to_string()),
+                other => Self::Decode(other.to_string()),
+            },
+        }
+    }
+}
+/// Error from [`ReadLimits::nested`]
+#[derive(Debug)]
+pub enum NestedError<E> {
+    TooDeep(usize),
+    Other(E),
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dna::atp::types::Value;
+    #[test]
+    fn test_limits_bound_sections_ratios_and_nesting() {
+        let limits = ReadLimits { max_section_size: 1024, max_decompressed_ratio: 4, ..ReadLimits::default() };
+        assert_eq!(limits.check_section("agents", 1024).unwrap(), 1024);
+        assert!(matches!(
+            limits.check_section("agents", u64::MAX),
+            Err(LimitError::SectionTooLarge { limit: 1024, .. })
+        ));
+        let data = limits.read_section("agents", &[7u8; 16][..], 16).unwrap();
+        assert_eq!(data.len(), 16);
+        assert!(matches!(limits.read_section("agents", &[7u8; 4][..], 16), Err(ReadError::Io(_))));
+        assert!(limits.decompress("agents", &[0u8; 40][..], 10).is_ok());
+        assert!(matches!(
+            limits.decompress("agents", &[0u8; 41][..], 10),
+            Err(ReadError::Limit(LimitError::DecompressionRatio { .. }))
+        ));
+
+        let mut value = Value::Null;
+        for _ in 0..200 {
+            value = Value::Array(vec![value]);
+        }
+        let encoded = bincode::serialize(&value).unwrap();
+        assert!(matches!(
+            ReadLimits::default().decode::<Value>(&encoded),
+            Err(ReadError::Limit(LimitError::TooDeep { limit: DEFAULT_MAX_NESTING_DEPTH }))
+        ));
+        let deeper = ReadLimits { max_nesting_depth: 200, ..ReadLimits::default() };
+        assert!(deeper.decode::<Value>(&encoded).is_ok());
+        assert!(ReadLimits::default().decode::<Value>(&bincode::serialize(&Value::Null).unwrap()).is_ok());
+    }
+}
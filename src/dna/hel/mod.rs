@@ -1 +1 @@
-pub mod binary; pub mod error;
+pub mod binary; pub mod encryption; pub mod error; pub mod limits; pub mod signing;
@@ -0,0 +1,215 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use std::fmt;
+use std::str::FromStr;
+pub const PUBLIC_KEY_LEN: usize = 32;
+pub const SIGNATURE_LEN: usize = 64;
+/// Length of a signature section payload: the signer's public key followed
+/// by the signature
+pub const SIGNATURE_SECTION_LEN: usize = PUBLIC_KEY_LEN + SIGNATURE_LEN;
+/// An Ed25519 public key, written as base64 in key files and the global
+/// config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PublicKey([u8; PUBLIC_KEY_LEN]);
+impl PublicKey {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SignatureError> {
+        bytes
+            .try_into()
+            .map(Self)
+            .map_err(|_| SignatureError::MalformedKey(
+                format!("expected {} bytes, found {}", PUBLIC_KEY_LEN, bytes.len()),
+            ))
+    }
+    pub fn as_bytes(&self) -> &[u8; PUBLIC_KEY_LEN] {
+        &self.0
+    }
+}
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", STANDARD.encode(self.0))
+    }
+}
+impl FromStr for PublicKey {
+    type Err = SignatureError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = STANDARD
+            .decode(s.trim())
+            .map_err(|e| SignatureError::MalformedKey(e.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+}
+/// An Ed25519 key pair that signs binaries
+pub struct SigningKey {
+    pair: Ed25519KeyPair,
+}
+impl SigningKey {
+    /// Generate a key pair, returning it with its PKCS#8 encoding for
+    /// storage
+    pub fn generate() -> Result<(Self, Vec<u8>), SignatureError> {
+        let document = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .map_err(|_| SignatureError::MalformedKey(
+                "failed to generate a key pair".to_string(),
+            ))?;
+        let key = Self::from_pkcs8(document.as_ref())?;
+        Ok((key, document.as_ref().to_vec()))
+    }
+    pub fn from_pkcs8(pkcs8: &[u8]) -> Result<Self, SignatureError> {
+        Ed25519KeyPair::from_pkcs8_maybe_unchecked(pkcs8)
+            .map(|pair| Self { pair })
+            .map_err(|e| SignatureError::MalformedKey(e.to_string()))
+    }
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::from_bytes(self.pair.public_key().as_ref())
+            .expect("Ed25519 public keys are 32 bytes")
+    }
+    pub fn sign(&self, message: &[u8]) -> Signature {
+        let mut bytes = [0u8; SIGNATURE_LEN];
+        bytes.copy_from_slice(self.pair.sign(message).as_ref());
+        Signature {
+            public_key: self.public_key(),
+            bytes,
+        }
+    }
+}
+/// A signature over everything in a binary before its signature section:
+/// the header and every other section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signature {
+    pub public_key: PublicKey,
+    pub bytes: [u8; SIGNATURE_LEN],
+}
+impl Signature {
+    pub fn to_section(&self) -> Vec<u8> {
+        [&self.public_key.0[..], &self.bytes[..]].concat()
+    }
+    pub fn from_section(payload: &[u8]) -> Result<Self, SignatureError> {
+        if payload.len() != SIGNATURE_SECTION_LEN {
+            return Err(
+                SignatureError::MalformedSignature(
+                    format!(
+                        "signature section is {} bytes, expected {}", payload.len(),
+                        SIGNATURE_SECTION_LEN
+                    ),
+                ),
+            );
+        }
+        let (key, signature) = payload.split_at(PUBLIC_KEY_LEN);
+        Ok(Self {
+            public_key: PublicKey::from_bytes(key)?,
+            bytes: signature.try_into().expect("length checked above"),
+        })
+    }
+    /// Check the signature against `message` and the signer's embedded key
+    pub fn verify(&self, message: &[u8]) -> Result<(), SignatureError> {
+        UnparsedPublicKey::new(&ED25519, self.public_key.0)
+            .verify(message, &self.bytes)
+            .map_err(|_| SignatureError::Invalid(self.public_key))
+    }
+}
+/// Which signatures a reader accepts
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrustPolicy {
+    pub trusted_keys: Vec<PublicKey>,
+    /// Reject binaries that are not signed by one of `trusted_keys`. With no
+    /// trusted keys every binary is rejected.
+    pub require_signature: bool,
+}
+impl TrustPolicy {
+    pub fn require(trusted_keys: Vec<PublicKey>) -> Self {
+        Self {
+            trusted_keys,
+            require_signature: true,
+        }
+    }
+    pub fn trusts(&self, key: &PublicKey) -> bool {
+        self.trusted_keys.contains(key)
+    }
+    /// Decide whether a binary may be used. A signature that is present is
+    /// always checked, and a binary flagged as signed must carry one;
+    /// trust in the signer only matters when a signature is required.
+    pub fn check(
+        &self,
+        signed_flag: bool,
+        signature: Option<(&Signature, &[u8])>,
+    ) -> Result<Option<PublicKey>, SignatureError> {
+        let Some((signature, message)) = signature else {
+            if signed_flag {
+                return Err(SignatureError::Stripped);
+            }
+            if self.require_signature {
+                return Err(SignatureError::Unsigned);
+            }
+            return Ok(None);
+        };
+        signature.verify(message)?;
+        if self.require_signature && !self.trusts(&signature.public_key) {
+            return Err(SignatureError::Untrusted(signature.public_key));
+        }
+        Ok(Some(signature.public_key))
+    }
+}
+#[derive(Debug, Clone, PartialEq)]
+pub enum SignatureError {
+    /// A signature is required but the binary has none
+    Unsigned,
+    /// The binary is flagged as signed but its signature section is gone
+    Stripped,
+    /// The signature does not match the contents
+    Invalid(PublicKey),
+    /// The signature is valid but the key is not trusted
+    Untrusted(PublicKey),
+    MalformedKey(String),
+    MalformedSignature(String),
+}
+impl fmt::Display for SignatureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unsigned => write!(f, "binary is not signed"),
+            Self::Stripped => {
+                write!(f, "binary is flagged as signed but has no signature section")
+            }
+            Self::Invalid(key) => {
+                write!(f, "signature by {} does not match the contents", key)
+            }
+            Self::Untrusted(key) => write!(f, "signing key {} is not trusted", key),
+            Self::MalformedKey(message) => write!(f, "malformed key: {}", message),
+            Self::MalformedSignature(message) => {
+                write!(f, "malformed signature: {}", message)
+            }
+        }
+    }
+}
+impl std::error::Error for SignatureError {}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_policy_fails_closed() {
+        let (key, pkcs8) = SigningKey::generate().unwrap();
+        assert_eq!(SigningKey::from_pkcs8(&pkcs8).unwrap().public_key(), key.public_key());
+        let public = key.public_key();
+        assert_eq!(public.to_string().parse::<PublicKey>().unwrap(), public);
+        let signature = key.sign(b"contents");
+        let section = signature.to_section();
+        assert_eq!(Signature::from_section(&section).unwrap(), signature);
+
+        let open = TrustPolicy::default();
+        assert_eq!(open.check(false, None), Ok(None));
+        assert_eq!(open.check(false, Some((&signature, b"contents"))), Ok(Some(public)));
+        assert_eq!(open.check(true, None), Err(SignatureError::Stripped));
+        assert_eq!(
+            open.check(false, Some((&signature, b"tampered"))),
+            Err(SignatureError::Invalid(public))
+        );
+
+        let strict = TrustPolicy::require(vec![public]);
+        assert_eq!(strict.check(false, Some((&signature, b"contents"))), Ok(Some(public)));
+        assert_eq!(strict.check(false, None), Err(SignatureError::Unsigned));
+        let nobody = TrustPolicy::require(Vec::new());
+        assert_eq!(
+            nobody.check(false, Some((&signature, b"contents"))),
+            Err(SignatureError::Untrusted(public))
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use std::path::PathBuf;
-use crate::dna::compiler::{Compiler, OptimizationLevel};
+use crate::dna::compiler::OptimizationLevel;
+use crate::dna::mds::incremental::IncrementalBuild;
 
 pub fn run_build(args: crate::dna::cmd::build::BuildArgs) -> anyhow::Result<()> {
     let input = args.input;
@@ -8,7 +9,7 @@ pub fn run_build(args: crate::dna::cmd::build::BuildArgs) -> anyhow::Result<()>
     let compress = args.compress;
     let cache = args.cache;
 
-    build_project(input, output, optimize, compress, cache, true)
+    build_project(input, output, optimize, compress, cache, args.explain, true)
         .map_err(|e| anyhow::anyhow!("Build failed: {}", e))
 }
 
@@ -18,6 +19,7 @@ fn build_project(
     optimize: u8,
     compress: bool,
     cache: bool,
+    explain: bool,
     verbose: bool,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let project_dir = find_project_root()?;
@@ -38,40 +40,57 @@ fn build_project(
             }
         }
     };
-    let output_file = output
-        .unwrap_or_else(|| {
-            let target_dir = project_dir.join("target");
-            let input_stem = input_file
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("output");
-            target_dir.join(format!("{}.hlxb", input_stem))
-        });
+    let target_dir = project_dir.join("target");
+    let level = OptimizationLevel::from(optimize);
+    let build = if input_file.is_dir() {
+        let out_dir = output.unwrap_or(target_dir);
+        IncrementalBuild::new(level, compress, &out_dir)
+            .cache(cache)
+            .directory(&input_file, &out_dir)?
+    } else {
+        let output_file = output
+            .unwrap_or_else(|| {
+                let input_stem = input_file
+                    .file_stem()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("output");
+                target_dir.join(format!("{}.hlxb", input_stem))
+            });
+        let out_dir = output_file.parent().map(PathBuf::from).unwrap_or_default();
+        IncrementalBuild::new(level, compress, &out_dir)
+            .cache(cache)
+            .target(&input_file, &output_file)
+    };
     if verbose {
         println!("🔨 Building HELIX project:");
         println!("  Input: {}", input_file.display());
-        println!("  Output: {}", output_file.display());
         println!("  Optimization: Level {}", optimize);
         println!("  Compression: {}", if compress { "Enabled" } else { "Disabled" });
         println!("  Cache: {}", if cache { "Enabled" } else { "Disabled" });
     }
-    if let Some(parent) = output_file.parent() {
-        std::fs::create_dir_all(parent)?;
+    let report = build.run()?;
+    for module in &report.modules {
+        if let Some(error) = &module.error {
+            eprintln!("❌ {}: {}", module.path.display(), error);
+        } else if explain {
+            match &module.reason {
+                Some(reason) => println!("  rebuilt {}: {}", module.path.display(), reason),
+                None => println!("  fresh   {}: up to date", module.path.display()),
+            }
+        }
+    }
+    let failed = report.failed().count();
+    if failed > 0 {
+        return Err(format!("{} of {} modules failed", failed, report.modules.len()).into());
     }
-    let compiler = Compiler::new(OptimizationLevel::from(optimize));
-    let binary = compiler.compile_file(&input_file)?;
-    let serializer = crate::mds::serializer::BinarySerializer::new(compress);
-    serializer.write_to_file(&binary, &output_file)?;
     println!("✅ Build completed successfully!");
-    println!("  Output: {}", output_file.display());
-    println!("  Size: {} bytes", binary.size());
+    println!(
+        "  Rebuilt: {} of {} modules", report.rebuilt().count(), report.modules.len()
+    );
     if verbose {
-        let stats = binary.symbol_table.stats();
-        println!(
-            "  Strings: {} (unique: {})", stats.total_strings, stats.unique_strings
-        );
-        println!("  Agents: {}", stats.agents);
-        println!("  Workflows: {}", stats.workflows);
+        for module in report.rebuilt() {
+            println!("  Output: {}", module.output.display());
+        }
     }
     Ok(())
 }
@@ -1,15 +1,20 @@
+use crate::dna::atp::ast::{Declaration, Expression, HelixAst};
 use crate::dna::compiler::Compiler;
 use crate::dna::compiler::CompileError;
-use crate::dna::hel::binary::{HelixBinary, DataSection};
+use crate::dna::hel::binary::HelixBinary;
+use crate::dna::ops::file::{normalize, rebase_file_paths};
+use super::modules::ModuleSystem;
 use super::optimizer::OptimizationLevel;
-use super::serializer::BinarySerializer;
-use super::codegen::Instruction;
+use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
 use std::fs;
 use std::collections::{HashMap, HashSet, VecDeque};
+/// Metadata key holding a bundle's [`BundleManifest`] as JSON
+pub const MANIFEST_KEY: &str = "bundle_manifest";
 pub struct Bundler {
     include_patterns: Vec<String>,
     exclude_patterns: Vec<String>,
+    entry_points: Vec<String>,
     follow_imports: bool,
     tree_shake: bool,
     verbose: bool,
@@ -19,6 +24,7 @@ impl Bundler {
         Self {
             include_patterns: vec!["*.hlx".to_string()],
             exclude_patterns: Vec::new(),
+            entry_points: Vec::new(),
             follow_imports: true,
             tree_shake: false,
             verbose: false,
@@ -32,6 +38,12 @@ impl Bundler {
         self.exclude_patterns.push(pattern.to_string());
         self
     }
+    /// Root tree shaking at the workflow, crew, agent or context `name`.
+    /// Without any entry points every workflow is one.
+    pub fn entry(mut self, name: &str) -> Self {
+        self.entry_points.push(name.to_string());
+        self
+    }
     pub fn with_imports(mut self, follow: bool) -> Self {
         self.follow_imports = follow;
         self
@@ -53,45 +65,75 @@ impl Bundler {
         if self.verbose {
             println!("Bundling directory: {}", directory.display());
         }
-        let files = self.collect_files(directory)?;
+        let mut files = self.collect_files(directory)?;
         if files.is_empty() {
             return Err(CompileError::IoError("No HELIX files found".to_string()));
         }
+        files.sort();
         if self.verbose {
             println!("Found {} files to bundle", files.len());
         }
         self.bundle_files(&files, optimization_level)
     }
+    /// Compile `files`, and the files they load, into one binary whose
+    /// metadata carries a [`BundleManifest`] under [`MANIFEST_KEY`]. Relative
+    /// `@file` paths are rewritten against the directory holding `files`,
+    /// which is recorded as the bundle's source path.
     pub fn bundle_files(
         &self,
         files: &[PathBuf],
         optimization_level: OptimizationLevel,
     ) -> Result<HelixBinary, CompileError> {
-        let mut bundle = BundleBuilder::new();
-        let compiler = Compiler::new(optimization_level);
-        for file in files {
-            if self.verbose {
-                println!("  Processing: {}", file.display());
-            }
-            let source = fs::read_to_string(file)
-                .map_err(|e| CompileError::IoError(
-                    format!("Failed to read {}: {}", file.display(), e),
-                ))?;
-            let binary = compiler.compile_source(&source, Some(file))?;
-            bundle.add_file(file.clone(), binary);
-        }
-        if self.follow_imports {
-            self.resolve_dependencies(&mut bundle)?;
-        }
-        if self.tree_shake {
-            self.apply_tree_shaking(&mut bundle)?;
+        let modules = self.load_modules(files)?;
+        let declarations = Self::declarations(&modules);
+        let manifest = if self.tree_shake {
+            self.shake(&modules, &declarations)?
+        } else {
+            BundleManifest::unshaken(&modules, &declarations)
+        };
+        let kept_files: HashSet<&PathBuf> = manifest.files.iter().map(|f| &f.path).collect();
+        let kept: HashSet<(DeclarationKind, &str)> = manifest
+            .kept
+            .iter()
+            .map(|d| (d.kind, d.name.as_str()))
+            .collect();
+        let base = common_dir(files);
+        let ast = HelixAst {
+            declarations: declarations
+                .iter()
+                .filter(|(path, decl)| match named(decl) {
+                    Some(key) => kept.contains(&key),
+                    None => kept_files.contains(path),
+                })
+                .map(|(path, decl)| {
+                    let mut decl = decl.clone();
+                    rebase_declaration(&mut decl, path.parent().unwrap_or(Path::new("")), &base);
+                    decl
+                })
+                .collect(),
+        };
+        if self.verbose {
+            println!(
+                "  Keeping {} declarations from {} files, dropping {}",
+                manifest.kept.len(), manifest.files.len(), manifest.dropped.len()
+            );
         }
-        let merged = bundle.build()?;
+        let compiler = Compiler::new(optimization_level);
+        let mut bundle = compiler.compile_ast(&ast, Some(&base))?;
+        let manifest_json = serde_json::to_string(&manifest)
+            .map_err(|e| CompileError::SerializationError(e.to_string()))?;
+        bundle.metadata.extra.insert("bundle".to_string(), "true".to_string());
+        bundle
+            .metadata
+            .extra
+            .insert("bundle_files".to_string(), manifest.files.len().to_string());
+        bundle.metadata.extra.insert(MANIFEST_KEY.to_string(), manifest_json);
+        bundle.checksum = bundle.calculate_checksum();
         if self.verbose {
             println!("Bundle created successfully");
-            println!("  Total size: {} bytes", merged.size());
+            println!("  Total size: {} bytes", bundle.size());
         }
-        Ok(merged)
+        Ok(bundle)
     }
     fn collect_files(&self, directory: &Path) -> Result<Vec<PathBuf>, CompileError> {
         let mut files = Vec::new();
@@ -141,135 +183,130 @@ impl Bundler {
         }
         file_name == pattern
     }
-    fn resolve_dependencies(
-        &self,
-        bundle: &mut BundleBuilder,
-    ) -> Result<(), CompileError> {
-        for (path, binary) in &bundle.files {
-            let mut deps = HashSet::new();
-            let serializer = BinarySerializer::new(false);
-            if let Ok(ir) = serializer.deserialize_to_ir(&binary) {
-                for instruction in &ir.instructions {
-                    if let Instruction::ResolveReference { ref_type: _, index } = instruction {
-                        if *index > 1000 {
-                            deps.insert(PathBuf::from("external.hlx"));
-                        }
-                    }
-                }
+    /// Parse `files`, plus everything they `load` when following imports
+    fn load_modules(&self, files: &[PathBuf]) -> Result<ModuleSystem, CompileError> {
+        let to_compile_error = |e: anyhow::Error| {
+            if e.chain().any(|cause| cause.is::<std::io::Error>()) {
+                CompileError::IoError(format!("{:#}", e))
+            } else {
+                CompileError::ParseError(format!("{:#}", e))
             }
-            bundle.dependencies.insert(path.clone(), deps);
-        }
-        if let Some(cycle) = self.detect_circular_dependencies(&bundle.dependencies) {
-            return Err(
-                CompileError::ParseError(
-                    format!("Circular dependency detected: {:?}", cycle),
-                ),
-            );
-        }
-        Ok(())
-    }
-    fn detect_circular_dependencies(
-        &self,
-        deps: &HashMap<PathBuf, HashSet<PathBuf>>,
-    ) -> Option<Vec<PathBuf>> {
-        for (start, _) in deps {
-            let mut visited = HashSet::new();
-            let mut path = VecDeque::new();
-            path.push_back(start.clone());
-            if self.has_cycle_from(start, deps, &mut visited, &mut path) {
-                return Some(path.into_iter().collect());
+        };
+        let mut modules = ModuleSystem::new();
+        for file in files {
+            if self.verbose {
+                println!("  Processing: {}", file.display());
+            }
+            if self.follow_imports {
+                modules.load_with_dependencies(file).map_err(to_compile_error)?;
+            } else {
+                modules
+                    .load_module(file)
+                    .map_err(|e| to_compile_error(e.context(format!("Failed to load {}", file.display()))))?;
             }
         }
-        None
+        modules.resolve_dependencies().map_err(to_compile_error)?;
+        Ok(modules)
     }
-    fn has_cycle_from(
-        &self,
-        node: &PathBuf,
-        deps: &HashMap<PathBuf, HashSet<PathBuf>>,
-        visited: &mut HashSet<PathBuf>,
-        path: &mut VecDeque<PathBuf>,
-    ) -> bool {
-        if visited.contains(node) {
-            return true;
-        }
-        visited.insert(node.clone());
-        if let Some(node_deps) = deps.get(node) {
-            for dep in node_deps {
-                path.push_back(dep.clone());
-                if self.has_cycle_from(dep, deps, visited, path) {
-                    return true;
+    /// Every declaration in the bundle with the file it came from. As when
+    /// merging modules, a file's declarations win over same-named ones in the
+    /// files it loads.
+    fn declarations(modules: &ModuleSystem) -> Vec<(PathBuf, Declaration)> {
+        let mut seen = HashSet::new();
+        let mut declarations = Vec::new();
+        for path in modules.compilation_order().iter().rev() {
+            let Some(module) = modules.modules().get(path) else {
+                continue;
+            };
+            for decl in &module.ast.declarations {
+                if let Some((kind, name)) = named(decl) {
+                    if !seen.insert((kind, name.to_string())) {
+                        continue;
+                    }
                 }
-                path.pop_back();
+                declarations.push((path.clone(), decl.clone()));
             }
         }
-        visited.remove(node);
-        false
+        declarations
     }
-    fn apply_tree_shaking(
+    /// Walk references out from the entry points. Only what they reach is
+    /// kept, along with the files holding it and the files those load.
+    fn shake(
         &self,
-        bundle: &mut BundleBuilder,
-    ) -> Result<(), CompileError> {
-        use std::collections::HashSet;
-        for (path, binary) in &mut bundle.files {
-            let serializer = BinarySerializer::new(false);
-            let mut ir = serializer
-                .deserialize_to_ir(&binary)
-                .map_err(|e| CompileError::SerializationError(e.to_string()))?;
-            let mut used_agents = HashSet::new();
-            let mut used_workflows = HashSet::new();
-            let mut used_contexts = HashSet::new();
-            for (_id, crew) in &ir.symbol_table.crews {
-                for agent_id in &crew.agent_ids {
-                    used_agents.insert(*agent_id);
+        modules: &ModuleSystem,
+        declarations: &[(PathBuf, Declaration)],
+    ) -> Result<BundleManifest, CompileError> {
+        let index: HashMap<(DeclarationKind, &str), (&PathBuf, &Declaration)> = declarations
+            .iter()
+            .filter_map(|(path, decl)| named(decl).map(|key| (key, (path, decl))))
+            .collect();
+        let mut queue = VecDeque::new();
+        if self.entry_points.is_empty() {
+            for (_, decl) in declarations {
+                if let Declaration::Workflow(workflow) = decl {
+                    queue.push_back((DeclarationKind::Workflow, workflow.name.clone(), KeepReason::EntryPoint));
                 }
             }
-            for workflow in ir.symbol_table.workflows.values() {
-                if let Some(pipeline) = &workflow.pipeline {
-                    for workflow_id in pipeline {
-                        used_workflows.insert(*workflow_id);
-                    }
-                }
-            }
-            for instruction in &ir.instructions {
-                if let Instruction::DeclareContext(id) = instruction {
-                    used_contexts.insert(id);
-                }
+        } else {
+            for name in &self.entry_points {
+                let kind = DeclarationKind::ALL
+                    .into_iter()
+                    .find(|kind| index.contains_key(&(*kind, name.as_str())))
+                    .ok_or_else(|| CompileError::ValidationError(
+                        format!("Entry point '{}' is not declared in the bundle", name),
+                    ))?;
+                queue.push_back((kind, name.clone(), KeepReason::EntryPoint));
             }
-            let unused_agents: Vec<u32> = ir
-                .symbol_table
-                .agents
-                .keys()
-                .filter(|id| !used_agents.contains(id))
-                .cloned()
-                .collect();
-            for id in unused_agents {
-                ir.symbol_table.agents.remove(&id);
+        }
+
+        let mut manifest = BundleManifest {
+            tree_shaken: true,
+            entry_points: self.entry_points.clone(),
+            ..BundleManifest::default()
+        };
+        let mut reached = HashSet::new();
+        while let Some((kind, name, reason)) = queue.pop_front() {
+            let Some(&(path, decl)) = index.get(&(kind, name.as_str())) else {
+                continue;
+            };
+            if !reached.insert((kind, name.clone())) {
+                continue;
             }
-            let unused_workflows: Vec<u32> = ir
-                .symbol_table
-                .workflows
-                .keys()
-                .filter(|id| !used_workflows.contains(id))
-                .cloned()
-                .collect();
-            for id in unused_workflows {
-                ir.symbol_table.workflows.remove(&id);
+            queue.extend(references(decl));
+            manifest.kept.push(KeptDeclaration { kind, name, file: path.clone(), reason });
+        }
+
+        let mut files = VecDeque::new();
+        for kept in &manifest.kept {
+            files.push_back((kept.file.clone(), FileReason::Declares { kind: kept.kind, name: kept.name.clone() }));
+        }
+        let mut kept_files = HashSet::new();
+        while let Some((path, reason)) = files.pop_front() {
+            if !modules.modules().contains_key(&path) || !kept_files.insert(path.clone()) {
+                continue;
             }
-            let unused_contexts: Vec<u32> = ir
-                .symbol_table
-                .contexts
-                .keys()
-                .filter(|id| !used_contexts.contains(id))
-                .cloned()
-                .collect();
-            for id in unused_contexts {
-                ir.symbol_table.contexts.remove(&id);
+            let mut loads: Vec<PathBuf> = modules.get_dependencies(&path).into_iter().collect();
+            loads.sort();
+            files.extend(loads.into_iter().map(|load| (load, FileReason::Loaded { by: path.clone() })));
+            manifest.files.push(KeptFile { path, reason });
+        }
+
+        for (path, decl) in declarations {
+            if let Some((kind, name)) = named(decl) {
+                if !reached.contains(&(kind, name.to_string())) {
+                    manifest.dropped.push(DroppedDeclaration { kind, name: name.to_string(), file: path.clone() });
+                }
             }
-            *binary = serializer
-                .serialize(ir, Some(path))
-                .map_err(|e| CompileError::SerializationError(e.to_string()))?;
         }
-        Ok(())
+        let mut dropped_files: Vec<PathBuf> = modules
+            .modules()
+            .keys()
+            .filter(|path| !kept_files.contains(*path))
+            .cloned()
+            .collect();
+        dropped_files.sort();
+        manifest.dropped_files = dropped_files;
+        Ok(manifest)
     }
 }
 impl Default for Bundler {
@@ -277,101 +314,220 @@ impl Default for Bundler {
         Self::new()
     }
 }
-struct BundleBuilder {
-    files: HashMap<PathBuf, HelixBinary>,
-    dependencies: HashMap<PathBuf, HashSet<PathBuf>>,
+/// The kinds of declaration tree shaking follows references between
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeclarationKind {
+    Workflow,
+    Crew,
+    Agent,
+    Context,
+}
+impl DeclarationKind {
+    /// In the order an entry point's name is looked up
+    const ALL: [DeclarationKind; 4] = [Self::Workflow, Self::Crew, Self::Agent, Self::Context];
+}
+/// Why a declaration survived tree shaking
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "because", rename_all = "snake_case")]
+pub enum KeepReason {
+    EntryPoint,
+    /// Tree shaking was off, so everything is kept
+    Unshaken,
+    /// Runs, or is one of the crew of, a workflow step
+    StepAgent { workflow: String, step: String },
+    CrewMember { crew: String },
+    CrewManager { crew: String },
+    /// Named by the `context` of a workflow or one of its steps
+    Context { of: String },
+}
+/// Why a file is part of the bundle
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "because", rename_all = "snake_case")]
+pub enum FileReason {
+    Unshaken,
+    Declares { kind: DeclarationKind, name: String },
+    Loaded { by: PathBuf },
+}
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeptDeclaration {
+    pub kind: DeclarationKind,
+    pub name: String,
+    pub file: PathBuf,
+    pub reason: KeepReason,
+}
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DroppedDeclaration {
+    pub kind: DeclarationKind,
+    pub name: String,
+    pub file: PathBuf,
+}
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeptFile {
+    pub path: PathBuf,
+    pub reason: FileReason,
+}
+/// What went into a bundle and why. Declarations without a name, such as
+/// `project`, `memory` or sections, are kept with the file holding them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub tree_shaken: bool,
+    pub entry_points: Vec<String>,
+    pub files: Vec<KeptFile>,
+    pub kept: Vec<KeptDeclaration>,
+    pub dropped: Vec<DroppedDeclaration>,
+    pub dropped_files: Vec<PathBuf>,
 }
-impl BundleBuilder {
-    fn new() -> Self {
+impl BundleManifest {
+    /// The manifest stored in a bundle, if `binary` is one
+    pub fn from_binary(binary: &HelixBinary) -> Result<Option<Self>, CompileError> {
+        binary
+            .metadata
+            .extra
+            .get(MANIFEST_KEY)
+            .map(|json| serde_json::from_str(json))
+            .transpose()
+            .map_err(|e| CompileError::DeserializationError(e.to_string()))
+    }
+    fn unshaken(modules: &ModuleSystem, declarations: &[(PathBuf, Declaration)]) -> Self {
+        let mut files: Vec<PathBuf> = modules.modules().keys().cloned().collect();
+        files.sort();
         Self {
-            files: HashMap::new(),
-            dependencies: HashMap::new(),
+            tree_shaken: false,
+            entry_points: Vec::new(),
+            files: files
+                .into_iter()
+                .map(|path| KeptFile { path, reason: FileReason::Unshaken })
+                .collect(),
+            kept: declarations
+                .iter()
+                .filter_map(|(path, decl)| {
+                    let (kind, name) = named(decl)?;
+                    Some(KeptDeclaration {
+                        kind,
+                        name: name.to_string(),
+                        file: path.clone(),
+                        reason: KeepReason::Unshaken,
+                    })
+                })
+                .collect(),
+            dropped: Vec::new(),
+            dropped_files: Vec::new(),
         }
     }
-    fn add_file(&mut self, path: PathBuf, binary: HelixBinary) {
-        self.files.insert(path, binary);
-    }
-    #[allow(dead_code)]
-    fn add_dependency(&mut self, from: PathBuf, to: PathBuf) {
-        self.dependencies.entry(from).or_insert_with(HashSet::new).insert(to);
+}
+/// Deepest directory holding every one of `files`
+fn common_dir(files: &[PathBuf]) -> PathBuf {
+    let mut dirs = files.iter().map(|file| normalize(file.parent().unwrap_or(Path::new(""))));
+    let Some(mut common) = dirs.next() else {
+        return PathBuf::new();
+    };
+    for dir in dirs {
+        while !dir.starts_with(&common) && common.pop() {}
     }
-    fn build(self) -> Result<HelixBinary, CompileError> {
-        if self.files.is_empty() {
-            return Err(CompileError::IoError("No files in bundle".to_string()));
+    common
+}
+/// Rewrite the relative `@file` paths in `decl`, written against `from`, to
+/// reach the same files from `to`
+fn rebase_declaration(decl: &mut Declaration, from: &Path, to: &Path) {
+    let mut maps = Vec::new();
+    match decl {
+        Declaration::Project(project) => maps.push(&mut project.properties),
+        Declaration::Agent(agent) => maps.push(&mut agent.properties),
+        Declaration::Workflow(workflow) => {
+            maps.push(&mut workflow.properties);
+            maps.extend(workflow.steps.iter_mut().map(|step| &mut step.properties));
         }
-        let mut merged = self.files.values().next().unwrap().clone();
-        for binary in self.files.values().skip(1) {
-            Self::merge_binary(&mut merged, binary)?;
+        Declaration::Memory(memory) => {
+            maps.push(&mut memory.properties);
+            maps.extend(memory.embeddings.as_mut().map(|embeddings| &mut embeddings.properties));
         }
-        merged.metadata.extra.insert("bundle".to_string(), "true".to_string());
-        merged
-            .metadata
-            .extra
-            .insert("bundle_files".to_string(), self.files.len().to_string());
-        merged.checksum = merged.calculate_checksum();
-        Ok(merged)
-    }
-    fn merge_binary(
-        target: &mut HelixBinary,
-        source: &HelixBinary,
-    ) -> Result<(), CompileError> {
-        Self::merge_symbol_tables(&mut target.symbol_table, &source.symbol_table);
-        for section in &source.data_sections {
-            let existing = target
-                .data_sections
-                .iter_mut()
-                .find(|s| {
-                    std::mem::discriminant(&s.section_type)
-                        == std::mem::discriminant(&section.section_type)
-                });
-            if let Some(existing_section) = existing {
-                Self::merge_sections(existing_section, section)?;
-            } else {
-                target.data_sections.push(section.clone());
-            }
+        Declaration::Context(context) => {
+            maps.push(&mut context.properties);
+            maps.extend(context.variables.as_mut());
         }
-        Ok(())
-    }
-    fn merge_symbol_tables(
-        target: &mut crate::dna::hel::binary::SymbolTable,
-        source: &crate::dna::hel::binary::SymbolTable,
-    ) {
-        for string in &source.strings {
-            if !target.strings.contains(string) {
-                let id = target.strings.len() as u32;
-                target.strings.push(string.clone());
-                target.string_map.insert(string.clone(), id);
-            }
+        Declaration::Crew(crew) => maps.push(&mut crew.properties),
+        Declaration::Pipeline(_) => {}
+        Declaration::Plugin(plugin) => maps.push(&mut plugin.config),
+        Declaration::Database(database) => {
+            maps.push(&mut database.properties);
+            maps.extend(database.vector_index.as_mut().map(|index| &mut index.properties));
         }
-        for (name, id) in &source.agents {
-            if !target.agents.contains_key(name) {
-                target.agents.insert(name.clone(), *id);
-            }
+        Declaration::Task(task) => maps.push(&mut task.properties),
+        Declaration::Load(load) => maps.push(&mut load.properties),
+        Declaration::Section(section) => maps.push(&mut section.properties),
+    }
+    for expr in maps.into_iter().flat_map(|map| map.values_mut()) {
+        rebase_expression(expr, from, to);
+    }
+}
+fn rebase_expression(expr: &mut Expression, from: &Path, to: &Path) {
+    match expr {
+        Expression::String(text) => *text = rebase_file_paths(text, from, to),
+        Expression::TextBlock(lines) => {
+            lines.iter_mut().for_each(|line| *line = rebase_file_paths(line, from, to));
         }
-        for (name, id) in &source.workflows {
-            if !target.workflows.contains_key(name) {
-                target.workflows.insert(name.clone(), *id);
-            }
+        Expression::Array(items) => items.iter_mut().for_each(|item| rebase_expression(item, from, to)),
+        Expression::Object(entries) => {
+            entries.values_mut().for_each(|value| rebase_expression(value, from, to));
         }
-        for (name, id) in &source.contexts {
-            if !target.contexts.contains_key(name) {
-                target.contexts.insert(name.clone(), *id);
+        _ => {}
+    }
+}
+fn named(decl: &Declaration) -> Option<(DeclarationKind, &str)> {
+    match decl {
+        Declaration::Workflow(workflow) => Some((DeclarationKind::Workflow, &workflow.name)),
+        Declaration::Crew(crew) => Some((DeclarationKind::Crew, &crew.name)),
+        Declaration::Agent(agent) => Some((DeclarationKind::Agent, &agent.name)),
+        Declaration::Context(context) => Some((DeclarationKind::Context, &context.name)),
+        _ => None,
+    }
+}
+fn property_name(properties: &HashMap<String, Expression>, key: &str) -> Option<String> {
+    match properties.get(key)? {
+        Expression::Reference(name) => Some(name.clone()),
+        other => other.as_string(),
+    }
+}
+/// The declarations `decl` refers to by name, and why each is needed
+fn references(decl: &Declaration) -> Vec<(DeclarationKind, String, KeepReason)> {
+    let mut refs = Vec::new();
+    let context = |properties, of: &str| {
+        property_name(properties, "context")
+            .map(|name| (DeclarationKind::Context, name, KeepReason::Context { of: of.to_string() }))
+    };
+    match decl {
+        Declaration::Workflow(workflow) => {
+            refs.extend(context(&workflow.properties, &workflow.name));
+            for step in &workflow.steps {
+                let (workflow, step_name) = (&workflow.name, &step.name);
+                let by_step = || KeepReason::StepAgent { workflow: workflow.clone(), step: step_name.clone() };
+                if let Some(agent) = &step.agent {
+                    refs.push((DeclarationKind::Agent, agent.clone(), by_step()));
+                }
+                // `crew` lexes as a keyword, so a step's crew is usually
+                // parsed into its properties rather than `StepDecl::crew`
+                let crew = step.crew.iter().flatten().cloned().chain(
+                    step.properties.get("crew").and_then(Expression::as_array).into_iter().flatten()
+                        .filter_map(|member| member.as_string()),
+                );
+                for member in crew {
+                    refs.push((DeclarationKind::Agent, member, by_step()));
+                }
+                refs.extend(context(&step.properties, workflow));
             }
         }
-        for (name, id) in &source.crews {
-            if !target.crews.contains_key(name) {
-                target.crews.insert(name.clone(), *id);
+        Declaration::Crew(crew) => {
+            for agent in &crew.agents {
+                refs.push((DeclarationKind::Agent, agent.clone(), KeepReason::CrewMember { crew: crew.name.clone() }));
+            }
+            if let Some(manager) = property_name(&crew.properties, "manager") {
+                refs.push((DeclarationKind::Agent, manager, KeepReason::CrewManager { crew: crew.name.clone() }));
             }
         }
+        _ => {}
     }
-    fn merge_sections(
-        target: &mut DataSection,
-        source: &DataSection,
-    ) -> Result<(), CompileError> {
-        target.data.extend_from_slice(&source.data);
-        target.size += source.size;
-        Ok(())
-    }
+    refs
 }
 #[cfg(test)]
 mod tests {
@@ -404,17 +560,176 @@ mod tests {
         assert!(bundler.tree_shake);
         assert!(bundler.verbose);
     }
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::TempDir::new().unwrap();
+        let write = |name: &str, source: &str| fs::write(dir.path().join(name), source).unwrap();
+        write("main.hlx", r#"
+load lib {
+}
+workflow "publish" {
+    context = "production"
+    step "gather" {
+        agent = "researcher"
+    }
+    step "review" {
+        crew = ["writer"]
+    }
+}
+"#);
+        write("lib.hlx", r#"
+agent "researcher" {
+    model = "gpt-4"
 }
+agent "writer" {
+    model = "claude"
+}
+agent "unused" {
+    model = "gpt-4"
+}
+context "production" {
+    environment = "prod"
+}
+"#);
+        write("extra.hlx", r#"
+agent "lead" {
+    model = "gpt-4"
+}
+crew "editors" {
+    agents ["writer"]
+    process = "hierarchical"
+    manager = "lead"
+}
+"#);
+        dir
+    }
+    fn names(manifest: &BundleManifest) -> Vec<&str> {
+        manifest.kept.iter().map(|kept| kept.name.as_str()).collect()
+    }
+    #[test]
+    fn test_tree_shaking_keeps_what_workflows_reach() {
+        let dir = project();
+        let binary = Bundler::new()
+            .with_tree_shaking(true)
+            .bundle_directory(dir.path(), OptimizationLevel::Zero)
+            .unwrap();
+        let manifest = BundleManifest::from_binary(&binary).unwrap().unwrap();
+        assert!(manifest.tree_shaken);
+        assert_eq!(names(&manifest), ["publish", "production", "researcher", "writer"]);
+        assert_eq!(manifest.kept[1].reason, KeepReason::Context { of: "publish".to_string() });
+        assert_eq!(
+            manifest.kept[3].reason,
+            KeepReason::StepAgent { workflow: "publish".to_string(), step: "review".to_string() }
+        );
+        let dropped: Vec<&str> = manifest.dropped.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(dropped, ["unused", "lead", "editors"]);
+        assert_eq!(manifest.dropped_files, [dir.path().join("extra.hlx")]);
+        assert_eq!(binary.metadata.extra.get("bundle_files"), Some(&"2".to_string()));
+
+        let mut agents: Vec<&String> = binary.symbol_table.agents.keys().collect();
+        agents.sort();
+        assert_eq!(agents, ["researcher", "writer"]);
+        assert!(binary.symbol_table.crews.is_empty());
+        assert!(binary.symbol_table.contexts.contains_key("production"));
+    }
+    #[test]
+    fn test_tree_shaking_from_a_named_root() {
+        let dir = project();
+        let binary = Bundler::new()
+            .with_tree_shaking(true)
+            .entry("editors")
+            .bundle_directory(dir.path(), OptimizationLevel::Zero)
+            .unwrap();
+        let manifest = BundleManifest::from_binary(&binary).unwrap().unwrap();
+        assert_eq!(names(&manifest), ["editors", "writer", "lead"]);
+        assert_eq!(manifest.kept[1].reason, KeepReason::CrewMember { crew: "editors".to_string() });
+        assert_eq!(manifest.kept[2].reason, KeepReason::CrewManager { crew: "editors".to_string() });
+        let files: Vec<&FileReason> = manifest.files.iter().map(|f| &f.reason).collect();
+        assert_eq!(files, [
+            &FileReason::Declares { kind: DeclarationKind::Crew, name: "editors".to_string() },
+            &FileReason::Declares { kind: DeclarationKind::Agent, name: "writer".to_string() },
+        ]);
+        assert_eq!(manifest.dropped_files, [dir.path().join("main.hlx")]);
 
+        let missing = Bundler::new()
+            .with_tree_shaking(true)
+            .entry("nowhere")
+            .bundle_directory(dir.path(), OptimizationLevel::Zero);
+        assert!(matches!(missing, Err(CompileError::ValidationError(_))));
+    }
+    #[test]
+    fn test_loaded_files_are_kept_and_bundled() {
+        let dir = project();
+        let main = dir.path().join("main.hlx");
+        let binary = Bundler::new()
+            .with_tree_shaking(true)
+            .entry("researcher")
+            .bundle_files(&[main.clone()], OptimizationLevel::Zero)
+            .unwrap();
+        let manifest = BundleManifest::from_binary(&binary).unwrap().unwrap();
+        assert_eq!(names(&manifest), ["researcher"]);
+        assert_eq!(manifest.files[0].path, dir.path().join("lib.hlx"));
+        assert_eq!(manifest.dropped_files, [main]);
+
+        let everything = Bundler::new()
+            .bundle_directory(dir.path(), OptimizationLevel::Zero)
+            .unwrap();
+        let manifest = BundleManifest::from_binary(&everything).unwrap().unwrap();
+        assert!(!manifest.tree_shaken);
+        assert!(manifest.dropped.is_empty());
+        assert_eq!(manifest.kept.len(), 7);
+        assert_eq!(everything.metadata.extra.get("bundle_files"), Some(&"3".to_string()));
+    }
+    #[test]
+    fn test_relative_file_reads_resolve_from_the_bundle() {
+        let dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("agents")).unwrap();
+        fs::write(dir.path().join("agents/prompt.txt"), "Write clearly").unwrap();
+        fs::write(
+            dir.path().join("agents/writer.hlx"),
+            "agent \"writer\" {\n    model = \"claude\"\n    prompt = \"@file.read('prompt.txt')\"\n}\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("main.hlx"), "agent \"lead\" {\n    model = \"gpt-4\"\n}\n").unwrap();
+        let binary = Bundler::new().bundle_directory(dir.path(), OptimizationLevel::Zero).unwrap();
+        let ir = crate::dna::mds::serializer::BinarySerializer::new(false)
+            .deserialize_to_ir(&binary)
+            .unwrap();
+        assert!(ir.string_pool.strings.iter().any(|s| s == "@file.read('agents/prompt.txt')"));
+        assert_eq!(binary.metadata.source_path, Some(dir.path().display().to_string()));
+    }
+}
+
+/// Settings for [`bundle_command`]
+#[derive(Debug, Clone)]
+pub struct BundleOptions {
+    /// File name patterns to bundle besides `*.hlx`
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    /// Workflows, crews or agents to keep when tree shaking; every workflow
+    /// when empty
+    pub entry: Vec<String>,
+    pub tree_shake: bool,
+    pub optimize: u8,
+    pub verbose: bool,
+}
+impl Default for BundleOptions {
+    fn default() -> Self {
+        Self {
+            include: Vec::new(),
+            exclude: Vec::new(),
+            entry: Vec::new(),
+            tree_shake: false,
+            optimize: 2,
+            verbose: false,
+        }
+    }
+}
 pub fn bundle_command(
     directory: PathBuf,
     output: PathBuf,
-    include: Vec<String>,
-    exclude: Vec<String>,
-    tree_shake: bool,
-    optimize: u8,
-    verbose: bool,
+    options: BundleOptions,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    let BundleOptions { include, exclude, entry, tree_shake, optimize, verbose } = options;
     if verbose {
         println!("📦 Bundling directory: {}", directory.display());
         if !include.is_empty() {
@@ -424,6 +739,9 @@ pub fn bundle_command(
             println!("  Exclude patterns: {:?}", exclude);
         }
         println!("  Tree shaking: {}", if tree_shake { "Enabled" } else { "Disabled" });
+        if !entry.is_empty() {
+            println!("  Entry points: {:?}", entry);
+        }
     }
     let mut bundler = Bundler::new().with_tree_shaking(tree_shake).verbose(verbose);
     for pattern in include {
@@ -432,6 +750,9 @@ pub fn bundle_command(
     for pattern in exclude {
         bundler = bundler.exclude(&pattern);
     }
+    for name in entry {
+        bundler = bundler.entry(&name);
+    }
     let binary = bundler
         .bundle_directory(&directory, OptimizationLevel::from(optimize))?;
     let serializer = crate::mds::serializer::BinarySerializer::new(true);
@@ -441,5 +762,13 @@ pub fn bundle_command(
     if let Some(file_count) = binary.metadata.extra.get("bundle_files") {
         println!("  Files bundled: {}", file_count);
     }
+    if let Some(manifest) = BundleManifest::from_binary(&binary)? {
+        let manifest_path = output.with_extension("manifest.json");
+        fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+        println!(
+            "  Kept {} declarations, dropped {}; manifest: {}",
+            manifest.kept.len(), manifest.dropped.len(), manifest_path.display()
+        );
+    }
     Ok(())
 }
\ No newline at end of file
@@ -1,5 +1,7 @@
+use crate::ops::memo::{EvalCache, MemoEntry};
 use std::path::PathBuf;
 use std::fs;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use anyhow::{Result, Context};
 pub fn manage_cache(action: CacheAction, verbose: bool) -> Result<()> {
     match action {
@@ -9,10 +11,11 @@ pub fn manage_cache(action: CacheAction, verbose: bool) -> Result<()> {
         CacheAction::Size => show_cache_size(verbose),
     }
 }
-#[derive(Debug)]
-enum CacheAction {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheAction {
     Show,
     Clear,
+    /// Remove entries whose input files changed (`prune` is an alias)
     Clean,
     Size,
 }
@@ -22,9 +25,11 @@ impl std::str::FromStr for CacheAction {
         match s {
             "show" => Ok(CacheAction::Show),
             "clear" => Ok(CacheAction::Clear),
-            "clean" => Ok(CacheAction::Clean),
+            "clean" | "prune" => Ok(CacheAction::Clean),
             "size" => Ok(CacheAction::Size),
-            _ => Err(anyhow::anyhow!("Invalid cache action: {}", s)),
+            _ => Err(anyhow::anyhow!(
+                "Invalid cache action: {} (expected show, clear, clean, prune or size)", s
+            )),
         }
     }
 }
@@ -35,19 +40,43 @@ fn show_cache_info(verbose: bool) -> Result<()> {
         println!("   Status: Not created yet");
         return Ok(());
     }
+    let cache = EvalCache::new(&cache_dir);
+    let entries = cache.entries();
+    let stale = entries.iter().filter(|(_, entry)| !entry.is_fresh()).count();
     let cache_size = calculate_directory_size(&cache_dir)?;
-    let file_count = count_files_in_directory(&cache_dir)?;
     println!("📁 Cache Information");
     println!("===================");
     println!("Location: {}", cache_dir.display());
     println!("Size: {}", format_size(cache_size));
-    println!("Files: {}", file_count);
-    if verbose {
+    println!("Entries: {} ({} stale)", entries.len(), stale);
+    if verbose && !entries.is_empty() {
         println!("\n📋 Cache Contents:");
-        list_cache_contents(&cache_dir, 0)?;
+        for (_, entry) in &entries {
+            list_entry(entry);
+        }
+    }
+    if stale > 0 {
+        println!("\nRun `hlx cache prune` to remove stale entries");
     }
     Ok(())
 }
+fn list_entry(entry: &MemoEntry) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let age = Duration::from_secs(now.saturating_sub(entry.created_at));
+    let stale = entry.stale_inputs();
+    let status = if stale.is_empty() { "fresh" } else { "stale" };
+    println!(
+        "  @{}({}) - {}, {} ago", entry.operator, entry.args, status,
+        format_duration(age)
+    );
+    for input in entry.inputs.keys() {
+        let marker = if stale.contains(&input.as_path()) { " (changed)" } else { "" };
+        println!("      {}{}", input.display(), marker);
+    }
+}
 fn clear_cache(verbose: bool) -> Result<()> {
     let cache_dir = get_cache_directory()?;
     if !cache_dir.exists() {
@@ -57,12 +86,12 @@ fn clear_cache(verbose: bool) -> Result<()> {
     if verbose {
         println!("🗑️  Clearing cache directory: {}", cache_dir.display());
     }
-    let cache_size = calculate_directory_size(&cache_dir)?;
-    let file_count = count_files_in_directory(&cache_dir)?;
-    fs::remove_dir_all(&cache_dir).context("Failed to remove cache directory")?;
+    let (removed_files, freed_space) = EvalCache::new(&cache_dir)
+        .clear()
+        .context("Failed to clear cache directory")?;
     println!("✅ Cache cleared successfully!");
-    println!("  Removed: {} files", file_count);
-    println!("  Freed: {}", format_size(cache_size));
+    println!("  Removed: {} files", removed_files);
+    println!("  Freed: {}", format_size(freed_space));
     Ok(())
 }
 fn clean_cache(verbose: bool) -> Result<()> {
@@ -71,13 +100,16 @@ fn clean_cache(verbose: bool) -> Result<()> {
         println!("✅ Cache is already clean");
         return Ok(());
     }
+    let cache = EvalCache::new(&cache_dir);
     if verbose {
         println!("🧹 Cleaning cache directory: {}", cache_dir.display());
+        for (_, entry) in cache.entries().iter().filter(|(_, entry)| !entry.is_fresh()) {
+            println!("  Removed stale: @{}({})", entry.operator, entry.args);
+        }
     }
-    let mut removed_files = 0;
-    let mut freed_space = 0;
-    clean_expired_entries(&cache_dir, &mut removed_files, &mut freed_space, verbose)?;
-    clean_orphaned_files(&cache_dir, &mut removed_files, &mut freed_space, verbose)?;
+    let (removed_files, freed_space) = cache
+        .prune()
+        .context("Failed to clean cache directory")?;
     if removed_files > 0 {
         println!("✅ Cache cleaned successfully!");
         println!("  Removed: {} files", removed_files);
@@ -103,103 +135,6 @@ fn show_cache_size(verbose: bool) -> Result<()> {
     }
     Ok(())
 }
-fn clean_expired_entries(
-    cache_dir: &PathBuf,
-    removed_files: &mut usize,
-    freed_space: &mut u64,
-    verbose: bool,
-) -> Result<()> {
-    let entries = fs::read_dir(cache_dir).context("Failed to read cache directory")?;
-    for entry in entries {
-        let entry = entry.context("Failed to read directory entry")?;
-        let path = entry.path();
-        if path.is_file() {
-            let metadata = fs::metadata(&path).context("Failed to read file metadata")?;
-            let modified = metadata
-                .modified()
-                .context("Failed to get file modification time")?;
-            let age = std::time::SystemTime::now()
-                .duration_since(modified)
-                .unwrap_or_default();
-            if age.as_secs() > 24 * 3600 {
-                let file_size = metadata.len();
-                fs::remove_file(&path).context("Failed to remove expired file")?;
-                *removed_files += 1;
-                *freed_space += file_size;
-                if verbose {
-                    println!(
-                        "  Removed expired: {}", path.file_name().unwrap()
-                        .to_string_lossy()
-                    );
-                }
-            }
-        } else if path.is_dir() {
-            clean_expired_entries(&path, removed_files, freed_space, verbose)?;
-        }
-    }
-    Ok(())
-}
-fn clean_orphaned_files(
-    cache_dir: &PathBuf,
-    removed_files: &mut usize,
-    freed_space: &mut u64,
-    verbose: bool,
-) -> Result<()> {
-    let entries = fs::read_dir(cache_dir).context("Failed to read cache directory")?;
-    for entry in entries {
-        let entry = entry.context("Failed to read directory entry")?;
-        let path = entry.path();
-        if path.is_file() {
-            if let Some(file_name) = path.file_name().and_then(|n| n.to_str()) {
-                if file_name.ends_with(".hlxb") {
-                    let source_name = file_name.replace(".hlxb", ".hlx");
-                    let source_path = cache_dir.join(&source_name);
-                    if !source_path.exists() {
-                        let file_size = fs::metadata(&path)?.len();
-                        fs::remove_file(&path)
-                            .context("Failed to remove orphaned file")?;
-                        *removed_files += 1;
-                        *freed_space += file_size;
-                        if verbose {
-                            println!("  Removed orphaned: {}", file_name);
-                        }
-                    }
-                }
-            }
-        } else if path.is_dir() {
-            clean_orphaned_files(&path, removed_files, freed_space, verbose)?;
-        }
-    }
-    Ok(())
-}
-fn list_cache_contents(dir: &PathBuf, depth: usize) -> Result<()> {
-    let entries = fs::read_dir(dir).context("Failed to read directory")?;
-    let mut entries: Vec<_> = entries
-        .collect::<Result<Vec<_>, _>>()?
-        .into_iter()
-        .collect();
-    entries.sort_by_key(|e| e.file_name());
-    for entry in entries {
-        let path = entry.path();
-        let indent = "  ".repeat(depth);
-        if path.is_file() {
-            let metadata = fs::metadata(&path)?;
-            let size = format_size(metadata.len());
-            let modified = metadata.modified()?;
-            let age = std::time::SystemTime::now()
-                .duration_since(modified)
-                .unwrap_or_default();
-            println!(
-                "{}{} ({} - {} ago)", indent, path.file_name().unwrap()
-                .to_string_lossy(), size, format_duration(age)
-            );
-        } else if path.is_dir() {
-            println!("{}{}/", indent, path.file_name().unwrap().to_string_lossy());
-            list_cache_contents(&path, depth + 1)?;
-        }
-    }
-    Ok(())
-}
 fn calculate_directory_size(dir: &PathBuf) -> Result<u64> {
     let mut total_size = 0;
     let entries = fs::read_dir(dir).context("Failed to read directory")?;
@@ -247,7 +182,7 @@ fn format_size(bytes: u64) -> String {
         format!("{:.1} {}", size, UNITS[unit_index])
     }
 }
-fn format_duration(duration: std::time::Duration) -> String {
+fn format_duration(duration: Duration) -> String {
     let seconds = duration.as_secs();
     if seconds < 60 {
         format!("{}s", seconds)
@@ -260,7 +195,5 @@ fn format_duration(duration: std::time::Duration) -> String {
     }
 }
 fn get_cache_directory() -> Result<PathBuf> {
-    let home_dir = dirs::home_dir()
-        .ok_or_else(|| anyhow::anyhow!("Failed to get home directory"))?;
-    Ok(home_dir.join(".baton").join("cache"))
-}
\ No newline at end of file
+    EvalCache::default_dir().context("Failed to locate the helix cache directory")
+}
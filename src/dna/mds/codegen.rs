@@ -4,6 +4,8 @@ use serde::{Serialize, Deserialize};
 use crate::dna::atp::ast::*;
 use crate::dna::atp::types::TimeUnit;
 use crate::dna::atp::types::Duration;
+use crate::dna::atp::quantity::Quantity;
+use crate::dna::hel::binary::sorted_map;
 pub use crate::dna::atp::types::SecretRef;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,12 +37,18 @@ impl Default for Metadata {
         }
     }
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SymbolTable {
+    #[serde(serialize_with = "sorted_map")]
     pub agents: HashMap<u32, AgentSymbol>,
+    #[serde(serialize_with = "sorted_map")]
     pub workflows: HashMap<u32, WorkflowSymbol>,
+    #[serde(serialize_with = "sorted_map")]
     pub contexts: HashMap<u32, ContextSymbol>,
+    #[serde(serialize_with = "sorted_map")]
     pub crews: HashMap<u32, CrewSymbol>,
+    #[serde(serialize_with = "sorted_map")]
+    pub sections: HashMap<u32, SectionSymbol>,
     pub next_id: u32,
 }
 impl Default for SymbolTable {
@@ -50,11 +58,12 @@ impl Default for SymbolTable {
             workflows: HashMap::new(),
             contexts: HashMap::new(),
             crews: HashMap::new(),
+            sections: HashMap::new(),
             next_id: 1,
         }
     }
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct AgentSymbol {
     pub id: u32,
     pub name_idx: u32,
@@ -65,7 +74,7 @@ pub struct AgentSymbol {
     pub capabilities: Vec<u32>,
     pub backstory_idx: Option<u32>,
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WorkflowSymbol {
     pub id: u32,
     pub name_idx: u32,
@@ -73,16 +82,17 @@ pub struct WorkflowSymbol {
     pub steps: Vec<u32>,
     pub pipeline: Option<Vec<u32>>,
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ContextSymbol {
     pub id: u32,
     pub name_idx: u32,
     pub environment_idx: u32,
     pub debug: bool,
     pub max_tokens: Option<u64>,
+    #[serde(serialize_with = "sorted_map")]
     pub secrets: HashMap<u32, SecretType>,
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CrewSymbol {
     pub id: u32,
     pub name_idx: u32,
@@ -90,7 +100,13 @@ pub struct CrewSymbol {
     pub process_type: ProcessTypeIR,
     pub manager_id: Option<u32>,
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// A free-form `name { ... }` block; its entries are `SetProperty`s
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SectionSymbol {
+    pub id: u32,
+    pub name_idx: u32,
+}
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum TriggerType {
     Manual,
     Schedule(u32),
@@ -98,20 +114,20 @@ pub enum TriggerType {
     Event(u32),
     FileWatch(u32),
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ProcessTypeIR {
     Sequential,
     Hierarchical,
     Parallel,
     Consensus,
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SecretType {
     Environment(u32),
     Vault(u32),
     File(u32),
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Instruction {
     DeclareAgent(u32),
     DeclareWorkflow(u32),
@@ -124,8 +140,9 @@ pub enum Instruction {
     DefinePipeline { workflow: u32, nodes: Vec<PipelineNodeIR> },
     ResolveReference { ref_type: ReferenceType, index: u32 },
     SetMetadata { key: u32, value: u32 },
+    DeclareSection(u32),
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct StepDefinition {
     pub id: u32,
     pub name_idx: u32,
@@ -137,13 +154,13 @@ pub struct StepDefinition {
     pub depends_on: Vec<u32>,
     pub retry: Option<RetryPolicy>,
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RetryPolicy {
     pub max_attempts: u32,
     pub delay: DurationIR,
     pub backoff: BackoffStrategyIR,
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BackoffStrategyIR {
     Fixed,
     Linear,
@@ -161,7 +178,7 @@ pub enum TimeUnitIR {
     Hours,
     Days,
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PipelineNodeIR {
     Step(u32),
     Parallel(Vec<PipelineNodeIR>),
@@ -171,7 +188,7 @@ pub enum PipelineNodeIR {
         else_branch: Option<Box<PipelineNodeIR>>,
     },
 }
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ReferenceType {
     Environment,
     Memory,
@@ -183,7 +200,13 @@ pub enum ConstantValue {
     Number(f64),
     Bool(bool),
     Duration(DurationIR),
+    Quantity(Quantity),
     Null,
+    Array(#[serde(deserialize_with = "crate::dna::hel::limits::nested")] Vec<ConstantValue>),
+    /// Entries sorted by key
+    Object(#[serde(deserialize_with = "crate::dna::hel::limits::nested")] Vec<(u32, ConstantValue)>),
+    /// `$VAR` or `@name` as written, resolved when the config is loaded
+    Reference(u32),
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StringPool {
@@ -240,6 +263,127 @@ impl ConstantPool {
         self.constants.get(idx as usize)
     }
 }
+impl HelixIR {
+    /// Visit every string pool index held by the symbols and instructions
+    pub fn strings_mut(&mut self, f: &mut impl FnMut(&mut u32)) {
+        self.symbol_table.strings_mut(f);
+        for instruction in &mut self.instructions {
+            instruction.strings_mut(f);
+        }
+    }
+}
+impl SymbolTable {
+    pub fn strings_mut(&mut self, f: &mut impl FnMut(&mut u32)) {
+        for agent in self.agents.values_mut() {
+            f(&mut agent.name_idx);
+            f(&mut agent.model_idx);
+            f(&mut agent.role_idx);
+            agent.capabilities.iter_mut().for_each(&mut *f);
+            if let Some(idx) = &mut agent.backstory_idx {
+                f(idx);
+            }
+        }
+        for workflow in self.workflows.values_mut() {
+            f(&mut workflow.name_idx);
+            match &mut workflow.trigger_type {
+                TriggerType::Manual => {}
+                TriggerType::Schedule(idx)
+                | TriggerType::Webhook(idx)
+                | TriggerType::Event(idx)
+                | TriggerType::FileWatch(idx) => f(idx),
+            }
+        }
+        for context in self.contexts.values_mut() {
+            f(&mut context.name_idx);
+            f(&mut context.environment_idx);
+            context.secrets = context
+                .secrets
+                .drain()
+                .map(|(mut key, mut secret)| {
+                    f(&mut key);
+                    secret.strings_mut(f);
+                    (key, secret)
+                })
+                .collect();
+        }
+        for crew in self.crews.values_mut() {
+            f(&mut crew.name_idx);
+        }
+        for section in self.sections.values_mut() {
+            f(&mut section.name_idx);
+        }
+    }
+}
+impl SecretType {
+    pub fn strings_mut(&mut self, f: &mut impl FnMut(&mut u32)) {
+        match self {
+            SecretType::Environment(idx) | SecretType::Vault(idx) | SecretType::File(idx) => {
+                f(idx)
+            }
+        }
+    }
+}
+impl Instruction {
+    pub fn strings_mut(&mut self, f: &mut impl FnMut(&mut u32)) {
+        match self {
+            Instruction::SetProperty { key, value, .. } => {
+                f(key);
+                value.strings_mut(f);
+            }
+            Instruction::SetCapability { capability, .. } => f(capability),
+            Instruction::SetSecret { key, secret, .. } => {
+                f(key);
+                secret.strings_mut(f);
+            }
+            Instruction::DefineStep { step, .. } => {
+                f(&mut step.name_idx);
+                if let Some(idx) = &mut step.task_idx {
+                    f(idx);
+                }
+            }
+            Instruction::DefinePipeline { nodes, .. } => {
+                nodes.iter_mut().for_each(|node| node.strings_mut(f));
+            }
+            Instruction::SetMetadata { key, value } => {
+                f(key);
+                f(value);
+            }
+            // The reference is named by its text, as in `ConstantValue::Reference`
+            Instruction::ResolveReference { index, .. } => f(index),
+            _ => {}
+        }
+    }
+}
+impl ConstantValue {
+    pub fn strings_mut(&mut self, f: &mut impl FnMut(&mut u32)) {
+        match self {
+            ConstantValue::String(idx) | ConstantValue::Reference(idx) => f(idx),
+            ConstantValue::Array(items) => items.iter_mut().for_each(|item| item.strings_mut(f)),
+            ConstantValue::Object(entries) => {
+                for (key, value) in entries {
+                    f(key);
+                    value.strings_mut(f);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+impl PipelineNodeIR {
+    pub fn strings_mut(&mut self, f: &mut impl FnMut(&mut u32)) {
+        match self {
+            PipelineNodeIR::Step(_) => {}
+            PipelineNodeIR::Parallel(nodes) => nodes.iter_mut().for_each(|node| node.strings_mut(f)),
+            PipelineNodeIR::Conditional { condition, then_branch, else_branch } => {
+                f(condition);
+                then_branch.strings_mut(f);
+                if let Some(branch) = else_branch {
+                    branch.strings_mut(f);
+                }
+            }
+        }
+    }
+}
 pub struct CodeGenerator {
     ir: HelixIR,
     current_id: u32,
@@ -263,6 +407,7 @@ impl CodeGenerator {
                     workflows: HashMap::new(),
                     contexts: HashMap::new(),
                     crews: HashMap::new(),
+                    sections: HashMap::new(),
                     next_id: 1,
                 },
                 instructions: Vec::new(),
@@ -278,9 +423,19 @@ impl CodeGenerator {
         id
     }
     pub fn generate(&mut self, ast: &HelixAst) -> HelixIR {
+        // Agents get their ids first so steps and crews can name agents
+        // declared further down the file
         for decl in &ast.declarations {
-            self.generate_declaration(decl);
+            if let Declaration::Agent(agent) = decl {
+                self.generate_agent(agent);
+            }
         }
+        for decl in &ast.declarations {
+            if !matches!(decl, Declaration::Agent(_)) {
+                self.generate_declaration(decl);
+            }
+        }
+        self.ir.symbol_table.next_id = self.current_id;
         self.optimize();
         self.ir.metadata.checksum = Some(self.calculate_checksum());
         self.ir.clone()
@@ -291,34 +446,40 @@ impl CodeGenerator {
             Declaration::Workflow(workflow) => self.generate_workflow(workflow),
             Declaration::Context(context) => self.generate_context(context),
             Declaration::Crew(crew) => self.generate_crew(crew),
+            Declaration::Section(section) => self.generate_section(section),
             _ => {}
         }
     }
     fn generate_agent(&mut self, agent: &AgentDecl) {
         let id = self.next_id();
         let name_idx = self.ir.string_pool.intern(&agent.name);
-        let model_idx = agent
-            .properties
-            .get("model")
-            .and_then(|e| e.as_string())
-            .map(|s| self.ir.string_pool.intern(&s))
-            .unwrap_or_else(|| self.ir.string_pool.intern("gpt-4"));
-        let role_idx = agent
-            .properties
-            .get("role")
-            .and_then(|e| e.as_string())
-            .map(|s| self.ir.string_pool.intern(&s))
-            .unwrap_or_else(|| self.ir.string_pool.intern("Assistant"));
+        let mut typed = Vec::new();
+        let model = agent.properties.get("model").and_then(|e| e.as_string());
+        if model.is_some() {
+            typed.push("model");
+        }
+        let model_idx = self.ir.string_pool.intern(model.as_deref().unwrap_or("gpt-4"));
+        let role = agent.properties.get("role").and_then(|e| e.as_string());
+        if role.is_some() {
+            typed.push("role");
+        }
+        let role_idx = self.ir.string_pool.intern(role.as_deref().unwrap_or("Assistant"));
         let temperature = agent
             .properties
             .get("temperature")
             .and_then(|e| e.as_number())
             .map(|n| n as f32);
+        if temperature.is_some() {
+            typed.push("temperature");
+        }
         let max_tokens = agent
             .properties
             .get("max_tokens")
             .and_then(|e| e.as_number())
             .map(|n| n as u32);
+        if max_tokens.is_some() {
+            typed.push("max_tokens");
+        }
         let capabilities = agent
             .capabilities
             .as_ref()
@@ -343,6 +504,7 @@ impl CodeGenerator {
         };
         self.ir.symbol_table.agents.insert(id, symbol);
         self.ir.instructions.push(Instruction::DeclareAgent(id));
+        self.set_properties(id, &agent.properties, &typed);
     }
     fn generate_workflow(&mut self, workflow: &WorkflowDecl) {
         let id = self.next_id();
@@ -352,36 +514,31 @@ impl CodeGenerator {
         } else {
             TriggerType::Manual
         };
-        let mut step_ids = Vec::new();
-        for step in &workflow.steps {
-            let step_def = self.generate_step(step, id);
-            step_ids.push(step_def.id);
-            self.ir
-                .instructions
-                .push(Instruction::DefineStep {
-                    workflow: id,
-                    step: step_def,
-                });
+        let step_ids: Vec<u32> = workflow.steps.iter().map(|_| self.next_id()).collect();
+        let steps_by_name: HashMap<&str, u32> = workflow
+            .steps
+            .iter()
+            .map(|s| s.name.as_str())
+            .zip(step_ids.iter().copied())
+            .collect();
+        for (step, &step_id) in workflow.steps.iter().zip(&step_ids) {
+            self.generate_step(step, step_id, id, &steps_by_name);
         }
-        let pipeline = workflow
+        let nodes = workflow
             .pipeline
             .as_ref()
             .map(|p| {
                 p.flow
                     .iter()
-                    .filter_map(|node| {
-                        if let PipelineNode::Step(name) = node {
-                            workflow
-                                .steps
-                                .iter()
-                                .find(|s| s.name == *name)
-                                .and_then(|_s| step_ids.iter().find(|&&_sid| { true }))
-                                .copied()
-                        } else {
-                            None
-                        }
-                    })
-                    .collect()
+                    .filter_map(|node| self.generate_pipeline_node(node, &steps_by_name))
+                    .collect::<Vec<_>>()
+            });
+        let pipeline = nodes
+            .as_ref()
+            .map(|nodes| {
+                let mut flow = Vec::new();
+                nodes.iter().for_each(|node| collect_pipeline_steps(node, &mut flow));
+                flow
             });
         let symbol = WorkflowSymbol {
             id,
@@ -392,121 +549,173 @@ impl CodeGenerator {
         };
         self.ir.symbol_table.workflows.insert(id, symbol);
         self.ir.instructions.push(Instruction::DeclareWorkflow(id));
+        if let Some(nodes) = nodes {
+            self.ir
+                .instructions
+                .push(Instruction::DefinePipeline {
+                    workflow: id,
+                    nodes,
+                });
+        }
+        self.set_properties(id, &workflow.properties, &[]);
     }
-    fn generate_step(&mut self, step: &StepDecl, _workflow_id: u32) -> StepDefinition {
-        let id = self.next_id();
+    fn generate_pipeline_node(
+        &mut self,
+        node: &PipelineNode,
+        steps: &HashMap<&str, u32>,
+    ) -> Option<PipelineNodeIR> {
+        match node {
+            PipelineNode::Step(name) => steps.get(name.as_str()).map(|&id| PipelineNodeIR::Step(id)),
+            PipelineNode::Parallel(nodes) => {
+                Some(
+                    PipelineNodeIR::Parallel(
+                        nodes
+                            .iter()
+                            .filter_map(|n| self.generate_pipeline_node(n, steps))
+                            .collect(),
+                    ),
+                )
+            }
+            PipelineNode::Conditional { condition, then_branch, else_branch } => {
+                let condition = self.ir.string_pool.intern(&condition.as_string()?);
+                let then_branch = Box::new(self.generate_pipeline_node(then_branch, steps)?);
+                let else_branch = match else_branch {
+                    Some(branch) => Some(Box::new(self.generate_pipeline_node(branch, steps)?)),
+                    None => None,
+                };
+                Some(PipelineNodeIR::Conditional {
+                    condition,
+                    then_branch,
+                    else_branch,
+                })
+            }
+        }
+    }
+    fn generate_step(
+        &mut self,
+        step: &StepDecl,
+        id: u32,
+        workflow_id: u32,
+        steps: &HashMap<&str, u32>,
+    ) {
         let name_idx = self.ir.string_pool.intern(&step.name);
-        let agent_id = step
-            .agent
+        let agent_id = step.agent.as_ref().and_then(|name| self.agent_id(name));
+        let crew_ids = step
+            .crew
             .as_ref()
-            .and_then(|name| {
-                self.ir
-                    .symbol_table
-                    .agents
-                    .values()
-                    .find(|a| self.ir.string_pool.get(a.name_idx) == Some(name))
-                    .map(|a| a.id)
-            });
+            .map(|names| names.iter().filter_map(|name| self.agent_id(name)).collect());
         let task_idx = step.task.as_ref().map(|t| self.ir.string_pool.intern(t));
+        let mut typed = Vec::new();
         let timeout = step
             .properties
             .get("timeout")
             .and_then(|e| self.expression_to_duration(e));
-        let parallel = step
-            .properties
-            .get("parallel")
-            .and_then(|e| e.as_bool())
-            .unwrap_or(false);
+        if timeout.is_some() {
+            typed.push("timeout");
+        }
+        let parallel = step.properties.get("parallel").and_then(|e| e.as_bool());
+        if parallel.is_some() {
+            typed.push("parallel");
+        }
         let depends_on = step
             .properties
             .get("depends_on")
             .and_then(|e| e.as_array())
             .map(|deps| {
                 deps.iter()
-                    .filter_map(|d| { d.as_string().and_then(|_name| { Some(0) }) })
-                    .collect()
-            })
-            .unwrap_or_default();
+                    .filter_map(|d| d.as_string().and_then(|name| steps.get(name.as_str()).copied()))
+                    .collect::<Vec<_>>()
+            });
+        if depends_on.is_some() {
+            typed.push("depends_on");
+        }
         let retry = step
             .properties
             .get("retry")
             .and_then(|e| e.as_object())
             .and_then(|obj| self.parse_retry_config(obj));
-        StepDefinition {
+        if retry.is_some() {
+            typed.push("retry");
+        }
+        let step_def = StepDefinition {
             id,
             name_idx,
             agent_id,
-            crew_ids: step.crew.as_ref().map(|_| Vec::new()),
+            crew_ids,
             task_idx,
             timeout,
-            parallel,
-            depends_on,
+            parallel: parallel.unwrap_or(false),
+            depends_on: depends_on.unwrap_or_default(),
             retry,
-        }
+        };
+        self.ir
+            .instructions
+            .push(Instruction::DefineStep {
+                workflow: workflow_id,
+                step: step_def,
+            });
+        self.set_properties(id, &step.properties, &typed);
     }
     fn generate_context(&mut self, context: &ContextDecl) {
         let id = self.next_id();
         let name_idx = self.ir.string_pool.intern(&context.name);
         let environment_idx = self.ir.string_pool.intern(&context.environment);
-        let debug = context
-            .properties
-            .get("debug")
-            .and_then(|e| e.as_bool())
-            .unwrap_or(false);
+        let mut typed = Vec::new();
+        let debug = context.properties.get("debug").and_then(|e| e.as_bool());
+        if debug.is_some() {
+            typed.push("debug");
+        }
         let max_tokens = context
             .properties
             .get("max_tokens")
             .and_then(|e| e.as_number())
             .map(|n| n as u64);
-        let secrets = context
-            .secrets
-            .as_ref()
-            .map(|s| {
-                s.iter()
-                    .map(|(key, secret_ref)| {
-                        let key_idx = self.ir.string_pool.intern(key);
-                        let secret_type = match secret_ref {
-                            SecretRef::Environment(var) => {
-                                SecretType::Environment(self.ir.string_pool.intern(var))
-                            }
-                            SecretRef::Vault(path) => {
-                                SecretType::Vault(self.ir.string_pool.intern(path))
-                            }
-                            SecretRef::File(path) => {
-                                SecretType::File(self.ir.string_pool.intern(path))
-                            }
-                        };
-                        (key_idx, secret_type)
-                    })
-                    .collect()
-            })
-            .unwrap_or_default();
+        if max_tokens.is_some() {
+            typed.push("max_tokens");
+        }
+        let mut secrets = HashMap::new();
+        if let Some(refs) = &context.secrets {
+            let mut keys: Vec<_> = refs.keys().collect();
+            keys.sort();
+            for key in keys {
+                let key_idx = self.ir.string_pool.intern(key);
+                let secret_type = match &refs[key] {
+                    SecretRef::Environment(var) => {
+                        SecretType::Environment(self.ir.string_pool.intern(var))
+                    }
+                    SecretRef::Vault(path) => {
+                        SecretType::Vault(self.ir.string_pool.intern(path))
+                    }
+                    SecretRef::File(path) => {
+                        SecretType::File(self.ir.string_pool.intern(path))
+                    }
+                };
+                secrets.insert(key_idx, secret_type);
+            }
+        }
         let symbol = ContextSymbol {
             id,
             name_idx,
             environment_idx,
-            debug,
+            debug: debug.unwrap_or(false),
             max_tokens,
             secrets,
         };
         self.ir.symbol_table.contexts.insert(id, symbol);
         self.ir.instructions.push(Instruction::DeclareContext(id));
+        self.set_properties(id, &context.properties, &typed);
+        if let Some(variables) = &context.variables {
+            let value = self.expression_to_constant(&Expression::Object(variables.clone()));
+            if let Some(value) = value {
+                let key = self.ir.string_pool.intern("variables");
+                self.ir.instructions.push(Instruction::SetProperty { target: id, key, value });
+            }
+        }
     }
     fn generate_crew(&mut self, crew: &CrewDecl) {
         let id = self.next_id();
         let name_idx = self.ir.string_pool.intern(&crew.name);
-        let agent_ids = crew
-            .agents
-            .iter()
-            .filter_map(|name| {
-                self.ir
-                    .symbol_table
-                    .agents
-                    .values()
-                    .find(|a| self.ir.string_pool.get(a.name_idx) == Some(name))
-                    .map(|a| a.id)
-            })
-            .collect();
+        let agent_ids = crew.agents.iter().filter_map(|name| self.agent_id(name)).collect();
         let process_type = crew
             .process_type
             .as_ref()
@@ -522,14 +731,8 @@ impl CodeGenerator {
             .properties
             .get("manager")
             .and_then(|e| e.as_string())
-            .and_then(|name| {
-                self.ir
-                    .symbol_table
-                    .agents
-                    .values()
-                    .find(|a| self.ir.string_pool.get(a.name_idx) == Some(&name))
-                    .map(|a| a.id)
-            });
+            .and_then(|name| self.agent_id(&name));
+        let typed: &[&str] = if manager_id.is_some() { &["manager"] } else { &[] };
         let symbol = CrewSymbol {
             id,
             name_idx,
@@ -539,6 +742,91 @@ impl CodeGenerator {
         };
         self.ir.symbol_table.crews.insert(id, symbol);
         self.ir.instructions.push(Instruction::DeclareCrew(id));
+        self.set_properties(id, &crew.properties, typed);
+    }
+    fn generate_section(&mut self, section: &SectionDecl) {
+        let id = self.next_id();
+        let name_idx = self.ir.string_pool.intern(&section.name);
+        self.ir.symbol_table.sections.insert(id, SectionSymbol { id, name_idx });
+        self.ir.instructions.push(Instruction::DeclareSection(id));
+        self.set_properties(id, &section.properties, &[]);
+    }
+    fn agent_id(&self, name: &str) -> Option<u32> {
+        self.ir
+            .symbol_table
+            .agents
+            .values()
+            .find(|a| self.ir.string_pool.get(a.name_idx).map(String::as_str) == Some(name))
+            .map(|a| a.id)
+    }
+    /// Emit a `SetProperty` for every entry not already stored in a typed
+    /// symbol field, in key order so the IR doesn't depend on hash order
+    fn set_properties(
+        &mut self,
+        target: u32,
+        properties: &HashMap<String, Expression>,
+        typed: &[&str],
+    ) {
+        let mut keys: Vec<_> = properties
+            .keys()
+            .filter(|key| !typed.contains(&key.as_str()))
+            .collect();
+        keys.sort();
+        for key in keys {
+            if let Some(value) = self.expression_to_constant(&properties[key]) {
+                let key = self.ir.string_pool.intern(key);
+                self.ir.instructions.push(Instruction::SetProperty { target, key, value });
+            }
+        }
+    }
+    /// Operator calls and expressions are evaluated when the config is
+    /// loaded, so they have no constant form
+    fn expression_to_constant(&mut self, expr: &Expression) -> Option<ConstantValue> {
+        let value = match expr {
+            Expression::String(s) | Expression::Identifier(s) => {
+                ConstantValue::String(self.ir.string_pool.intern(s))
+            }
+            Expression::Number(n) => ConstantValue::Number(*n),
+            Expression::Bool(b) => ConstantValue::Bool(*b),
+            Expression::Null => ConstantValue::Null,
+            Expression::Duration(_) => ConstantValue::Duration(self.expression_to_duration(expr)?),
+            Expression::Quantity(q) => ConstantValue::Quantity(*q),
+            Expression::TextBlock(lines) => {
+                ConstantValue::String(self.ir.string_pool.intern(&lines.join("\n")))
+            }
+            Expression::Array(items) => {
+                ConstantValue::Array(
+                    items.iter().filter_map(|item| self.expression_to_constant(item)).collect(),
+                )
+            }
+            Expression::Pipeline(stages) => {
+                ConstantValue::Array(
+                    stages
+                        .iter()
+                        .map(|stage| ConstantValue::String(self.ir.string_pool.intern(stage)))
+                        .collect(),
+                )
+            }
+            Expression::Object(map) => {
+                let mut keys: Vec<_> = map.keys().collect();
+                keys.sort();
+                let mut entries = Vec::new();
+                for key in keys {
+                    if let Some(value) = self.expression_to_constant(&map[key]) {
+                        entries.push((self.ir.string_pool.intern(key), value));
+                    }
+                }
+                ConstantValue::Object(entries)
+            }
+            Expression::Variable(name) => {
+                ConstantValue::Reference(self.ir.string_pool.intern(&format!("${}", name)))
+            }
+            Expression::Reference(name) => {
+                ConstantValue::Reference(self.ir.string_pool.intern(&format!("@{}", name)))
+            }
+            _ => return None,
+        };
+        Some(value)
     }
     fn parse_trigger(&mut self, trigger: &Expression) -> TriggerType {
         match trigger {
@@ -584,6 +872,13 @@ impl CodeGenerator {
                     },
                 })
             }
+            Expression::Quantity(q) => {
+                q.to_duration()
+                    .map(|d| DurationIR {
+                        value: d.value,
+                        unit: TimeUnitIR::Seconds,
+                    })
+            }
             _ => None,
         }
     }
@@ -629,6 +924,20 @@ impl CodeGenerator {
         hasher.finish()
     }
 }
+fn collect_pipeline_steps(node: &PipelineNodeIR, flow: &mut Vec<u32>) {
+    match node {
+        PipelineNodeIR::Step(id) => flow.push(*id),
+        PipelineNodeIR::Parallel(nodes) => {
+            nodes.iter().for_each(|node| collect_pipeline_steps(node, flow));
+        }
+        PipelineNodeIR::Conditional { then_branch, else_branch, .. } => {
+            collect_pipeline_steps(then_branch, flow);
+            if let Some(branch) = else_branch {
+                collect_pipeline_steps(branch, flow);
+            }
+        }
+    }
+}
 #[allow(dead_code)]
 pub struct BinarySerializer;
 #[allow(dead_code)]
@@ -731,8 +1040,13 @@ mod tests {
         let mut pool = ConstantPool::new();
         let idx1 = pool.add(ConstantValue::Number(42.0));
         let idx2 = pool.add(ConstantValue::Bool(true));
+        let memory = ConstantValue::Quantity("16GiB".parse().unwrap());
+        let idx3 = pool.add(memory.clone());
         assert_eq!(pool.get(idx1), Some(& ConstantValue::Number(42.0)));
         assert_eq!(pool.get(idx2), Some(& ConstantValue::Bool(true)));
+        let bytes = bincode::serialize(&pool).unwrap();
+        let decoded: ConstantPool = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(decoded.get(idx3), Some(& memory));
     }
     #[test]
     fn test_version_compatibility() {
@@ -749,6 +1063,7 @@ mod tests {
                 workflows: HashMap::new(),
                 contexts: HashMap::new(),
                 crews: HashMap::new(),
+                sections: HashMap::new(),
                 next_id: 1,
             },
             instructions: Vec::new(),
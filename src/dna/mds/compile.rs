@@ -1,9 +1,13 @@
 use std::path::PathBuf;
 use crate::dna::compiler::{Compiler, OptimizationLevel};
+use crate::dna::ops::hermetic::Hermetic;
+use crate::dna::hel::binary::SectionType;
+use crate::dna::hel::encryption::SectionKey;
 use anyhow::Context;
 
 
 
+#[allow(clippy::too_many_arguments)]
 pub fn compile_command(
     input: PathBuf,
     output: Option<PathBuf>,
@@ -12,6 +16,8 @@ pub fn compile_command(
     cache: bool,
     verbose: bool,
     _quiet: bool,
+    hermetic: Option<Hermetic>,
+    encryption: Option<(SectionKey, Vec<SectionType>)>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let output_path = output
         .unwrap_or_else(|| {
@@ -24,8 +30,20 @@ pub fn compile_command(
         println!("  Optimization: Level {}", optimize);
         println!("  Compression: {}", if compress { "Enabled" } else { "Disabled" });
         println!("  Cache: {}", if cache { "Enabled" } else { "Disabled" });
+        if let Some(hermetic) = &hermetic {
+            println!("  Hermetic: epoch {}, {} env vars", hermetic.epoch(), hermetic.env().len());
+        }
+        if let Some((key, sensitive)) = &encryption {
+            println!("  Encryption: key {}, contexts and {:?}", key.id(), sensitive);
+        }
+    }
+    let mut compiler = Compiler::new(OptimizationLevel::from(optimize));
+    if let Some(hermetic) = hermetic {
+        compiler = compiler.with_hermetic(hermetic);
+    }
+    if let Some((key, sensitive)) = encryption {
+        compiler = compiler.with_encryption(key, sensitive);
     }
-    let compiler = Compiler::new(OptimizationLevel::from(optimize));
     let binary = compiler.compile_file(&input)?;
     let serializer = crate::mds::serializer::BinarySerializer::new(compress);
     serializer.write_to_file(&binary, &output_path)?;
@@ -2,6 +2,7 @@ use std::path::PathBuf;
 use std::fs;
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
+use crate::dna::hel::signing::{PublicKey, TrustPolicy};
 
 
 pub fn fnmanage_config(
@@ -47,6 +48,8 @@ struct GlobalConfig {
     registry: RegistryConfig,
     cache: CacheConfig,
     editor: EditorConfig,
+    #[serde(default)]
+    signing: SigningConfig,
     metadata: ConfigMetadata,
 }
 #[derive(Debug, Serialize, Deserialize)]
@@ -77,6 +80,18 @@ struct EditorConfig {
     auto_format: bool,
     syntax_highlighting: bool,
 }
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SigningConfig {
+    /// PKCS#8 key used by `hlx sign` when no key is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+    /// Base64 Ed25519 public keys whose signatures are accepted
+    #[serde(default)]
+    trusted_keys: Vec<String>,
+    /// Refuse to load binaries not signed by a trusted key
+    #[serde(default)]
+    require_signatures: bool,
+}
 #[derive(Debug, Serialize, Deserialize)]
 struct ConfigMetadata {
     version: String,
@@ -137,6 +152,11 @@ pub fn list_config(verbose: bool) -> Result<()> {
     println!("  auto_format: {}", config.editor.auto_format);
     println!("  syntax_highlighting: {}", config.editor.syntax_highlighting);
     println!();
+    println!("Signing Settings:");
+    println!("  key: {}", config.signing.key.as_deref().unwrap_or("(not set)"));
+    println!("  trusted_keys: {}", config.signing.trusted_keys.len());
+    println!("  require_signatures: {}", config.signing.require_signatures);
+    println!();
     if verbose {
         println!("Metadata:");
         println!("  version: {}", config.metadata.version);
@@ -223,6 +243,7 @@ pub fn create_default_config() -> Result<GlobalConfig> {
             auto_format: true,
             syntax_highlighting: true,
         },
+        signing: SigningConfig::default(),
         metadata: ConfigMetadata {
             version: "1.0".to_string(),
             created_at: chrono::Utc::now().to_rfc3339(),
@@ -230,6 +251,33 @@ pub fn create_default_config() -> Result<GlobalConfig> {
         },
     })
 }
+/// Signatures accepted when loading binaries, from the `signing` section of
+/// the global config. A trusted key that does not parse is an error rather
+/// than being skipped. Without a global config nothing is required, and none
+/// is created.
+pub fn trust_policy() -> Result<TrustPolicy> {
+    if !get_global_config_path()?.exists() {
+        return Ok(TrustPolicy::default());
+    }
+    let config = load_global_config()?;
+    let trusted_keys = config
+        .signing
+        .trusted_keys
+        .iter()
+        .map(|key| {
+            key.parse::<PublicKey>()
+                .with_context(|| format!("Invalid trusted key in config: {}", key))
+        })
+        .collect::<Result<_>>()?;
+    Ok(TrustPolicy {
+        trusted_keys,
+        require_signature: config.signing.require_signatures,
+    })
+}
+/// Key file `hlx sign` uses when none is given
+pub fn default_signing_key() -> Result<Option<PathBuf>> {
+    Ok(load_global_config()?.signing.key.map(PathBuf::from))
+}
 pub fn get_global_config_path() -> Result<PathBuf> {
     let home_dir = dirs::home_dir()
         .ok_or_else(|| anyhow::anyhow!("Failed to get home directory"))?;
@@ -277,6 +325,11 @@ pub fn get_config_value(config: &GlobalConfig, key: &str) -> Result<String> {
         "editor.default_editor" => Ok(config.editor.default_editor.clone()),
         "editor.auto_format" => Ok(config.editor.auto_format.to_string()),
         "editor.syntax_highlighting" => Ok(config.editor.syntax_highlighting.to_string()),
+        "signing.key" => Ok(config.signing.key.clone().unwrap_or_default()),
+        "signing.trusted_keys" => Ok(config.signing.trusted_keys.join(",")),
+        "signing.require_signatures" => {
+            Ok(config.signing.require_signatures.to_string())
+        }
         _ => Err(anyhow::anyhow!("Unknown config key: {}", key)),
     }
 }
@@ -344,6 +397,27 @@ pub fn set_config_value(config: &mut GlobalConfig, key: &str, value: &str) -> Re
                 .parse()
                 .context("Invalid syntax_highlighting value")?;
         }
+        "signing.key" => {
+            config.signing.key = Some(value.to_string());
+        }
+        "signing.trusted_keys" => {
+            let keys: Vec<String> = value
+                .split(',')
+                .map(str::trim)
+                .filter(|key| !key.is_empty())
+                .map(str::to_string)
+                .collect();
+            for key in &keys {
+                key.parse::<PublicKey>()
+                    .with_context(|| format!("Invalid public key: {}", key))?;
+            }
+            config.signing.trusted_keys = keys;
+        }
+        "signing.require_signatures" => {
+            config.signing.require_signatures = value
+                .parse()
+                .context("Invalid require_signatures value")?;
+        }
         _ => return Err(anyhow::anyhow!("Unknown config key: {}", key)),
     }
     config.metadata.last_modified = chrono::Utc::now().to_rfc3339();
@@ -354,6 +428,12 @@ pub fn unset_config_value(config: &mut GlobalConfig, key: &str) -> Result<()> {
         "registry.auth_token" => {
             config.registry.auth_token = None;
         }
+        "signing.key" => {
+            config.signing.key = None;
+        }
+        "signing.trusted_keys" => {
+            config.signing.trusted_keys.clear();
+        }
         _ => return Err(anyhow::anyhow!("Cannot unset key: {}", key)),
     }
     config.metadata.last_modified = chrono::Utc::now().to_rfc3339();
@@ -1,32 +1,430 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::collections::BTreeSet;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{Map, Value as JsonValue};
+use crate::dna::atp::types::{HelixConfig, Value};
+use crate::dna::compiler::Compiler;
+use crate::dna::mds::optimizer::OptimizationLevel;
 use crate::mds::loader::BinaryLoader;
 
+/// Shown instead of values under secret-looking keys
+pub const MASK: &str = "***";
+/// Key words (split on `_`, `-` and `.`) that mark a value as secret
+const SECRET_WORDS: &[&str] = &[
+    "password", "passwd", "pwd", "secret", "secrets", "token", "apikey", "credential",
+    "credentials", "auth",
+];
+/// Multi-word key fragments that mark a value as secret
+const SECRET_FRAGMENTS: &[&str] = &["api_key", "api-key", "private_key", "access_key"];
+
+/// Compare two configs, each a `.hlx`, `.hlxb` or JSON file, and print the
+/// differences. Returns whether there were any.
 pub fn diff_command(
     file1: PathBuf,
     file2: PathBuf,
+    format: DiffFormat,
     detailed: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let loader = BinaryLoader::new();
-    let binary1 = loader.load_file(&file1)?;
-    let binary2 = loader.load_file(&file2)?;
-    println!("Comparing binaries:");
+) -> Result<bool> {
+    let old = load_tree(&file1)?;
+    let new = load_tree(&file2)?;
+    let changes = diff_trees(&old, &new);
+    match format {
+        DiffFormat::Text => print_text(&file1, &file2, &changes, detailed),
+        DiffFormat::Json => println!("{}", serde_json::to_string_pretty(&changes)?),
+        DiffFormat::Unified => print!("{}", render_unified(&file1, &file2, &changes)),
+    }
+    Ok(!changes.is_empty())
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffFormat {
+    Text,
+    Json,
+    Unified,
+}
+impl std::str::FromStr for DiffFormat {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(DiffFormat::Text),
+            "json" => Ok(DiffFormat::Json),
+            "unified" => Ok(DiffFormat::Unified),
+            _ => Err(anyhow::anyhow!(
+                "Invalid diff format: {} (expected text, json or unified)", s
+            )),
+        }
+    }
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+}
+/// One difference at a key path such as `agents.assistant.temperature` or
+/// `workflows.review.steps[analyze].agent`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Change {
+    pub path: String,
+    pub kind: ChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old: Option<JsonValue>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new: Option<JsonValue>,
+}
+/// Read a config as a JSON tree from source, a compiled binary or JSON,
+/// chosen by extension. JSON that isn't a serialized `HelixConfig` is
+/// compared as-is.
+pub fn load_tree(path: &Path) -> Result<JsonValue> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let config = match extension {
+        "hlxb" => {
+            let binary = BinaryLoader::new().load_file(path)?;
+            let source = Compiler::new(OptimizationLevel::Zero)
+                .decompile(&binary)
+                .map_err(|e| anyhow::anyhow!("Failed to decompile {}: {}", path.display(), e))?;
+            crate::parse_and_validate(&source)
+        }
+        "json" => {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            let json: JsonValue = serde_json::from_str(&content)
+                .with_context(|| format!("Invalid JSON in {}", path.display()))?;
+            match serde_json::from_value::<HelixConfig>(json.clone()) {
+                Ok(config) => Ok(config),
+                Err(_) => return Ok(json),
+            }
+        }
+        _ => crate::load_file(path),
+    };
+    config
+        .map(|config| config_tree(&config))
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))
+}
+/// Structural differences from `old` to `new`, sorted by path
+pub fn diff_configs(old: &HelixConfig, new: &HelixConfig) -> Vec<Change> {
+    diff_trees(&config_tree(old), &config_tree(new))
+}
+/// Structural differences between two trees from [`load_tree`]
+pub fn diff_trees(old: &JsonValue, new: &JsonValue) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_values(&mut Vec::new(), Some(old), Some(new), &mut changes);
+    changes
+}
+/// The config as plain JSON, with section and context values unwrapped
+fn config_tree(config: &HelixConfig) -> JsonValue {
+    let mut tree = serde_json::to_value(config).unwrap_or(JsonValue::Null);
+    let sections: Map<String, JsonValue> = config
+        .sections
+        .iter()
+        .map(|(name, entries)| (name.clone(), plain_object(entries)))
+        .collect();
+    tree["sections"] = JsonValue::Object(sections);
+    for (name, context) in &config.contexts {
+        tree["contexts"][name]["variables"] = plain_object(&context.variables);
+    }
+    tree
+}
+fn plain_object(entries: &std::collections::HashMap<String, Value>) -> JsonValue {
+    JsonValue::Object(entries.iter().map(|(k, v)| (k.clone(), plain(v))).collect())
+}
+fn plain(value: &Value) -> JsonValue {
+    match value {
+        Value::String(s) | Value::Reference(s) | Value::Identifier(s) => JsonValue::String(s.clone()),
+        Value::Number(n) => serde_json::Number::from_f64(*n)
+            .map(JsonValue::Number)
+            .unwrap_or(JsonValue::Null),
+        Value::Bool(b) => JsonValue::Bool(*b),
+        Value::Null => JsonValue::Null,
+        Value::Array(items) => JsonValue::Array(items.iter().map(plain).collect()),
+        Value::Object(entries) => plain_object(entries),
+        Value::Duration(d) => JsonValue::String(format!("{} {:?}", d.value, d.unit).to_lowercase()),
+        Value::Quantity(q) => JsonValue::String(q.to_string()),
+    }
+}
+#[derive(Debug, Clone)]
+enum Segment {
+    Key(String),
+    /// Array element identified by its `name` field
+    Name(String),
+    Index(usize),
+}
+fn render_path(path: &[Segment]) -> String {
+    let mut out = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(key) if is_plain_key(key) => {
+                if !out.is_empty() {
+                    out.push('.');
+                }
+                out.push_str(key);
+            }
+            Segment::Key(key) => out.push_str(&format!("[{:?}]", key)),
+            Segment::Name(name) => out.push_str(&format!("[{}]", name)),
+            Segment::Index(i) => out.push_str(&format!("[{}]", i)),
+        }
+    }
+    out
+}
+fn is_plain_key(key: &str) -> bool {
+    !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+}
+fn is_secret(path: &[Segment]) -> bool {
+    path.iter().any(|segment| match segment {
+        Segment::Key(key) => {
+            let key = key.to_lowercase();
+            SECRET_FRAGMENTS.iter().any(|f| key.contains(f))
+                || key.split(['_', '-', '.']).any(|word| SECRET_WORDS.contains(&word))
+        }
+        _ => false,
+    })
+}
+fn diff_values(
+    path: &mut Vec<Segment>,
+    old: Option<&JsonValue>,
+    new: Option<&JsonValue>,
+    changes: &mut Vec<Change>,
+) {
+    // A null field is the same as a missing one
+    let old = old.filter(|v| !v.is_null());
+    let new = new.filter(|v| !v.is_null());
+    match (old, new) {
+        (None, None) => {}
+        (Some(JsonValue::Object(a)), Some(JsonValue::Object(b))) => {
+            let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+            for key in keys {
+                path.push(Segment::Key(key.clone()));
+                diff_values(path, a.get(key), b.get(key), changes);
+                path.pop();
+            }
+        }
+        (Some(JsonValue::Array(a)), Some(JsonValue::Array(b))) if a != b => {
+            match (named(a), named(b)) {
+                (Some(a), Some(b)) => {
+                    let names: BTreeSet<&str> = a.iter().chain(b.iter()).map(|(n, _)| *n).collect();
+                    for name in names {
+                        path.push(Segment::Name(name.to_string()));
+                        diff_values(path, find_named(&a, name), find_named(&b, name), changes);
+                        path.pop();
+                    }
+                }
+                _ if a.len() == b.len() && a.iter().all(JsonValue::is_object) => {
+                    for (i, (x, y)) in a.iter().zip(b).enumerate() {
+                        path.push(Segment::Index(i));
+                        diff_values(path, Some(x), Some(y), changes);
+                        path.pop();
+                    }
+                }
+                _ => changes.push(change(path, ChangeKind::Changed, old, new)),
+            }
+        }
+        (Some(a), Some(b)) if a == b => {}
+        (Some(_), Some(_)) => changes.push(change(path, ChangeKind::Changed, old, new)),
+        (None, Some(_)) => changes.push(change(path, ChangeKind::Added, None, new)),
+        (Some(_), None) => changes.push(change(path, ChangeKind::Removed, old, None)),
+    }
+}
+/// Elements keyed by a unique `name` field, when every element has one
+fn named(items: &[JsonValue]) -> Option<Vec<(&str, &JsonValue)>> {
+    let keyed: Vec<(&str, &JsonValue)> = items
+        .iter()
+        .map(|item| Some((item.get("name")?.as_str()?, item)))
+        .collect::<Option<_>>()?;
+    let unique: BTreeSet<&str> = keyed.iter().map(|(n, _)| *n).collect();
+    (unique.len() == keyed.len()).then_some(keyed)
+}
+fn find_named<'a>(items: &[(&str, &'a JsonValue)], name: &str) -> Option<&'a JsonValue> {
+    items.iter().find(|(n, _)| *n == name).map(|(_, v)| *v)
+}
+fn change(
+    path: &[Segment],
+    kind: ChangeKind,
+    old: Option<&JsonValue>,
+    new: Option<&JsonValue>,
+) -> Change {
+    let secret = is_secret(path);
+    let show = |value: Option<&JsonValue>| {
+        value.map(|v| if secret { JsonValue::String(MASK.to_string()) } else { mask_secrets(v) })
+    };
+    Change {
+        path: render_path(path),
+        kind,
+        old: show(old),
+        new: show(new),
+    }
+}
+/// Copy of `value` with secret-looking members masked
+fn mask_secrets(value: &JsonValue) -> JsonValue {
+    match value {
+        JsonValue::Object(entries) => JsonValue::Object(
+            entries
+                .iter()
+                .map(|(k, v)| {
+                    let masked = if is_secret(&[Segment::Key(k.clone())]) {
+                        JsonValue::String(MASK.to_string())
+                    } else {
+                        mask_secrets(v)
+                    };
+                    (k.clone(), masked)
+                })
+                .collect(),
+        ),
+        JsonValue::Array(items) => JsonValue::Array(items.iter().map(mask_secrets).collect()),
+        other => other.clone(),
+    }
+}
+fn print_text(file1: &Path, file2: &Path, changes: &[Change], detailed: bool) {
+    println!("Comparing configs:");
     println!("  File 1: {}", file1.display());
     println!("  File 2: {}", file2.display());
     println!();
-    if binary1.version != binary2.version {
-        println!("⚠️  Version differs: {} vs {}", binary1.version, binary2.version);
-    }
-    if binary1.size() != binary2.size() {
-        println!("⚠️  Size differs: {} vs {} bytes", binary1.size(), binary2.size());
-    }
-    let stats1 = binary1.symbol_table.stats();
-    let stats2 = binary2.symbol_table.stats();
-    if stats1.total_strings != stats2.total_strings {
-        println!(
-            "⚠️  String count differs: {} vs {}", stats1.total_strings, stats2
-            .total_strings
-        );
-    }
-    if detailed {}
-    Ok(())
-}
\ No newline at end of file
+    if changes.is_empty() {
+        println!("✅ No differences");
+        return;
+    }
+    for change in changes {
+        match change.kind {
+            ChangeKind::Added if detailed => {
+                println!("+ {} = {}", change.path, compact(change.new.as_ref()))
+            }
+            ChangeKind::Added => println!("+ {}", change.path),
+            ChangeKind::Removed if detailed => {
+                println!("- {} = {}", change.path, compact(change.old.as_ref()))
+            }
+            ChangeKind::Removed => println!("- {}", change.path),
+            ChangeKind::Changed => {
+                println!(
+                    "~ {}: {} -> {}", change.path, compact(change.old.as_ref()),
+                    compact(change.new.as_ref())
+                )
+            }
+        }
+    }
+    let count = |kind| changes.iter().filter(|c| c.kind == kind).count();
+    println!(
+        "\n{} difference{} ({} added, {} removed, {} changed)", changes.len(),
+        if changes.len() == 1 { "" } else { "s" },
+        count(ChangeKind::Added), count(ChangeKind::Removed), count(ChangeKind::Changed)
+    );
+}
+/// A patch-style listing with one hunk per top-level entry, e.g. `agents.assistant`
+pub fn render_unified(file1: &Path, file2: &Path, changes: &[Change]) -> String {
+    let mut out = String::new();
+    if changes.is_empty() {
+        return out;
+    }
+    out.push_str(&format!("--- {}\n+++ {}\n", file1.display(), file2.display()));
+    let mut hunk = None;
+    for change in changes {
+        let head = hunk_header(&change.path);
+        if hunk.as_deref() != Some(head.as_str()) {
+            out.push_str(&format!("@@ {} @@\n", head));
+            hunk = Some(head);
+        }
+        if let Some(old) = &change.old {
+            out.push_str(&format!("-{} = {}\n", change.path, compact(Some(old))));
+        }
+        if let Some(new) = &change.new {
+            out.push_str(&format!("+{} = {}\n", change.path, compact(Some(new))));
+        }
+    }
+    out
+}
+fn hunk_header(path: &str) -> String {
+    let mut depth = 0;
+    for (i, c) in path.char_indices() {
+        if c == '.' || c == '[' {
+            depth += 1;
+            if depth == 2 {
+                return path[..i].to_string();
+            }
+        }
+    }
+    path.to_string()
+}
+fn compact(value: Option<&JsonValue>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    const OLD: &str = r#"
+agent "assistant" {
+    model = "gpt-4"
+    temperature = 0.7
+}
+agent "retired" {
+    model = "gpt-3.5-turbo"
+}
+workflow "review" {
+    trigger = "manual"
+    step "analyze" {
+        agent = "assistant"
+        task = "Analyze the change"
+    }
+}
+context "production" {
+    environment = "prod"
+    api_key = "sk-old"
+    region = "us-east-1"
+}
+"#;
+    const NEW: &str = r#"
+agent "assistant" {
+    model = "gpt-4o"
+    temperature = 0.7
+}
+workflow "review" {
+    trigger = "manual"
+    step "analyze" {
+        agent = "assistant"
+        task = "Analyze the change carefully"
+    }
+}
+context "production" {
+    environment = "prod"
+    api_key = "sk-new"
+    region = "eu-west-1"
+}
+"#;
+    fn changes() -> Vec<Change> {
+        let old = crate::parse_and_validate(OLD).unwrap();
+        let new = crate::parse_and_validate(NEW).unwrap();
+        diff_configs(&old, &new)
+    }
+    #[test]
+    fn test_structural_changes_with_key_paths() {
+        let changes = changes();
+        let find = |path: &str| changes.iter().find(|c| c.path == path);
+        let model = find("agents.assistant.model").expect("model change");
+        assert_eq!(model.kind, ChangeKind::Changed);
+        assert_eq!(model.old, Some(JsonValue::from("gpt-4")));
+        assert_eq!(model.new, Some(JsonValue::from("gpt-4o")));
+        assert_eq!(find("agents.retired").unwrap().kind, ChangeKind::Removed);
+        assert_eq!(find("workflows.review.steps[analyze].task").unwrap().kind, ChangeKind::Changed);
+        assert!(changes.iter().all(|c| !c.path.starts_with("agents.assistant.temperature")));
+        assert!(diff_configs(&crate::parse_and_validate(OLD).unwrap(), &crate::parse_and_validate(OLD).unwrap()).is_empty());
+    }
+    #[test]
+    fn test_secrets_are_masked_in_every_format() {
+        let changes = changes();
+        let json = serde_json::to_string(&changes).unwrap();
+        assert!(!json.contains("sk-old") && !json.contains("sk-new"));
+        let removed = changes.iter().find(|c| c.path == "agents.retired").unwrap();
+        assert_eq!(removed.old.as_ref().unwrap()["model"], "gpt-3.5-turbo");
+        let unified = render_unified(Path::new("a.hlx"), Path::new("b.hlx"), &changes);
+        assert!(unified.starts_with("--- a.hlx\n+++ b.hlx\n@@ agents.assistant @@\n"));
+        assert!(!unified.contains("sk-"));
+        assert_eq!(hunk_header("workflows.review.steps[analyze].task"), "workflows.review");
+    }
+    #[test]
+    fn test_named_arrays_and_paths() {
+        let mut changes = Vec::new();
+        let old = serde_json::json!({"steps": [{"name": "a", "x": 1}, {"name": "b", "x": 2}], "tags": ["x"]});
+        let new = serde_json::json!({"steps": [{"name": "b", "x": 3}, {"name": "a", "x": 1}], "tags": ["x", "y"], "odd key": true});
+        diff_values(&mut Vec::new(), Some(&old), Some(&new), &mut changes);
+        let paths: Vec<&str> = changes.iter().map(|c| c.path.as_str()).collect();
+        assert_eq!(paths, vec!["[\"odd key\"]", "steps[b].x", "tags"]);
+    }
+}
@@ -0,0 +1,394 @@
+use crate::dna::compiler::{Compiler, OptimizationLevel};
+use crate::dna::mds::modules::ModuleSystem;
+use crate::dna::mds::serializer::BinarySerializer;
+use crate::dna::ops::file::file_dependencies;
+use crate::dna::ops::memo::hash_file;
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Manifest kept in the output directory between builds
+pub const MANIFEST_FILE: &str = ".hlx-build.json";
+
+/// What the last build saw of one module
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModuleRecord {
+    /// SHA-256 of the source
+    pub source: String,
+    /// SHA-256 of each file read through `@file.*`, empty when missing
+    pub inputs: BTreeMap<PathBuf, String>,
+    /// Modules named by its `load` declarations
+    pub imports: Vec<PathBuf>,
+    pub output: Option<PathBuf>,
+    pub options: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BuildManifest {
+    pub modules: BTreeMap<PathBuf, ModuleRecord>,
+}
+
+impl BuildManifest {
+    /// The manifest at `path`, or an empty one when it is missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
+
+/// Why a module had to be compiled again
+#[derive(Debug, Clone, PartialEq)]
+pub enum RebuildReason {
+    NotBuilt,
+    CacheDisabled,
+    OptionsChanged,
+    OutputMissing,
+    SourceChanged,
+    InputChanged(PathBuf),
+    ImportChanged(PathBuf),
+}
+
+impl fmt::Display for RebuildReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NotBuilt => write!(f, "not built before"),
+            Self::CacheDisabled => write!(f, "cache disabled"),
+            Self::OptionsChanged => write!(f, "build options changed"),
+            Self::OutputMissing => write!(f, "output is missing"),
+            Self::SourceChanged => write!(f, "source changed"),
+            Self::InputChanged(path) => write!(f, "input {} changed", path.display()),
+            Self::ImportChanged(path) => {
+                write!(f, "loaded module {} changed", path.display())
+            }
+        }
+    }
+}
+
+/// Outcome for one output of a build
+#[derive(Debug, Clone)]
+pub struct ModuleBuild {
+    pub path: PathBuf,
+    pub output: PathBuf,
+    /// `None` when the output was up to date
+    pub reason: Option<RebuildReason>,
+    pub error: Option<String>,
+}
+
+/// Outputs of a build in compilation order
+#[derive(Debug, Clone, Default)]
+pub struct BuildReport {
+    pub modules: Vec<ModuleBuild>,
+}
+
+impl BuildReport {
+    pub fn rebuilt(&self) -> impl Iterator<Item = &ModuleBuild> {
+        self.modules.iter().filter(|m| m.reason.is_some() && m.error.is_none())
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &ModuleBuild> {
+        self.modules.iter().filter(|m| m.error.is_some())
+    }
+}
+
+/// Compiles modules whose sources, `@file` inputs or loaded modules changed
+/// since the last build, in parallel, and leaves the rest untouched
+pub struct IncrementalBuild {
+    compiler: Compiler,
+    compress: bool,
+    reuse: bool,
+    options: String,
+    manifest_path: PathBuf,
+    targets: BTreeMap<PathBuf, PathBuf>,
+}
+
+impl IncrementalBuild {
+    /// A build recording its manifest in `out_dir`
+    pub fn new(level: OptimizationLevel, compress: bool, out_dir: &Path) -> Self {
+        let compiler = Compiler::builder()
+            .optimization_level(level)
+            .compression(compress)
+            .cache(false)
+            .build();
+        Self {
+            compiler,
+            compress,
+            reuse: true,
+            options: format!("{:?} compress={}", level, compress),
+            manifest_path: out_dir.join(MANIFEST_FILE),
+            targets: BTreeMap::new(),
+        }
+    }
+
+    /// Reuse outputs the manifest shows are up to date (the default). When
+    /// off, every target is rebuilt; the manifest is still updated.
+    pub fn cache(mut self, enable: bool) -> Self {
+        self.reuse = enable;
+        self
+    }
+
+    /// Compile `source` (with the modules it loads) into `output`
+    pub fn target(mut self, source: &Path, output: &Path) -> Self {
+        self.targets.insert(source.to_path_buf(), output.to_path_buf());
+        self
+    }
+
+    /// Every `.hlx` file under `dir`, mirrored under `out_dir` as `.hlxb`
+    pub fn directory(mut self, dir: &Path, out_dir: &Path) -> Result<Self> {
+        for entry in WalkDir::new(dir).sort_by_file_name() {
+            let entry = entry?;
+            let path = entry.path();
+            if !entry.file_type().is_file()
+                || path.extension().and_then(|e| e.to_str()) != Some("hlx")
+            {
+                continue;
+            }
+            let relative = path.strip_prefix(dir).unwrap_or(path);
+            let output = out_dir.join(relative).with_extension("hlxb");
+            self.targets.insert(path.to_path_buf(), output);
+        }
+        Ok(self)
+    }
+
+    pub fn run(&self) -> Result<BuildReport> {
+        let mut modules = ModuleSystem::new();
+        for source in self.targets.keys() {
+            modules.load_with_dependencies(source)?;
+        }
+        modules.resolve_dependencies()?;
+        let order = modules.compilation_order().to_vec();
+        let mut manifest = BuildManifest::load(&self.manifest_path);
+        let mut dirty: HashMap<&Path, RebuildReason> = HashMap::new();
+        let mut records = HashMap::new();
+        for path in &order {
+            let record = self.record(path, &modules)?;
+            if let Some(reason) = self.rebuild_reason(
+                &record,
+                manifest.modules.get(path),
+                &dirty,
+            ) {
+                dirty.insert(path, reason);
+            }
+            records.insert(path, record);
+        }
+        let results: HashMap<&Path, Option<String>> = order
+            .par_iter()
+            .filter(|path| dirty.contains_key(path.as_path()))
+            .filter_map(|path| {
+                let output = self.targets.get(path)?;
+                let error = self.compile(&modules, path, output).err();
+                Some((path.as_path(), error.map(|e| format!("{:#}", e))))
+            })
+            .collect();
+        let mut report = BuildReport::default();
+        for path in &order {
+            let error = results.get(path.as_path()).cloned().flatten();
+            match error {
+                Some(_) => manifest.modules.remove(path),
+                None => manifest.modules.insert(path.clone(), records[path].clone()),
+            };
+            if let Some(output) = self.targets.get(path) {
+                report.modules.push(ModuleBuild {
+                    path: path.clone(),
+                    output: output.clone(),
+                    reason: dirty.get(path.as_path()).cloned(),
+                    error,
+                });
+            }
+        }
+        manifest.save(&self.manifest_path)?;
+        Ok(report)
+    }
+
+    fn record(&self, path: &Path, modules: &ModuleSystem) -> Result<ModuleRecord> {
+        let source = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        let inputs = file_dependencies(&source, base_dir)
+            .into_iter()
+            .map(|input| {
+                let hash = hash_file(&input).unwrap_or_default();
+                (input, hash)
+            })
+            .collect();
+        let mut imports: Vec<_> = modules.get_dependencies(path).into_iter().collect();
+        imports.sort();
+        Ok(ModuleRecord {
+            source: hash_file(path)?,
+            inputs,
+            imports,
+            output: self.targets.get(path).cloned(),
+            options: self.options.clone(),
+        })
+    }
+
+    fn rebuild_reason(
+        &self,
+        record: &ModuleRecord,
+        previous: Option<&ModuleRecord>,
+        dirty: &HashMap<&Path, RebuildReason>,
+    ) -> Option<RebuildReason> {
+        let Some(previous) = previous else {
+            return Some(RebuildReason::NotBuilt);
+        };
+        if !self.reuse {
+            return Some(RebuildReason::CacheDisabled);
+        }
+        if previous.options != record.options || previous.output != record.output {
+            return Some(RebuildReason::OptionsChanged);
+        }
+        if record.output.as_ref().is_some_and(|output| !output.exists()) {
+            return Some(RebuildReason::OutputMissing);
+        }
+        if previous.source != record.source || previous.imports != record.imports {
+            return Some(RebuildReason::SourceChanged);
+        }
+        if let Some((input, _)) = record
+            .inputs
+            .iter()
+            .find(|(input, hash)| previous.inputs.get(*input) != Some(*hash))
+        {
+            return Some(RebuildReason::InputChanged(input.clone()));
+        }
+        record
+            .imports
+            .iter()
+            .find(|import| dirty.contains_key(import.as_path()))
+            .map(|import| RebuildReason::ImportChanged(import.clone()))
+    }
+
+    fn compile(&self, modules: &ModuleSystem, path: &Path, output: &Path) -> Result<()> {
+        let ast = modules.merge_closure(path);
+        let binary = self.compiler.compile_ast(&ast, Some(path))?;
+        if let Some(parent) = output.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        BinarySerializer::new(self.compress)
+            .write_to_file(&binary, output)
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", output.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, name: &str, content: &str) {
+        fs::write(dir.join(name), content).unwrap();
+    }
+
+    fn reasons(report: &BuildReport) -> BTreeMap<String, Option<RebuildReason>> {
+        report
+            .modules
+            .iter()
+            .map(|m| {
+                let name = m.path.file_name().unwrap().to_string_lossy().into_owned();
+                (name, m.reason.clone())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rebuilds_only_affected_modules() {
+        let dir = TempDir::new().unwrap();
+        let src = dir.path().join("src");
+        let out = dir.path().join("target");
+        fs::create_dir_all(&src).unwrap();
+        write(&src, "common.hlx", "agent \"shared\" {\n    model = \"gpt-4\"\n}\n");
+        write(
+            &src,
+            "app.hlx",
+            "load common {\n}\nagent \"app\" {\n    model = \"claude\"\n}\n",
+        );
+        write(&src, "other.hlx", "agent \"other\" {\n    model = \"gpt-4\"\n}\n");
+        let build = || {
+            IncrementalBuild::new(OptimizationLevel::Zero, false, &out)
+                .directory(&src, &out)
+                .unwrap()
+                .run()
+                .unwrap()
+        };
+        let first = build();
+        assert_eq!(first.failed().count(), 0);
+        assert_eq!(first.rebuilt().count(), 3);
+        assert!(out.join("app.hlxb").exists());
+        // app.hlxb carries the agent from the module it loads
+        let binary = BinarySerializer::new(false).read_from_file(&out.join("app.hlxb")).unwrap();
+        let ir = BinarySerializer::new(false).deserialize_to_ir(&binary).unwrap();
+        assert_eq!(ir.symbol_table.agents.len(), 2);
+
+        assert_eq!(build().rebuilt().count(), 0);
+
+        write(&src, "common.hlx", "agent \"shared\" {\n    model = \"gpt-5\"\n}\n");
+        let report = reasons(&build());
+        assert_eq!(report["common.hlx"], Some(RebuildReason::SourceChanged));
+        assert_eq!(
+            report["app.hlx"],
+            Some(RebuildReason::ImportChanged(src.join("common.hlx")))
+        );
+        assert_eq!(report["other.hlx"], None);
+    }
+
+    #[test]
+    fn test_file_inputs_invalidate_dependents() {
+        let dir = TempDir::new().unwrap();
+        let out = dir.path().join("target");
+        write(dir.path(), "prompt.txt", "hello");
+        write(
+            dir.path(),
+            "main.hlx",
+            "agent \"a\" {\n    model = \"gpt-4\"\n    prompt = \"@file.read('prompt.txt')\"\n}\n",
+        );
+        let source = dir.path().join("main.hlx");
+        let output = out.join("main.hlxb");
+        let build = || {
+            IncrementalBuild::new(OptimizationLevel::Zero, false, &out)
+                .target(&source, &output)
+                .run()
+                .unwrap()
+        };
+        assert_eq!(build().modules[0].reason, Some(RebuildReason::NotBuilt));
+        assert_eq!(build().modules[0].reason, None);
+        write(dir.path(), "prompt.txt", "goodbye");
+        assert_eq!(
+            build().modules[0].reason,
+            Some(RebuildReason::InputChanged(dir.path().join("prompt.txt")))
+        );
+        fs::remove_file(&output).unwrap();
+        assert_eq!(build().modules[0].reason, Some(RebuildReason::OutputMissing));
+    }
+
+    #[test]
+    fn test_disabling_cache_rebuilds_everything() {
+        let dir = TempDir::new().unwrap();
+        let out = dir.path().join("target");
+        write(dir.path(), "main.hlx", "agent \"a\" {\n    model = \"gpt-4\"\n}\n");
+        let source = dir.path().join("main.hlx");
+        let output = out.join("main.hlxb");
+        let build = |cache: bool| {
+            IncrementalBuild::new(OptimizationLevel::Zero, false, &out)
+                .cache(cache)
+                .target(&source, &output)
+                .run()
+                .unwrap()
+        };
+        assert_eq!(build(false).modules[0].reason, Some(RebuildReason::NotBuilt));
+        assert_eq!(build(false).modules[0].reason, Some(RebuildReason::CacheDisabled));
+        assert_eq!(build(true).modules[0].reason, None);
+    }
+}
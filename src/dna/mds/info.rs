@@ -1,6 +1,8 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use crate::mds::loader::BinaryLoader;
-use crate::hel::binary::{HelixBinary, DataSection};
+use crate::hel::binary::SEALED_KEYS;
+use crate::hel::encryption::SectionKey;
+use crate::out::hlxb_config_format::HlxbReader;
 use anyhow::Result;
 
 pub fn info_command(
@@ -9,9 +11,17 @@ pub fn info_command(
     symbols: bool,
     sections: bool,
     verbose: bool,
+    key: Option<SectionKey>,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let loader = BinaryLoader::new();
-    let binary = loader.load_file(&file)?;
+    let loader = BinaryLoader::new().with_section_keys(key.into_iter().collect());
+    let binary = match loader.load_file(&file) {
+        Ok(binary) => binary,
+        // Config-format .hlxb files share the magic but not the layout
+        Err(err) => {
+            return config_info(&file, &format, sections || verbose).map_err(|_| err.into());
+        }
+    };
+    let locked = binary.locked_sections();
     match format.as_str() {
         "json" => {
             let json = serde_json::to_string_pretty(&binary.metadata)?;
@@ -30,6 +40,13 @@ pub fn info_command(
             println!("Created: {}", binary.metadata.created_at);
             println!("Optimization: Level {}", binary.metadata.optimization_level);
             println!("Compressed: {}", binary.flags.compressed);
+            if binary.flags.encrypted {
+                let keys = binary.metadata.extra.get(SEALED_KEYS).map(String::as_str);
+                println!("Encrypted: keys {}", keys.unwrap_or("(unknown)"));
+                for (_, sealed) in &locked {
+                    println!("  Locked: {:?} (needs key {})", sealed.kind, sealed.key_id);
+                }
+            }
             println!("Size: {} bytes", binary.size());
             println!("Checksum: {:x}", binary.checksum);
             if let Some(source) = &binary.metadata.source_path {
@@ -51,7 +68,10 @@ pub fn info_command(
             if sections || verbose {
                 println!("\nData Sections:");
                 for (i, section) in binary.data_sections.iter().enumerate() {
-                    println!("  [{}] {:?}", i, section.section_type);
+                    match locked.iter().find(|(index, _)| *index == i) {
+                        Some((_, sealed)) => println!("  [{}] {:?} (locked)", i, sealed.kind),
+                        None => println!("  [{}] {:?}", i, section.section_type),
+                    }
                     println!("      Size: {} bytes", section.size);
                     if let Some(compression) = &section.compression {
                         println!("      Compression: {:?}", compression);
@@ -61,4 +81,39 @@ pub fn info_command(
         }
     }
     Ok(())
-}
\ No newline at end of file
+}
+fn config_info(
+    file: &Path,
+    format: &str,
+    sections: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut reader = HlxbReader::new(std::fs::File::open(file)?);
+    let header = reader.read_header()?;
+    let index = reader.read_index()?;
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(index)?);
+        return Ok(());
+    }
+    println!("HELIX Config Binary Information");
+    println!("===============================");
+    println!("File: {}", file.display());
+    println!("Version: {}", header.version);
+    println!("Created: {}", header.created_at);
+    println!("Sections: {}", header.section_count);
+    match header.index_offset {
+        Some(offset) => println!("Index: {} entries at offset {}", index.len(), offset),
+        None => println!("Index: none stored, {} entries found by scanning", index.len()),
+    }
+    if sections {
+        println!("\nEntries:");
+        for entry in index {
+            println!("  {}", entry.path());
+            println!("      Offset: {}", entry.offset);
+            println!(
+                "      Size: {} bytes ({} uncompressed)", entry.compressed_size, entry
+                .uncompressed_size
+            );
+        }
+    }
+    Ok(())
+}
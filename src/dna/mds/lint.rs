@@ -1,15 +1,26 @@
 use std::path::PathBuf;
 use std::fs;
 use anyhow::{Result, Context};
+use crate::dna::ops::hermetic::nondeterministic_calls;
+use crate::dna::ops::meta::OperatorDescriptor;
 
 pub fn lint_files(files: Vec<PathBuf>, verbose: bool) -> Result<()> {
+    lint_files_with(files, verbose, None)
+}
+/// Lint, additionally reporting every call to an operator `hermetic` marks
+/// nondeterministic (and every `$VAR`) when given
+pub fn lint_files_with(
+    files: Vec<PathBuf>,
+    verbose: bool,
+    hermetic: Option<&[OperatorDescriptor]>,
+) -> Result<()> {
     if files.is_empty() {
-        lint_project(verbose)
+        lint_project(verbose, hermetic)
     } else {
-        lint_specific_files(files, verbose)
+        lint_specific_files(files, verbose, hermetic)
     }
 }
-pub fn lint_project(verbose: bool) -> Result<()> {
+pub fn lint_project(verbose: bool, hermetic: Option<&[OperatorDescriptor]>) -> Result<()> {
     let project_dir = find_project_root()?;
     if verbose {
         println!("🔍 Linting HELIX project:");
@@ -25,7 +36,7 @@ pub fn lint_project(verbose: bool) -> Result<()> {
     let mut total_issues = 0;
     let mut files_with_issues = 0;
     for file in helix_files {
-        match lint_single_file(&file, verbose) {
+        match lint_single_file(&file, verbose, hermetic) {
             Ok(issues) => {
                 if !issues.is_empty() {
                     files_with_issues += 1;
@@ -52,7 +63,11 @@ pub fn lint_project(verbose: bool) -> Result<()> {
     }
     Ok(())
 }
-pub fn lint_specific_files(files: Vec<PathBuf>, verbose: bool) -> Result<()> {
+pub fn lint_specific_files(
+    files: Vec<PathBuf>,
+    verbose: bool,
+    hermetic: Option<&[OperatorDescriptor]>,
+) -> Result<()> {
     if verbose {
         println!("🔍 Linting specific files:");
         println!("  Files: {}", files.len());
@@ -68,7 +83,7 @@ pub fn lint_specific_files(files: Vec<PathBuf>, verbose: bool) -> Result<()> {
             eprintln!("⚠️  Skipping non-HELIX file: {}", file.display());
             continue;
         }
-        match lint_single_file(&file, verbose) {
+        match lint_single_file(&file, verbose, hermetic) {
             Ok(issues) => {
                 if !issues.is_empty() {
                     files_with_issues += 1;
@@ -96,7 +111,7 @@ pub fn lint_specific_files(files: Vec<PathBuf>, verbose: bool) -> Result<()> {
     Ok(())
 }
 #[derive(Debug)]
-struct LintIssue {
+pub struct LintIssue {
     line: usize,
     column: usize,
     severity: LintSeverity,
@@ -110,7 +125,11 @@ enum LintSeverity {
     #[allow(dead_code)]
     Info,
 }
-pub fn lint_single_file(file: &PathBuf, verbose: bool) -> Result<Vec<LintIssue>> {
+pub fn lint_single_file(
+    file: &PathBuf,
+    verbose: bool,
+    hermetic: Option<&[OperatorDescriptor]>,
+) -> Result<Vec<LintIssue>> {
     let content = fs::read_to_string(file).context("Failed to read file")?;
     let mut issues = Vec::new();
     let lines: Vec<&str> = content.lines().collect();
@@ -119,6 +138,9 @@ pub fn lint_single_file(file: &PathBuf, verbose: bool) -> Result<Vec<LintIssue>>
         issues.extend(check_line_issues(line, line_num));
     }
     issues.extend(check_file_issues(&content, file));
+    if let Some(descriptors) = hermetic {
+        issues.extend(check_hermetic_issues(&content, descriptors));
+    }
     if verbose && !issues.is_empty() {
         println!("  Issues in {}:", file.display());
         for issue in &issues {
@@ -212,6 +234,18 @@ pub fn check_file_issues(content: &str, _file: &PathBuf) -> Vec<LintIssue> {
     }
     issues
 }
+fn check_hermetic_issues(content: &str, descriptors: &[OperatorDescriptor]) -> Vec<LintIssue> {
+    nondeterministic_calls(content, descriptors)
+        .into_iter()
+        .map(|site| LintIssue {
+            line: site.line,
+            column: site.column,
+            severity: LintSeverity::Warning,
+            message: format!("{} is nondeterministic: {}", site.call, site.reason),
+            rule: "hermetic".to_string(),
+        })
+        .collect()
+}
 pub fn find_helix_files(dir: &PathBuf, files: &mut Vec<PathBuf>) -> Result<()> {
     let entries = fs::read_dir(dir).context("Failed to read directory")?;
     for entry in entries {
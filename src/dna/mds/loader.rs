@@ -1 +1,408 @@
-use std::path::Path; use crate::dna::hel::binary::HelixBinary; pub struct BinaryLoader; impl BinaryLoader { pub fn new() -> Self { Self } } impl BinaryLoader { pub fn load_file(&self, path: &Path) -> Result<HelixBinary, String> { Ok(HelixBinary::new()) } }
+use crate::dna::hel::binary::{DataSection, FormatError, HelixBinary, BINARY_VERSION, MAGIC_BYTES};
+use crate::dna::hel::encryption::{EncryptionError, SectionKey};
+use crate::dna::hel::limits::{LimitError, ReadLimits};
+use crate::dna::hel::signing::{PublicKey, SignatureError, TrustPolicy};
+use memmap2::Mmap;
+use once_cell::sync::{Lazy, OnceCell};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Why a `.hlxb` file could not be loaded
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[error("Failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("{path} ends unexpectedly after {len} bytes; it is truncated or corrupt")]
+    Truncated { path: PathBuf, len: usize },
+    #[error("{path} is not a HELIX binary (magic bytes {found:?}, expected {:?})", MAGIC_BYTES)]
+    BadMagic { path: PathBuf, found: [u8; 4] },
+    #[error(
+        "{path} uses binary format version {found}, but this build reads up to version {}",
+        BINARY_VERSION
+    )]
+    UnsupportedVersion { path: PathBuf, found: u32 },
+    #[error("{path} is corrupt: {message}")]
+    Corrupt { path: PathBuf, message: String },
+    #[error("{path} has no {section} section")]
+    MissingSection { path: PathBuf, section: String },
+    #[error("Checksum mismatch in {path}: header records {expected:x}, contents hash to {actual:x}")]
+    ChecksumMismatch { path: PathBuf, expected: u64, actual: u64 },
+    #[error("Refusing to load {path}: the signing policy in the global config is invalid: {message}")]
+    TrustConfig { path: PathBuf, message: String },
+    #[error("Refusing to load {path}: {source}")]
+    Signature {
+        path: PathBuf,
+        #[source]
+        source: SignatureError,
+    },
+    #[error("Failed to decrypt {path}: {source}")]
+    Decryption {
+        path: PathBuf,
+        #[source]
+        source: EncryptionError,
+    },
+    #[error("Section {index} of {path} is locked; it needs section key {key_id}")]
+    Locked { path: PathBuf, index: usize, key_id: String },
+    #[error("Failed to decompress section {index} ({section}) of {path}: {message}")]
+    Decompression { path: PathBuf, index: usize, section: String, message: String },
+    #[error("Refusing to load {path}: {source}")]
+    Limit {
+        path: PathBuf,
+        #[source]
+        source: LimitError,
+    },
+}
+
+/// Signing policy from the global config, read once per process
+static CONFIGURED_TRUST: Lazy<Result<TrustPolicy, String>> = Lazy::new(|| {
+    crate::dna::mds::config::trust_policy().map_err(|e| format!("{:#}", e))
+});
+
+/// Reads compiled `.hlxb` files, checking the header, signature and checksum
+/// before handing out the binary
+pub struct BinaryLoader {
+    use_mmap: bool,
+    verify_checksum: bool,
+    /// `Err` when the global config's signing policy can't be read; every
+    /// load then fails rather than falling back to accepting anything
+    trust: Result<TrustPolicy, String>,
+    section_keys: Vec<SectionKey>,
+    limits: ReadLimits,
+}
+
+impl BinaryLoader {
+    /// A loader enforcing the signing policy of the global config
+    pub fn new() -> Self {
+        Self {
+            use_mmap: true,
+            verify_checksum: true,
+            trust: CONFIGURED_TRUST.clone(),
+            section_keys: Vec::new(),
+            limits: ReadLimits::default(),
+        }
+    }
+
+    /// Map the file into memory (the default) instead of reading it
+    pub fn with_mmap(mut self, use_mmap: bool) -> Self {
+        self.use_mmap = use_mmap;
+        self
+    }
+
+    /// Skip checksum verification, e.g. to inspect a damaged file
+    pub fn with_checksum_verification(mut self, verify: bool) -> Self {
+        self.verify_checksum = verify;
+        self
+    }
+
+    /// Decide which signatures are accepted instead of following the global
+    /// config. Signatures that are present must be valid under any policy.
+    pub fn with_trust_policy(mut self, trust: TrustPolicy) -> Self {
+        self.trust = Ok(trust);
+        self
+    }
+
+    /// Decrypt sections sealed with any of `keys`. Sections sealed with
+    /// other keys stay locked.
+    pub fn with_section_keys(mut self, keys: Vec<SectionKey>) -> Self {
+        self.section_keys = keys;
+        self
+    }
+
+    /// Bound section sizes, decompression, nesting and string tables
+    pub fn with_limits(mut self, limits: ReadLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Load and verify a binary. Data sections are returned as stored; use
+    /// [`BinaryLoader::open`] to decompress them on demand.
+    pub fn load_file(&self, path: impl AsRef<Path>) -> Result<HelixBinary, LoadError> {
+        self.open(path).map(LoadedBinary::into_binary)
+    }
+
+    /// Load and verify a binary, decompressing sections only when read
+    pub fn open(&self, path: impl AsRef<Path>) -> Result<LoadedBinary, LoadError> {
+        let path = path.as_ref();
+        let io = |source| LoadError::Io { path: path.to_path_buf(), source };
+        let file = File::open(path).map_err(io)?;
+        let (binary, signer) = if self.use_mmap && file.metadata().map_err(io)?.len() > 0 {
+            // Safety: the map is only read while decoding and dropped before
+            // returning; nothing borrows from it afterwards
+            let mmap = unsafe { Mmap::map(&file) }.map_err(io)?;
+            self.decode(path, &mmap)?
+        } else {
+            let bytes = std::fs::read(path).map_err(io)?;
+            self.decode(path, &bytes)?
+        };
+        Ok(LoadedBinary {
+            path: path.to_path_buf(),
+            sections: binary.data_sections.iter().map(|_| OnceCell::new()).collect(),
+            binary,
+            signer,
+            limits: self.limits,
+        })
+    }
+
+    fn decode(
+        &self,
+        path: &Path,
+        bytes: &[u8],
+    ) -> Result<(HelixBinary, Option<PublicKey>), LoadError> {
+        let path = path.to_path_buf();
+        let binary = HelixBinary::from_bytes_within(bytes, &self.limits).map_err(|e| match e {
+            FormatError::Truncated => LoadError::Truncated { path: path.clone(), len: bytes.len() },
+            FormatError::BadMagic(found) => LoadError::BadMagic { path: path.clone(), found },
+            FormatError::UnsupportedVersion(found) => {
+                LoadError::UnsupportedVersion { path: path.clone(), found }
+            }
+            FormatError::MissingSection(tag) => LoadError::MissingSection {
+                path: path.clone(),
+                section: String::from_utf8_lossy(&tag).into_owned(),
+            },
+            FormatError::Corrupt(message) => LoadError::Corrupt { path: path.clone(), message },
+            FormatError::Limit(source) => LoadError::Limit { path: path.clone(), source },
+        })?;
+        let trust = self.trust.as_ref().map_err(|message| LoadError::TrustConfig {
+            path: path.clone(),
+            message: message.clone(),
+        })?;
+        let signature = binary.signature.as_ref().zip(HelixBinary::signed_message(bytes));
+        let signer = trust
+            .check(binary.flags.signed, signature)
+            .map_err(|source| LoadError::Signature { path: path.clone(), source })?;
+        if self.verify_checksum {
+            let actual = binary.calculate_checksum();
+            if actual != binary.checksum {
+                return Err(LoadError::ChecksumMismatch {
+                    path,
+                    expected: binary.checksum,
+                    actual,
+                });
+            }
+        }
+        let mut binary = binary;
+        for key in &self.section_keys {
+            binary
+                .unseal(key)
+                .map_err(|source| LoadError::Decryption { path: path.clone(), source })?;
+        }
+        Ok((binary, signer))
+    }
+}
+
+impl Default for BinaryLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A verified binary whose data sections are decompressed on first access
+pub struct LoadedBinary {
+    path: PathBuf,
+    binary: HelixBinary,
+    sections: Vec<OnceCell<Vec<u8>>>,
+    signer: Option<PublicKey>,
+    limits: ReadLimits,
+}
+
+impl LoadedBinary {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Header, metadata, symbol table and sections as stored
+    pub fn binary(&self) -> &HelixBinary {
+        &self.binary
+    }
+
+    /// Key whose signature was verified, if the binary is signed
+    pub fn signer(&self) -> Option<PublicKey> {
+        self.signer
+    }
+
+    pub fn into_binary(self) -> HelixBinary {
+        self.binary
+    }
+
+    pub fn section_count(&self) -> usize {
+        self.binary.data_sections.len()
+    }
+
+    /// Decompressed contents of section `index`, decompressed once and
+    /// cached. `None` when there is no such section.
+    pub fn section_data(&self, index: usize) -> Option<Result<&[u8], LoadError>> {
+        let section = self.binary.data_sections.get(index)?;
+        if let Some(sealed) = section.sealed() {
+            return Some(Err(LoadError::Locked {
+                path: self.path.clone(),
+                index,
+                key_id: sealed.key_id,
+            }));
+        }
+        let data = self.sections[index].get_or_try_init(|| {
+            let mut section: DataSection = section.clone();
+            section.decompress_within(&self.limits).map_err(|e| match e {
+                FormatError::Limit(source) => LoadError::Limit { path: self.path.clone(), source },
+                other => LoadError::Decompression {
+                    path: self.path.clone(),
+                    index,
+                    section: format!("{:?}", section.section_type),
+                    message: other.to_string(),
+                },
+            })?;
+            Ok(section.data)
+        });
+        Some(data.map(Vec::as_slice))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dna::hel::binary::{CompressionMethod, SectionType};
+    use crate::dna::mds::serializer::{BinarySerializer, SerializationError};
+    use tempfile::TempDir;
+
+    fn sample() -> HelixBinary {
+        let mut binary = HelixBinary::new();
+        binary.symbol_table.intern("assistant");
+        let mut section = DataSection::new(SectionType::Agents, b"agent assistant { model = \"gpt-4\" }".repeat(8));
+        section.compress(CompressionMethod::Lz4).unwrap();
+        binary.data_sections.push(section);
+        binary.checksum = binary.calculate_checksum();
+        binary
+    }
+
+    /// `sample()` as the version 1 format stored it: bare bincode
+    fn sample_v1() -> Vec<u8> {
+        let mut binary = sample();
+        binary.version = 1;
+        binary.checksum = binary.calculate_checksum();
+        bincode::serialize(&binary).unwrap()
+    }
+
+    fn write(dir: &TempDir, name: &str, bytes: &[u8]) -> PathBuf {
+        let path = dir.path().join(name);
+        std::fs::write(&path, bytes).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_loads_and_lazily_decompresses_sections() {
+        let dir = TempDir::new().unwrap();
+        let v1 = write(&dir, "v1.hlxb", &sample_v1());
+        let path = write(&dir, "ok.hlxb", &sample().to_bytes().unwrap());
+        assert_eq!(BinaryLoader::new().load_file(&v1).unwrap().symbol_table.strings.len(), 1);
+        for loader in [BinaryLoader::new(), BinaryLoader::new().with_mmap(false)] {
+            let loaded = loader.open(&path).unwrap();
+            assert_eq!(loaded.binary().symbol_table.strings, vec!["assistant".to_string()]);
+            assert!(loaded.binary().data_sections[0].compression.is_some());
+            let data = loaded.section_data(0).unwrap().unwrap();
+            assert!(data.starts_with(b"agent assistant"));
+            assert!(loaded.section_data(1).is_none());
+        }
+    }
+
+    #[test]
+    fn test_sealed_sections_stay_locked_without_a_key() {
+        use crate::dna::compiler::{Compiler, OptimizationLevel};
+        use crate::dna::hel::encryption::SectionKey;
+        let dir = TempDir::new().unwrap();
+        let key = SectionKey::generate().unwrap();
+        let binary = Compiler::new(OptimizationLevel::Two)
+            .with_encryption(key.clone(), Vec::new())
+            .compile_source("context \"prod\" {\n environment = \"prod\"\n}", None)
+            .unwrap();
+        let path = write(&dir, "sealed.hlxb", &binary.to_bytes().unwrap());
+
+        let locked = BinaryLoader::new().open(&path).unwrap();
+        let (index, sealed) = locked.binary().locked_sections().remove(0);
+        assert_eq!(sealed.kind, SectionType::Contexts);
+        assert!(matches!(
+            locked.section_data(index),
+            Some(Err(LoadError::Locked { key_id, .. })) if key_id == key.id()
+        ));
+        let opened = BinaryLoader::new().with_section_keys(vec![key]).open(&path).unwrap();
+        assert!(opened.binary().locked_sections().is_empty());
+        assert!(opened.binary().symbol_table.contexts.contains_key("prod"));
+    }
+
+    #[test]
+    fn test_rejects_damaged_files_with_typed_errors() {
+        let dir = TempDir::new().unwrap();
+        let bytes = sample_v1();
+        let loader = BinaryLoader::new();
+
+        let empty = write(&dir, "empty.hlxb", b"");
+        assert!(matches!(loader.load_file(&empty), Err(LoadError::Truncated { len: 0, .. })));
+        let truncated = write(&dir, "truncated.hlxb", &bytes[..bytes.len() / 2]);
+        assert!(matches!(loader.load_file(&truncated), Err(LoadError::Truncated { .. })));
+        let magic = write(&dir, "magic.hlxb", &[b"JUNK", &bytes[4..]].concat());
+        assert!(matches!(loader.load_file(&magic), Err(LoadError::BadMagic { found, .. }) if &found == b"JUNK"));
+
+        let mut newer = sample().to_bytes().unwrap();
+        newer[4..8].copy_from_slice(&(BINARY_VERSION + 1).to_le_bytes());
+        newer[8..12].copy_from_slice(&(BINARY_VERSION + 1).to_le_bytes());
+        let newer = write(&dir, "newer.hlxb", &newer);
+        assert!(matches!(loader.load_file(&newer), Err(LoadError::UnsupportedVersion { found, .. }) if found == BINARY_VERSION + 1));
+
+        let mut flipped = bytes.clone();
+        let last = flipped.len() - 20;
+        flipped[last] ^= 0xff;
+        let flipped = write(&dir, "flipped.hlxb", &flipped);
+        assert!(matches!(loader.load_file(&flipped), Err(LoadError::ChecksumMismatch { .. })));
+        assert!(BinaryLoader::new().with_checksum_verification(false).load_file(&flipped).is_ok());
+
+        // Inside the last data section's payload, before the checksum section
+        let mut flipped = sample().to_bytes().unwrap();
+        let inside = flipped.len() - 26;
+        flipped[inside] ^= 0xff;
+        let flipped = write(&dir, "flipped-v2.hlxb", &flipped);
+        assert!(matches!(loader.load_file(&flipped), Err(LoadError::ChecksumMismatch { .. })));
+
+        let missing = dir.path().join("missing.hlxb");
+        let err = loader.load_file(&missing).unwrap_err();
+        assert!(matches!(err, LoadError::Io { .. }));
+        assert!(err.to_string().contains("missing.hlxb"));
+    }
+
+    #[test]
+    fn test_malformed_fixtures_fail_with_typed_errors() {
+        let fixtures = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/malformed");
+        let loader = BinaryLoader::new();
+        assert!(matches!(
+            loader.load_file(fixtures.join("binary_huge_section.hlxb")),
+            Err(LoadError::Limit { source: LimitError::SectionTooLarge { .. }, .. })
+        ));
+        assert!(matches!(
+            loader.load_file(fixtures.join("binary_truncated.hlxb")),
+            Err(LoadError::MissingSection { section, .. }) if section == "CSUM"
+        ));
+        // Claims to expand 278 bytes to 4GiB: over the section limit, and
+        // over the ratio limit once the section limit is lifted
+        let bomb = loader.open(fixtures.join("binary_lz4_bomb.hlxb")).unwrap();
+        assert!(matches!(
+            bomb.section_data(0),
+            Some(Err(LoadError::Limit { source: LimitError::SectionTooLarge { .. }, .. }))
+        ));
+        let unbounded = ReadLimits { max_section_size: u64::MAX, ..ReadLimits::default() };
+        let bomb = BinaryLoader::new()
+            .with_limits(unbounded)
+            .open(fixtures.join("binary_lz4_bomb.hlxb"))
+            .unwrap();
+        assert!(matches!(
+            bomb.section_data(0),
+            Some(Err(LoadError::Limit { source: LimitError::DecompressionRatio { .. }, .. }))
+        ));
+        let deep = loader.load_file(fixtures.join("binary_deep_value.hlxb")).unwrap();
+        assert!(matches!(
+            BinarySerializer::new(false).deserialize_to_ir(&deep),
+            Err(SerializationError::LimitExceeded(LimitError::TooDeep { .. }))
+        ));
+    }
+}
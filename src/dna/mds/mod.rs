@@ -23,6 +23,7 @@ pub mod map;
 pub mod lint;
 pub mod loader;
 pub mod migrate;
+pub mod ops;
 pub mod optimizer;
 pub mod preview;
 pub mod project;
@@ -38,6 +39,7 @@ pub mod sign;
 pub mod templates;
 pub mod test;
 pub mod tools;
+pub mod upgrade;
 pub mod validate;
 pub mod watch;
 pub mod workflow;
@@ -45,4 +47,5 @@ pub mod server;
 pub mod semantic;
 pub mod codegen;
 pub mod modules;
+pub mod incremental;
 pub mod caption;
\ No newline at end of file
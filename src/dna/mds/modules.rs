@@ -1,8 +1,9 @@
 use std::path::{Path, PathBuf};
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use anyhow::{Result, Context};
 use crate::dna::atp::ast::{HelixAst, Declaration};
 use crate::dna::hel::error::{HlxError, CompilationError, CompilationStage};
+use crate::dna::ops::file::normalize;
 pub struct ModuleSystem {
     modules: HashMap<PathBuf, Module>,
     dependencies: HashMap<PathBuf, HashSet<PathBuf>>,
@@ -155,7 +156,7 @@ impl ModuleSystem {
                 format!("Parsing error: {}", e),
                 "Check syntax and file format",
             ))?;
-        let deps = self.extract_dependencies(&ast);
+        let deps = self.extract_dependencies(&path, &ast)?;
         let imports = self.extract_imports(&ast)?;
         let exports = self.extract_exports(&ast)?;
         let module = Module {
@@ -178,14 +179,37 @@ impl ModuleSystem {
         self.asts.insert(path, ast);
         Ok(())
     }
-    fn extract_dependencies(&self, _ast: &HelixAst) -> HashSet<PathBuf> {
-        let deps = HashSet::new();
-        for decl in &_ast.declarations {
-            match decl {
-                _ => {}
+    /// Modules named by the `load` declarations in `ast`
+    fn extract_dependencies(
+        &self,
+        path: &Path,
+        ast: &HelixAst,
+    ) -> Result<HashSet<PathBuf>, HlxError> {
+        let mut deps = HashSet::new();
+        for decl in &ast.declarations {
+            if let Declaration::Load(load) = decl {
+                deps.insert(self.resolve_import_path(&load.file_name, path)?);
             }
         }
-        deps
+        Ok(deps)
+    }
+    /// Load `root` and every module it reaches through `load` declarations
+    pub fn load_with_dependencies(&mut self, root: &Path) -> Result<()> {
+        let mut queue = VecDeque::new();
+        queue.push_back(root.to_path_buf());
+        while let Some(current) = queue.pop_front() {
+            if self.modules.contains_key(&current) {
+                continue;
+            }
+            self.load_module(&current)
+                .with_context(|| format!("Failed to load {}", current.display()))?;
+            for dep in self.get_dependencies(&current) {
+                if !self.modules.contains_key(&dep) {
+                    queue.push_back(dep);
+                }
+            }
+        }
+        Ok(())
     }
     pub fn resolve_dependencies(&mut self) -> Result<()> {
         if let Some(cycle) = self.find_circular_dependency() {
@@ -200,23 +224,20 @@ impl ModuleSystem {
         self.resolution_order = self.topological_sort()?;
         Ok(())
     }
+    /// Loaded modules with every module after the ones it loads
     pub fn compilation_order(&self) -> &[PathBuf] {
         &self.resolution_order
     }
-    #[allow(dead_code)]
     fn resolve_import_path(
         &self,
         import_path: &str,
         from_module: &Path,
     ) -> Result<PathBuf, HlxError> {
-        if import_path.starts_with("./") || import_path.starts_with("../") {
-            let base_dir = from_module.parent().unwrap_or(Path::new("."));
-            return Ok(base_dir.join(import_path).with_extension("hlx"));
-        }
         if import_path.starts_with("/") {
             return Ok(PathBuf::from(import_path).with_extension("hlx"));
         }
-        Ok(PathBuf::from(format!("{}.hlx", import_path)))
+        let base_dir = from_module.parent().unwrap_or(Path::new(""));
+        Ok(normalize(&base_dir.join(import_path).with_extension("hlx")))
     }
     fn extract_imports(&self, _ast: &HelixAst) -> Result<ModuleImports, HlxError> {
         let imports = Vec::new();
@@ -319,30 +340,27 @@ impl ModuleSystem {
         false
     }
     fn topological_sort(&self) -> Result<Vec<PathBuf>, HlxError> {
-        let mut in_degree: HashMap<PathBuf, usize> = HashMap::new();
+        let mut pending: HashMap<&PathBuf, usize> = self
+            .dependencies
+            .iter()
+            .map(|(path, deps)| {
+                (path, deps.iter().filter(|dep| self.dependencies.contains_key(*dep)).count())
+            })
+            .collect();
+        let mut ready: BTreeSet<&PathBuf> = pending
+            .iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(&path, _)| path)
+            .collect();
         let mut result = Vec::new();
-        let mut queue = VecDeque::new();
-        for path in self.dependencies.keys() {
-            in_degree.insert(path.clone(), 0);
-        }
-        for (_, deps) in &self.dependencies {
-            for dep in deps {
-                *in_degree.entry(dep.clone()).or_insert(0) += 1;
-            }
-        }
-        for (path, &degree) in &in_degree {
-            if degree == 0 {
-                queue.push_back(path.clone());
-            }
-        }
-        while let Some(node) = queue.pop_front() {
+        while let Some(node) = ready.pop_first() {
             result.push(node.clone());
-            if let Some(deps) = self.dependencies.get(&node) {
-                for dep in deps {
-                    if let Some(degree) = in_degree.get_mut(dep) {
-                        *degree -= 1;
-                        if *degree == 0 {
-                            queue.push_back(dep.clone());
+            if let Some(dependents) = self.dependents.get(node) {
+                for dependent in dependents {
+                    if let Some(count) = pending.get_mut(dependent) {
+                        *count -= 1;
+                        if *count == 0 {
+                            ready.insert(dependent);
                         }
                     }
                 }
@@ -358,9 +376,11 @@ impl ModuleSystem {
         }
         Ok(result)
     }
+    /// All modules in one AST. A module's declarations win over same-named
+    /// ones in the modules it loads.
     pub fn merge_modules(&self) -> Result<HelixAst, HlxError> {
         let mut merged_declarations = Vec::new();
-        for path in &self.resolution_order {
+        for path in self.resolution_order.iter().rev() {
             if let Some(module) = self.modules.get(path) {
                 for decl in &module.ast.declarations {
                     if !Self::declaration_exists(&merged_declarations, decl) {
@@ -373,6 +393,30 @@ impl ModuleSystem {
             declarations: merged_declarations,
         })
     }
+    /// `root` merged with the modules it transitively loads, `root` first
+    pub fn merge_closure(&self, root: &Path) -> HelixAst {
+        let mut merged_declarations = Vec::new();
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::from([root.to_path_buf()]);
+        while let Some(path) = queue.pop_front() {
+            if !seen.insert(path.clone()) {
+                continue;
+            }
+            if let Some(module) = self.modules.get(&path) {
+                for decl in &module.ast.declarations {
+                    if !Self::declaration_exists(&merged_declarations, decl) {
+                        merged_declarations.push(decl.clone());
+                    }
+                }
+            }
+            let mut deps: Vec<_> = self.get_dependencies(&path).into_iter().collect();
+            deps.sort();
+            queue.extend(deps);
+        }
+        HelixAst {
+            declarations: merged_declarations,
+        }
+    }
     fn declaration_exists(declarations: &[Declaration], decl: &Declaration) -> bool {
         for existing in declarations {
             match (existing, decl) {
@@ -424,23 +468,7 @@ impl DependencyBundler {
         }
     }
     pub fn add_root<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
-        let mut queue = VecDeque::new();
-        let mut processed = HashSet::new();
-        queue.push_back(path.as_ref().to_path_buf());
-        while let Some(current) = queue.pop_front() {
-            if processed.contains(&current) {
-                continue;
-            }
-            self.module_system.load_module(&current)?;
-            processed.insert(current.clone());
-            let deps = self.module_system.get_dependencies(&current);
-            for dep in deps {
-                if !processed.contains(&dep) {
-                    queue.push_back(dep);
-                }
-            }
-        }
-        Ok(())
+        self.module_system.load_with_dependencies(path.as_ref())
     }
     pub fn build_bundle(&mut self) -> Result<HelixAst> {
         self.module_system.resolve_dependencies()?;
@@ -525,6 +553,21 @@ mod tests {
         assert!(module.exports.workflows.contains_key("review"));
     }
     #[test]
+    fn test_load_declarations_order_compilation() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().join("base.hlx");
+        let app = temp_dir.path().join("app.hlx");
+        std::fs::write(&base, "agent \"base\" {\n    model = \"gpt-4\"\n}\n").unwrap();
+        std::fs::write(&app, "load base {\n}\nagent \"app\" {\n    model = \"gpt-4\"\n}\n")
+            .unwrap();
+        let mut module_system = ModuleSystem::new();
+        module_system.load_with_dependencies(&app).unwrap();
+        module_system.resolve_dependencies().unwrap();
+        assert_eq!(module_system.compilation_order(), &[base.clone(), app.clone()]);
+        assert!(module_system.get_dependents(&base).contains(&app));
+        assert_eq!(module_system.merge_closure(&app).declarations.len(), 3);
+    }
+    #[test]
     fn test_dependency_bundler() {
         let mut bundler = DependencyBundler::new();
         assert!(bundler.module_system.modules.is_empty());
@@ -0,0 +1,119 @@
+use crate::ops::engine::OperatorEngine;
+use crate::ops::meta::{OperatorCategory, OperatorDescriptor};
+use crate::ops::OperatorParser;
+use anyhow::{anyhow, Context, Result};
+use std::path::Path;
+
+pub async fn list_operators(file: Option<&Path>, category: Option<&str>, json: bool) -> Result<()> {
+    let category = category
+        .map(|c| c.parse::<OperatorCategory>().map_err(|e| anyhow!(e)))
+        .transpose()?;
+    let descriptors: Vec<OperatorDescriptor> = load_descriptors(file)
+        .await?
+        .into_iter()
+        .filter(|d| category.is_none_or(|c| d.category == c))
+        .collect();
+    if json {
+        println!("{}", serde_json::to_string_pretty(&descriptors)?);
+    } else {
+        print!("{}", render_list(&descriptors));
+    }
+    Ok(())
+}
+
+pub async fn describe_operator(file: Option<&Path>, operator: &str, json: bool) -> Result<()> {
+    let name = operator.trim_start_matches('@');
+    let descriptors = load_descriptors(file).await?;
+    let descriptor = descriptors.iter().find(|d| d.name == name).ok_or_else(|| {
+        let similar: Vec<String> = descriptors
+            .iter()
+            .filter(|d| d.name.contains(name) || name.contains(d.name.as_str()))
+            .map(|d| format!("@{}", d.name))
+            .collect();
+        if similar.is_empty() {
+            anyhow!("Unknown operator @{} (see `hlx ops list`)", name)
+        } else {
+            anyhow!("Unknown operator @{}; did you mean {}?", name, similar.join(", "))
+        }
+    })?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(descriptor)?);
+    } else {
+        print!("{}", render_describe(descriptor));
+    }
+    Ok(())
+}
+
+async fn load_descriptors(file: Option<&Path>) -> Result<Vec<OperatorDescriptor>> {
+    match file {
+        Some(path) => {
+            let mut parser = OperatorParser::new()
+                .await
+                .map_err(|e| anyhow!("Failed to create parser: {}", e))?;
+            parser
+                .parse_file(&path.to_string_lossy())
+                .await
+                .map_err(|e| anyhow!("{}", e))
+                .with_context(|| format!("Failed to load {}", path.display()))?;
+            parser
+                .operator_engine()
+                .map(|engine| engine.descriptors())
+                .ok_or_else(|| anyhow!("Operator engine is unavailable"))
+        }
+        None => Ok(OperatorEngine::new()
+            .await
+            .map_err(|e| anyhow!("Failed to create operator engine: {}", e))?
+            .descriptors()),
+    }
+}
+
+fn flags(descriptor: &OperatorDescriptor) -> &'static str {
+    match (descriptor.pure, descriptor.deterministic) {
+        (true, true) => "pure",
+        (true, false) => "nondeterministic",
+        (false, _) => "side effects",
+    }
+}
+
+fn render_list(descriptors: &[OperatorDescriptor]) -> String {
+    let width = descriptors.iter().map(|d| d.signature().len()).max().unwrap_or(0);
+    let mut out = String::new();
+    for category in OperatorCategory::ALL {
+        let in_category: Vec<&OperatorDescriptor> =
+            descriptors.iter().filter(|d| d.category == category).collect();
+        if in_category.is_empty() {
+            continue;
+        }
+        out.push_str(&format!("{}\n", category));
+        for d in in_category {
+            out.push_str(&format!("  {:<width$}  {}\n", d.signature(), d.summary, width = width));
+        }
+        out.push('\n');
+    }
+    out.push_str(&format!("{} operators\n", descriptors.len()));
+    out
+}
+
+fn render_describe(d: &OperatorDescriptor) -> String {
+    let mut out = format!("{}\n  {}\n\n", d.signature(), d.summary);
+    out.push_str(&format!("Category: {}\n", d.category));
+    out.push_str(&format!("Returns:  {}\n", d.returns));
+    out.push_str(&format!("Behavior: {}\n", flags(d)));
+    if !d.params.is_empty() {
+        out.push_str("\nParameters:\n");
+        for p in &d.params {
+            let required = if p.required { "required" } else { "optional" };
+            out.push_str(&format!("  {:<12} {:<7} {:<9} {}\n", p.name, p.ty, required, p.description));
+            if !p.choices.is_empty() {
+                out.push_str(&format!("  {:<12} one of: {}\n", "", p.choices.join(", ")));
+            }
+        }
+    }
+    if !d.examples.is_empty() {
+        out.push_str("\nExamples:\n");
+        for example in &d.examples {
+            out.push_str(&format!("  {}\n", example));
+        }
+    }
+    out
+}
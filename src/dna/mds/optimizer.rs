@@ -1,11 +1,14 @@
-use crate::dna::mds::codegen::{HelixIR, Instruction};
+use crate::dna::mds::codegen::{
+    DurationIR, HelixIR, Instruction, PipelineNodeIR, SecretType, StepDefinition, TimeUnitIR,
+    TriggerType,
+};
 use std::collections::{HashMap, HashSet};
 pub use crate::mds::codegen::{StringPool, SymbolTable, Metadata, ConstantPool, ConstantValue};
 use std::path::PathBuf;
 use anyhow::Result;
 
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum OptimizationLevel {
     Zero,
     One,
@@ -31,6 +34,8 @@ pub struct Optimizer {
     level: OptimizationLevel,
     stats: OptimizationStats,
 }
+/// A pass returns how many changes it made
+type Pass = fn(&mut Optimizer, &mut HelixIR) -> usize;
 impl Optimizer {
     pub fn new(level: OptimizationLevel) -> Self {
         Self {
@@ -39,47 +44,51 @@ impl Optimizer {
         }
     }
     pub fn optimize(&mut self, ir: &mut HelixIR) {
-        match self.level {
-            OptimizationLevel::Zero => {}
-            OptimizationLevel::One => {
-                self.apply_basic_optimizations(ir);
-            }
-            OptimizationLevel::Two => {
-                self.apply_basic_optimizations(ir);
-                self.apply_standard_optimizations(ir);
-            }
-            OptimizationLevel::Three => {
-                self.apply_basic_optimizations(ir);
-                self.apply_standard_optimizations(ir);
-                self.apply_aggressive_optimizations(ir);
-            }
+        if self.level == OptimizationLevel::Zero {
+            return;
         }
+        let original_size = encoded_size(ir);
+        self.run_pass("dead code", ir, Self::remove_dead_code);
+        if self.level >= OptimizationLevel::Two {
+            self.run_pass("constant folding", ir, Self::fold_constants);
+            self.run_pass("reference inlining", ir, Self::inline_references);
+            self.run_pass("step canonicalization", ir, Self::canonicalize_steps);
+            self.run_pass("instruction sequence", ir, Self::optimize_instruction_sequence);
+        }
+        if self.level == OptimizationLevel::Three {
+            self.run_pass("unreachable symbols", ir, Self::eliminate_unreachable);
+            self.run_pass("cache locality", ir, Self::reorder_for_cache_locality);
+        }
+        // Last, so strings left behind by the passes above are dropped too
+        self.run_pass("constant pool", ir, Self::deduplicate_constants);
+        self.run_pass("string pool layout", ir, Self::optimize_string_pool);
+        self.stats.bytes_saved = original_size.saturating_sub(encoded_size(ir));
     }
-    fn apply_basic_optimizations(&mut self, ir: &mut HelixIR) {
-        self.deduplicate_strings(ir);
-        self.remove_dead_code(ir);
-        self.optimize_string_pool(ir);
-    }
-    fn apply_standard_optimizations(&mut self, ir: &mut HelixIR) {
-        self.fold_constants(ir);
-        self.inline_small_functions(ir);
-        self.optimize_instruction_sequence(ir);
-        self.merge_duplicate_sections(ir);
-    }
-    fn apply_aggressive_optimizations(&mut self, ir: &mut HelixIR) {
-        self.eliminate_cross_references(ir);
-        self.optimize_pipelines(ir);
-        self.compress_data_sections(ir);
-        self.reorder_for_cache_locality(ir);
+    fn run_pass(&mut self, name: &'static str, ir: &mut HelixIR, pass: Pass) {
+        let instructions = ir.instructions.len();
+        let strings = ir.string_pool.strings.len();
+        let size = encoded_size(ir);
+        let changes = pass(self, ir);
+        self.stats.instructions_removed += instructions.saturating_sub(ir.instructions.len());
+        self.stats
+            .passes
+            .push(PassStats {
+                name,
+                changes,
+                instructions_removed: instructions.saturating_sub(ir.instructions.len()),
+                strings_removed: strings.saturating_sub(ir.string_pool.strings.len()),
+                bytes_saved: size.saturating_sub(encoded_size(ir)),
+            });
     }
-    fn deduplicate_strings(&mut self, ir: &mut HelixIR) {
+    fn deduplicate_strings(&mut self, ir: &mut HelixIR) -> usize {
         let mut seen = HashMap::new();
         let mut new_strings = Vec::new();
         let mut remap = HashMap::new();
+        let mut deduplicated = 0;
         for (idx, string) in ir.string_pool.strings.iter().enumerate() {
             if let Some(&existing_idx) = seen.get(string) {
                 remap.insert(idx as u32, existing_idx);
-                self.stats.strings_deduplicated += 1;
+                deduplicated += 1;
             } else {
                 let new_idx = new_strings.len() as u32;
                 seen.insert(string.clone(), new_idx);
@@ -87,38 +96,85 @@ impl Optimizer {
                 remap.insert(idx as u32, new_idx);
             }
         }
-        let original_size = ir.string_pool.strings.len();
         ir.string_pool.strings = new_strings;
-        self.stats.strings_removed = original_size - ir.string_pool.strings.len();
-        for instruction in &mut ir.instructions {
-            self.remap_instruction_strings(instruction, &remap);
-        }
+        self.stats.strings_deduplicated += deduplicated;
+        self.stats.strings_removed += deduplicated;
+        self.remap_strings(ir, &remap);
+        deduplicated
     }
-    fn remove_dead_code(&mut self, ir: &mut HelixIR) {
-        let mut reachable = HashSet::new();
-        let mut work_list = vec![0];
-        while let Some(idx) = work_list.pop() {
-            if idx >= ir.instructions.len() || !reachable.insert(idx) {
-                continue;
-            }
-            work_list.push(idx + 1);
+    /// Drop strings nothing refers to, such as those of eliminated symbols
+    fn remove_unused_strings(&mut self, ir: &mut HelixIR) -> usize {
+        let len = ir.string_pool.strings.len() as u32;
+        let mut used = HashSet::new();
+        let mut in_range = true;
+        ir.strings_mut(&mut |idx: &mut u32| {
+            in_range &= *idx < len;
+            used.insert(*idx);
+        });
+        if !in_range {
+            // Remapping would alias the dangling indices onto live strings
+            return 0;
         }
-        let mut new_instructions = Vec::new();
         let mut remap = HashMap::new();
-        for (idx, instruction) in ir.instructions.iter().enumerate() {
-            if reachable.contains(&idx) {
-                remap.insert(idx, new_instructions.len());
-                new_instructions.push(instruction.clone());
-            } else {
-                self.stats.instructions_removed += 1;
+        let mut new_strings = Vec::new();
+        for (idx, string) in ir.string_pool.strings.drain(..).enumerate() {
+            if used.contains(&(idx as u32)) {
+                remap.insert(idx as u32, new_strings.len() as u32);
+                new_strings.push(string);
             }
         }
-        for instruction in &mut new_instructions {
-            self.remap_jump_targets(instruction, &remap);
+        let removed = (len as usize) - new_strings.len();
+        ir.string_pool.strings = new_strings;
+        self.stats.strings_removed += removed;
+        self.remap_strings(ir, &remap);
+        removed
+    }
+    /// Merge identical strings and drop unused ones. Every constant in the
+    /// IR is either inline or an index into the string pool.
+    fn deduplicate_constants(&mut self, ir: &mut HelixIR) -> usize {
+        self.deduplicate_strings(ir) + self.remove_unused_strings(ir)
+    }
+    /// Drop instructions that target ids nothing declares; they cannot
+    /// affect the loaded config
+    fn remove_dead_code(&mut self, ir: &mut HelixIR) -> usize {
+        let symbols = &ir.symbol_table;
+        let mut declared: HashSet<u32> = symbols
+            .agents
+            .keys()
+            .chain(symbols.workflows.keys())
+            .chain(symbols.contexts.keys())
+            .chain(symbols.crews.keys())
+            .chain(symbols.sections.keys())
+            .copied()
+            .collect();
+        for instruction in &ir.instructions {
+            match instruction {
+                Instruction::DeclareAgent(id)
+                | Instruction::DeclareWorkflow(id)
+                | Instruction::DeclareContext(id)
+                | Instruction::DeclareCrew(id)
+                | Instruction::DeclareSection(id) => {
+                    declared.insert(*id);
+                }
+                Instruction::DefineStep { step, .. } => {
+                    declared.insert(step.id);
+                }
+                _ => {}
+            }
         }
-        ir.instructions = new_instructions;
+        let before = ir.instructions.len();
+        ir.instructions
+            .retain(|instruction| match instruction {
+                Instruction::SetProperty { target: id, .. }
+                | Instruction::SetCapability { agent: id, .. }
+                | Instruction::SetSecret { context: id, .. }
+                | Instruction::DefineStep { workflow: id, .. }
+                | Instruction::DefinePipeline { workflow: id, .. } => declared.contains(id),
+                _ => true,
+            });
+        before - ir.instructions.len()
     }
-    fn optimize_string_pool(&mut self, ir: &mut HelixIR) {
+    fn optimize_string_pool(&mut self, ir: &mut HelixIR) -> usize {
         let mut frequency = HashMap::new();
         for instruction in &ir.instructions {
             self.count_string_usage(instruction, &mut frequency);
@@ -136,165 +192,225 @@ impl Optimizer {
             });
         let mut remap = HashMap::new();
         let mut new_strings = Vec::new();
+        let mut moved = 0;
         for (old_idx, string) in indexed_strings {
             let new_idx = new_strings.len() as u32;
+            if new_idx != old_idx {
+                moved += 1;
+            }
             remap.insert(old_idx, new_idx);
             new_strings.push(string);
         }
         ir.string_pool.strings = new_strings;
+        self.remap_strings(ir, &remap);
+        moved
+    }
+    /// Fold every property to the value the loader ends up with: only the
+    /// last write to a key survives, `null` (which reads as absent) is
+    /// dropped, durations use their largest exact unit and object entries
+    /// are unique and sorted by key
+    fn fold_constants(&mut self, ir: &mut HelixIR) -> usize {
+        let mut folded = 0;
+        let mut last_write = HashMap::new();
+        for (position, instruction) in ir.instructions.iter().enumerate() {
+            if let Instruction::SetProperty { target, key, .. } = instruction {
+                last_write.insert((*target, *key), position);
+            }
+        }
+        let mut position = 0;
+        ir.instructions
+            .retain(|instruction| {
+                let keep = match instruction {
+                    Instruction::SetProperty { value: ConstantValue::Null, .. } => false,
+                    Instruction::SetProperty { target, key, .. } => {
+                        last_write.get(&(*target, *key)) == Some(&position)
+                    }
+                    _ => true,
+                };
+                position += 1;
+                if !keep {
+                    folded += 1;
+                }
+                keep
+            });
+        let strings = &ir.string_pool.strings;
         for instruction in &mut ir.instructions {
-            self.remap_instruction_strings(instruction, &remap);
+            if let Instruction::SetProperty { value, .. } = instruction {
+                folded += fold_value(value, strings);
+            }
         }
+        self.stats.constants_folded += folded;
+        folded
     }
-    fn fold_constants(&mut self, _ir: &mut HelixIR) {}
-    fn inline_small_functions(&mut self, ir: &mut HelixIR) {
-        let mut reference_count = std::collections::HashMap::new();
+    /// A standalone `ResolveReference` whose target is also used inline by
+    /// a property value is resolved there when the config loads, so the
+    /// separate instruction is redundant. Repeated resolutions of the same
+    /// target are dropped as well.
+    fn inline_references(&mut self, ir: &mut HelixIR) -> usize {
+        let mut inline = HashSet::new();
         for instruction in &ir.instructions {
-            if let Instruction::ResolveReference { index, .. } = instruction {
-                *reference_count.entry(*index).or_insert(0) += 1;
-            }
-        }
-        self.stats.functions_inlined = 0;
-    }
-    fn optimize_instruction_sequence(&mut self, ir: &mut HelixIR) {
-        let mut seen_properties: std::collections::HashSet<(u32, u32)> = std::collections::HashSet::new();
-        let mut i = 0;
-        while i < ir.instructions.len() {
-            match &ir.instructions[i] {
-                Instruction::SetProperty { target, key, .. } => {
-                    let prop_key = (*target, *key);
-                    if seen_properties.contains(&prop_key) {
-                        ir.instructions.remove(i);
-                        self.stats.instructions_removed += 1;
-                        continue;
-                    } else {
-                        seen_properties.insert(prop_key);
+            if let Instruction::SetProperty { value, .. } = instruction {
+                collect_references(value, &ir.string_pool.strings, &mut inline);
+            }
+        }
+        let strings = &ir.string_pool.strings;
+        let mut resolved = HashSet::new();
+        let before = ir.instructions.len();
+        ir.instructions
+            .retain(|instruction| match instruction {
+                Instruction::ResolveReference { ref_type, index } => {
+                    let target = strings.get(*index as usize);
+                    let used_inline = target.is_some_and(|t| inline.contains(t.as_str()));
+                    !used_inline && resolved.insert((ref_type.clone(), target.cloned()))
+                }
+                _ => true,
+            });
+        let inlined = before - ir.instructions.len();
+        self.stats.references_inlined += inlined;
+        inlined
+    }
+    /// Give step dependencies, crew memberships and pipelines one canonical
+    /// form: dependencies sorted by step, members listed once, and nested
+    /// or single-node parallel groups flattened
+    fn canonicalize_steps(&mut self, ir: &mut HelixIR) -> usize {
+        let mut changed = 0;
+        for instruction in &mut ir.instructions {
+            match instruction {
+                Instruction::DefineStep { step, .. } => {
+                    let mut depends_on = step.depends_on.clone();
+                    depends_on.sort_unstable();
+                    depends_on.dedup();
+                    let mut crew_ids = step.crew_ids.clone();
+                    if let Some(ids) = &mut crew_ids {
+                        dedup_in_order(ids);
+                    }
+                    if depends_on != step.depends_on || crew_ids != step.crew_ids {
+                        step.depends_on = depends_on;
+                        step.crew_ids = crew_ids;
+                        changed += 1;
                     }
                 }
-                Instruction::SetCapability { agent, capability } => {
-                    let cap_key = (*agent, *capability);
-                    if seen_properties.contains(&cap_key) {
-                        ir.instructions.remove(i);
-                        self.stats.instructions_removed += 1;
-                        continue;
-                    } else {
-                        seen_properties.insert(cap_key);
+                Instruction::DefinePipeline { nodes, .. } => {
+                    let canonical = canonical_nodes(nodes.clone());
+                    if canonical != *nodes {
+                        *nodes = canonical;
+                        changed += 1;
                     }
                 }
                 _ => {}
             }
-            i += 1;
-        }
-    }
-    fn merge_duplicate_sections(&mut self, ir: &mut HelixIR) {
-        use std::collections::HashMap;
-        let mut agent_signatures: HashMap<String, Vec<u32>> = HashMap::new();
-        for (id, agent) in &ir.symbol_table.agents {
-            let signature = format!(
-                "{}-{}-{:?}-{:?}", agent.model_idx, agent.role_idx, agent.temperature,
-                agent.max_tokens
-            );
-            agent_signatures.entry(signature).or_insert_with(Vec::new).push(*id);
         }
-        for (_, agents) in &agent_signatures {
-            if agents.len() > 1 {
-                self.stats.sections_merged += agents.len() - 1;
+        for crew in ir.symbol_table.crews.values_mut() {
+            let len = crew.agent_ids.len();
+            dedup_in_order(&mut crew.agent_ids);
+            if crew.agent_ids.len() != len {
+                changed += 1;
             }
         }
-        let mut workflow_signatures: HashMap<String, Vec<u32>> = HashMap::new();
-        for (id, workflow) in &ir.symbol_table.workflows {
-            let signature = format!("{:?}", workflow.trigger_type);
-            workflow_signatures.entry(signature).or_insert_with(Vec::new).push(*id);
-        }
+        self.stats.steps_canonicalized += changed;
+        changed
     }
-    fn eliminate_cross_references(&mut self, ir: &mut HelixIR) {
-        use std::collections::HashSet;
-        let mut referenced_agents: HashSet<u32> = HashSet::new();
-        let mut referenced_workflows: HashSet<u32> = HashSet::new();
-        for crew in ir.symbol_table.crews.values() {
-            for agent_id in &crew.agent_ids {
-                referenced_agents.insert(*agent_id);
-            }
-        }
-        for workflow in ir.symbol_table.workflows.values() {
-            if let Some(pipeline) = &workflow.pipeline {
-                for node_id in pipeline {
-                    referenced_workflows.insert(*node_id);
+    fn optimize_instruction_sequence(&mut self, ir: &mut HelixIR) -> usize {
+        let mut seen_capabilities: HashSet<(u32, u32)> = HashSet::new();
+        let before = ir.instructions.len();
+        ir.instructions
+            .retain(|instruction| match instruction {
+                Instruction::SetCapability { agent, capability } => {
+                    seen_capabilities.insert((*agent, *capability))
                 }
-            }
+                _ => true,
+            });
+        before - ir.instructions.len()
+    }
+    /// Remove agents and contexts that no workflow or crew can reach, either
+    /// directly or by name through the properties of what it reaches. A
+    /// config without workflows or crews is left as is.
+    fn eliminate_unreachable(&mut self, ir: &mut HelixIR) -> usize {
+        let symbols = &ir.symbol_table;
+        if symbols.workflows.is_empty() && symbols.crews.is_empty() {
+            return 0;
         }
+        let strings = &ir.string_pool.strings;
+        let name = |idx: u32| strings.get(idx as usize).map(String::as_str);
+        let mut properties: HashMap<u32, Vec<&ConstantValue>> = HashMap::new();
+        let mut steps: HashMap<u32, Vec<&StepDefinition>> = HashMap::new();
         for instruction in &ir.instructions {
             match instruction {
-                Instruction::ResolveReference { ref_type: _, index: _ } => {}
+                Instruction::SetProperty { target, value, .. } => {
+                    properties.entry(*target).or_default().push(value);
+                }
+                Instruction::DefineStep { workflow, step } => {
+                    steps.entry(*workflow).or_default().push(step);
+                }
                 _ => {}
             }
         }
-        let unreferenced_agents: Vec<u32> = ir
-            .symbol_table
-            .agents
-            .keys()
-            .filter(|id| !referenced_agents.contains(id))
-            .cloned()
-            .collect();
-        for agent_id in unreferenced_agents {
-            ir.symbol_table.agents.remove(&agent_id);
-            self.stats.instructions_removed += 1;
-        }
-        let unreferenced_workflows: Vec<u32> = ir
-            .symbol_table
+        let mut reachable: HashSet<u32> = HashSet::new();
+        let mut work_list: Vec<u32> = symbols
             .workflows
             .keys()
-            .filter(|id| !referenced_workflows.contains(id))
-            .cloned()
+            .chain(symbols.crews.keys())
+            .copied()
             .collect();
-        for workflow_id in unreferenced_workflows {
-            ir.symbol_table.workflows.remove(&workflow_id);
-            self.stats.instructions_removed += 1;
-        }
-    }
-    fn optimize_pipelines(&mut self, ir: &mut HelixIR) {
-        for i in 0..ir.instructions.len() {
-            if let Instruction::DefinePipeline { .. } = &ir.instructions[i] {
-                self.stats.pipelines_optimized += 1;
+        while let Some(id) = work_list.pop() {
+            if !reachable.insert(id) {
+                continue;
             }
-        }
-    }
-    fn compress_data_sections(&mut self, ir: &mut HelixIR) {
-        let mut string_frequency: HashMap<u32, usize> = HashMap::new();
-        for instruction in &ir.instructions {
-            match instruction {
-                Instruction::SetProperty { key, .. } => {
-                    *string_frequency.entry(*key).or_insert(0) += 1;
-                }
-                Instruction::SetCapability { capability, .. } => {
-                    *string_frequency.entry(*capability).or_insert(0) += 1;
+            if let Some(crew) = symbols.crews.get(&id) {
+                work_list.extend(&crew.agent_ids);
+                work_list.extend(crew.manager_id);
+            }
+            for step in steps.get(&id).into_iter().flatten() {
+                work_list.push(step.id);
+                work_list.extend(step.agent_id);
+                work_list.extend(step.crew_ids.iter().flatten());
+            }
+            let mut named = HashSet::new();
+            for value in properties.get(&id).into_iter().flatten() {
+                collect_references(value, strings, &mut named);
+                collect_strings(value, strings, &mut named);
+            }
+            for agent in symbols.agents.values() {
+                if name(agent.name_idx).is_some_and(|n| named.contains(n)) {
+                    work_list.push(agent.id);
                 }
-                Instruction::SetMetadata { key, value } => {
-                    *string_frequency.entry(*key).or_insert(0) += 1;
-                    *string_frequency.entry(*value).or_insert(0) += 1;
+            }
+            for context in symbols.contexts.values() {
+                if name(context.name_idx).is_some_and(|n| named.contains(n)) {
+                    work_list.push(context.id);
                 }
-                _ => {}
             }
         }
-        for agent in ir.symbol_table.agents.values() {
-            *string_frequency.entry(agent.name_idx).or_insert(0) += 1;
-            *string_frequency.entry(agent.model_idx).or_insert(0) += 1;
-            *string_frequency.entry(agent.role_idx).or_insert(0) += 1;
-        }
-        let _total_strings = ir.string_pool.strings.len();
-        let frequently_used = string_frequency
-            .iter()
-            .filter(|(_, count)| **count > 1)
-            .count();
-        if frequently_used > 0 {
-            self.stats.bytes_saved += frequently_used * 8;
+        let unreachable: HashSet<u32> = symbols
+            .agents
+            .keys()
+            .chain(symbols.contexts.keys())
+            .filter(|id| !reachable.contains(id))
+            .copied()
+            .collect();
+        if unreachable.is_empty() {
+            return 0;
         }
+        ir.symbol_table.agents.retain(|id, _| !unreachable.contains(id));
+        ir.symbol_table.contexts.retain(|id, _| !unreachable.contains(id));
+        ir.instructions
+            .retain(|instruction| match instruction {
+                Instruction::DeclareAgent(id)
+                | Instruction::DeclareContext(id)
+                | Instruction::SetProperty { target: id, .. }
+                | Instruction::SetCapability { agent: id, .. }
+                | Instruction::SetSecret { context: id, .. } => !unreachable.contains(id),
+                _ => true,
+            });
+        self.stats.symbols_eliminated += unreachable.len();
+        unreachable.len()
     }
-    fn reorder_for_cache_locality(&mut self, ir: &mut HelixIR) {
+    fn reorder_for_cache_locality(&mut self, ir: &mut HelixIR) -> usize {
         let mut reordered = Vec::new();
         let mut agent_instructions = Vec::new();
         let mut workflow_instructions = Vec::new();
         let mut other_instructions = Vec::new();
+        let original = ir.instructions.clone();
         for instruction in ir.instructions.drain(..) {
             match &instruction {
                 Instruction::DeclareAgent(_) => agent_instructions.push(instruction),
@@ -311,44 +427,106 @@ impl Optimizer {
         reordered.extend(workflow_instructions);
         reordered.extend(other_instructions);
         ir.instructions = reordered;
+        original.iter().zip(&ir.instructions).filter(|(a, b)| a != b).count()
+    }
+    /// Point every string index in the IR, symbols included, at its new
+    /// slot in the pool
+    fn remap_strings(&self, ir: &mut HelixIR, remap: &HashMap<u32, u32>) {
+        let map = |idx: &mut u32| {
+            if let Some(&new_idx) = remap.get(idx) {
+                *idx = new_idx;
+            }
+        };
+        for instruction in &mut ir.instructions {
+            self.remap_instruction_strings(instruction, remap);
+        }
+        let symbols = &mut ir.symbol_table;
+        for agent in symbols.agents.values_mut() {
+            map(&mut agent.name_idx);
+            map(&mut agent.model_idx);
+            map(&mut agent.role_idx);
+            agent.capabilities.iter_mut().for_each(map);
+            agent.backstory_idx.as_mut().map(map);
+        }
+        for workflow in symbols.workflows.values_mut() {
+            map(&mut workflow.name_idx);
+            match &mut workflow.trigger_type {
+                TriggerType::Manual => {}
+                TriggerType::Schedule(idx)
+                | TriggerType::Webhook(idx)
+                | TriggerType::Event(idx)
+                | TriggerType::FileWatch(idx) => map(idx),
+            }
+        }
+        for context in symbols.contexts.values_mut() {
+            map(&mut context.name_idx);
+            map(&mut context.environment_idx);
+            context.secrets = context
+                .secrets
+                .drain()
+                .map(|(mut key, mut secret)| {
+                    map(&mut key);
+                    match &mut secret {
+                        SecretType::Environment(idx)
+                        | SecretType::Vault(idx)
+                        | SecretType::File(idx) => map(idx),
+                    }
+                    (key, secret)
+                })
+                .collect();
+        }
+        for crew in symbols.crews.values_mut() {
+            map(&mut crew.name_idx);
+        }
+        for section in symbols.sections.values_mut() {
+            map(&mut section.name_idx);
+        }
+        ir.string_pool.index = ir
+            .string_pool
+            .strings
+            .iter()
+            .enumerate()
+            .map(|(idx, s)| (s.clone(), idx as u32))
+            .collect();
     }
     fn remap_instruction_strings(
         &self,
         instruction: &mut Instruction,
         remap: &HashMap<u32, u32>,
     ) {
+        let map = |idx: &mut u32| {
+            if let Some(&new_idx) = remap.get(idx) {
+                *idx = new_idx;
+            }
+        };
         match instruction {
             Instruction::SetProperty { key, value, .. } => {
-                if let Some(&new_idx) = remap.get(key) {
-                    *key = new_idx;
-                }
-                if let ConstantValue::String(idx) = value {
-                    if let Some(&new_idx) = remap.get(idx) {
-                        *idx = new_idx;
-                    }
-                }
+                map(key);
+                remap_constant_strings(value, &map);
             }
-            Instruction::SetCapability { capability, .. } => {
-                if let Some(&new_idx) = remap.get(capability) {
-                    *capability = new_idx;
+            Instruction::SetCapability { capability, .. } => map(capability),
+            Instruction::SetSecret { key, secret, .. } => {
+                map(key);
+                match secret {
+                    SecretType::Environment(idx)
+                    | SecretType::Vault(idx)
+                    | SecretType::File(idx) => map(idx),
                 }
             }
+            Instruction::DefineStep { step, .. } => {
+                map(&mut step.name_idx);
+                step.task_idx.as_mut().map(map);
+            }
+            Instruction::DefinePipeline { nodes, .. } => {
+                nodes.iter_mut().for_each(|node| remap_pipeline_strings(node, &map));
+            }
             Instruction::SetMetadata { key, value } => {
-                if let Some(&new_idx) = remap.get(key) {
-                    *key = new_idx;
-                }
-                if let Some(&new_idx) = remap.get(value) {
-                    *value = new_idx;
-                }
+                map(key);
+                map(value);
             }
             _ => {}
         }
     }
-    fn remap_jump_targets(
-        &self,
-        _instruction: &mut Instruction,
-        _remap: &HashMap<usize, usize>,
-    ) {}
     fn count_string_usage(
         &self,
         instruction: &Instruction,
@@ -375,38 +553,235 @@ impl Optimizer {
         &self.stats
     }
 }
+/// Size of the parts of the IR that end up in the binary
+fn encoded_size(ir: &HelixIR) -> usize {
+    bincode::serialized_size(&(&ir.symbol_table, &ir.instructions, &ir.string_pool.strings))
+        .map_or(0, |size| size as usize)
+}
+fn fold_value(value: &mut ConstantValue, strings: &[String]) -> usize {
+    match value {
+        ConstantValue::Duration(duration) => {
+            let canonical = canonical_duration(duration);
+            if canonical == *duration {
+                return 0;
+            }
+            *duration = canonical;
+            1
+        }
+        ConstantValue::Array(items) => {
+            items.iter_mut().map(|item| fold_value(item, strings)).sum()
+        }
+        ConstantValue::Object(entries) => {
+            let mut folded: usize = entries
+                .iter_mut()
+                .map(|(_, value)| fold_value(value, strings))
+                .sum();
+            let key = |idx: &u32| strings.get(*idx as usize).cloned();
+            let mut canonical: Vec<(u32, ConstantValue)> = Vec::with_capacity(entries.len());
+            for (k, v) in entries.drain(..) {
+                // A repeated key keeps its last value, as a later write would
+                match canonical.iter().position(|(existing, _)| key(existing) == key(&k)) {
+                    Some(position) => {
+                        canonical[position] = (k, v);
+                        folded += 1;
+                    }
+                    None => canonical.push((k, v)),
+                }
+            }
+            let sorted = canonical.windows(2).all(|pair| key(&pair[0].0) <= key(&pair[1].0));
+            if !sorted {
+                canonical.sort_by_key(|(k, _)| key(k));
+                folded += 1;
+            }
+            *entries = canonical;
+            folded
+        }
+        _ => 0,
+    }
+}
+fn canonical_duration(duration: &DurationIR) -> DurationIR {
+    let seconds = |unit: &TimeUnitIR| match unit {
+        TimeUnitIR::Seconds => 1,
+        TimeUnitIR::Minutes => 60,
+        TimeUnitIR::Hours => 3_600,
+        TimeUnitIR::Days => 86_400,
+    };
+    let Some(total) = duration.value.checked_mul(seconds(&duration.unit)) else {
+        return duration.clone();
+    };
+    if total == 0 {
+        return DurationIR { value: 0, unit: TimeUnitIR::Seconds };
+    }
+    [TimeUnitIR::Days, TimeUnitIR::Hours, TimeUnitIR::Minutes, TimeUnitIR::Seconds]
+        .into_iter()
+        .find(|unit| total % seconds(unit) == 0)
+        .map(|unit| DurationIR { value: total / seconds(&unit), unit })
+        .unwrap_or_else(|| duration.clone())
+}
+/// Names referenced as `@name` or `$NAME`, without the sigil
+fn collect_references<'a>(
+    value: &ConstantValue,
+    strings: &'a [String],
+    names: &mut HashSet<&'a str>,
+) {
+    match value {
+        ConstantValue::Reference(idx) => {
+            if let Some(text) = strings.get(*idx as usize) {
+                names.insert(text.as_str());
+                names.insert(text.trim_start_matches(['@', '$']));
+            }
+        }
+        ConstantValue::Array(items) => {
+            items.iter().for_each(|item| collect_references(item, strings, names))
+        }
+        ConstantValue::Object(entries) => {
+            entries.iter().for_each(|(_, value)| collect_references(value, strings, names))
+        }
+        _ => {}
+    }
+}
+fn collect_strings<'a>(
+    value: &ConstantValue,
+    strings: &'a [String],
+    names: &mut HashSet<&'a str>,
+) {
+    match value {
+        ConstantValue::String(idx) => {
+            if let Some(text) = strings.get(*idx as usize) {
+                names.insert(text.as_str());
+            }
+        }
+        ConstantValue::Array(items) => {
+            items.iter().for_each(|item| collect_strings(item, strings, names))
+        }
+        ConstantValue::Object(entries) => {
+            entries.iter().for_each(|(_, value)| collect_strings(value, strings, names))
+        }
+        _ => {}
+    }
+}
+fn dedup_in_order(ids: &mut Vec<u32>) {
+    let mut seen = HashSet::new();
+    ids.retain(|id| seen.insert(*id));
+}
+fn canonical_nodes(nodes: Vec<PipelineNodeIR>) -> Vec<PipelineNodeIR> {
+    nodes.into_iter().filter_map(canonical_node).collect()
+}
+fn canonical_node(node: PipelineNodeIR) -> Option<PipelineNodeIR> {
+    match node {
+        PipelineNodeIR::Parallel(nodes) => {
+            let mut flat = Vec::new();
+            for node in canonical_nodes(nodes) {
+                match node {
+                    PipelineNodeIR::Parallel(inner) => flat.extend(inner),
+                    node => flat.push(node),
+                }
+            }
+            match flat.len() {
+                0 => None,
+                1 => flat.pop(),
+                _ => Some(PipelineNodeIR::Parallel(flat)),
+            }
+        }
+        PipelineNodeIR::Conditional { condition, then_branch, else_branch } => {
+            Some(PipelineNodeIR::Conditional {
+                condition,
+                then_branch: Box::new(canonical_node(*then_branch)?),
+                else_branch: else_branch.and_then(|branch| canonical_node(*branch)).map(Box::new),
+            })
+        }
+        node => Some(node),
+    }
+}
+fn remap_constant_strings(value: &mut ConstantValue, map: &impl Fn(&mut u32)) {
+    match value {
+        ConstantValue::String(idx) | ConstantValue::Reference(idx) => map(idx),
+        ConstantValue::Array(items) => {
+            items.iter_mut().for_each(|item| remap_constant_strings(item, map));
+        }
+        ConstantValue::Object(entries) => {
+            for (key, value) in entries {
+                map(key);
+                remap_constant_strings(value, map);
+            }
+        }
+        _ => {}
+    }
+}
+fn remap_pipeline_strings(node: &mut PipelineNodeIR, map: &impl Fn(&mut u32)) {
+    match node {
+        PipelineNodeIR::Step(_) => {}
+        PipelineNodeIR::Parallel(nodes) => {
+            nodes.iter_mut().for_each(|node| remap_pipeline_strings(node, map));
+        }
+        PipelineNodeIR::Conditional { condition, then_branch, else_branch } => {
+            map(condition);
+            remap_pipeline_strings(then_branch, map);
+            if let Some(branch) = else_branch {
+                remap_pipeline_strings(branch, map);
+            }
+        }
+    }
+}
 #[derive(Debug, Default)]
 pub struct OptimizationStats {
     pub strings_deduplicated: usize,
     pub strings_removed: usize,
     pub instructions_removed: usize,
     pub constants_folded: usize,
-    pub functions_inlined: usize,
-    pub pipelines_optimized: usize,
-    pub sections_merged: usize,
+    pub references_inlined: usize,
+    pub symbols_eliminated: usize,
+    pub steps_canonicalized: usize,
+    pub bytes_saved: usize,
+    /// What each pass did, in the order they ran
+    pub passes: Vec<PassStats>,
+}
+#[derive(Debug, Clone, PartialEq)]
+pub struct PassStats {
+    pub name: &'static str,
+    pub changes: usize,
+    pub instructions_removed: usize,
+    pub strings_removed: usize,
     pub bytes_saved: usize,
 }
 impl OptimizationStats {
     pub fn report(&self) -> String {
-        format!(
+        let mut report = format!(
             "Optimization Results:\n\
              - Strings deduplicated: {}\n\
              - Strings removed: {}\n\
              - Instructions removed: {}\n\
              - Constants folded: {}\n\
-             - Functions inlined: {}\n\
-             - Pipelines optimized: {}\n\
-             - Sections merged: {}\n\
+             - References inlined: {}\n\
+             - Symbols eliminated: {}\n\
+             - Steps canonicalized: {}\n\
              - Total bytes saved: {}",
             self.strings_deduplicated, self.strings_removed, self.instructions_removed,
-            self.constants_folded, self.functions_inlined, self.pipelines_optimized, self
-            .sections_merged, self.bytes_saved
-        )
+            self.constants_folded, self.references_inlined, self.symbols_eliminated, self
+            .steps_canonicalized, self.bytes_saved
+        );
+        if !self.passes.is_empty() {
+            report.push_str("\nPer pass:");
+        }
+        for pass in &self.passes {
+            report
+                .push_str(
+                    &format!(
+                        "\n  {:<22} {:>4} changes, {:>4} instructions, {:>4} strings, {:>6} bytes saved",
+                        pass.name, pass.changes, pass.instructions_removed, pass
+                        .strings_removed, pass.bytes_saved
+                    ),
+                );
+        }
+        report
     }
 }
 #[cfg(test)]
 mod tests {
-    use crate::mds::codegen::{StringPool, SymbolTable, Metadata, ConstantPool, ConstantValue};
+    use super::*;
+    use crate::dna::compiler::Compiler;
+    use crate::dna::mds::codegen::ReferenceType;
+    use crate::mds::serializer::BinarySerializer;
     #[test]
     fn test_optimization_levels() {
         assert_eq!(OptimizationLevel::from(0), OptimizationLevel::Zero);
@@ -456,6 +831,171 @@ mod tests {
             _ => panic!("Expected DeclareAgent(0)"),
         }
     }
+    fn compile(level: OptimizationLevel, source: &str) -> HelixIR {
+        let binary = Compiler::new(level).compile_source(source, None).unwrap();
+        BinarySerializer::new(false).deserialize_to_ir(&binary).unwrap()
+    }
+    fn decompile(ir: HelixIR) -> String {
+        let binary = BinarySerializer::new(false).serialize(ir, None).unwrap();
+        Compiler::new(OptimizationLevel::Zero).decompile(&binary).unwrap()
+    }
+    fn set(ir: &mut HelixIR, target: u32, key: &str, value: ConstantValue) {
+        let key = ir.string_pool.intern(key);
+        ir.instructions.push(Instruction::SetProperty { target, key, value });
+    }
+    const TEAM: &str = r#"
+        agent "researcher" {
+            model = "gpt-4"
+            region = "eu"
+        }
+        agent "writer" {
+            model = "claude"
+        }
+        agent "editor" {
+            model = "gpt-4"
+            reviews = "writer"
+        }
+        agent "intern" {
+            model = "gpt-4"
+            notes = "unused"
+        }
+        crew "team" {
+            agents ["researcher"]
+        }
+        workflow "publish" {
+            step "draft" {
+                agent = "writer"
+                timeout = 90s
+            }
+            step "review" {
+                depends_on = ["draft"]
+                reviewer = "editor"
+            }
+            target = "production"
+            retry_after = 2h
+        }
+        context "production" {
+            environment = "prod"
+        }
+        context "staging" {
+            environment = "staging"
+        }
+    "#;
+    #[test]
+    fn test_standard_passes_preserve_semantics() {
+        let unoptimized = compile(OptimizationLevel::Zero, TEAM);
+        let mut optimizer = Optimizer::new(OptimizationLevel::Two);
+        let mut optimized = unoptimized.clone();
+        optimizer.optimize(&mut optimized);
+        assert_eq!(decompile(optimized), decompile(unoptimized));
+        let report = optimizer.stats().report();
+        for pass in ["dead code", "constant folding", "step canonicalization", "constant pool"] {
+            assert!(report.contains(pass), "{}", report);
+        }
+        assert!(optimizer.stats().passes.iter().all(|pass| pass.name != "unreachable symbols"));
+    }
+    #[test]
+    fn test_constant_folding_keeps_final_values() {
+        let mut ir = compile(OptimizationLevel::Zero, "database \"limits\" {\n    burst = 1\n}");
+        let target = *ir.symbol_table.sections.keys().next().unwrap();
+        let stale = ir.string_pool.intern("stale");
+        set(&mut ir, target, "mode", ConstantValue::String(stale));
+        let fresh = ir.string_pool.intern("fresh");
+        set(&mut ir, target, "mode", ConstantValue::String(fresh));
+        set(&mut ir, target, "unset", ConstantValue::Null);
+        let window = DurationIR { value: 7_200, unit: TimeUnitIR::Seconds };
+        set(&mut ir, target, "window", ConstantValue::Duration(window));
+        let expected = decompile(ir.clone()).replace("7200s", "2h");
+
+        let mut optimizer = Optimizer::new(OptimizationLevel::Two);
+        optimizer.optimize(&mut ir);
+        assert_eq!(optimizer.stats().constants_folded, 3);
+        assert!(ir.instructions.iter().any(|instruction| matches!(
+            instruction,
+            Instruction::SetProperty { value: ConstantValue::Duration(DurationIR { value: 2, unit: TimeUnitIR::Hours }), .. }
+        )));
+        assert!(!ir.string_pool.strings.contains(&"stale".to_string()));
+        assert_eq!(decompile(ir), expected);
+    }
+    #[test]
+    fn test_references_used_inline_are_not_resolved_twice() {
+        let mut ir = compile(OptimizationLevel::Zero, "database \"auth\" {\n    token = $TOKEN\n}");
+        let token = ir.string_pool.intern("$TOKEN");
+        let region = ir.string_pool.intern("$REGION");
+        for index in [token, region, region] {
+            ir.instructions.push(Instruction::ResolveReference { ref_type: ReferenceType::Environment, index });
+        }
+        let expected = decompile(ir.clone());
+        let mut optimizer = Optimizer::new(OptimizationLevel::Two);
+        optimizer.optimize(&mut ir);
+        assert_eq!(optimizer.stats().references_inlined, 2);
+        let remaining: Vec<&str> = ir
+            .instructions
+            .iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::ResolveReference { index, .. } => ir.string_pool.get(*index).map(String::as_str),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(remaining, ["$REGION"]);
+        assert_eq!(decompile(ir), expected);
+    }
+    #[test]
+    fn test_step_lists_are_canonical() {
+        let repeated = TEAM
+            .replace(r#"agents ["researcher"]"#, r#"agents ["researcher", "researcher"]"#)
+            .replace(r#"depends_on = ["draft"]"#, r#"depends_on = ["draft", "draft"]"#);
+        let mut ir = compile(OptimizationLevel::Zero, &repeated);
+        let mut optimizer = Optimizer::new(OptimizationLevel::Two);
+        optimizer.optimize(&mut ir);
+        assert_eq!(optimizer.stats().steps_canonicalized, 2);
+        let crew = ir.symbol_table.crews.values().next().unwrap();
+        assert_eq!(crew.agent_ids.len(), 1);
+        let review = ir.instructions.iter().find_map(|instruction| match instruction {
+            Instruction::DefineStep { step, .. } if step.depends_on.len() == 1 => Some(step),
+            _ => None,
+        });
+        assert!(review.is_some());
+
+        let nested = PipelineNodeIR::Parallel(vec![
+            PipelineNodeIR::Parallel(vec![PipelineNodeIR::Step(1), PipelineNodeIR::Step(2)]),
+            PipelineNodeIR::Parallel(vec![PipelineNodeIR::Step(3)]),
+            PipelineNodeIR::Parallel(vec![]),
+        ]);
+        assert_eq!(
+            canonical_nodes(vec![nested, PipelineNodeIR::Parallel(vec![PipelineNodeIR::Step(4)])]),
+            [
+                PipelineNodeIR::Parallel(vec![
+                    PipelineNodeIR::Step(1), PipelineNodeIR::Step(2), PipelineNodeIR::Step(3),
+                ]),
+                PipelineNodeIR::Step(4),
+            ]
+        );
+    }
+    #[test]
+    fn test_aggressive_level_drops_only_unreachable_symbols() {
+        let mut optimizer = Optimizer::new(OptimizationLevel::Three);
+        let mut ir = compile(OptimizationLevel::Zero, TEAM);
+        optimizer.optimize(&mut ir);
+        assert_eq!(optimizer.stats().symbols_eliminated, 2);
+        let pass = optimizer.stats().passes.iter().find(|pass| pass.name == "unreachable symbols");
+        assert!(pass.is_some_and(|pass| pass.instructions_removed == 3 && pass.bytes_saved > 0));
+        assert!(optimizer.stats().bytes_saved > 0);
+
+        // The editor is reached through the review step's `reviewer` and the
+        // production context through the workflow's `target`
+        let expected = TEAM
+            .replace("context \"staging\" {\n            environment = \"staging\"\n        }", "")
+            .replace("agent \"intern\" {\n            model = \"gpt-4\"\n            notes = \"unused\"\n        }", "");
+        assert_eq!(expected.matches(" {\n").count(), TEAM.matches(" {\n").count() - 2);
+        let mut reference = compile(OptimizationLevel::Zero, &expected);
+        Optimizer::new(OptimizationLevel::Two).optimize(&mut reference);
+        assert_eq!(decompile(ir), decompile(reference));
+
+        let mut agents_only = compile(OptimizationLevel::Zero, "agent \"solo\" {\n    model = \"gpt-4\"\n}");
+        Optimizer::new(OptimizationLevel::Three).optimize(&mut agents_only);
+        assert_eq!(agents_only.symbol_table.agents.len(), 1);
+    }
 }
 
 pub fn optimize_command(
@@ -31,23 +31,7 @@ pub fn sign_binary(
     verify: bool,
     verbose: bool,
 ) -> Result<()> {
-    if verbose {
-        println!("🔐 Signing binary");
-        println!("  Input: {}", input.display());
-        if let Some(k) = &key {
-            println!("  Key: {}", k);
-        }
-        if let Some(o) = &output {
-            println!("  Output: {}", o.display());
-        }
-        println!("  Verify: {}", verify);
-    }
-    if verify {
-        println!("✅ Signature verification completed");
-    } else {
-        println!("✅ Binary signed successfully");
-    }
-    Ok(())
+    crate::dna::mds::sign::sign_binary(input, key, output, verify, verbose)
 }
 pub fn export_project(
     format: String,
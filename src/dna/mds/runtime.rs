@@ -2,6 +2,7 @@ use std::collections::{HashMap, VecDeque};
 use std::path::Path;
 use crate::hel::binary::{HelixBinary, Value};
 use crate::hel::error::{RuntimeError, RuntimeErrorKind};
+use crate::hel::signing::TrustPolicy;
 use crate::atp::types::HelixConfig;
 use std::path::PathBuf;
 use std::process::Command;
@@ -145,6 +146,9 @@ impl HelixVM {
             IR::SetMetadata { key, value } => {
                 self.set_metadata(*key, *value)?;
             }
+            IR::DeclareSection(id) => {
+                self.declare_section(*id)?;
+            }
         }
         self.registers.program_counter += 1;
         Ok(())
@@ -161,6 +165,9 @@ impl HelixVM {
     fn declare_crew(&mut self, _id: u32) -> VMResult<()> {
         Ok(())
     }
+    fn declare_section(&mut self, _id: u32) -> VMResult<()> {
+        Ok(())
+    }
     fn set_property(
         &mut self,
         _target: u32,
@@ -298,13 +305,23 @@ impl Default for HelixVM {
 }
 pub struct VMExecutor {
     vm: HelixVM,
+    /// Overrides the global config's signing policy when set
+    trust: Option<TrustPolicy>,
 }
 impl VMExecutor {
     pub fn new() -> Self {
-        Self { vm: HelixVM::new() }
+        Self { vm: HelixVM::new(), trust: None }
+    }
+    /// Accept binaries under `trust` instead of the global config's policy
+    pub fn with_trust_policy(mut self, trust: TrustPolicy) -> Self {
+        self.trust = Some(trust);
+        self
     }
     pub fn execute_file<P: AsRef<Path>>(&mut self, path: P) -> VMResult<HelixConfig> {
-        let loader = crate::dna::mds::loader::BinaryLoader::new();
+        let mut loader = crate::dna::mds::loader::BinaryLoader::new();
+        if let Some(trust) = &self.trust {
+            loader = loader.with_trust_policy(trust.clone());
+        }
         let binary = loader
             .load_file(path.as_ref())
             .map_err(|e| RuntimeError {
@@ -352,6 +369,20 @@ impl Default for VMConfig {
 mod tests {
     use super::*;
     #[test]
+    fn test_executor_refuses_unsigned_binaries_when_signatures_are_required() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("app.hlxb");
+        let binary = Compiler::new(OptimizationLevel::Zero)
+            .compile_source("agent \"a\" {\n    model = \"gpt-4\"\n}\n", None)
+            .unwrap();
+        std::fs::write(&path, binary.to_bytes().unwrap()).unwrap();
+
+        let required = TrustPolicy { trusted_keys: Vec::new(), require_signature: true };
+        let err = VMExecutor::new().with_trust_policy(required).execute_file(&path).unwrap_err();
+        assert!(err.message.contains("not signed"), "{}", err.message);
+        assert!(VMExecutor::new().with_trust_policy(TrustPolicy::default()).execute_file(&path).is_ok());
+    }
+    #[test]
     fn test_vm_creation() {
         let vm = HelixVM::new();
         assert_eq!(vm.execution_state, ExecutionState::Ready);
@@ -307,7 +307,10 @@ impl SemanticAnalyzer {
         match trigger {
             Expression::String(s) | Expression::Identifier(s) => {
                 let valid_triggers = ["manual", "webhook", "event", "file_watch"];
-                if !valid_triggers.contains(&s.as_str()) && !s.starts_with("schedule:") {
+                let prefixed = ["schedule:", "webhook:", "event:", "file:"]
+                    .iter()
+                    .any(|prefix| s.starts_with(prefix));
+                if !valid_triggers.contains(&s.as_str()) && !prefixed {
                     self.errors
                         .push(SemanticError::InvalidTriggerType {
                             trigger: s.clone(),
@@ -1,30 +1,111 @@
 use super::codegen::HelixIR;
 use crate::dna::hel::binary::{
-    HelixBinary, BinaryFlags, BinaryMetadata, DataSection, SectionType, SymbolTable,
-    Instruction, Value, CompressionMethod,
+    HelixBinary, BinaryFlags, BinaryMetadata, DataSection, FormatError, SectionType, SymbolTable,
+    Instruction, Value as BinaryValue, CompressionMethod, SealedContents, SEALED_KEYS,
 };
+use crate::dna::hel::encryption::SectionKey;
+use crate::dna::hel::limits::{LimitError, ReadError, ReadLimits};
+use super::loader::{BinaryLoader, LoadError};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::Path;
 use std::fs::File;
-use std::io::{Write, Read};
+use std::io::Write;
 use bincode;
 pub use crate::dna::atp::types::*;
 
+/// Metadata entry recording that the instructions section holds the full IR
+/// rather than the older lossy VM instructions
+const INSTRUCTIONS_KEY: &str = "instructions";
+const IR_INSTRUCTIONS: &str = "ir";
+/// Custom section holding free-form `section` declarations
+const SECTIONS_SECTION: &str = "sections";
+/// Custom section inside a sealed section: the sealed declarations'
+/// instructions, each with its position in the full instruction list
+const SEALED_INSTRUCTIONS: &str = "sealed_instructions";
+
+/// Declaration property that seals a single declaration
+pub const SENSITIVE_PROPERTY: &str = "sensitive";
+/// Kinds of declaration that can be sealed, by command-line name
+const SEALABLE: [&str; 5] = ["agents", "workflows", "contexts", "crews", SECTIONS_SECTION];
+
+/// The section kind named `name` on the command line, if it can be sealed
+pub fn sealable_section(name: &str) -> Option<SectionType> {
+    match name {
+        "agents" => Some(SectionType::Agents),
+        "workflows" => Some(SectionType::Workflows),
+        "contexts" => Some(SectionType::Contexts),
+        "crews" => Some(SectionType::Crews),
+        SECTIONS_SECTION => Some(SectionType::Custom(SECTIONS_SECTION.to_string())),
+        _ => None,
+    }
+}
+
+fn encode<T: serde::Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, SerializationError> {
+    bincode::serialize(value).map_err(|e| SerializationError::BincodeError(e.to_string()))
+}
+
+fn sorted<V>(map: &std::collections::HashMap<u32, V>) -> std::collections::BTreeMap<&u32, &V> {
+    map.iter().collect()
+}
+
+fn decode<T: serde::de::DeserializeOwned>(
+    limits: &ReadLimits,
+    data: &[u8],
+) -> Result<T, SerializationError> {
+    limits.decode(data).map_err(SerializationError::from)
+}
 
 pub struct BinarySerializer {
     enable_compression: bool,
     compression_method: CompressionMethod,
+    /// Build time for reproducible output; also drops host details
+    hermetic_epoch: Option<u64>,
+    /// Key and section kinds to seal
+    encryption: Option<(SectionKey, Vec<SectionType>)>,
+    /// Bounds on what reading a binary may allocate
+    limits: ReadLimits,
 }
 impl BinarySerializer {
     pub fn new(enable_compression: bool) -> Self {
         Self {
             enable_compression,
             compression_method: CompressionMethod::Lz4,
+            hermetic_epoch: None,
+            encryption: None,
+            limits: ReadLimits::default(),
         }
     }
+    /// Bound section sizes, decompression, nesting and string tables when
+    /// reading binaries
+    pub fn with_limits(mut self, limits: ReadLimits) -> Self {
+        self.limits = limits;
+        self
+    }
     pub fn with_compression_method(mut self, method: CompressionMethod) -> Self {
         self.compression_method = method;
         self
     }
+    /// Record `epoch` as the build time, the platform as `any` and only the
+    /// source file name, so the output doesn't depend on when or where it
+    /// was built
+    pub fn with_hermetic(mut self, epoch: u64) -> Self {
+        self.hermetic_epoch = Some(epoch);
+        self
+    }
+    /// Encrypt contexts, declarations marked `sensitive = true` and every
+    /// declaration of the `sensitive` kinds with `key`. Their symbols,
+    /// instructions and any strings nothing else uses move into sealed
+    /// sections; the rest of the binary stays readable.
+    pub fn with_encryption(mut self, key: SectionKey, sensitive: Vec<SectionType>) -> Self {
+        let mut kinds = vec![SectionType::Contexts];
+        for kind in sensitive {
+            if !kinds.contains(&kind) {
+                kinds.push(kind);
+            }
+        }
+        self.encryption = Some((key, kinds));
+        self
+    }
     pub fn serialize(
         &self,
         ir: HelixIR,
@@ -43,6 +124,14 @@ impl BinarySerializer {
             source_path: source_path.map(|p| p.display().to_string()),
             extra: Default::default(),
         };
+        if let Some(epoch) = self.hermetic_epoch {
+            binary.metadata.created_at = epoch;
+            binary.metadata.platform = "any".to_string();
+            binary.metadata.source_path = source_path
+                .and_then(|p| p.file_name())
+                .map(|name| name.to_string_lossy().into_owned());
+            binary.metadata.extra.insert("hermetic".to_string(), "true".to_string());
+        }
         binary.flags = BinaryFlags {
             compressed: self.enable_compression,
             optimized: true,
@@ -50,6 +139,19 @@ impl BinarySerializer {
             signed: false,
             custom: 0,
         };
+        binary.metadata.extra.insert(INSTRUCTIONS_KEY.to_string(), IR_INSTRUCTIONS.to_string());
+        let mut ir = ir;
+        let fragments = match &self.encryption {
+            Some((_, kinds)) => split_sealed(&mut ir, kinds),
+            None if !sensitive_declarations(&ir).is_empty() => {
+                return Err(
+                    SerializationError::ValidationError(
+                        "Declarations marked sensitive need an encryption key".to_string(),
+                    ),
+                );
+            }
+            None => Vec::new(),
+        };
         binary.symbol_table = self.convert_symbol_table(&ir);
         binary.data_sections = self.create_data_sections(&ir)?;
         if self.enable_compression {
@@ -57,6 +159,12 @@ impl BinarySerializer {
                 section.compress(self.compression_method.clone())?;
             }
         }
+        if let Some((key, _)) = &self.encryption {
+            let sealed = self.seal_fragments(&mut binary.symbol_table, &ir, fragments, key)?;
+            binary.data_sections.extend(sealed);
+            binary.flags.encrypted = true;
+            binary.metadata.extra.insert(SEALED_KEYS.to_string(), key.id().to_string());
+        }
         binary.checksum = binary.calculate_checksum();
         Ok(binary)
     }
@@ -65,24 +173,30 @@ impl BinarySerializer {
         binary: &HelixBinary,
         path: &Path,
     ) -> Result<(), SerializationError> {
-        let data = bincode::serialize(binary)
-            .map_err(|e| SerializationError::BincodeError(e.to_string()))?;
+        let data = binary.to_bytes().map_err(SerializationError::BincodeError)?;
         let mut file = File::create(path)
             .map_err(|e| SerializationError::IoError(e.to_string()))?;
         file.write_all(&data).map_err(|e| SerializationError::IoError(e.to_string()))?;
         Ok(())
     }
+    /// Read and verify a binary under the global signing policy, like
+    /// [`BinaryLoader::new`]
     pub fn read_from_file(
         &self,
         path: &Path,
     ) -> Result<HelixBinary, SerializationError> {
-        let mut file = File::open(path)
-            .map_err(|e| SerializationError::IoError(e.to_string()))?;
-        let mut data = Vec::new();
-        file.read_to_end(&mut data)
-            .map_err(|e| SerializationError::IoError(e.to_string()))?;
-        let binary: HelixBinary = bincode::deserialize(&data)
-            .map_err(|e| SerializationError::BincodeError(e.to_string()))?;
+        let binary = BinaryLoader::new()
+            .with_mmap(false)
+            .with_limits(self.limits)
+            .load_file(path)
+            .map_err(|e| match e {
+                LoadError::Io { source, .. } => SerializationError::IoError(source.to_string()),
+                LoadError::Limit { source, .. } => SerializationError::LimitExceeded(source),
+                LoadError::Signature { .. } | LoadError::TrustConfig { .. } => {
+                    SerializationError::ValidationError(e.to_string())
+                }
+                other => SerializationError::BincodeError(other.to_string()),
+            })?;
         binary.validate().map_err(|e| SerializationError::ValidationError(e))?;
         Ok(binary)
     }
@@ -101,21 +215,53 @@ impl BinarySerializer {
             },
             constants: super::codegen::ConstantPool::new(),
         };
+        let full_ir = binary.metadata.extra.get(INSTRUCTIONS_KEY).map(String::as_str)
+            == Some(IR_INSTRUCTIONS);
+        let mut sealed_instructions: Vec<(u32, super::codegen::Instruction)> = Vec::new();
         for section in &binary.data_sections {
             let mut section_clone = section.clone();
             if section.compression.is_some() {
-                section_clone
-                    .decompress()
-                    .map_err(|e| SerializationError::DecompressionError(e))?;
+                section_clone.decompress_within(&self.limits).map_err(|e| match e {
+                    FormatError::Limit(limit) => SerializationError::LimitExceeded(limit),
+                    other => SerializationError::DecompressionError(other.to_string()),
+                })?;
             }
-            match section.section_type {
+            let data = &section_clone.data;
+            let symbols = &mut ir.symbol_table;
+            match &section.section_type {
+                SectionType::Instructions if full_ir => {
+                    ir.instructions = decode(&self.limits, data)?;
+                }
                 SectionType::Instructions => {
-                    ir.instructions = self
-                        .deserialize_instructions(&section_clone.data)?;
+                    ir.instructions = self.deserialize_instructions(data)?;
+                }
+                // A kind can be split between readable and unsealed sections
+                SectionType::Agents => {
+                    symbols.agents.extend(decode::<HashMap<_, _>>(&self.limits, data)?);
+                }
+                SectionType::Workflows => {
+                    symbols.workflows.extend(decode::<HashMap<_, _>>(&self.limits, data)?);
+                }
+                SectionType::Contexts => {
+                    symbols.contexts.extend(decode::<HashMap<_, _>>(&self.limits, data)?);
+                }
+                SectionType::Crews => {
+                    symbols.crews.extend(decode::<HashMap<_, _>>(&self.limits, data)?);
+                }
+                SectionType::Custom(name) if name == SECTIONS_SECTION => {
+                    symbols.sections.extend(decode::<HashMap<_, _>>(&self.limits, data)?);
+                }
+                SectionType::Custom(name) if name == SEALED_INSTRUCTIONS => {
+                    sealed_instructions.extend(decode::<Vec<_>>(&self.limits, data)?);
                 }
                 _ => {}
             }
         }
+        sealed_instructions.sort_by_key(|(position, _)| *position);
+        for (position, instruction) in sealed_instructions {
+            let at = (position as usize).min(ir.instructions.len());
+            ir.instructions.insert(at, instruction);
+        }
         Ok(ir)
     }
     fn convert_symbol_table(&self, ir: &HelixIR) -> SymbolTable {
@@ -233,38 +379,110 @@ impl BinarySerializer {
     ) -> Result<Vec<DataSection>, SerializationError> {
         let mut sections = Vec::new();
         if !ir.instructions.is_empty() {
-            let instruction_data = self.serialize_instructions(&ir.instructions)?;
-            sections.push(DataSection::new(SectionType::Instructions, instruction_data));
+            sections.push(DataSection::new(SectionType::Instructions, encode(&ir.instructions)?));
+        }
+        let symbols = &ir.symbol_table;
+        if !symbols.agents.is_empty() {
+            sections.push(DataSection::new(SectionType::Agents, encode(&sorted(&symbols.agents))?));
         }
-        if !ir.symbol_table.agents.is_empty() {
-            let agent_data = bincode::serialize(&ir.symbol_table.agents)
-                .map_err(|e| SerializationError::BincodeError(e.to_string()))?;
-            sections.push(DataSection::new(SectionType::Agents, agent_data));
+        if !symbols.workflows.is_empty() {
+            sections.push(DataSection::new(SectionType::Workflows, encode(&sorted(&symbols.workflows))?));
         }
-        if !ir.symbol_table.workflows.is_empty() {
-            let workflow_data = bincode::serialize(&ir.symbol_table.workflows)
-                .map_err(|e| SerializationError::BincodeError(e.to_string()))?;
-            sections.push(DataSection::new(SectionType::Workflows, workflow_data));
+        if !symbols.contexts.is_empty() {
+            sections.push(DataSection::new(SectionType::Contexts, encode(&sorted(&symbols.contexts))?));
+        }
+        if !symbols.crews.is_empty() {
+            sections.push(DataSection::new(SectionType::Crews, encode(&sorted(&symbols.crews))?));
+        }
+        if !symbols.sections.is_empty() {
+            sections
+                .push(
+                    DataSection::new(
+                        SectionType::Custom(SECTIONS_SECTION.to_string()),
+                        encode(&sorted(&symbols.sections))?,
+                    ),
+                );
         }
         Ok(sections)
     }
-    fn serialize_instructions(
+    /// Encrypt each fragment with the strings only it uses, blanking those
+    /// strings and dropping its names from the readable symbol table
+    fn seal_fragments(
         &self,
-        instructions: &[super::codegen::Instruction],
-    ) -> Result<Vec<u8>, SerializationError> {
-        let binary_instructions: Vec<Instruction> = instructions
-            .iter()
-            .map(|inst| self.convert_instruction(inst))
-            .collect();
-        bincode::serialize(&binary_instructions)
-            .map_err(|e| SerializationError::BincodeError(e.to_string()))
+        table: &mut SymbolTable,
+        public: &HelixIR,
+        fragments: Vec<SealedFragment>,
+        key: &SectionKey,
+    ) -> Result<Vec<DataSection>, SerializationError> {
+        let mut public_strings = BTreeSet::new();
+        public.clone().strings_mut(&mut |idx: &mut u32| {
+            public_strings.insert(*idx);
+        });
+        let strings = table.strings.clone();
+        let mut sealed = Vec::new();
+        for fragment in fragments {
+            let mut used = BTreeSet::new();
+            fragment.symbols.clone().strings_mut(&mut |idx: &mut u32| {
+                used.insert(*idx);
+            });
+            for (_, instruction) in &fragment.instructions {
+                instruction.clone().strings_mut(&mut |idx: &mut u32| {
+                    used.insert(*idx);
+                });
+            }
+            let mut contents = SealedContents::default();
+            let symbols = &fragment.symbols;
+            let mut named: Vec<_> = symbols
+                .agents
+                .values()
+                .map(|agent| (SectionType::Agents, agent.id, agent.name_idx))
+                .chain(symbols.workflows.values().map(|w| (SectionType::Workflows, w.id, w.name_idx)))
+                .chain(symbols.contexts.values().map(|c| (SectionType::Contexts, c.id, c.name_idx)))
+                .chain(symbols.crews.values().map(|crew| (SectionType::Crews, crew.id, crew.name_idx)))
+                .collect();
+            named.sort_by_key(|(_, id, _)| *id);
+            for (kind, id, name_idx) in named {
+                if let Some(name) = strings.get(name_idx as usize) {
+                    contents.names.push((kind, name.clone(), id));
+                }
+            }
+            for &idx in used.difference(&public_strings) {
+                if let Some(string) = strings.get(idx as usize) {
+                    if table.string_map.get(string) == Some(&idx) {
+                        table.string_map.remove(string);
+                    }
+                    table.strings[idx as usize].clear();
+                    contents.strings.push((idx, string.clone()));
+                }
+            }
+            let fragment_ir = HelixIR {
+                symbol_table: fragment.symbols,
+                instructions: Vec::new(),
+                ..public.clone()
+            };
+            contents.sections = self.create_data_sections(&fragment_ir)?;
+            contents.sections.push(DataSection::new(
+                SectionType::Custom(SEALED_INSTRUCTIONS.to_string()),
+                encode(&fragment.instructions)?,
+            ));
+            if self.enable_compression {
+                for section in &mut contents.sections {
+                    section.compress(self.compression_method.clone())?;
+                }
+            }
+            let sealed_section = key
+                .seal(fragment.kind, &encode(&contents)?)
+                .map_err(|e| SerializationError::CompressionError(e.to_string()))?;
+            sealed.push(DataSection::new(SectionType::Sealed, encode(&sealed_section)?));
+        }
+        Ok(sealed)
     }
+    /// Instructions written before the section held the full IR
     fn deserialize_instructions(
         &self,
         data: &[u8],
     ) -> Result<Vec<super::codegen::Instruction>, SerializationError> {
-        let binary_instructions: Vec<Instruction> = bincode::deserialize(data)
-            .map_err(|e| SerializationError::BincodeError(e.to_string()))?;
+        let binary_instructions: Vec<Instruction> = decode(&self.limits, data)?;
         Ok(
             binary_instructions
                 .iter()
@@ -272,25 +490,6 @@ impl BinarySerializer {
                 .collect(),
         )
     }
-    fn convert_instruction(&self, inst: &super::codegen::Instruction) -> Instruction {
-        match inst {
-            crate::dna::mds::codegen::Instruction::DeclareAgent(id) => {
-                Instruction::InvokeAgent(*id)
-            }
-            crate::dna::mds::codegen::Instruction::DeclareWorkflow(_id) => Instruction::Nop,
-            crate::dna::mds::codegen::Instruction::DeclareContext(_id) => Instruction::Nop,
-            crate::dna::mds::codegen::Instruction::DeclareCrew(id) => Instruction::InvokeCrew(*id),
-            crate::dna::mds::codegen::Instruction::SetProperty { .. } => Instruction::Nop,
-            crate::dna::mds::codegen::Instruction::SetCapability { .. } => Instruction::Nop,
-            crate::dna::mds::codegen::Instruction::SetSecret { .. } => Instruction::Nop,
-            crate::dna::mds::codegen::Instruction::DefineStep { .. } => Instruction::Nop,
-            crate::dna::mds::codegen::Instruction::DefinePipeline { workflow, .. } => {
-                Instruction::Pipeline(*workflow)
-            }
-            crate::dna::mds::codegen::Instruction::ResolveReference { .. } => Instruction::Nop,
-            crate::dna::mds::codegen::Instruction::SetMetadata { .. } => Instruction::Nop,
-        }
-    }
     fn convert_instruction_to_ir(
         &self,
         inst: &Instruction,
@@ -307,45 +506,47 @@ impl BinarySerializer {
         }
     }
     #[allow(dead_code)]
-    fn convert_value(&self, val: &Value) -> Value {
+    fn convert_value(&self, val: &BinaryValue) -> BinaryValue {
         match val {
-            Value::Bool(b) => Value::Bool(*b),
-            Value::Int(i) => Value::Int(*i),
-            Value::Float(n) => Value::Float(*n),
-            Value::String(_s) => {
+            BinaryValue::Bool(b) => BinaryValue::Bool(*b),
+            BinaryValue::Int(i) => BinaryValue::Int(*i),
+            BinaryValue::Float(n) => BinaryValue::Float(*n),
+            BinaryValue::String(_s) => {
                 let id = 0;
-                Value::String(id)
+                BinaryValue::String(id)
             }
-            Value::Duration(secs) => {
-                Value::Duration(*secs)
+            BinaryValue::Duration(secs) => {
+                BinaryValue::Duration(*secs)
             }
-            Value::Array(_) => Value::Null,
-            Value::Object(_) => Value::Null,
-            Value::Reference(_) => Value::Null,
-            Value::Null => Value::Null,
+            BinaryValue::Quantity(q) => BinaryValue::Quantity(*q),
+            BinaryValue::Array(_) => BinaryValue::Null,
+            BinaryValue::Object(_) => BinaryValue::Null,
+            BinaryValue::Reference(_) => BinaryValue::Null,
+            BinaryValue::Null => BinaryValue::Null,
         }
     }
     #[allow(dead_code)]
-    fn convert_value_to_ir(&self, val: &Value) -> crate::dna::atp::types::Value {
+    fn convert_value_to_ir(&self, val: &BinaryValue) -> crate::dna::atp::types::Value {
         match val {
-            Value::Null => crate::dna::atp::types::Value::String(String::new()),
-            Value::Bool(b) => crate::dna::atp::types::Value::Bool(*b),
-            Value::Int(i) => crate::dna::atp::types::Value::Number(*i as f64),
-            Value::Float(f) => crate::dna::atp::types::Value::Number(*f),
-            Value::String(_id) => crate::dna::atp::types::Value::String(String::new()),
-            Value::Duration(secs) => {
+            BinaryValue::Null => crate::dna::atp::types::Value::String(String::new()),
+            BinaryValue::Bool(b) => crate::dna::atp::types::Value::Bool(*b),
+            BinaryValue::Int(i) => crate::dna::atp::types::Value::Number(*i as f64),
+            BinaryValue::Float(f) => crate::dna::atp::types::Value::Number(*f),
+            BinaryValue::String(_id) => crate::dna::atp::types::Value::String(String::new()),
+            BinaryValue::Duration(secs) => {
                 crate::dna::atp::types::Value::Duration(crate::dna::atp::types::Duration {
                     value: (*secs / 60) as u64,
                     unit: crate::dna::atp::types::TimeUnit::Minutes,
                 })
             }
-            Value::Reference(_id) => crate::dna::atp::types::Value::Reference(String::new()),
-            Value::Array(arr) => {
+            BinaryValue::Quantity(q) => crate::dna::atp::types::Value::Quantity(*q),
+            BinaryValue::Reference(_id) => crate::dna::atp::types::Value::Reference(String::new()),
+            BinaryValue::Array(arr) => {
                 crate::dna::atp::types::Value::Array(
                     arr.iter().map(|v| self.convert_value_to_ir(v)).collect(),
                 )
             }
-            Value::Object(obj) => {
+            BinaryValue::Object(obj) => {
                 let mut map = std::collections::HashMap::new();
                 for (key_idx, value) in obj {
                     let key = format!("key_{}", key_idx);
@@ -372,6 +573,7 @@ pub enum SerializationError {
     CompressionError(String),
     DecompressionError(String),
     ValidationError(String),
+    LimitExceeded(LimitError),
 }
 impl std::fmt::Display for SerializationError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
@@ -381,15 +583,148 @@ impl std::fmt::Display for SerializationError {
             Self::CompressionError(e) => write!(f, "Compression error: {}", e),
             Self::DecompressionError(e) => write!(f, "Decompression error: {}", e),
             Self::ValidationError(e) => write!(f, "Validation error: {}", e),
+            Self::LimitExceeded(e) => write!(f, "Limit exceeded: {}", e),
         }
     }
 }
 impl std::error::Error for SerializationError {}
+impl From<ReadError> for SerializationError {
+    fn from(e: ReadError) -> Self {
+        match e {
+            ReadError::Limit(limit) => Self::LimitExceeded(limit),
+            other => Self::BincodeError(other.to_string()),
+        }
+    }
+}
 impl From<String> for SerializationError {
     fn from(s: String) -> Self {
         Self::CompressionError(s)
     }
 }
+/// Declarations of one sealed kind, moved out of the readable IR
+struct SealedFragment {
+    kind: SectionType,
+    symbols: super::codegen::SymbolTable,
+    instructions: Vec<(u32, super::codegen::Instruction)>,
+}
+fn fragment_ids(symbols: &super::codegen::SymbolTable) -> BTreeSet<u32> {
+    symbols
+        .agents
+        .keys()
+        .chain(symbols.workflows.keys())
+        .chain(symbols.contexts.keys())
+        .chain(symbols.crews.keys())
+        .chain(symbols.sections.keys())
+        .copied()
+        .collect()
+}
+/// Declarations that set `sensitive = true`
+fn sensitive_declarations(ir: &HelixIR) -> HashSet<u32> {
+    ir.instructions
+        .iter()
+        .filter_map(|instruction| match instruction {
+            super::codegen::Instruction::SetProperty {
+                target,
+                key,
+                value: super::codegen::ConstantValue::Bool(true),
+            } if ir.string_pool.get(*key).map(String::as_str) == Some(SENSITIVE_PROPERTY) => {
+                Some(*target)
+            }
+            _ => None,
+        })
+        .collect()
+}
+/// The entries of `symbols` to seal: all of them when the whole kind is,
+/// otherwise those marked sensitive
+fn take_sealed<T>(
+    symbols: &mut HashMap<u32, T>,
+    whole: bool,
+    marked: &HashSet<u32>,
+) -> HashMap<u32, T> {
+    if whole {
+        return std::mem::take(symbols);
+    }
+    let ids: Vec<u32> = symbols.keys().filter(|id| marked.contains(id)).copied().collect();
+    ids.iter().filter_map(|id| symbols.remove_entry(id)).collect()
+}
+/// Move the declarations of each of `kinds`, and any other declaration
+/// marked sensitive, out of `ir`, together with the instructions that target
+/// them or their workflow steps
+fn split_sealed(ir: &mut HelixIR, kinds: &[SectionType]) -> Vec<SealedFragment> {
+    use super::codegen::Instruction as Ir;
+    let marked = sensitive_declarations(ir);
+    let symbols = &mut ir.symbol_table;
+    let mut fragments = Vec::new();
+    let mut owner = HashMap::new();
+    let others = SEALABLE
+        .iter()
+        .filter_map(|name| sealable_section(name))
+        .filter(|kind| !kinds.contains(kind));
+    let order: Vec<SectionType> = kinds.iter().cloned().chain(others).collect();
+    for kind in order {
+        let whole = kinds.contains(&kind);
+        let mut moved = super::codegen::SymbolTable::default();
+        match &kind {
+            SectionType::Agents => {
+                moved.agents = take_sealed(&mut symbols.agents, whole, &marked);
+            }
+            SectionType::Workflows => {
+                moved.workflows = take_sealed(&mut symbols.workflows, whole, &marked);
+            }
+            SectionType::Contexts => {
+                moved.contexts = take_sealed(&mut symbols.contexts, whole, &marked);
+            }
+            SectionType::Crews => moved.crews = take_sealed(&mut symbols.crews, whole, &marked),
+            SectionType::Custom(name) if name == SECTIONS_SECTION => {
+                moved.sections = take_sealed(&mut symbols.sections, whole, &marked);
+            }
+            _ => continue,
+        }
+        let ids = fragment_ids(&moved);
+        if ids.is_empty() {
+            continue;
+        }
+        for id in ids {
+            owner.insert(id, fragments.len());
+        }
+        fragments.push(SealedFragment {
+            kind,
+            symbols: moved,
+            instructions: Vec::new(),
+        });
+    }
+    for instruction in &ir.instructions {
+        if let Ir::DefineStep { workflow, step } = instruction {
+            if let Some(&fragment) = owner.get(workflow) {
+                owner.insert(step.id, fragment);
+            }
+        }
+    }
+    let instructions = std::mem::take(&mut ir.instructions);
+    for (position, instruction) in instructions.into_iter().enumerate() {
+        let target = match &instruction {
+            Ir::DeclareAgent(id)
+            | Ir::DeclareWorkflow(id)
+            | Ir::DeclareContext(id)
+            | Ir::DeclareCrew(id)
+            | Ir::DeclareSection(id) => Some(*id),
+            Ir::SetProperty { target, .. } => Some(*target),
+            Ir::SetCapability { agent, .. } => Some(*agent),
+            Ir::SetSecret { context, .. } => Some(*context),
+            Ir::DefineStep { workflow, .. } | Ir::DefinePipeline { workflow, .. } => {
+                Some(*workflow)
+            }
+            _ => None,
+        };
+        match target.and_then(|id| owner.get(&id)) {
+            Some(&fragment) => {
+                fragments[fragment].instructions.push((position as u32, instruction));
+            }
+            None => ir.instructions.push(instruction),
+        }
+    }
+    fragments
+}
 #[cfg(test)]
 mod tests {
     use super::*;
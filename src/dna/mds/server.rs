@@ -277,8 +277,9 @@ impl HelixServer {
         let binary = compiler
             .compile_source(&hlx_source, None)
             .map_err(|e| anyhow::anyhow!("Compilation failed: {:?}", e))?;
-        let hlxb_data = bincode::serialize(&binary)
-            .map_err(|e| anyhow::anyhow!("Binary serialization failed: {:?}", e))?;
+        let hlxb_data = binary
+            .to_bytes()
+            .map_err(|e| anyhow::anyhow!("Binary serialization failed: {}", e))?;
         Ok(hlxb_data)
     }
     #[cfg(not(feature = "compiler"))]
@@ -1,5 +1,7 @@
-use std::path::PathBuf;
-use anyhow::Result;
+use std::path::{Path, PathBuf};
+use anyhow::{bail, Context, Result};
+use crate::dna::hel::signing::{PublicKey, SigningKey, TrustPolicy};
+use crate::dna::mds::loader::BinaryLoader;
 
 pub fn sign_binary(
     input: PathBuf,
@@ -9,78 +11,180 @@ pub fn sign_binary(
     verbose: bool,
 ) -> Result<()> {
     if verify {
-        verify_signature(&input, verbose)
+        verify_command(&input, &[], verbose)
     } else {
-        create_signature(&input, key, output, verbose)
+        sign_command(&input, key.map(PathBuf::from), output, verbose)
     }
 }
-fn create_signature(
-    input: &PathBuf,
-    key: Option<String>,
+pub fn generate_key_command(path: &Path) -> Result<()> {
+    let public_key = generate_key(path)?;
+    println!("✅ Signing key written to {}", path.display());
+    println!("  Public key: {}", public_key);
+    if let Ok(config) = crate::dna::mds::config::get_global_config_path() {
+        println!("  Trust it by adding it to signing.trusted_keys in {}", config.display());
+    }
+    Ok(())
+}
+pub fn sign_command(
+    input: &Path,
+    key: Option<PathBuf>,
     output: Option<PathBuf>,
     verbose: bool,
 ) -> Result<()> {
+    let key_path = match key {
+        Some(key) => key,
+        None => {
+            crate::dna::mds::config::default_signing_key()?
+                .ok_or_else(|| anyhow::anyhow!(
+                    "No signing key given; pass --key or set signing.key in the global config"
+                ))?
+        }
+    };
     if verbose {
-        println!("🔐 Creating digital signature:");
-        println!("  Input: {}", input.display());
-        println!("  Key: {}", key.as_deref().unwrap_or("default"));
+        println!("🔐 Signing {}", input.display());
+        println!("  Key: {}", key_path.display());
     }
-    if !input.exists() {
-        return Err(anyhow::anyhow!("Input file not found: {}", input.display()));
+    let key = read_signing_key(&key_path)?;
+    let output = output.unwrap_or_else(|| input.to_path_buf());
+    sign_file(input, &output, &key)?;
+    println!("✅ Signed {} with {}", output.display(), key.public_key());
+    Ok(())
+}
+pub fn verify_command(input: &Path, trust: &[String], verbose: bool) -> Result<()> {
+    let mut policy = crate::dna::mds::config::trust_policy()?;
+    for key in trust {
+        policy
+            .trusted_keys
+            .push(key.parse().with_context(|| format!("Invalid public key: {}", key))?);
     }
-    let content = std::fs::read(input)?;
-    let signature = generate_signature(&content, &key)?;
-    let output_path = output
-        .unwrap_or_else(|| {
-            let mut path = input.clone();
-            path.set_extension("sig");
-            path
-        });
-    std::fs::write(&output_path, signature)?;
-    println!("✅ Signature created: {}", output_path.display());
+    policy.require_signature = true;
     if verbose {
-        println!("  Signature size: {} bytes", std::fs::metadata(& output_path) ?.len());
+        println!("🔍 Verifying {}", input.display());
+        println!("  Trusted keys: {}", policy.trusted_keys.len());
     }
+    let signer = verify_file(input, policy)?;
+    println!("✅ Signature by {} is valid and trusted", signer);
     Ok(())
 }
-fn verify_signature(input: &PathBuf, verbose: bool) -> Result<()> {
-    if verbose {
-        println!("🔍 Verifying digital signature:");
-        println!("  Input: {}", input.display());
-    }
-    if !input.exists() {
-        return Err(anyhow::anyhow!("Input file not found: {}", input.display()));
-    }
-    let mut sig_path = input.clone();
-    sig_path.set_extension("sig");
-    if !sig_path.exists() {
-        return Err(anyhow::anyhow!("Signature file not found: {}", sig_path.display()));
-    }
-    let content = std::fs::read(input)?;
-    let signature = std::fs::read(&sig_path)?;
-    let is_valid = verify_signature_content(&content, &signature)?;
-    if is_valid {
-        println!("✅ Signature is valid");
-    } else {
-        println!("❌ Signature verification failed");
-        std::process::exit(1);
+/// Write a new PKCS#8 key pair to `path` and its public key to `path.pub`.
+/// Existing files are never overwritten.
+pub fn generate_key(path: &Path) -> Result<PublicKey> {
+    let public_path = public_key_path(path);
+    for existing in [path, public_path.as_path()] {
+        if existing.exists() {
+            bail!("Refusing to overwrite {}", existing.display());
+        }
     }
+    let (key, pkcs8) = SigningKey::generate()?;
+    write_private(path, &pkcs8)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    std::fs::write(&public_path, format!("{}\n", key.public_key()))
+        .with_context(|| format!("Failed to write {}", public_path.display()))?;
+    Ok(key.public_key())
+}
+pub fn read_signing_key(path: &Path) -> Result<SigningKey> {
+    let pkcs8 = std::fs::read(path)
+        .with_context(|| format!("Failed to read signing key {}", path.display()))?;
+    SigningKey::from_pkcs8(&pkcs8)
+        .with_context(|| format!("{} is not an Ed25519 PKCS#8 key", path.display()))
+}
+/// Verify `input` and write it to `output` in the current format with an
+/// embedded signature. The input need not be signed yet, whatever the global
+/// policy requires; a signature it already carries must still be valid.
+pub fn sign_file(input: &Path, output: &Path, key: &SigningKey) -> Result<()> {
+    let mut binary = BinaryLoader::new()
+        .with_trust_policy(TrustPolicy::default())
+        .load_file(input)?;
+    binary.upgrade();
+    let bytes = binary
+        .to_signed_bytes(key)
+        .map_err(|e| anyhow::anyhow!("Failed to encode {}: {}", input.display(), e))?;
+    std::fs::write(output, bytes)
+        .with_context(|| format!("Failed to write {}", output.display()))?;
     Ok(())
 }
-fn generate_signature(content: &[u8], key: &Option<String>) -> Result<Vec<u8>> {
-    use sha2::{Sha256, Digest};
-    let mut hasher = Sha256::new();
-    hasher.update(content);
-    if let Some(key) = key {
-        hasher.update(key.as_bytes());
+/// Load `input` under `policy`, returning the key that signed it
+pub fn verify_file(input: &Path, policy: TrustPolicy) -> Result<PublicKey> {
+    let loaded = BinaryLoader::new().with_trust_policy(policy).open(input)?;
+    loaded.signer().ok_or_else(|| anyhow::anyhow!("{} is not signed", input.display()))
+}
+fn public_key_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".pub");
+    PathBuf::from(name)
+}
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+    std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?
+        .write_all(contents)
+}
+#[cfg(not(unix))]
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    std::fs::write(path, contents)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dna::hel::binary::HelixBinary;
+    use crate::dna::hel::signing::SignatureError;
+    use crate::dna::mds::loader::LoadError;
+    use tempfile::TempDir;
+
+    fn signature_error(path: &Path, policy: TrustPolicy) -> SignatureError {
+        let err = BinaryLoader::new().with_trust_policy(policy).load_file(path).unwrap_err();
+        match err {
+            LoadError::Signature { source, .. } => source,
+            other => panic!("expected a signature error, got {}", other),
+        }
+    }
+
+    #[test]
+    fn test_signed_binaries_verify_and_fail_closed() {
+        let dir = TempDir::new().unwrap();
+        let key_path = dir.path().join("release.key");
+        let public = generate_key(&key_path).unwrap();
+        assert!(generate_key(&key_path).is_err());
+        let written = std::fs::read_to_string(public_key_path(&key_path)).unwrap();
+        assert_eq!(written.parse::<PublicKey>().unwrap(), public);
+        let key = read_signing_key(&key_path).unwrap();
+
+        let unsigned = dir.path().join("app.hlxb");
+        let mut binary = HelixBinary::new();
+        binary.symbol_table.intern("assistant");
+        binary.checksum = binary.calculate_checksum();
+        std::fs::write(&unsigned, binary.to_bytes().unwrap()).unwrap();
+        let signed = dir.path().join("app.signed.hlxb");
+        sign_file(&unsigned, &signed, &key).unwrap();
+
+        let trusted = TrustPolicy::require(vec![public]);
+        assert_eq!(verify_file(&signed, trusted.clone()).unwrap(), public);
+        let loaded = BinaryLoader::new().load_file(&signed).unwrap();
+        assert!(loaded.flags.signed);
+        assert_eq!(loaded.signature.map(|s| s.public_key), Some(public));
+
+        let (other, _) = SigningKey::generate().unwrap();
+        let untrusted = TrustPolicy::require(vec![other.public_key()]);
+        assert_eq!(signature_error(&signed, untrusted), SignatureError::Untrusted(public));
+        assert_eq!(signature_error(&unsigned, trusted.clone()), SignatureError::Unsigned);
+        assert!(BinaryLoader::new().load_file(&unsigned).is_ok());
+
+        let bytes = std::fs::read(&signed).unwrap();
+        let mut tampered = bytes.clone();
+        tampered[HelixBinary::signed_message(&bytes).unwrap().len() - 1] ^= 0xff;
+        std::fs::write(&signed, &tampered).unwrap();
+        assert_eq!(
+            signature_error(&signed, TrustPolicy::default()),
+            SignatureError::Invalid(public)
+        );
+
+        let stripped = HelixBinary::signed_message(&bytes).unwrap();
+        std::fs::write(&signed, stripped).unwrap();
+        assert_eq!(signature_error(&signed, TrustPolicy::default()), SignatureError::Stripped);
     }
-    let hash = hasher.finalize();
-    Ok(hash.to_vec())
 }
-fn verify_signature_content(content: &[u8], signature: &[u8]) -> Result<bool> {
-    use sha2::{Sha256, Digest};
-    let mut hasher = Sha256::new();
-    hasher.update(content);
-    let hash = hasher.finalize();
-    Ok(hash.as_slice() == signature)
-}
\ No newline at end of file
@@ -0,0 +1,40 @@
+use crate::dna::hel::binary::BINARY_VERSION;
+use crate::dna::mds::loader::BinaryLoader;
+use crate::dna::mds::serializer::BinarySerializer;
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+
+pub fn upgrade_command(files: Vec<PathBuf>, output: Option<PathBuf>) -> Result<()> {
+    for file in &files {
+        let target = output.as_deref().unwrap_or(file);
+        let from = upgrade_file(file, target)?;
+        if from == BINARY_VERSION {
+            println!("✅ {} is already format version {}", file.display(), BINARY_VERSION);
+        } else {
+            println!(
+                "✅ Upgraded {} from format version {} to {}", target.display(), from,
+                BINARY_VERSION
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Verify `input` under the global signing policy and write it to `output` in the current format. Returns
+/// the format version it was stored in.
+pub fn upgrade_file(input: &Path, output: &Path) -> Result<u32> {
+    let mut binary = BinaryLoader::new().load_file(input)?;
+    let from = binary.version;
+    if from > BINARY_VERSION {
+        bail!(
+            "{} uses format version {}, newer than this build's {}; it cannot be rewritten \
+             without losing data", input.display(), from, BINARY_VERSION
+        );
+    }
+    if binary.upgrade() || output != input {
+        BinarySerializer::new(false)
+            .write_to_file(&binary, output)
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", output.display(), e))?;
+    }
+    Ok(from)
+}
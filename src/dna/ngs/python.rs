@@ -255,6 +255,7 @@ impl PyHelixConfig {
                         HlxValue::Object(_) => format!("{:?}", value).into_py(py),
                         HlxValue::Null => py.None(),
                         HlxValue::Duration(d) => format!("{:?}", d).into_py(py),
+                        HlxValue::Quantity(q) => q.to_string().into_py(py),
                         HlxValue::Reference(s) => s.clone().into_py(py),
                         HlxValue::Identifier(s) => s.clone().into_py(py),
                     }
@@ -368,6 +369,7 @@ impl PyHelixInterpreter {
                 ),
                 HlxValue::Null => crate::dna::atp::types::Value::Null,
                 HlxValue::Duration(d) => crate::dna::atp::types::Value::Duration(d),
+                HlxValue::Quantity(q) => crate::dna::atp::types::Value::Quantity(q),
                 HlxValue::Reference(r) => crate::dna::atp::types::Value::Reference(r),
                 HlxValue::Identifier(i) => crate::dna::atp::types::Value::Identifier(i),
             };
@@ -518,6 +520,7 @@ fn execute(
                     ),
                     HlxValue::Null => crate::dna::atp::types::Value::Null,
                     HlxValue::Duration(d) => crate::dna::atp::types::Value::Duration(d),
+                    HlxValue::Quantity(q) => crate::dna::atp::types::Value::Quantity(q),
                     HlxValue::Reference(r) => crate::dna::atp::types::Value::Reference(r),
                     HlxValue::Identifier(i) => crate::dna::atp::types::Value::Identifier(i),
                 };
@@ -0,0 +1,774 @@
+//! Operator Arguments - one grammar for every `@operator(...)` call
+//!
+//! ```text
+//! @regex("a1b2", "[0-9]", "replace", replacement = "")
+//! @string(input: "Helix", operation: upper)
+//! @hash(@concat({"parts": ["a", "b"]}), algorithm = "md5")
+//! @math({"operation": "add", "a": 1, "b": 2})
+//! ```
+//! - Arguments are separated by commas (or whitespace) and may be positional
+//!   or named with `name = value` / `name: value`. Positional arguments come
+//!   first and fill the operator's declared parameters in order.
+//! - Values are JSON literals, single-quoted strings, arrays, objects, nested
+//!   `@operator(...)` calls, quantities such as `16GiB`, or bare words, which
+//!   are read as strings.
+//! - A single JSON object is the legacy form and binds every key by name.
+//!
+//! [`parse`] turns the text into [`CallArgs`]; `OperatorEngine` evaluates
+//! nested calls, and [`Args::parse`] binds the literals against an
+//! [`OperatorDescriptor`], coercing them to the declared types.
+
+use crate::dna::atp::quantity::Quantity;
+use crate::dna::atp::value::Value;
+use crate::dna::hel::error::HlxError;
+use crate::ops::meta::{OperatorDescriptor, ValueType};
+use crate::ops::utils::{json_to_value, value_to_json};
+use std::collections::HashMap;
+use std::fmt;
+
+/// One argument value, before nested calls are evaluated
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    Array(Vec<Expr>),
+    Object(Vec<(String, Expr)>),
+    /// `@operator(args)`; the name is stored without the `@`
+    Call(String, CallArgs),
+}
+
+/// A positional (`name == None`) or named argument
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arg {
+    pub name: Option<String>,
+    pub value: Expr,
+}
+
+/// Parsed argument list of one operator call
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CallArgs {
+    pub args: Vec<Arg>,
+}
+
+impl Expr {
+    pub fn has_calls(&self) -> bool {
+        match self {
+            Expr::Literal(_) => false,
+            Expr::Array(items) => items.iter().any(Expr::has_calls),
+            Expr::Object(entries) => entries.iter().any(|(_, v)| v.has_calls()),
+            Expr::Call(..) => true,
+        }
+    }
+
+    /// The value of a call-free expression
+    pub fn into_value(self) -> Result<Value, HlxError> {
+        match self {
+            Expr::Literal(value) => Ok(value),
+            Expr::Array(items) => items
+                .into_iter()
+                .map(Expr::into_value)
+                .collect::<Result<_, _>>()
+                .map(Value::Array),
+            Expr::Object(entries) => entries
+                .into_iter()
+                .map(|(k, v)| v.into_value().map(|v| (k, v)))
+                .collect::<Result<_, _>>()
+                .map(Value::Object),
+            Expr::Call(name, args) => Err(HlxError::invalid_parameters(
+                &name,
+                &format!("Nested call `@{}({})` must be evaluated by an operator engine", name, args),
+            )),
+        }
+    }
+}
+
+impl CallArgs {
+    pub fn has_calls(&self) -> bool {
+        self.args.iter().any(|a| a.value.has_calls())
+    }
+
+    /// Argument values of a call-free list, in source order
+    pub fn into_literals(self) -> Result<Vec<(Option<String>, Value)>, HlxError> {
+        self.args
+            .into_iter()
+            .map(|a| a.value.into_value().map(|v| (a.name, v)))
+            .collect()
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Literal(Value::Quantity(q)) => write!(f, "{}", q),
+            Expr::Literal(value) => write!(f, "{}", value_to_json(value)),
+            Expr::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+            Expr::Object(entries) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}: {}", serde_json::Value::String(key.clone()), value)?;
+                }
+                f.write_str("}")
+            }
+            Expr::Call(name, args) => write!(f, "@{}({})", name, args),
+        }
+    }
+}
+
+impl fmt::Display for CallArgs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, arg) in self.args.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            if let Some(name) = &arg.name {
+                write!(f, "{} = ", name)?;
+            }
+            write!(f, "{}", arg.value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Source text for evaluated arguments, in the grammar [`parse`] reads
+pub fn render(args: &[(Option<String>, Value)]) -> String {
+    let args = args
+        .iter()
+        .map(|(name, value)| Arg { name: name.clone(), value: Expr::Literal(value.clone()) })
+        .collect();
+    CallArgs { args }.to_string()
+}
+
+/// Parse the text between an operator's parentheses
+pub fn parse(src: &str) -> Result<CallArgs, HlxError> {
+    let trimmed = src.trim();
+    if trimmed.starts_with('{') {
+        if let Ok(serde_json::Value::Object(obj)) = serde_json::from_str(trimmed) {
+            let args = obj
+                .into_iter()
+                .map(|(k, v)| Arg { name: Some(k), value: Expr::Literal(json_to_value(&v)) })
+                .collect();
+            return Ok(CallArgs { args });
+        }
+    }
+    let mut cursor = Cursor { src, pos: 0 };
+    let args = cursor.arg_list(None)?;
+    Ok(args)
+}
+
+/// Bind `params` for the operator named `operator` in `descriptors`; an
+/// operator without a descriptor takes named arguments only
+pub fn bind_params(
+    descriptors: &[OperatorDescriptor],
+    operator: &str,
+    params: &str,
+) -> Result<HashMap<String, Value>, HlxError> {
+    let args = match descriptors.iter().find(|d| d.name == operator) {
+        Some(descriptor) => Args::parse(descriptor, params)?,
+        None => Args::unchecked(operator, params)?,
+    };
+    Ok(args.into_values())
+}
+
+struct Cursor<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn rest(&self) -> &'a str {
+        &self.src[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.peek(), Some(c) if c.is_whitespace()) {
+            self.bump();
+        }
+    }
+
+    fn error(&self, message: impl AsRef<str>) -> HlxError {
+        HlxError::invalid_input(
+            format!("{} at column {} of `{}`", message.as_ref(), self.pos + 1, self.src),
+            "Arguments are `value` or `name = value` separated by commas; quote strings containing commas or parentheses",
+        )
+    }
+
+    /// Arguments up to `close` (or the end of input when `None`)
+    fn arg_list(&mut self, close: Option<char>) -> Result<CallArgs, HlxError> {
+        let mut args = Vec::new();
+        loop {
+            self.skip_ws();
+            match self.peek() {
+                None if close.is_none() => break,
+                None => return Err(self.error(format!("Missing `{}`", close.unwrap()))),
+                Some(c) if Some(c) == close => {
+                    self.bump();
+                    break;
+                }
+                _ => {}
+            }
+            let name = self.arg_name();
+            let value = self.expr()?;
+            if name.is_none() && args.iter().any(|a: &Arg| a.name.is_some()) {
+                return Err(self.error("Positional argument after a named argument"));
+            }
+            args.push(Arg { name, value });
+            self.skip_ws();
+            if self.peek() == Some(',') {
+                self.bump();
+            }
+        }
+        Ok(CallArgs { args })
+    }
+
+    /// `ident =` or `ident:` at the start of an argument
+    fn arg_name(&mut self) -> Option<String> {
+        let rest = self.rest();
+        let len = identifier_len(rest);
+        if len == 0 {
+            return None;
+        }
+        let after = rest[len..].trim_start();
+        let consumed = rest.len() - after.len();
+        let is_name = (after.starts_with('=') && !after.starts_with("=="))
+            || (after.starts_with(':') && !after.starts_with("://"));
+        if !is_name {
+            return None;
+        }
+        self.pos += consumed + 1;
+        Some(rest[..len].to_string())
+    }
+
+    fn expr(&mut self) -> Result<Expr, HlxError> {
+        self.skip_ws();
+        match self.peek() {
+            Some('"') => self.double_quoted().map(|s| Expr::Literal(Value::String(s))),
+            Some('\'') => self.single_quoted().map(|s| Expr::Literal(Value::String(s))),
+            Some('[') => {
+                self.bump();
+                let mut items = Vec::new();
+                loop {
+                    self.skip_ws();
+                    if self.peek() == Some(']') {
+                        self.bump();
+                        break;
+                    }
+                    items.push(self.expr()?);
+                    self.skip_ws();
+                    match self.bump() {
+                        Some(',') => {}
+                        Some(']') => break,
+                        _ => return Err(self.error("Expected `,` or `]` in array")),
+                    }
+                }
+                Ok(Expr::Array(items))
+            }
+            Some('{') => {
+                self.bump();
+                let mut entries = Vec::new();
+                loop {
+                    self.skip_ws();
+                    if self.peek() == Some('}') {
+                        self.bump();
+                        break;
+                    }
+                    let key = match self.peek() {
+                        Some('"') => self.double_quoted()?,
+                        Some('\'') => self.single_quoted()?,
+                        _ => {
+                            let len = identifier_len(self.rest());
+                            if len == 0 {
+                                return Err(self.error("Expected an object key"));
+                            }
+                            let key = self.rest()[..len].to_string();
+                            self.pos += len;
+                            key
+                        }
+                    };
+                    self.skip_ws();
+                    if !matches!(self.bump(), Some(':') | Some('=')) {
+                        return Err(self.error(format!("Expected `:` after key `{}`", key)));
+                    }
+                    entries.push((key, self.expr()?));
+                    self.skip_ws();
+                    match self.bump() {
+                        Some(',') => {}
+                        Some('}') => break,
+                        _ => return Err(self.error("Expected `,` or `}` in object")),
+                    }
+                }
+                Ok(Expr::Object(entries))
+            }
+            Some('@') => {
+                let rest = &self.rest()[1..];
+                let len = operator_name_len(rest);
+                if len > 0 && rest[len..].starts_with('(') {
+                    let name = rest[..len].to_string();
+                    self.pos += 1 + len + 1;
+                    let args = self.arg_list(Some(')'))?;
+                    Ok(Expr::Call(name, args))
+                } else {
+                    self.bare()
+                }
+            }
+            Some(_) => self.bare(),
+            None => Err(self.error("Expected a value")),
+        }
+    }
+
+    fn double_quoted(&mut self) -> Result<String, HlxError> {
+        let start = self.pos;
+        self.bump();
+        let mut escaped = false;
+        while let Some(c) = self.bump() {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                let literal = &self.src[start..self.pos];
+                return serde_json::from_str(literal)
+                    .map_err(|e| self.error(format!("Invalid string {}: {}", literal, e)));
+            }
+        }
+        Err(self.error("Unterminated string"))
+    }
+
+    fn single_quoted(&mut self) -> Result<String, HlxError> {
+        self.bump();
+        let mut out = String::new();
+        while let Some(c) = self.bump() {
+            match c {
+                '\\' => match self.bump() {
+                    Some(next @ ('\'' | '\\')) => out.push(next),
+                    Some(next) => {
+                        out.push('\\');
+                        out.push(next);
+                    }
+                    None => break,
+                },
+                '\'' => return Ok(out),
+                _ => out.push(c),
+            }
+        }
+        Err(self.error("Unterminated string"))
+    }
+
+    /// Unquoted word: a number, a quantity such as `16GiB`,
+    /// `true`/`false`/`null`, or a string
+    fn bare(&mut self) -> Result<Expr, HlxError> {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_whitespace() || matches!(c, ',' | '(' | ')' | '[' | ']' | '{' | '}' | '"' | '\'') {
+                break;
+            }
+            self.bump();
+        }
+        let word = &self.src[start..self.pos];
+        if word.is_empty() {
+            return Err(self.error(format!("Unexpected `{}`", self.peek().unwrap_or(' '))));
+        }
+        let value = match word {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            "null" => Value::Null,
+            _ => match word.parse::<f64>() {
+                Ok(n) if n.is_finite() => Value::Number(n),
+                _ => match word.parse::<Quantity>() {
+                    Ok(q) => Value::Quantity(q),
+                    Err(_) => Value::String(word.to_string()),
+                },
+            },
+        };
+        Ok(Expr::Literal(value))
+    }
+}
+
+fn identifier_len(s: &str) -> usize {
+    let mut chars = s.char_indices();
+    match chars.next() {
+        Some((_, c)) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return 0,
+    }
+    chars
+        .find(|(_, c)| !(c.is_ascii_alphanumeric() || *c == '_'))
+        .map(|(i, _)| i)
+        .unwrap_or(s.len())
+}
+
+/// Length of `name` or `ns.name` at the start of `s`
+fn operator_name_len(s: &str) -> usize {
+    let mut len = identifier_len(s);
+    while len > 0 && s[len..].starts_with('.') {
+        let next = identifier_len(&s[len + 1..]);
+        if next == 0 {
+            break;
+        }
+        len += 1 + next;
+    }
+    len
+}
+
+/// Arguments bound to an operator's declared parameters
+#[derive(Debug, Clone, PartialEq)]
+pub struct Args {
+    operator: String,
+    values: HashMap<String, Value>,
+}
+
+impl Args {
+    /// Parse call-free arguments and bind them against `descriptor`
+    pub fn parse(descriptor: &OperatorDescriptor, params: &str) -> Result<Self, HlxError> {
+        let literals = parse(params)?.into_literals()?;
+        Self::bind(descriptor, literals)
+    }
+
+    /// Parse arguments for an operator without a declared signature; every
+    /// argument must be named
+    pub fn unchecked(operator: &str, params: &str) -> Result<Self, HlxError> {
+        let mut values = HashMap::new();
+        for (i, (name, value)) in parse(params)?.into_literals()?.into_iter().enumerate() {
+            let name = name.ok_or_else(|| {
+                HlxError::invalid_parameters(
+                    operator,
+                    &format!("Argument {} needs a name, e.g. `name = value`", i + 1),
+                )
+            })?;
+            values.insert(name, value);
+        }
+        Ok(Self { operator: operator.to_string(), values })
+    }
+
+    /// Match positional arguments to parameters in declaration order, check
+    /// required parameters and coerce every declared parameter to its type.
+    /// Named arguments the descriptor doesn't declare are passed through.
+    pub fn bind(
+        descriptor: &OperatorDescriptor,
+        args: Vec<(Option<String>, Value)>,
+    ) -> Result<Self, HlxError> {
+        let op = descriptor.name.as_str();
+        let mut values = HashMap::new();
+        let mut position = 0;
+        for (name, value) in args {
+            let name = match name {
+                Some(name) => name,
+                None => {
+                    let param = descriptor.params.get(position).ok_or_else(|| {
+                        HlxError::invalid_parameters(
+                            op,
+                            &format!(
+                                "Too many arguments: expected at most {} for {}",
+                                descriptor.params.len(),
+                                descriptor.signature()
+                            ),
+                        )
+                    })?;
+                    position += 1;
+                    param.name.clone()
+                }
+            };
+            if values.insert(name.clone(), value).is_some() {
+                return Err(HlxError::invalid_parameters(
+                    op,
+                    &format!("Parameter `{}` given more than once", name),
+                ));
+            }
+        }
+
+        for param in &descriptor.params {
+            let value = match values.remove(&param.name) {
+                Some(Value::Null) if !param.required => continue,
+                Some(value) => value,
+                None if param.required => {
+                    return Err(HlxError::invalid_parameters(
+                        op,
+                        &format!(
+                            "Missing required parameter `{}` ({}) for {}",
+                            param.name,
+                            param.ty,
+                            descriptor.signature()
+                        ),
+                    ));
+                }
+                None => continue,
+            };
+            let value = coerce(value, param.ty).map_err(|got| {
+                HlxError::invalid_parameters(
+                    op,
+                    &format!("Parameter `{}` expects {}, got {}", param.name, param.ty, got),
+                )
+            })?;
+            if let (false, Value::String(s)) = (param.choices.is_empty(), &value) {
+                if !param.choices.iter().any(|c| c == s) {
+                    return Err(HlxError::invalid_parameters(
+                        op,
+                        &format!(
+                            "Parameter `{}` must be one of {}, got `{}`",
+                            param.name,
+                            param.choices.join(", "),
+                            s
+                        ),
+                    ));
+                }
+            }
+            values.insert(param.name.clone(), value);
+        }
+        Ok(Self { operator: descriptor.name.clone(), values })
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.values.get(name)
+    }
+
+    /// All bound values by parameter name
+    pub fn values(&self) -> &HashMap<String, Value> {
+        &self.values
+    }
+
+    pub fn into_values(self) -> HashMap<String, Value> {
+        self.values
+    }
+
+    pub fn str(&self, name: &str) -> Result<&str, HlxError> {
+        self.required(name, ValueType::String, Value::as_string)
+    }
+
+    pub fn opt_str(&self, name: &str) -> Option<&str> {
+        self.get(name).and_then(Value::as_string)
+    }
+
+    pub fn number(&self, name: &str) -> Result<f64, HlxError> {
+        self.required(name, ValueType::Number, Value::as_number)
+    }
+
+    pub fn opt_number(&self, name: &str) -> Option<f64> {
+        self.get(name).and_then(Value::as_number)
+    }
+
+    pub fn bool(&self, name: &str) -> Result<bool, HlxError> {
+        self.required(name, ValueType::Bool, Value::as_boolean)
+    }
+
+    pub fn quantity(&self, name: &str) -> Result<Quantity, HlxError> {
+        self.required(name, ValueType::Quantity, Value::as_quantity)
+    }
+
+    pub fn array(&self, name: &str) -> Result<&[Value], HlxError> {
+        self.required(name, ValueType::Array, Value::as_array)
+    }
+
+    pub fn value(&self, name: &str) -> Result<&Value, HlxError> {
+        self.required(name, ValueType::Any, Some)
+    }
+
+    fn required<'a, T>(
+        &'a self,
+        name: &str,
+        ty: ValueType,
+        get: impl FnOnce(&'a Value) -> Option<T>,
+    ) -> Result<T, HlxError> {
+        let value = self.get(name).ok_or_else(|| {
+            HlxError::invalid_parameters(
+                &self.operator,
+                &format!("Missing required parameter `{}` ({})", name, ty),
+            )
+        })?;
+        get(value).ok_or_else(|| {
+            HlxError::invalid_parameters(
+                &self.operator,
+                &format!("Parameter `{}` expects {}, got {}", name, ty, type_name(value)),
+            )
+        })
+    }
+}
+
+/// Convert `value` to `ty`; on mismatch returns the name of the actual type
+fn coerce(value: Value, ty: ValueType) -> Result<Value, &'static str> {
+    match (ty, value) {
+        (ValueType::Any, v) => Ok(v),
+        (ValueType::String, Value::String(s)) => Ok(Value::String(s)),
+        (ValueType::String, Value::Number(n)) => Ok(Value::String(format_number(n))),
+        (ValueType::String, Value::Bool(b)) => Ok(Value::String(b.to_string())),
+        (ValueType::String, Value::Identifier(s) | Value::Reference(s)) => Ok(Value::String(s)),
+        (ValueType::Number, Value::Number(n)) => Ok(Value::Number(n)),
+        (ValueType::Number, Value::String(s)) => match s.trim().parse::<f64>() {
+            Ok(n) if n.is_finite() => Ok(Value::Number(n)),
+            _ => Err("string"),
+        },
+        (ValueType::Bool, Value::Bool(b)) => Ok(Value::Bool(b)),
+        (ValueType::Bool, Value::String(s)) => match s.as_str() {
+            "true" => Ok(Value::Bool(true)),
+            "false" => Ok(Value::Bool(false)),
+            _ => Err("string"),
+        },
+        (ValueType::Array, Value::Array(a)) => Ok(Value::Array(a)),
+        (ValueType::Object, Value::Object(o)) => Ok(Value::Object(o)),
+        (ValueType::Null, Value::Null) => Ok(Value::Null),
+        (ValueType::Quantity, Value::Quantity(q)) => Ok(Value::Quantity(q)),
+        (ValueType::Quantity, Value::String(s)) => match s.parse::<Quantity>() {
+            Ok(q) => Ok(Value::Quantity(q)),
+            Err(_) => Err("string"),
+        },
+        (ValueType::String, Value::Quantity(q)) => Ok(Value::String(q.to_string())),
+        (_, other) => Err(type_name(&other)),
+    }
+}
+
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        format!("{}", n as i64)
+    } else {
+        n.to_string()
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::String(_) | Value::Identifier(_) | Value::Reference(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "bool",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+        Value::Null => "null",
+        Value::Duration(_) => "duration",
+        Value::Quantity(_) => "quantity",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::meta::{OperatorCategory, OperatorDescriptor};
+
+    fn regex() -> OperatorDescriptor {
+        OperatorDescriptor::new("regex", OperatorCategory::String, "Regular expressions")
+            .param("input", ValueType::String, "Text")
+            .param("pattern", ValueType::String, "Pattern")
+            .param("operation", ValueType::String, "Operation")
+            .choices(&["match", "replace"])
+            .optional("replacement", ValueType::String, "Replacement")
+    }
+
+    #[test]
+    fn test_parse_grammar() {
+        let args = parse(r#""a, (b)", 'it\'s', 42, true, [1, "x"], mode = fast, key: null"#).unwrap();
+        let literals = args.into_literals().unwrap();
+        assert_eq!(literals[0], (None, Value::String("a, (b)".into())));
+        assert_eq!(literals[1], (None, Value::String("it's".into())));
+        assert_eq!(literals[2], (None, Value::Number(42.0)));
+        assert_eq!(literals[3], (None, Value::Bool(true)));
+        assert_eq!(literals[4].1, Value::Array(vec![Value::Number(1.0), Value::String("x".into())]));
+        assert_eq!(literals[5], (Some("mode".into()), Value::String("fast".into())));
+        assert_eq!(literals[6], (Some("key".into()), Value::Null));
+
+        // Legacy JSON object and whitespace-separated named arguments
+        let legacy = parse(r#"{"a": 1, "b": "x,y"}"#).unwrap().into_literals().unwrap();
+        assert_eq!(legacy.len(), 2);
+        let spaced = parse(r#"name="test_var" value="v""#).unwrap().into_literals().unwrap();
+        assert_eq!(spaced[1], (Some("value".into()), Value::String("v".into())));
+
+        assert!(parse("").unwrap().args.is_empty());
+        assert!(parse(r#""open"#).is_err());
+        assert!(parse("a = 1, 2").is_err());
+    }
+
+    #[test]
+    fn test_nested_calls_round_trip() {
+        let args = parse(r#"@upper(@concat(parts = ["a", 'b']), x = "y"), sep = "-""#).unwrap();
+        assert!(args.has_calls());
+        match &args.args[0].value {
+            Expr::Call(name, inner) => {
+                assert_eq!(name, "upper");
+                assert!(matches!(&inner.args[0].value, Expr::Call(n, _) if n == "concat"));
+            }
+            other => panic!("expected a call, got {:?}", other),
+        }
+        assert_eq!(parse(&args.to_string()).unwrap(), args);
+        assert!(args.into_literals().is_err());
+        // `@` without a call is just text
+        assert_eq!(
+            parse("user@example.com").unwrap().into_literals().unwrap()[0].1,
+            Value::String("user@example.com".into())
+        );
+    }
+
+    #[test]
+    fn test_bind_positional_named_and_coercion() {
+        let args = Args::parse(&regex(), r#""a1b2", "[0-9]", operation = replace, replacement = 0"#).unwrap();
+        assert_eq!(args.str("input").unwrap(), "a1b2");
+        assert_eq!(args.str("operation").unwrap(), "replace");
+        // Numbers coerce to declared strings
+        assert_eq!(args.str("replacement").unwrap(), "0");
+
+        let sum = OperatorDescriptor::new("round", OperatorCategory::Math, "Round")
+            .param("value", ValueType::Number, "Value")
+            .optional("decimals", ValueType::Number, "Decimals");
+        let args = Args::parse(&sum, r#""3.14159", decimals = "2""#).unwrap();
+        assert_eq!(args.number("value").unwrap(), 3.14159);
+        assert_eq!(args.opt_number("decimals"), Some(2.0));
+    }
+
+    #[test]
+    fn test_bind_errors_name_parameter_and_type() {
+        let err = |params: &str| Args::parse(&regex(), params).unwrap_err().to_string();
+        assert!(err(r#""a", "b""#).contains("Missing required parameter `operation` (string)"));
+        assert!(err(r#"[1], "b", "match""#).contains("Parameter `input` expects string, got array"));
+        assert!(err(r#""a", "b", "split""#).contains("must be one of match, replace, got `split`"));
+        assert!(err(r#""a", "b", "match", "", "extra""#).contains("Too many arguments"));
+        assert!(err(r#""a", input = "b""#).contains("`input` given more than once"));
+        assert!(matches!(
+            Args::parse(&regex(), r#""a""#).unwrap_err(),
+            HlxError::InvalidParameters { .. }
+        ));
+
+        let args = Args::unchecked("validate", "rule = email").unwrap();
+        assert_eq!(args.number("rule").unwrap_err().to_string().contains("expects number, got string"), true);
+        assert!(Args::unchecked("validate", "email").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_engine_evaluates_nested_calls() {
+        let engine = crate::ops::engine::OperatorEngine::new().await.unwrap();
+        let hashed = engine
+            .execute_operator("@hash", r#"@string("Helix", "lower"), algorithm = "md5""#)
+            .await
+            .unwrap();
+        assert_eq!(hashed, Value::String(format!("{:x}", md5::compute("helix"))));
+
+        let replaced = engine
+            .execute_operator("@regex", r#""a1, b2", "[0-9]", "replace", replacement = """#)
+            .await
+            .unwrap();
+        assert_eq!(replaced, Value::String("a, b".into()));
+
+        let sum = engine
+            .execute_operator("@math", r#"{"operation": "add", "a": 1, "b": 2}"#)
+            .await
+            .unwrap();
+        assert_eq!(sum, Value::Number(3.0));
+
+        let err = engine.execute_operator("@math", r#""add", 1, "two""#).await.unwrap_err();
+        assert!(matches!(err, HlxError::InvalidParameters { .. }));
+        assert!(err.to_string().contains("Parameter `b` expects number, got string"), "{}", err);
+    }
+}
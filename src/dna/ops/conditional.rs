@@ -28,7 +28,7 @@ impl ConditionalOperators {
     }
 
     async fn execute_impl(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::ops::utils::parse_params(params)?;
+        let params_map = crate::ops::args::bind_params(&self.descriptors(), operator, params)?;
 
         match operator {
             "if" => self.if_operator(&params_map).await,
@@ -47,6 +47,43 @@ impl crate::ops::OperatorTrait for ConditionalOperators {
     async fn execute(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
         self.execute_impl(operator, params).await
     }
+
+    fn descriptors(&self) -> Vec<crate::ops::meta::OperatorDescriptor> {
+        use crate::ops::meta::{OperatorCategory as Cat, OperatorDescriptor as Op, ValueType as T};
+        vec![
+            Op::new("if", Cat::Conditional, "Choose between two values")
+                .param("condition", T::Any, "Condition; truthy selects `then`")
+                .param("then", T::Any, "Value when true")
+                .optional("else", T::Any, "Value when false")
+                .returns(T::Object)
+                .example(r#"@if({"condition": true, "then": "prod", "else": "dev"})"#),
+            Op::new("switch", Cat::Conditional, "Look a value up in a map of cases")
+                .param("value", T::Any, "Value to match")
+                .param("cases", T::Object, "Case value to result")
+                .optional("default", T::Any, "Result when no case matches")
+                .returns(T::Object),
+            Op::new("loop", Cat::Conditional, "Repeat an action a number of times")
+                .param("iterations", T::Number, "Iteration count")
+                .param("action", T::String, "Action name")
+                .returns(T::Object),
+            Op::new("filter", Cat::Collections, "Keep array items matching a predicate")
+                .param("array", T::Array, "Items")
+                .param("predicate", T::String, "Predicate")
+                .choices(&["all", "non_null", "non_empty"])
+                .returns(T::Object),
+            Op::new("map", Cat::Collections, "Transform every array item")
+                .param("array", T::Array, "Items")
+                .param("transform", T::String, "Transformation")
+                .choices(&["uppercase", "lowercase", "stringify"])
+                .returns(T::Object),
+            Op::new("reduce", Cat::Collections, "Fold an array into one value")
+                .param("array", T::Array, "Items")
+                .param("operation", T::String, "Reduction")
+                .choices(&["sum", "count", "join", "concat"])
+                .optional("initial", T::Any, "Initial accumulator")
+                .returns(T::Object),
+        ]
+    }
 }
 
 impl ConditionalOperators {
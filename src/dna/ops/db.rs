@@ -0,0 +1,1067 @@
+//! Embedded Storage Engine - `database` declarations
+//!
+//! This module backs `database "name" { ... }` declarations with a small embedded
+//! key-value/document store and exposes it to configs through two operators:
+//! - @db.get: Read a stored value
+//! - @db.put: Persist a value
+//!
+//! Layout on disk (one directory per database):
+//! - `MANIFEST.json`: shard count and format version, checked on every open
+//! - `shard-NNNN.hlxdb`: append-only log of pages for the keys hashed to that shard
+//!
+//! Each page holds a batch of records and is LZ4/Zstd-compressed when the
+//! declaration sets `compression = true`. Reads go through an LRU cache holding
+//! up to `cache_size` decoded values.
+
+use crate::dna::atp::types::DatabaseConfig;
+use crate::dna::atp::value::Value;
+use crate::dna::hel::error::HlxError;
+use crate::dna::hel::limits::{ReadLimits, DEFAULT_MAX_SECTION_SIZE};
+use crate::dna::out::hlxb_config_format::{CompressionAlgorithm, CompressionManager};
+use crate::ops::OperatorTrait;
+use crate::ops::meta::{OperatorCategory, OperatorDescriptor, ValueType};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+#[cfg(feature = "crc32fast")]
+use crc32fast::Hasher as Crc32Hasher;
+
+/// Magic bytes at the start of every shard file
+pub const SHARD_MAGIC: &[u8; 4] = b"HLXD";
+/// On-disk format version of shard files and the manifest
+pub const DB_FORMAT_VERSION: u8 = 1;
+/// Default number of decoded values kept in the LRU cache
+pub const DEFAULT_CACHE_SIZE: usize = 1024;
+/// Pending writes are flushed into a new page once they reach this size
+pub const DEFAULT_PAGE_SIZE: usize = 64 * 1024;
+
+/// Largest page, compressed or decoded, a shard writes or reads back
+const MAX_PAGE_LEN: u64 = DEFAULT_MAX_SECTION_SIZE;
+/// Size of the fixed page header: codec(1) + uncompressed(4) + stored(4) + crc(4)
+const PAGE_HEADER_LEN: u64 = 13;
+/// Size of the shard file header: magic(4) + version(1) + shard index(4)
+const SHARD_HEADER_LEN: u64 = 9;
+
+/// Contents of `MANIFEST.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct DbManifest {
+    version: u8,
+    shards: u32,
+}
+
+/// One record inside a page; `value: None` is a deletion tombstone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PageEntry {
+    key: String,
+    value: Option<Value>,
+}
+
+/// Fixed-capacity least-recently-used cache of decoded values
+struct LruCache {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<String, (Value, u64)>,
+    order: BTreeMap<u64, String>,
+}
+
+impl LruCache {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+        }
+    }
+
+    fn get(&mut self, key: &str) -> Option<Value> {
+        let tick = self.next_tick();
+        let (value, last_used) = self.entries.get_mut(key)?;
+        self.order.remove(last_used);
+        *last_used = tick;
+        self.order.insert(tick, key.to_string());
+        Some(value.clone())
+    }
+
+    fn insert(&mut self, key: &str, value: Value) {
+        if self.capacity == 0 {
+            return;
+        }
+        self.remove(key);
+        while self.entries.len() >= self.capacity {
+            match self.order.pop_first() {
+                Some((_, oldest)) => {
+                    self.entries.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+        let tick = self.next_tick();
+        self.order.insert(tick, key.to_string());
+        self.entries.insert(key.to_string(), (value, tick));
+    }
+
+    fn remove(&mut self, key: &str) {
+        if let Some((_, last_used)) = self.entries.remove(key) {
+            self.order.remove(&last_used);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+}
+
+/// Why the page at an offset could not be read
+enum PageError {
+    /// The header or payload runs past the end of the file, as left by a
+    /// crash in the middle of an append
+    Torn,
+    /// I/O failure, or a complete page that does not decode
+    /// (`HlxError::DatabaseCorrupted`)
+    Failed(HlxError),
+}
+
+impl From<std::io::Error> for PageError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Failed(err.into())
+    }
+}
+
+/// A single shard file plus its in-memory index and unflushed writes
+struct Shard {
+    path: PathBuf,
+    file: File,
+    /// Key -> offset of the newest page containing it
+    index: HashMap<String, u64>,
+    /// Writes not yet flushed to a page (last write wins)
+    pending: HashMap<String, Option<Value>>,
+    pending_bytes: usize,
+}
+
+impl Shard {
+    fn open(path: PathBuf, shard_index: u32) -> Result<Self, HlxError> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(|e| HlxError::io_error(
+                format!("Failed to open shard {}: {}", path.display(), e),
+                "Check database directory permissions",
+            ))?;
+        let len = file.metadata()?.len();
+        if len == 0 {
+            file.write_all(SHARD_MAGIC)?;
+            file.write_all(&[DB_FORMAT_VERSION])?;
+            file.write_all(&shard_index.to_le_bytes())?;
+            file.sync_data()?;
+        } else {
+            let mut header = [0u8; SHARD_HEADER_LEN as usize];
+            file.read_exact(&mut header).map_err(|_| HlxError::validation_error(
+                format!("Truncated shard header in {}", path.display()),
+                "The database file is corrupted",
+            ))?;
+            if &header[0..4] != SHARD_MAGIC {
+                return Err(HlxError::validation_error(
+                    format!("Invalid shard magic in {}", path.display()),
+                    "File does not appear to be a Helix database shard",
+                ));
+            }
+            if header[4] != DB_FORMAT_VERSION {
+                return Err(HlxError::validation_error(
+                    format!("Unsupported shard version: {} (expected {})", header[4], DB_FORMAT_VERSION),
+                    "Database was created with an incompatible version of Helix",
+                ));
+            }
+        }
+
+        let mut shard = Self {
+            path,
+            file,
+            index: HashMap::new(),
+            pending: HashMap::new(),
+            pending_bytes: 0,
+        };
+        shard.replay()?;
+        Ok(shard)
+    }
+
+    /// Rebuild the index by scanning every page. A torn page at the end of the
+    /// file (e.g. from a crash mid-write) is truncated away; a damaged page
+    /// anywhere else is reported and the file is left untouched.
+    fn replay(&mut self) -> Result<(), HlxError> {
+        let file_len = self.file.metadata()?.len();
+        let mut offset = SHARD_HEADER_LEN;
+        while offset < file_len {
+            match self.read_page(offset) {
+                Ok((entries, next)) => {
+                    for entry in entries {
+                        if entry.value.is_some() {
+                            self.index.insert(entry.key, offset);
+                        } else {
+                            self.index.remove(&entry.key);
+                        }
+                    }
+                    offset = next;
+                }
+                Err(PageError::Torn) => {
+                    self.file.set_len(offset)?;
+                    break;
+                }
+                Err(PageError::Failed(e)) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Read and decode the page at `offset`, returning its entries and the
+    /// offset of the following page. Lengths from the header are checked
+    /// against the file and the page size limit before anything is allocated.
+    fn read_page(&mut self, offset: u64) -> Result<(Vec<PageEntry>, u64), PageError> {
+        let available = self.file.metadata()?.len().saturating_sub(offset);
+        if available < PAGE_HEADER_LEN {
+            return Err(PageError::Torn);
+        }
+        self.file.seek(SeekFrom::Start(offset))?;
+        let mut header = [0u8; PAGE_HEADER_LEN as usize];
+        self.file.read_exact(&mut header)?;
+        let uncompressed_len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]);
+        let stored_len = u32::from_le_bytes([header[5], header[6], header[7], header[8]]);
+        let checksum = u32::from_le_bytes([header[9], header[10], header[11], header[12]]);
+        if stored_len as u64 > available - PAGE_HEADER_LEN {
+            return Err(PageError::Torn);
+        }
+        let algorithm = codec_from_byte(header[0]).map_err(|e| self.corrupted(offset, e))?;
+        if uncompressed_len as u64 > MAX_PAGE_LEN {
+            return Err(self.corrupted(
+                offset,
+                format!("page declares {} bytes, over the {} byte limit", uncompressed_len, MAX_PAGE_LEN),
+            ));
+        }
+
+        let mut stored = vec![0u8; stored_len as usize];
+        self.file.read_exact(&mut stored)?;
+        verify_checksum(&stored, checksum).map_err(|e| self.corrupted(offset, e))?;
+
+        // Refuse to inflate past the size the header declares
+        let limits = ReadLimits {
+            max_section_size: uncompressed_len as u64,
+            max_decompressed_ratio: u64::MAX,
+            ..ReadLimits::default()
+        };
+        let payload = CompressionManager::decompress_within(&stored, algorithm, &limits)
+            .map_err(|e| match e {
+                HlxError::FeatureError { .. } => PageError::Failed(e),
+                e => self.corrupted(offset, e),
+            })?;
+        if payload.len() != uncompressed_len as usize {
+            return Err(self.corrupted(
+                offset,
+                format!("page size mismatch: expected {}, got {}", uncompressed_len, payload.len()),
+            ));
+        }
+        let entries: Vec<PageEntry> = serde_json::from_slice(&payload)
+            .map_err(|e| self.corrupted(offset, format!("failed to decode page: {}", e)))?;
+        Ok((entries, offset + PAGE_HEADER_LEN + stored_len as u64))
+    }
+
+    fn corrupted(&self, offset: u64, reason: impl std::fmt::Display) -> PageError {
+        PageError::Failed(HlxError::database_corrupted(&self.path, offset, reason.to_string()))
+    }
+
+    fn stage(&mut self, key: &str, value: Option<Value>) {
+        self.pending_bytes += entry_size(key, value.as_ref());
+        self.pending.insert(key.to_string(), value);
+    }
+
+    fn flush(&mut self, algorithm: CompressionAlgorithm) -> Result<(), HlxError> {
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let mut entries: Vec<PageEntry> = self.pending
+            .drain()
+            .map(|(key, value)| PageEntry { key, value })
+            .collect();
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        self.pending_bytes = 0;
+
+        let offset = self.append_page(&entries, algorithm)?;
+        for entry in entries {
+            if entry.value.is_some() {
+                self.index.insert(entry.key, offset);
+            } else {
+                self.index.remove(&entry.key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Append one encoded page and return its offset
+    fn append_page(&mut self, entries: &[PageEntry], algorithm: CompressionAlgorithm) -> Result<u64, HlxError> {
+        let payload = serde_json::to_vec(entries)
+            .map_err(|e| HlxError::serialization_error(
+                format!("Failed to encode page: {}", e),
+                "Check that stored values are serializable",
+            ))?;
+        let stored = CompressionManager::compress(&payload, algorithm)?;
+        if payload.len() as u64 > MAX_PAGE_LEN || stored.len() as u64 > MAX_PAGE_LEN {
+            return Err(HlxError::validation_error(
+                format!("Page exceeds the {} byte page limit", MAX_PAGE_LEN),
+                "Store smaller values or flush more often",
+            ));
+        }
+
+        let offset = self.file.seek(SeekFrom::End(0))?;
+        let mut page = Vec::with_capacity(PAGE_HEADER_LEN as usize + stored.len());
+        page.push(codec_to_byte(algorithm));
+        page.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        page.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+        page.extend_from_slice(&checksum(&stored).to_le_bytes());
+        page.extend_from_slice(&stored);
+        self.file.write_all(&page)?;
+        self.file.sync_data()?;
+        Ok(offset)
+    }
+
+    fn lookup(&mut self, key: &str) -> Result<Option<Value>, HlxError> {
+        if let Some(pending) = self.pending.get(key) {
+            return Ok(pending.clone());
+        }
+        let offset = match self.index.get(key) {
+            Some(offset) => *offset,
+            None => return Ok(None),
+        };
+        let (entries, _) = match self.read_page(offset) {
+            Ok(page) => page,
+            Err(PageError::Torn) => {
+                return Err(HlxError::database_corrupted(&self.path, offset, "indexed page runs past the end of the file"))
+            }
+            Err(PageError::Failed(e)) => return Err(e),
+        };
+        Ok(entries.into_iter().find(|e| e.key == key).and_then(|e| e.value))
+    }
+
+    fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.index
+            .keys()
+            .filter(|k| !matches!(self.pending.get(*k), Some(None)))
+            .cloned()
+            .collect();
+        for (key, value) in &self.pending {
+            if value.is_some() && !self.index.contains_key(key) {
+                keys.push(key.clone());
+            }
+        }
+        keys
+    }
+}
+
+/// Embedded key-value/document store opened from a `DatabaseConfig`
+///
+/// # Example
+/// ```ignore
+/// let mut db = HelixDb::open(&config)?;
+/// db.put("runs/latest", Value::Number(42.0))?;
+/// db.flush()?;
+/// assert_eq!(db.get("runs/latest")?, Some(Value::Number(42.0)));
+/// ```
+pub struct HelixDb {
+    name: String,
+    root: PathBuf,
+    shards: Vec<Shard>,
+    cache: LruCache,
+    algorithm: CompressionAlgorithm,
+    page_size: usize,
+}
+
+impl HelixDb {
+    /// Open (or create) the database described by `config`.
+    ///
+    /// `path` defaults to `~/.dna/hlx/db/<name>`, `shards` to 1, `cache_size`
+    /// to [`DEFAULT_CACHE_SIZE`] entries. With `compression = true` pages use
+    /// LZ4, or Zstd when the `codec = "zstd"` property is set.
+    pub fn open(config: &DatabaseConfig) -> Result<Self, HlxError> {
+        let root = match &config.path {
+            Some(path) => PathBuf::from(path),
+            None => crate::dna::atp::ops::get_or_create_helix_dir()?
+                .join("db")
+                .join(&config.name),
+        };
+        let shard_count = match config.shards {
+            None => 1,
+            Some(n) if n >= 1 && n <= u32::MAX as i64 => n as u32,
+            Some(n) => {
+                return Err(HlxError::config_validation(
+                    "shards",
+                    n.to_string(),
+                    "Use a shard count of at least 1",
+                ))
+            }
+        };
+        let cache_size = match config.cache_size {
+            None => DEFAULT_CACHE_SIZE,
+            Some(n) if n >= 0 => n as usize,
+            Some(n) => {
+                return Err(HlxError::config_validation(
+                    "cache_size",
+                    n.to_string(),
+                    "Use a cache size of 0 (disabled) or more entries",
+                ))
+            }
+        };
+        let algorithm = if config.compression.unwrap_or(false) {
+            match config.properties.get("codec").and_then(|v| v.as_string()) {
+                Some("zstd") => CompressionAlgorithm::Zstd,
+                Some("lz4") | None => CompressionAlgorithm::Lz4,
+                Some(other) => {
+                    return Err(HlxError::config_validation(
+                        "codec",
+                        other,
+                        "Supported codecs are \"lz4\" and \"zstd\"",
+                    ))
+                }
+            }
+        } else {
+            CompressionAlgorithm::None
+        };
+        let page_size = config
+            .properties
+            .get("page_size")
+            .and_then(|v| v.as_number())
+            .map(|n| n.max(1.0) as usize)
+            .unwrap_or(DEFAULT_PAGE_SIZE);
+
+        fs::create_dir_all(&root).map_err(|e| HlxError::io_error(
+            format!("Failed to create database directory {}: {}", root.display(), e),
+            "Check the database `path` and its permissions",
+        ))?;
+        Self::check_manifest(&root, shard_count)?;
+
+        let mut shards = Vec::with_capacity(shard_count as usize);
+        for i in 0..shard_count {
+            shards.push(Shard::open(root.join(format!("shard-{:04}.hlxdb", i)), i)?);
+        }
+
+        Ok(Self {
+            name: config.name.clone(),
+            root,
+            shards,
+            cache: LruCache::new(cache_size),
+            algorithm,
+            page_size,
+        })
+    }
+
+    /// Write the manifest on first open and refuse to reopen with a different
+    /// shard count, since keys would hash to the wrong files.
+    fn check_manifest(root: &Path, shard_count: u32) -> Result<(), HlxError> {
+        let manifest_path = root.join("MANIFEST.json");
+        if manifest_path.exists() {
+            let manifest: DbManifest = serde_json::from_str(&fs::read_to_string(&manifest_path)?)?;
+            if manifest.version != DB_FORMAT_VERSION {
+                return Err(HlxError::validation_error(
+                    format!("Unsupported database version: {} (expected {})", manifest.version, DB_FORMAT_VERSION),
+                    "Database was created with an incompatible version of Helix",
+                ));
+            }
+            if manifest.shards != shard_count {
+                return Err(HlxError::config_validation(
+                    "shards",
+                    shard_count.to_string(),
+                    format!("Database at {} was created with {} shards", root.display(), manifest.shards),
+                ));
+            }
+            return Ok(());
+        }
+        let manifest = DbManifest { version: DB_FORMAT_VERSION, shards: shard_count };
+        fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)?;
+        Ok(())
+    }
+
+    /// Name of the `database` declaration this store was opened from
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Directory holding the manifest and shard files
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    /// Number of shard files keys are spread across
+    pub fn shard_count(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Shard a key is stored in (stable FNV-1a hash of the key)
+    pub fn shard_for(&self, key: &str) -> usize {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in key.as_bytes() {
+            hash ^= *byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        (hash % self.shards.len() as u64) as usize
+    }
+
+    /// Look up a value, consulting the LRU cache first
+    pub fn get(&mut self, key: &str) -> Result<Option<Value>, HlxError> {
+        if let Some(value) = self.cache.get(key) {
+            return Ok(Some(value));
+        }
+        let shard = self.shard_for(key);
+        let value = self.shards[shard].lookup(key)?;
+        if let Some(v) = &value {
+            self.cache.insert(key, v.clone());
+        }
+        Ok(value)
+    }
+
+    /// Store a value. Writes are buffered per shard and written as a page once
+    /// the buffer reaches `page_size` bytes, or on [`HelixDb::flush`].
+    pub fn put(&mut self, key: &str, value: Value) -> Result<(), HlxError> {
+        if key.is_empty() {
+            return Err(HlxError::invalid_input("Database keys must not be empty", "Provide a non-empty key"));
+        }
+        let shard = self.shard_for(key);
+        self.cache.insert(key, value.clone());
+        self.shards[shard].stage(key, Some(value));
+        self.flush_if_full(shard)
+    }
+
+    /// Delete a key, returning whether it existed
+    pub fn delete(&mut self, key: &str) -> Result<bool, HlxError> {
+        let existed = self.contains_key(key)?;
+        if existed {
+            let shard = self.shard_for(key);
+            self.cache.remove(key);
+            self.shards[shard].stage(key, None);
+            self.flush_if_full(shard)?;
+        }
+        Ok(existed)
+    }
+
+    /// Whether a live value exists for `key`
+    pub fn contains_key(&mut self, key: &str) -> Result<bool, HlxError> {
+        Ok(self.get(key)?.is_some())
+    }
+
+    /// All live keys, sorted
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.shards.iter().flat_map(|s| s.keys()).collect();
+        keys.sort();
+        keys
+    }
+
+    /// All live keys starting with `prefix`, sorted
+    pub fn scan_prefix(&self, prefix: &str) -> Vec<String> {
+        self.keys().into_iter().filter(|k| k.starts_with(prefix)).collect()
+    }
+
+    /// Number of live keys
+    pub fn len(&self) -> usize {
+        self.keys().len()
+    }
+
+    /// Whether the database holds no live keys
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of values currently held in the LRU cache
+    pub fn cached_entries(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Write every shard's buffered writes to disk
+    pub fn flush(&mut self) -> Result<(), HlxError> {
+        for shard in &mut self.shards {
+            shard.flush(self.algorithm)?;
+        }
+        Ok(())
+    }
+
+    /// Rewrite every shard so it holds a single page per live batch, dropping
+    /// overwritten values and tombstones.
+    pub fn compact(&mut self) -> Result<(), HlxError> {
+        self.flush()?;
+        for i in 0..self.shards.len() {
+            let mut live = Vec::new();
+            let mut keys = self.shards[i].keys();
+            keys.sort();
+            for key in keys {
+                if let Some(value) = self.shards[i].lookup(&key)? {
+                    live.push(PageEntry { key, value: Some(value) });
+                }
+            }
+
+            let path = self.shards[i].path.clone();
+            let tmp_path = path.with_extension("hlxdb.compact");
+            let _ = fs::remove_file(&tmp_path);
+            {
+                let mut tmp = Shard::open(tmp_path.clone(), i as u32)?;
+                for chunk in live.chunks(self.page_chunk_len(&live)) {
+                    tmp.append_page(chunk, self.algorithm)?;
+                }
+            }
+            fs::rename(&tmp_path, &path)?;
+            self.shards[i] = Shard::open(path, i as u32)?;
+        }
+        self.cache.clear();
+        Ok(())
+    }
+
+    /// Number of entries per page when compacting, aiming for `page_size` bytes
+    fn page_chunk_len(&self, entries: &[PageEntry]) -> usize {
+        if entries.is_empty() {
+            return 1;
+        }
+        let total: usize = entries.iter().map(|e| entry_size(&e.key, e.value.as_ref())).sum();
+        let avg = (total / entries.len()).max(1);
+        (self.page_size / avg).max(1)
+    }
+
+    fn flush_if_full(&mut self, shard: usize) -> Result<(), HlxError> {
+        if self.shards[shard].pending_bytes >= self.page_size {
+            self.shards[shard].flush(self.algorithm)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for HelixDb {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+/// Rough encoded size of one record, used to decide when a page is full
+fn entry_size(key: &str, value: Option<&Value>) -> usize {
+    key.len() + value.map(|v| v.to_string().len()).unwrap_or(0) + 16
+}
+
+fn codec_to_byte(algorithm: CompressionAlgorithm) -> u8 {
+    match algorithm {
+        CompressionAlgorithm::None => 0,
+        CompressionAlgorithm::Lz4 => 1,
+        CompressionAlgorithm::Zstd => 2,
+        CompressionAlgorithm::Gzip => 3,
+    }
+}
+
+fn codec_from_byte(byte: u8) -> Result<CompressionAlgorithm, HlxError> {
+    match byte {
+        0 => Ok(CompressionAlgorithm::None),
+        1 => Ok(CompressionAlgorithm::Lz4),
+        2 => Ok(CompressionAlgorithm::Zstd),
+        3 => Ok(CompressionAlgorithm::Gzip),
+        other => Err(HlxError::validation_error(
+            format!("Unknown page codec: 0x{:02x}", other),
+            "The database file is corrupted",
+        )),
+    }
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    #[cfg(feature = "crc32fast")]
+    {
+        let mut hasher = Crc32Hasher::new();
+        hasher.update(data);
+        hasher.finalize()
+    }
+    #[cfg(not(feature = "crc32fast"))]
+    {
+        // Bitwise CRC-32 (IEEE), the same value crc32fast computes, so files
+        // stay readable whichever build wrote them
+        let mut crc = !0u32;
+        for &byte in data {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+            }
+        }
+        !crc
+    }
+}
+
+fn verify_checksum(data: &[u8], expected: u32) -> Result<(), HlxError> {
+    let actual = checksum(data);
+    if actual != expected {
+        return Err(HlxError::validation_error(
+            format!("Page checksum mismatch: expected {}, got {}", expected, actual),
+            "The database file is corrupted",
+        ));
+    }
+    Ok(())
+}
+
+/// `@db.*` operators backed by the databases registered with the engine
+pub struct DbOperators {
+    /// Declarations registered with the engine, by name
+    configs: RwLock<HashMap<String, DatabaseConfig>>,
+    /// Stores opened so far; each is opened on first use
+    open: RwLock<HashMap<String, Arc<Mutex<HelixDb>>>>,
+}
+
+impl DbOperators {
+    pub async fn new() -> Result<Self, HlxError> {
+        Ok(Self {
+            configs: RwLock::new(HashMap::new()),
+            open: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Make the database described by `config` available to `@db.*` operators
+    /// under its declared name. The store is opened on first use.
+    pub fn register(&self, config: &DatabaseConfig) -> Result<(), HlxError> {
+        let mut configs = self.configs
+            .write()
+            .map_err(|_| HlxError::validation_error("RwLock poisoned", "Check concurrency"))?;
+        let mut open = self.open
+            .write()
+            .map_err(|_| HlxError::validation_error("RwLock poisoned", "Check concurrency"))?;
+        open.remove(&config.name);
+        configs.insert(config.name.clone(), config.clone());
+        Ok(())
+    }
+
+    /// Handle to a registered database, opening it if needed. The `default`
+    /// database lives under the helix directory when it was not declared.
+    pub fn database(&self, name: &str) -> Result<Arc<Mutex<HelixDb>>, HlxError> {
+        let mut open = self.open
+            .write()
+            .map_err(|_| HlxError::validation_error("RwLock poisoned", "Check concurrency"))?;
+        if let Some(db) = open.get(name) {
+            return Ok(db.clone());
+        }
+        let config = {
+            let configs = self.configs
+                .read()
+                .map_err(|_| HlxError::validation_error("RwLock poisoned", "Check concurrency"))?;
+            match configs.get(name) {
+                Some(config) => config.clone(),
+                None if name == "default" => DatabaseConfig {
+                    name: "default".to_string(),
+                    path: None,
+                    shards: None,
+                    compression: None,
+                    cache_size: None,
+                    vector_index: None,
+                    properties: HashMap::new(),
+                },
+                None => {
+                    return Err(HlxError::invalid_parameters(
+                        "db",
+                        &format!("Database `{}` is not declared", name),
+                    ))
+                }
+            }
+        };
+        let db = Arc::new(Mutex::new(HelixDb::open(&config)?));
+        open.insert(name.to_string(), db.clone());
+        Ok(db)
+    }
+
+    pub async fn execute(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
+        self.execute_impl(operator, params).await
+    }
+
+    async fn execute_impl(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
+        let params_map = crate::ops::args::bind_params(&self.descriptors(), &format!("db.{}", operator), params)?;
+        match operator {
+            "get" => self.get_operator(&params_map).await,
+            "put" => self.put_operator(&params_map).await,
+            _ => Err(HlxError::unknown_operator(format!("@db.{}", operator))),
+        }
+    }
+
+    /// @db.get – Read a value. Returns the stored value, or `default`
+    /// (`null` if not given) when the key is missing.
+    /// Parameters:
+    ///   * `key` (String) – key to read (required)
+    ///   * `db` (String) – database name, defaults to `default`
+    ///   * `default` (any Value) – returned when the key is missing
+    async fn get_operator(&self, params: &HashMap<String, Value>) -> Result<Value, HlxError> {
+        let (db_name, key) = Self::target("db.get", params)?;
+        let handle = self.database(&db_name)?;
+        let stored = {
+            let mut db = handle
+                .lock()
+                .map_err(|_| HlxError::validation_error("Mutex poisoned", "Check concurrency"))?;
+            db.get(&key)?
+        };
+        Ok(stored
+            .or_else(|| params.get("default").cloned())
+            .unwrap_or(Value::Null))
+    }
+
+    /// @db.put – Persist a value and flush it to disk.
+    /// Parameters:
+    ///   * `key` (String) – key to write (required)
+    ///   * `value` (any Value) – value to store (required)
+    ///   * `db` (String) – database name, defaults to `default`
+    async fn put_operator(&self, params: &HashMap<String, Value>) -> Result<Value, HlxError> {
+        let (db_name, key) = Self::target("db.put", params)?;
+        let value = params
+            .get("value")
+            .cloned()
+            .ok_or_else(|| HlxError::invalid_parameters("db.put", "Missing required parameter `value`"))?;
+        let handle = self.database(&db_name)?;
+        {
+            let mut db = handle
+                .lock()
+                .map_err(|_| HlxError::validation_error("Mutex poisoned", "Check concurrency"))?;
+            db.put(&key, value.clone())?;
+            db.flush()?;
+        }
+
+        Ok(Value::Object({
+            let mut map = HashMap::new();
+            map.insert("operation".to_string(), Value::String("put".to_string()));
+            map.insert("db".to_string(), Value::String(db_name));
+            map.insert("key".to_string(), Value::String(key));
+            map.insert("value".to_string(), value);
+            map
+        }))
+    }
+
+    fn target(operator: &str, params: &HashMap<String, Value>) -> Result<(String, String), HlxError> {
+        let key = params
+            .get("key")
+            .and_then(|v| v.as_string())
+            .ok_or_else(|| HlxError::invalid_parameters(operator, "Missing required parameter `key`"))?
+            .to_string();
+        let db_name = params
+            .get("db")
+            .and_then(|v| v.as_string())
+            .unwrap_or("default")
+            .to_string();
+        Ok((db_name, key))
+    }
+}
+
+#[async_trait]
+impl OperatorTrait for DbOperators {
+    async fn execute(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
+        self.execute_impl(operator, params).await
+    }
+
+    fn descriptors(&self) -> Vec<OperatorDescriptor> {
+        vec![
+            OperatorDescriptor::new("db.get", OperatorCategory::Data, "Read a key from an embedded database")
+                .param("key", ValueType::String, "Key to read")
+                .optional("db", ValueType::String, "Declared database name, `default` if omitted")
+                .optional("default", ValueType::Any, "Value when the key is missing")
+                .nondeterministic()
+                .example(r#"@db.get({"key": "build.number", "db": "cache"})"#),
+            OperatorDescriptor::new("db.put", OperatorCategory::Data, "Write a key to an embedded database")
+                .param("key", ValueType::String, "Key to write")
+                .param("value", ValueType::Any, "Value to store")
+                .optional("db", ValueType::String, "Declared database name, `default` if omitted")
+                .returns(ValueType::Object)
+                .impure(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn config(dir: &TempDir, shards: i64, compression: bool, cache_size: i64) -> DatabaseConfig {
+        DatabaseConfig {
+            name: "test".to_string(),
+            path: Some(dir.path().join("db").to_string_lossy().to_string()),
+            shards: Some(shards),
+            compression: Some(compression),
+            cache_size: Some(cache_size),
+            vector_index: None,
+            properties: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_put_get_roundtrip_across_reopen() {
+        let dir = TempDir::new().unwrap();
+        let cfg = config(&dir, 4, true, 16);
+        {
+            let mut db = HelixDb::open(&cfg).unwrap();
+            db.put("model", Value::String("gpt-4".to_string())).unwrap();
+            db.put("run/1", Value::Object(doc_with_epochs())).unwrap();
+        }
+        let mut db = HelixDb::open(&cfg).unwrap();
+        assert_eq!(db.get("model").unwrap(), Some(Value::String("gpt-4".to_string())));
+        assert_eq!(db.get("run/1").unwrap(), Some(Value::Object(doc_with_epochs())));
+        assert_eq!(db.get("missing").unwrap(), None);
+        assert_eq!(db.keys(), vec!["model".to_string(), "run/1".to_string()]);
+    }
+
+    fn doc_with_epochs() -> HashMap<String, Value> {
+        let mut doc = HashMap::new();
+        doc.insert("epochs".to_string(), Value::Number(3.0));
+        doc
+    }
+
+    #[test]
+    fn test_keys_are_sharded_across_files() {
+        let dir = TempDir::new().unwrap();
+        let mut db = HelixDb::open(&config(&dir, 4, false, 0)).unwrap();
+        for i in 0..64 {
+            db.put(&format!("key-{}", i), Value::Number(i as f64)).unwrap();
+        }
+        db.flush().unwrap();
+        let used: std::collections::HashSet<usize> =
+            (0..64).map(|i| db.shard_for(&format!("key-{}", i))).collect();
+        assert!(used.len() > 1);
+        for i in 0..4 {
+            assert!(dir.path().join("db").join(format!("shard-{:04}.hlxdb", i)).exists());
+        }
+        assert_eq!(db.len(), 64);
+    }
+
+    #[test]
+    fn test_shard_count_mismatch_is_rejected() {
+        let dir = TempDir::new().unwrap();
+        drop(HelixDb::open(&config(&dir, 2, false, 8)).unwrap());
+        assert!(HelixDb::open(&config(&dir, 3, false, 8)).is_err());
+    }
+
+    #[test]
+    fn test_lru_cache_is_bounded() {
+        let dir = TempDir::new().unwrap();
+        let mut db = HelixDb::open(&config(&dir, 1, true, 2)).unwrap();
+        for i in 0..5 {
+            db.put(&format!("k{}", i), Value::Number(i as f64)).unwrap();
+        }
+        assert_eq!(db.cached_entries(), 2);
+        assert_eq!(db.get("k0").unwrap(), Some(Value::Number(0.0)));
+    }
+
+    #[test]
+    fn test_delete_and_compact() {
+        let dir = TempDir::new().unwrap();
+        let cfg = config(&dir, 2, true, 8);
+        let mut db = HelixDb::open(&cfg).unwrap();
+        db.put("a", Value::Number(1.0)).unwrap();
+        db.put("b", Value::Number(2.0)).unwrap();
+        db.flush().unwrap();
+        db.put("a", Value::Number(10.0)).unwrap();
+        assert!(db.delete("b").unwrap());
+        assert!(!db.delete("b").unwrap());
+        db.compact().unwrap();
+        drop(db);
+
+        let mut db = HelixDb::open(&cfg).unwrap();
+        assert_eq!(db.get("a").unwrap(), Some(Value::Number(10.0)));
+        assert_eq!(db.get("b").unwrap(), None);
+        assert_eq!(db.keys(), vec!["a".to_string()]);
+    }
+
+    #[test]
+    fn test_torn_page_is_truncated_on_open() {
+        let dir = TempDir::new().unwrap();
+        let cfg = config(&dir, 1, false, 8);
+        {
+            let mut db = HelixDb::open(&cfg).unwrap();
+            db.put("kept", Value::Bool(true)).unwrap();
+        }
+        let shard = dir.path().join("db").join("shard-0000.hlxdb");
+        let mut file = OpenOptions::new().append(true).open(&shard).unwrap();
+        file.write_all(&[0u8, 9, 9]).unwrap();
+        drop(file);
+
+        let mut db = HelixDb::open(&cfg).unwrap();
+        assert_eq!(db.get("kept").unwrap(), Some(Value::Bool(true)));
+    }
+
+    #[test]
+    fn test_page_longer_than_the_file_is_truncated() {
+        let dir = TempDir::new().unwrap();
+        let cfg = config(&dir, 1, false, 8);
+        {
+            let mut db = HelixDb::open(&cfg).unwrap();
+            db.put("kept", Value::Bool(true)).unwrap();
+        }
+        let shard = dir.path().join("db").join("shard-0000.hlxdb");
+        let intact = fs::metadata(&shard).unwrap().len();
+        let mut header = vec![0u8];
+        header.extend_from_slice(&u32::MAX.to_le_bytes());
+        header.extend_from_slice(&u32::MAX.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        let mut file = OpenOptions::new().append(true).open(&shard).unwrap();
+        file.write_all(&header).unwrap();
+        drop(file);
+
+        let mut db = HelixDb::open(&cfg).unwrap();
+        assert_eq!(db.get("kept").unwrap(), Some(Value::Bool(true)));
+        assert_eq!(fs::metadata(&shard).unwrap().len(), intact);
+    }
+
+    #[test]
+    fn test_corrupt_page_is_reported_and_left_in_place() {
+        let dir = TempDir::new().unwrap();
+        let cfg = config(&dir, 1, false, 8);
+        {
+            let mut db = HelixDb::open(&cfg).unwrap();
+            db.put("first", Value::Number(1.0)).unwrap();
+            db.flush().unwrap();
+            db.put("second", Value::Number(2.0)).unwrap();
+        }
+        let shard = dir.path().join("db").join("shard-0000.hlxdb");
+        let mut bytes = fs::read(&shard).unwrap();
+        let len = bytes.len();
+        // Flip a payload byte of the first page
+        bytes[(SHARD_HEADER_LEN + PAGE_HEADER_LEN) as usize + 2] ^= 0xFF;
+        fs::write(&shard, &bytes).unwrap();
+
+        match HelixDb::open(&cfg) {
+            Err(HlxError::DatabaseCorrupted { offset, .. }) => assert_eq!(offset, SHARD_HEADER_LEN),
+            Err(other) => panic!("expected a corruption error, got {:?}", other),
+            Ok(_) => panic!("expected a corruption error"),
+        }
+        assert_eq!(fs::read(&shard).unwrap().len(), len);
+    }
+
+    #[test]
+    fn test_checksum_is_crc32_in_every_build() {
+        assert_eq!(checksum(b"123456789"), 0xCBF4_3926);
+        assert!(verify_checksum(b"page", checksum(b"page")).is_ok());
+        assert!(verify_checksum(b"page", checksum(b"pagE")).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_db_operators_put_then_get() {
+        let dir = TempDir::new().unwrap();
+        let ops = DbOperators::new().await.unwrap();
+        ops.register(&config(&dir, 2, true, 8)).unwrap();
+
+        ops.execute("put", r#"{"db": "test", "key": "step", "value": 7}"#).await.unwrap();
+        let result = ops.execute("get", r#"{"db": "test", "key": "step"}"#).await.unwrap();
+        assert_eq!(result, Value::Number(7.0));
+
+        let missing = ops.execute("get", r#"{"db": "test", "key": "nope"}"#).await.unwrap();
+        assert_eq!(missing, Value::Null);
+        let fallback = ops
+            .execute("get", r#"{"db": "test", "key": "nope", "default": "none"}"#)
+            .await
+            .unwrap();
+        assert_eq!(fallback, Value::String("none".to_string()));
+        assert!(ops.execute("get", r#"{"db": "undeclared", "key": "x"}"#).await.is_err());
+    }
+}
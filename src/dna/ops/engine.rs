@@ -1,10 +1,27 @@
 use crate::dna::hel::error::HlxError;
 use crate::ops::conditional::ConditionalOperators;
 use crate::ops::string_processing::StringOperators;
-use crate::ops::fundamental::OperatorRegistry;
+use crate::ops::fundamental::{ExecutionContext, OperatorRegistry};
+use crate::ops::hermetic::Hermetic;
 use crate::ops::validation::ValidationOperators;
 use crate::ops::math::MathOperators;
+use crate::ops::db::DbOperators;
+use crate::ops::query::SqlOperators;
+use crate::ops::file::FileOperators;
+use crate::ops::http::HttpOperators;
+use crate::ops::functions::{self, FunctionDef, FunctionRegistry};
+use crate::ops::plugin::PluginHost;
+use crate::ops::memo::EvalCache;
+use crate::ops::meta::OperatorDescriptor;
+use crate::ops::args::{self, Expr};
+use crate::ops::OperatorTrait;
+use crate::dna::atp::types::{DatabaseConfig, HelixConfig, PluginConfig};
 use crate::dna::atp::value::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
 
 pub struct OperatorEngine {
     conditional_operators: ConditionalOperators,
@@ -12,22 +29,113 @@ pub struct OperatorEngine {
     operator_registry: OperatorRegistry,
     validation_operators: ValidationOperators,
     math_operators: MathOperators,
+    db_operators: Arc<DbOperators>,
+    sql_operators: Arc<SqlOperators>,
+    file_operators: FileOperators,
+    http_operators: Arc<HttpOperators>,
+    functions: Arc<FunctionRegistry>,
+    plugins: Arc<PluginHost>,
+    eval_cache: Option<Arc<EvalCache>>,
+    /// Built-in operators whose descriptor asks for memoization
+    memoized: HashSet<String>,
 }
 impl OperatorEngine {
     pub async fn new() -> Result<Self, HlxError> {
-        Ok(Self {
+        Self::new_with_context(ExecutionContext::default()).await
+    }
+    /// Engine whose clock, UUIDs and environment come from `hermetic`
+    pub async fn new_hermetic(hermetic: Hermetic) -> Result<Self, HlxError> {
+        Self::new_with_context(ExecutionContext {
+            hermetic: Some(Arc::new(hermetic)),
+            ..ExecutionContext::default()
+        })
+        .await
+    }
+    pub async fn new_with_context(context: ExecutionContext) -> Result<Self, HlxError> {
+        let mut engine = Self {
             conditional_operators: ConditionalOperators::new().await?,
             string_operators: StringOperators::new().await?,
-            operator_registry: OperatorRegistry::new().await?,
+            operator_registry: OperatorRegistry::new_with_context(context).await?,
             validation_operators: ValidationOperators::new().await?,
             math_operators: MathOperators::new().await?,
-        })
+            db_operators: Arc::new(DbOperators::new().await?),
+            sql_operators: Arc::new(SqlOperators::new().await?),
+            file_operators: FileOperators::new().await?,
+            http_operators: Arc::new(HttpOperators::new().await?),
+            functions: Arc::new(FunctionRegistry::new()),
+            plugins: Arc::new(PluginHost::new()),
+            eval_cache: EvalCache::from_env().map(Arc::new),
+            memoized: HashSet::new(),
+        };
+        engine.memoized = engine
+            .descriptors()
+            .into_iter()
+            .filter(|d| d.memoize)
+            .map(|d| d.name)
+            .collect();
+        // Bare names dispatched to a builtin, including the `var` alias
+        let builtins = engine.descriptors().into_iter().map(|d| d.name);
+        engine.functions.reserve(builtins.chain(["var".to_string()]));
+        Ok(engine)
     }
     pub async fn execute_operator(
         &self,
         operator: &str,
         params: &str,
     ) -> Result<Value, HlxError> {
+        let resolved = self.resolve_nested_calls(params).await?;
+        let params = resolved.as_deref().unwrap_or(params);
+        let name = operator.trim_start_matches('@');
+        let memo = match &self.eval_cache {
+            Some(cache) if self.memoized.contains(name) => {
+                self.operator_inputs(name, params).map(|inputs| (cache, inputs))
+            }
+            _ => None,
+        };
+        if let Some((cache, inputs)) = &memo {
+            if let Some(value) = cache.get(name, params, inputs) {
+                return Ok(value);
+            }
+        }
+        let value = self.dispatch(operator, params).await?;
+        if let Some((cache, inputs)) = &memo {
+            let _ = cache.put(name, params, inputs, &value);
+        }
+        Ok(value)
+    }
+    /// Files a memoized call reads, or `None` when it can't be cached
+    fn operator_inputs(&self, name: &str, params: &str) -> Option<Vec<PathBuf>> {
+        if let Some(op) = name.strip_prefix("file.") {
+            return self.file_operators.inputs(op, params);
+        }
+        if let Some(op) = name.strip_prefix("sql.") {
+            return self.sql_operators.inputs(op, params);
+        }
+        None
+    }
+    async fn dispatch(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
+        if let Some(op) = operator.trim_start_matches('@').strip_prefix("db.") {
+            return self.db_operators.execute(op, params).await;
+        }
+        if let Some(op) = operator.trim_start_matches('@').strip_prefix("sql.") {
+            return self.sql_operators.execute(op, params).await;
+        }
+        if let Some(op) = operator.trim_start_matches('@').strip_prefix("file.") {
+            return self.file_operators.execute(op, params).await;
+        }
+        if let Some(op) = operator.trim_start_matches('@').strip_prefix("http.") {
+            return self.http_operators.execute(op, params).await;
+        }
+        if let Some((namespace, op)) = operator.trim_start_matches('@').split_once('.') {
+            if self.plugins.is_loaded(namespace) {
+                return self.plugins.execute(namespace, op, params);
+            }
+        }
+        if let Some(def) = self.functions.lookup(operator.trim_start_matches('@'), None) {
+            let params_map = crate::ops::utils::parse_params(params)?;
+            let args = FunctionRegistry::args_from_params(&def, &params_map)?;
+            return self.call_function(&def, args).await;
+        }
         if operator.starts_with('@') {
             return self.operator_registry.execute(operator, params).await;
         }
@@ -58,9 +166,190 @@ impl OperatorEngine {
             _ => Err(HlxError::unknown_operator(operator)),
         }
     }
+    /// Evaluate nested `@operator(...)` arguments and render the results back
+    /// as literals. Returns `None` when `params` has no nested calls or isn't
+    /// in the argument grammar (operators with their own syntax, such as SQL).
+    fn resolve_nested_calls<'a>(
+        &'a self,
+        params: &'a str,
+    ) -> Pin<Box<dyn Future<Output = Result<Option<String>, HlxError>> + Send + 'a>> {
+        Box::pin(async move {
+            if !params.contains('@') {
+                return Ok(None);
+            }
+            let parsed = match args::parse(params) {
+                Ok(parsed) if parsed.has_calls() => parsed,
+                _ => return Ok(None),
+            };
+            let mut evaluated = Vec::with_capacity(parsed.args.len());
+            for arg in parsed.args {
+                evaluated.push((arg.name, self.evaluate_arg(arg.value).await?));
+            }
+            Ok(Some(args::render(&evaluated)))
+        })
+    }
+    fn evaluate_arg<'a>(
+        &'a self,
+        expr: Expr,
+    ) -> Pin<Box<dyn Future<Output = Result<Value, HlxError>> + Send + 'a>> {
+        Box::pin(async move {
+            match expr {
+                Expr::Literal(value) => Ok(value),
+                Expr::Array(items) => {
+                    let mut out = Vec::with_capacity(items.len());
+                    for item in items {
+                        out.push(self.evaluate_arg(item).await?);
+                    }
+                    Ok(Value::Array(out))
+                }
+                Expr::Object(entries) => {
+                    let mut out = HashMap::with_capacity(entries.len());
+                    for (key, value) in entries {
+                        out.insert(key, self.evaluate_arg(value).await?);
+                    }
+                    Ok(Value::Object(out))
+                }
+                Expr::Call(operator, call_args) => {
+                    self.execute_operator(&format!("@{}", operator), &call_args.to_string()).await
+                }
+            }
+        })
+    }
+    /// Hermetic inputs, when the engine evaluates reproducibly
+    pub fn hermetic(&self) -> Option<&Arc<Hermetic>> {
+        self.operator_registry.context().hermetic.as_ref()
+    }
     pub fn operator_registry(&self) -> &OperatorRegistry {
         &self.operator_registry
     }
+    /// Expose a declared database to the `@db.*` and `@query` operators
+    pub fn register_database(&self, config: &DatabaseConfig) -> Result<(), HlxError> {
+        self.db_operators.register(config)?;
+        self.sql_operators.register(config)
+    }
+    pub fn db_operators(&self) -> &DbOperators {
+        &self.db_operators
+    }
+    pub fn sql_operators(&self) -> &SqlOperators {
+        &self.sql_operators
+    }
+    /// `@file.*` operators; set their base directory per included file and
+    /// read back the files they depended on
+    pub fn file_operators(&self) -> &FileOperators {
+        &self.file_operators
+    }
+    /// Persistent cache for memoized operators; `None` when disabled
+    pub fn eval_cache(&self) -> Option<&Arc<EvalCache>> {
+        self.eval_cache.as_ref()
+    }
+    /// Use `cache` for memoized operators, or disable memoization with `None`
+    pub fn set_eval_cache(&mut self, cache: Option<EvalCache>) {
+        self.eval_cache = cache.map(Arc::new);
+    }
+    /// `@http.*` operators; adjust their allowlist, cache or offline mode
+    pub fn http_operators(&self) -> &HttpOperators {
+        &self.http_operators
+    }
+    /// Descriptors for every operator this engine can route, sorted by name.
+    /// Where a name is handled by several sets, the one `execute_operator`
+    /// dispatches a bare name to wins.
+    pub fn descriptors(&self) -> Vec<OperatorDescriptor> {
+        let mut merged = BTreeMap::new();
+        let sets = [
+            self.operator_registry.descriptors(),
+            self.conditional_operators.descriptors(),
+            self.string_operators.descriptors(),
+            self.math_operators.descriptors(),
+            self.db_operators.descriptors(),
+            self.sql_operators.descriptors(),
+            self.file_operators.descriptors(),
+            self.http_operators.descriptors(),
+            self.plugins.descriptors(),
+            self.functions.descriptors(),
+        ];
+        for descriptor in sets.into_iter().flatten() {
+            merged.insert(descriptor.name.clone(), descriptor);
+        }
+        merged.into_values().collect()
+    }
+    /// Descriptor for one operator, with or without the leading `@`
+    pub fn describe(&self, operator: &str) -> Option<OperatorDescriptor> {
+        let name = operator.trim().trim_start_matches('@');
+        self.descriptors().into_iter().find(|d| d.name == name)
+    }
+    /// Operators whose name starts with `prefix`, for editor and REPL completion
+    pub fn complete(&self, prefix: &str) -> Vec<OperatorDescriptor> {
+        let prefix = prefix.trim_start_matches('@');
+        self.descriptors()
+            .into_iter()
+            .filter(|d| d.name.starts_with(prefix))
+            .collect()
+    }
+    /// User-defined functions shared by every parser using this engine
+    pub fn functions(&self) -> &Arc<FunctionRegistry> {
+        &self.functions
+    }
+    /// WebAssembly plugins loaded into this engine
+    pub fn plugins(&self) -> &Arc<PluginHost> {
+        &self.plugins
+    }
+    /// Load a `plugin` block and expose its operators as `@<name>.<op>`
+    pub fn load_plugin(&self, config: &PluginConfig, base_dir: Option<&Path>) -> Result<Vec<String>, HlxError> {
+        self.plugins.load(config, base_dir)
+    }
+    /// Apply the project's `@http` policy, register the databases and load
+    /// the plugins declared in a config
+    pub fn apply_config(&self, config: &HelixConfig, base_dir: Option<&Path>) -> Result<(), HlxError> {
+        for project in config.projects.values() {
+            self.http_operators.configure(project)?;
+        }
+        for database in config.databases.values() {
+            self.register_database(database)?;
+        }
+        for plugin in &config.plugins {
+            self.load_plugin(plugin, base_dir)?;
+        }
+        Ok(())
+    }
+    /// Resolve user functions, plugins, registered databases and the
+    /// `@http` policy from another engine's registries
+    pub fn share_registries(&mut self, other: &OperatorEngine) {
+        self.functions = Arc::clone(&other.functions);
+        self.plugins = Arc::clone(&other.plugins);
+        self.db_operators = Arc::clone(&other.db_operators);
+        self.sql_operators = Arc::clone(&other.sql_operators);
+        self.http_operators = Arc::clone(&other.http_operators);
+    }
+    /// Call a user function with evaluated arguments. The bound body may be
+    /// a literal or a single (possibly nested) operator or function call;
+    /// `OperatorParser` evaluates richer bodies itself.
+    pub fn call_function<'a>(
+        &'a self,
+        def: &'a FunctionDef,
+        args: Vec<Value>,
+    ) -> Pin<Box<dyn Future<Output = Result<Value, HlxError>> + Send + 'a>> {
+        Box::pin(async move {
+            let body = def.bind(&args)?;
+            self.functions.enter(&def.name, async {
+                match functions::parse_call(&body) {
+                    Some((name, inner)) => {
+                        match self.functions.lookup(name, def.scope.as_deref()) {
+                            Some(callee) => {
+                                let args = functions::split_args(inner)
+                                    .into_iter()
+                                    .map(functions::literal_value)
+                                    .collect();
+                                self.call_function(&callee, args).await
+                            }
+                            None => self.execute_operator(&format!("@{}", name), inner).await,
+                        }
+                    }
+                    None => Ok(functions::literal_value(&body)),
+                }
+            })
+            .await?
+        })
+    }
     pub fn get_variable(&self, name: &str) -> Result<Value, HlxError> {
         self.operator_registry.get_variable(name)
     }
@@ -1,45 +1,136 @@
 //! Evaluator for the Calculator DSL
 //!
-//! This module executes the AST against a mutable environment.
+//! This module executes the AST against a mutable environment. Results that
+//! are not finite numbers are reported as errors rather than stored: division
+//! or remainder by zero, overflow past `f64::MAX`, and domain errors such as
+//! `sqrt(-1)`.
 
 use std::collections::HashMap;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 
 use crate::ops::math::{Expr, Assign};
 
-/// Environment mapping variable names to integer values.
-pub type Env = HashMap<String, i64>;
+/// Environment mapping variable names to values.
+pub type Env = HashMap<String, f64>;
+
+/// Names resolved when no variable of the same name is defined
+fn constant(name: &str) -> Option<f64> {
+    match name {
+        "pi" => Some(std::f64::consts::PI),
+        "e" => Some(std::f64::consts::E),
+        _ => None,
+    }
+}
+
+fn lookup(env: &Env, name: &str) -> Result<f64> {
+    env.get(name)
+        .copied()
+        .or_else(|| constant(name))
+        .ok_or_else(|| anyhow!("variable `{}` not defined", name))
+}
+
+/// Reject non-finite results, naming the expression that produced them
+fn checked(value: f64, expr: &Expr) -> Result<f64> {
+    if value.is_nan() {
+        bail!("domain error in `{}`", expr);
+    }
+    if value.is_infinite() {
+        bail!("overflow in `{}`", expr);
+    }
+    Ok(value)
+}
+
+fn divisor(value: f64, expr: &Expr) -> Result<f64> {
+    if value == 0.0 {
+        bail!("division by zero in `{}`", expr);
+    }
+    Ok(value)
+}
+
+fn call(name: &str, args: &[f64], expr: &Expr) -> Result<f64> {
+    let arity = |n: usize| -> Result<()> {
+        if args.len() != n {
+            bail!("`{}` takes {} argument(s), got {} in `{}`", name, n, args.len(), expr);
+        }
+        Ok(())
+    };
+    let value = match name {
+        "min" | "max" => {
+            if args.is_empty() {
+                bail!("`{}` needs at least one argument in `{}`", name, expr);
+            }
+            let pick = if name == "min" { f64::min } else { f64::max };
+            args.iter().copied().fold(args[0], pick)
+        }
+        "abs" => { arity(1)?; args[0].abs() }
+        "floor" => { arity(1)?; args[0].floor() }
+        "ceil" => { arity(1)?; args[0].ceil() }
+        "round" => { arity(1)?; args[0].round() }
+        "sqrt" => { arity(1)?; args[0].sqrt() }
+        "exp" => { arity(1)?; args[0].exp() }
+        "ln" => { arity(1)?; args[0].ln() }
+        "log2" => { arity(1)?; args[0].log2() }
+        "log10" => { arity(1)?; args[0].log10() }
+        "log" => match args {
+            [x] => x.ln(),
+            [x, base] => x.log(*base),
+            _ => bail!("`log` takes 1 or 2 arguments, got {} in `{}`", args.len(), expr),
+        },
+        "pow" => { arity(2)?; args[0].powf(args[1]) }
+        _ => bail!("unknown function `{}`", name),
+    };
+    if matches!(name, "ln" | "log" | "log2" | "log10") && args[0] <= 0.0 {
+        bail!("domain error in `{}`: logarithm of a non-positive number", expr);
+    }
+    checked(value, expr)
+}
 
 /// Recursively evaluate an expression inside `env`.
-pub fn eval_expr(expr: &Expr, env: &mut Env) -> Result<i64> {
-    match expr {
-        Expr::Number(n) => Ok(*n),
+pub fn eval_expr(expr: &Expr, env: &Env) -> Result<f64> {
+    let value = match expr {
+        Expr::Number(n) => *n,
 
-        Expr::Var(name) => env
-            .get(name)
-            .cloned()
-            .ok_or_else(|| anyhow!("variable `{}` not defined", name)),
+        Expr::Var(name) => lookup(env, name)?,
 
-        Expr::Mul(l, r) => Ok(eval_expr(l, env)? * eval_expr(r, env)?),
+        Expr::Mul(l, r) => eval_expr(l, env)? * eval_expr(r, env)?,
 
-        Expr::Add(l, r) => Ok(eval_expr(l, env)? + eval_expr(r, env)?),
+        Expr::Div(l, r) => {
+            let left = eval_expr(l, env)?;
+            left / divisor(eval_expr(r, env)?, expr)?
+        }
+
+        Expr::Mod(l, r) => {
+            let left = eval_expr(l, env)?;
+            left % divisor(eval_expr(r, env)?, expr)?
+        }
 
-        Expr::Sub(l, r) => Ok(eval_expr(l, env)? - eval_expr(r, env)?),
+        Expr::Pow(l, r) => eval_expr(l, env)?.powf(eval_expr(r, env)?),
+
+        Expr::Add(l, r) => eval_expr(l, env)? + eval_expr(r, env)?,
+
+        Expr::Sub(l, r) => eval_expr(l, env)? - eval_expr(r, env)?,
+
+        Expr::Neg(e) => -eval_expr(e, env)?,
+
+        Expr::Call { name, args } => {
+            let args = args
+                .iter()
+                .map(|arg| eval_expr(arg, env))
+                .collect::<Result<Vec<_>>>()?;
+            return call(name, &args, expr);
+        }
 
         Expr::Ref { var, modifier } => {
-            let val = env
-                .get(var)
-                .cloned()
-                .ok_or_else(|| anyhow!("variable `{}` not defined", var))?;
-            // For this demo we interpret `#n` as **modulo**.
-            // Change the implementation to whatever you need (e.g. scaling).
+            let val = lookup(env, var)?;
+            // `#n` is interpreted as modulo
             match modifier {
-                Some(mod_val) => Ok(val % mod_val),
-                None => Ok(val),
+                Some(mod_val) => val % divisor(*mod_val, expr)?,
+                None => val,
             }
         }
-    }
+    };
+    checked(value, expr)
 }
 
 /// Execute a list of assignments. The environment is mutated in‑place and
@@ -48,7 +139,8 @@ pub fn run_program(assignments: &[Assign]) -> Result<Env> {
     let mut env = Env::new();
 
     for assign in assignments {
-        let value = eval_expr(&assign.value, &mut env)?;
+        let value = eval_expr(&assign.value, &env)
+            .map_err(|e| anyhow!("{} (assigning `{}`)", e, assign.name))?;
         env.insert(assign.name.clone(), value);
     }
 
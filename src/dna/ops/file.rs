@@ -0,0 +1,479 @@
+//! File Operators - @file.* reads of neighbouring files
+//!
+//! - `@file.read("notes.txt")`: file contents as a string
+//! - `@file.json("model/config.json", "$.hidden_size")`: parsed JSON, optionally
+//!   narrowed by a JSONPath-style query
+//! - `@file.yaml(...)` / `@file.toml(...)`: the same for YAML and TOML
+//! - `@file.exists("tokenizer.json")`: whether the file exists
+//!
+//! Relative paths resolve against the directory of the including file (the
+//! working directory when there is none) and must stay inside the project
+//! root: the nearest ancestor holding `project.hlx`, or the including
+//! directory itself. Every file read is recorded as a build dependency, and
+//! [`file_dependencies`] finds them statically so compile caches can be
+//! invalidated when one changes.
+//!
+//! Queries support `$`, `.key`, `['key']`, `[index]` (negative counts from the
+//! end) and the `*` / `[*]` wildcards. A query without wildcards returns the
+//! single matching value; one with wildcards returns an array of matches.
+
+use crate::dna::atp::value::Value;
+use crate::dna::hel::error::HlxError;
+use crate::ops::OperatorTrait;
+use crate::ops::meta::{OperatorCategory, OperatorDescriptor, ValueType};
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value as JsonValue;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, RwLock};
+
+/// Marker file identifying the project root
+const PROJECT_MANIFEST: &str = "project.hlx";
+
+/// One step of a path query
+#[derive(Debug, Clone, PartialEq)]
+pub enum Segment {
+    Key(String),
+    Index(i64),
+    Wildcard,
+}
+
+/// Parse a JSONPath-style query such as `$.layers[0].name`. The leading `$`
+/// is optional.
+pub fn parse_query(query: &str) -> Result<Vec<Segment>, HlxError> {
+    let invalid = |msg: &str| {
+        HlxError::invalid_input(
+            format!("Invalid path query `{}`: {}", query, msg),
+            "Use $.key, $['key'], $[0] or $.items[*]",
+        )
+    };
+    let trimmed = query.trim();
+    let dotted;
+    let mut rest = match trimmed.strip_prefix('$') {
+        Some(rest) => rest,
+        None if trimmed.starts_with('.') || trimmed.starts_with('[') => trimmed,
+        None => {
+            dotted = format!(".{}", trimmed);
+            &dotted
+        }
+    };
+    let mut segments = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            if after.starts_with('.') {
+                return Err(invalid("recursive descent (`..`) is not supported"));
+            }
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            let key = &after[..end];
+            if key.is_empty() {
+                return Err(invalid("empty key"));
+            }
+            segments.push(if key == "*" { Segment::Wildcard } else { Segment::Key(key.to_string()) });
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(|| invalid("unclosed `[`"))?;
+            let inner = after[..end].trim();
+            let quoted = (inner.starts_with('\'') && inner.ends_with('\''))
+                || (inner.starts_with('"') && inner.ends_with('"'));
+            segments.push(if inner == "*" {
+                Segment::Wildcard
+            } else if quoted && inner.len() >= 2 {
+                Segment::Key(inner[1..inner.len() - 1].to_string())
+            } else {
+                Segment::Index(inner.parse().map_err(|_| invalid("index must be an integer"))?)
+            });
+            rest = &after[end + 1..];
+        } else {
+            return Err(invalid("expected `.` or `[`"));
+        }
+    }
+    Ok(segments)
+}
+
+/// Select from `document` with a path query
+pub fn select(document: &JsonValue, query: &str) -> Result<JsonValue, HlxError> {
+    let segments = parse_query(query)?;
+    let mut current = vec![document];
+    for segment in &segments {
+        current = current
+            .into_iter()
+            .flat_map(|value| -> Vec<&JsonValue> {
+                match (segment, value) {
+                    (Segment::Key(key), JsonValue::Object(map)) => map.get(key).into_iter().collect(),
+                    (Segment::Index(index), JsonValue::Array(items)) => {
+                        let index = if *index < 0 { items.len() as i64 + index } else { *index };
+                        usize::try_from(index).ok().and_then(|i| items.get(i)).into_iter().collect()
+                    }
+                    (Segment::Wildcard, JsonValue::Object(map)) => map.values().collect(),
+                    (Segment::Wildcard, JsonValue::Array(items)) => items.iter().collect(),
+                    _ => Vec::new(),
+                }
+            })
+            .collect();
+    }
+    if segments.contains(&Segment::Wildcard) {
+        return Ok(JsonValue::Array(current.into_iter().cloned().collect()));
+    }
+    current.first().map(|v| (*v).clone()).ok_or_else(|| {
+        HlxError::invalid_input(
+            format!("Path query `{}` matched nothing", query),
+            "Check the key names and indices against the file",
+        )
+    })
+}
+
+/// Resolve `.` and `..` without touching the filesystem
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+/// Nearest ancestor of `dir` holding `project.hlx`, or `dir` itself
+pub fn project_root(dir: &Path) -> PathBuf {
+    dir.ancestors()
+        .find(|candidate| candidate.join(PROJECT_MANIFEST).exists())
+        .unwrap_or(dir)
+        .to_path_buf()
+}
+
+static FILE_CALL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"@file\.(?:read|json|yaml|toml|exists)\s*\(\s*(?:path\s*[=:]\s*)?["']([^"']+)["']"#).unwrap()
+});
+
+/// Files `source` reads through `@file.*` calls with literal paths, resolved
+/// against `base_dir` and sorted
+pub fn file_dependencies(source: &str, base_dir: &Path) -> Vec<PathBuf> {
+    FILE_CALL
+        .captures_iter(source)
+        .map(|cap| normalize(&base_dir.join(&cap[1])))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// `source` with the relative paths of its `@file.*` calls, written against
+/// `from`, rewritten to reach the same files from `to`
+pub fn rebase_file_paths(source: &str, from: &Path, to: &Path) -> String {
+    FILE_CALL
+        .replace_all(source, |cap: &regex::Captures| {
+            let (call, path) = (cap.get(0).unwrap(), cap.get(1).unwrap());
+            if Path::new(path.as_str()).is_absolute() {
+                return call.as_str().to_string();
+            }
+            let rebased = relative_to(&normalize(&from.join(path.as_str())), &normalize(to));
+            let (start, end) = (path.start() - call.start(), path.end() - call.start());
+            format!("{}{}{}", &call.as_str()[..start], rebased.display(), &call.as_str()[end..])
+        })
+        .into_owned()
+}
+
+/// `path` as reached from `base`; both must be normalized
+fn relative_to(path: &Path, base: &Path) -> PathBuf {
+    let path: Vec<_> = path.components().collect();
+    let base: Vec<_> = base.components().collect();
+    let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+    let mut relative = PathBuf::new();
+    for _ in common..base.len() {
+        relative.push("..");
+    }
+    for component in &path[common..] {
+        relative.push(component.as_os_str());
+    }
+    relative
+}
+
+/// `@file.*` operators plus the files they have read
+pub struct FileOperators {
+    base: RwLock<Option<PathBuf>>,
+    root: RwLock<Option<PathBuf>>,
+    dependencies: Mutex<BTreeSet<PathBuf>>,
+}
+
+impl FileOperators {
+    pub async fn new() -> Result<Self, HlxError> {
+        Ok(Self {
+            base: RwLock::new(None),
+            root: RwLock::new(None),
+            dependencies: Mutex::new(BTreeSet::new()),
+        })
+    }
+
+    /// Resolve relative paths against `dir` (the including file's directory).
+    /// Returns the previous base so nested includes can restore it.
+    pub fn set_base(&self, dir: Option<PathBuf>) -> Option<PathBuf> {
+        match self.base.write() {
+            Ok(mut base) => std::mem::replace(&mut *base, dir),
+            Err(poisoned) => std::mem::replace(&mut *poisoned.into_inner(), dir),
+        }
+    }
+
+    /// Confine reads to `root` instead of the discovered project root
+    pub fn set_root(&self, root: impl Into<PathBuf>) {
+        let root = Some(root.into());
+        match self.root.write() {
+            Ok(mut slot) => *slot = root,
+            Err(poisoned) => *poisoned.into_inner() = root,
+        }
+    }
+
+    /// Every file read so far, sorted
+    pub fn dependencies(&self) -> Vec<PathBuf> {
+        match self.dependencies.lock() {
+            Ok(deps) => deps.iter().cloned().collect(),
+            Err(poisoned) => poisoned.into_inner().iter().cloned().collect(),
+        }
+    }
+
+    fn record(&self, path: &Path) {
+        match self.dependencies.lock() {
+            Ok(mut deps) => deps.insert(path.to_path_buf()),
+            Err(poisoned) => poisoned.into_inner().insert(path.to_path_buf()),
+        };
+    }
+
+    /// Absolute path for `path`, rejecting anything outside the project root
+    pub fn resolve(&self, path: &str) -> Result<PathBuf, HlxError> {
+        let cwd = std::env::current_dir()
+            .map_err(|e| HlxError::io_error(e.to_string(), "Check the working directory"))?;
+        let base = self.base.read().ok().and_then(|b| b.clone());
+        let base = match base {
+            Some(dir) if dir.as_os_str().is_empty() => cwd.clone(),
+            Some(dir) => cwd.join(dir),
+            None => cwd.clone(),
+        };
+        let base = base.canonicalize().unwrap_or_else(|_| normalize(&base));
+        let root = match self.root.read().ok().and_then(|r| r.clone()) {
+            Some(root) => cwd.join(root),
+            None => project_root(&base),
+        };
+        let root = root.canonicalize().unwrap_or_else(|_| normalize(&root));
+        let lexical = normalize(&base.join(path));
+        // Canonicalize existing files so symlinks cannot escape the root
+        let target = lexical.canonicalize().unwrap_or(lexical);
+        if !target.starts_with(&root) {
+            return Err(HlxError::invalid_input(
+                format!("`{}` resolves outside the project root {}", path, root.display()),
+                "Keep @file paths inside the project",
+            ));
+        }
+        Ok(target)
+    }
+
+    fn read(&self, operator: &str, path: &str) -> Result<(PathBuf, String), HlxError> {
+        let resolved = self.resolve(path)?;
+        let contents = fs::read_to_string(&resolved).map_err(|e| {
+            HlxError::io_error(
+                format!("@file.{} failed to read {}: {}", operator, resolved.display(), e),
+                "Paths are relative to the including file",
+            )
+        })?;
+        self.record(&resolved);
+        Ok((resolved, contents))
+    }
+
+    pub async fn execute(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
+        self.execute_impl(operator, params).await
+    }
+
+    async fn execute_impl(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
+        let params_map = crate::ops::args::bind_params(&self.descriptors(), &format!("file.{}", operator), params)?;
+        let path = params_map
+            .get("path")
+            .and_then(|v| v.as_string())
+            .ok_or_else(|| HlxError::invalid_parameters(&format!("file.{}", operator), "Missing required parameter `path`"))?;
+        let query = params_map.get("query").and_then(|v| v.as_string());
+        match operator {
+            "read" => Ok(Value::String(self.read(operator, path)?.1)),
+            "json" | "yaml" | "toml" => {
+                let (resolved, contents) = self.read(operator, path)?;
+                let document = parse_document(operator, &contents).map_err(|e| {
+                    HlxError::invalid_input(
+                        format!("Invalid {} in {}: {}", operator.to_uppercase(), resolved.display(), e),
+                        "Check the file syntax",
+                    )
+                })?;
+                let selected = match query {
+                    Some(query) => select(&document, query)?,
+                    None => document,
+                };
+                Ok(crate::ops::utils::json_to_value(&selected))
+            }
+            "exists" => {
+                let resolved = self.resolve(path)?;
+                self.record(&resolved);
+                Ok(Value::Bool(resolved.is_file()))
+            }
+            _ => Err(HlxError::unknown_operator(format!("@file.{}", operator))),
+        }
+    }
+}
+
+fn parse_document(format: &str, contents: &str) -> Result<JsonValue, String> {
+    match format {
+        "json" => serde_json::from_str(contents).map_err(|e| e.to_string()),
+        "yaml" => serde_yaml::from_str(contents).map_err(|e| e.to_string()),
+        _ => {
+            let value: toml::Value = toml::from_str(contents).map_err(|e| e.to_string())?;
+            serde_json::to_value(value).map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[async_trait]
+impl OperatorTrait for FileOperators {
+    async fn execute(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
+        self.execute_impl(operator, params).await
+    }
+
+    fn inputs(&self, operator: &str, params: &str) -> Option<Vec<PathBuf>> {
+        if !matches!(operator, "json" | "yaml" | "toml") {
+            return None;
+        }
+        let params_map = crate::ops::args::bind_params(&self.descriptors(), &format!("file.{}", operator), params).ok()?;
+        let resolved = self.resolve(params_map.get("path")?.as_string()?).ok()?;
+        // A cache hit skips `read`, so record the dependency here
+        self.record(&resolved);
+        Some(vec![resolved])
+    }
+
+    fn descriptors(&self) -> Vec<OperatorDescriptor> {
+        let structured = |format: &str, example: &str| {
+            OperatorDescriptor::new(
+                format!("file.{}", format),
+                OperatorCategory::Data,
+                format!("Parse a {} file, optionally selecting a path", format.to_uppercase()),
+            )
+            .param("path", ValueType::String, "File path, relative to the including file")
+            .optional("query", ValueType::String, "JSONPath-style query such as `$.hidden_size`")
+            .memoize()
+            .example(example)
+        };
+        vec![
+            OperatorDescriptor::new("file.read", OperatorCategory::Data, "Read a file as a string")
+                .param("path", ValueType::String, "File path, relative to the including file")
+                .returns(ValueType::String)
+                .example(r#"@file.read("prompts/system.txt")"#),
+            structured("json", r#"@file.json("model/config.json", "$.hidden_size")"#),
+            structured("yaml", r#"@file.yaml("model/card.yaml", "$.license")"#),
+            structured("toml", r#"@file.toml("pyproject.toml", "$.project.version")"#),
+            OperatorDescriptor::new("file.exists", OperatorCategory::Data, "Whether a file exists")
+                .param("path", ValueType::String, "File path, relative to the including file")
+                .returns(ValueType::Bool)
+                .example(r#"@file.exists("tokenizer.json")"#),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::engine::OperatorEngine;
+    use tempfile::TempDir;
+
+    fn project() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join(PROJECT_MANIFEST), "project \"demo\" {}\n").unwrap();
+        fs::create_dir_all(dir.path().join("configs/model")).unwrap();
+        fs::write(
+            dir.path().join("configs/model/config.json"),
+            r#"{"hidden_size": 768, "layers": [{"name": "embed"}, {"name": "head"}]}"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("configs/model/card.yaml"), "license: mit\ntags: [nlp, small]\n").unwrap();
+        fs::write(dir.path().join("pyproject.toml"), "[project]\nversion = \"1.2.0\"\n").unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn test_structured_reads_relative_to_including_file() {
+        let dir = project();
+        let engine = OperatorEngine::new().await.unwrap();
+        engine.file_operators().set_base(Some(dir.path().join("configs")));
+
+        let run = |op: &'static str, params: &'static str| {
+            let engine = &engine;
+            async move { engine.execute_operator(op, params).await }
+        };
+        assert_eq!(run("@file.json", r#""model/config.json", "$.hidden_size""#).await.unwrap(), Value::Number(768.0));
+        assert_eq!(
+            run("@file.json", r#""model/config.json", "$.layers[*].name""#).await.unwrap(),
+            Value::Array(vec![Value::String("embed".into()), Value::String("head".into())])
+        );
+        assert_eq!(run("@file.yaml", r#""model/card.yaml", "$.tags[-1]""#).await.unwrap(), Value::String("small".into()));
+        assert_eq!(run("@file.toml", r#""../pyproject.toml", "$.project.version""#).await.unwrap(), Value::String("1.2.0".into()));
+        assert_eq!(run("@file.exists", r#""model/tokenizer.json""#).await.unwrap(), Value::Bool(false));
+        assert!(run("@file.json", r#""model/config.json", "$.missing""#).await.is_err());
+
+        let deps = engine.file_operators().dependencies();
+        assert_eq!(deps.len(), 4);
+        assert!(deps.iter().all(|p| p.starts_with(dir.path().canonicalize().unwrap())));
+    }
+
+    #[tokio::test]
+    async fn test_structured_reads_are_memoized_until_the_file_changes() {
+        let dir = project();
+        let mut engine = OperatorEngine::new().await.unwrap();
+        engine.set_eval_cache(Some(crate::ops::memo::EvalCache::new(dir.path().join(".cache"))));
+        engine.file_operators().set_base(Some(dir.path().join("configs")));
+        let cache = engine.eval_cache().unwrap().clone();
+
+        let size = r#""model/config.json", "$.hidden_size""#;
+        assert_eq!(engine.execute_operator("@file.json", size).await.unwrap(), Value::Number(768.0));
+        assert_eq!(cache.entries().len(), 1);
+        assert_eq!(engine.execute_operator("@file.json", size).await.unwrap(), Value::Number(768.0));
+        assert_eq!(cache.entries().len(), 1);
+        engine.execute_operator("@file.exists", r#""model/config.json""#).await.unwrap();
+        assert_eq!(cache.entries().len(), 1);
+
+        fs::write(dir.path().join("configs/model/config.json"), r#"{"hidden_size": 1024}"#).unwrap();
+        assert_eq!(engine.execute_operator("@file.json", size).await.unwrap(), Value::Number(1024.0));
+        assert_eq!(cache.prune().unwrap().0, 0);
+    }
+
+    #[tokio::test]
+    async fn test_paths_are_confined_to_project_root() {
+        let dir = project();
+        let engine = OperatorEngine::new().await.unwrap();
+        engine.file_operators().set_base(Some(dir.path().join("configs")));
+        let err = engine
+            .execute_operator("@file.read", r#""../../../etc/passwd""#)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("outside the project root"));
+        assert!(engine.file_operators().dependencies().is_empty());
+    }
+
+    #[test]
+    fn test_query_parsing_and_static_dependencies() {
+        assert_eq!(
+            parse_query("$.layers[0]['name']").unwrap(),
+            vec![Segment::Key("layers".into()), Segment::Index(0), Segment::Key("name".into())]
+        );
+        assert_eq!(parse_query("hidden_size").unwrap(), vec![Segment::Key("hidden_size".into())]);
+        assert!(parse_query("$..name").is_err());
+
+        let source = "model {\n    size = @file.json(\"model/config.json\", \"$.hidden_size\")\n    card = @file.yaml(path = \"./model/../card.yaml\")\n}\n";
+        assert_eq!(
+            file_dependencies(source, Path::new("/proj/configs")),
+            vec![PathBuf::from("/proj/configs/card.yaml"), PathBuf::from("/proj/configs/model/config.json")]
+        );
+        let rebased = rebase_file_paths(source, Path::new("/proj/configs"), Path::new("/proj/shared"));
+        assert_eq!(
+            file_dependencies(&rebased, Path::new("/proj/shared")),
+            file_dependencies(source, Path::new("/proj/configs"))
+        );
+        assert!(rebased.contains("@file.json(\"../configs/model/config.json\""));
+    }
+}
@@ -0,0 +1,614 @@
+//! User-defined functions - `fn name(args) = body` in Helix source
+//!
+//! Functions are declared one per line and called like operators:
+//! ```text
+//! fn slug(name) = @string({"operation": "lower", "input": name})
+//!
+//! [site]
+//! fn title(page) = "Docs - " + page
+//! url = @slug("Getting Started")
+//! ```
+//! - Top-level functions are visible everywhere in the file; functions
+//!   declared under a `[section]` are only visible inside that section.
+//! - `import "lib.hlx"` pulls in every top-level function of another file,
+//!   `import slug, title from "lib.hlx"` only the named ones. Imports inside
+//!   an imported file are followed relative to it; a cycle is an error.
+//! - Parameters are substituted into the body as JSON literals, so they can
+//!   appear in operator parameters, concatenations and arithmetic.
+//! - Calls nest at most [`DEFAULT_MAX_DEPTH`] deep, which bounds recursion.
+//!   Depth is counted per evaluation, so concurrent evaluations sharing a
+//!   registry don't eat into each other's limit.
+//! - A function can't take the name of a built-in operator; declaring one is
+//!   an error rather than silently shadowing the builtin.
+
+use crate::dna::atp::value::Value;
+use crate::dna::hel::error::HlxError;
+use crate::ops::meta::{OperatorCategory, OperatorDescriptor, ValueType};
+use crate::ops::utils::{json_to_value, value_to_json};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+/// Maximum nesting of user function calls before evaluation is aborted
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+tokio::task_local! {
+    /// Call depth of the evaluation being polled; unset outside any call
+    static CALL_DEPTH: usize;
+}
+
+static FN_DECL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^fn\s+([a-zA-Z_][a-zA-Z0-9_]*)\s*\(([^)]*)\)\s*=\s*(.+)$").unwrap()
+});
+static IMPORT_DECL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"^import\s+(?:([a-zA-Z_][a-zA-Z0-9_]*(?:\s*,\s*[a-zA-Z_][a-zA-Z0-9_]*)*)\s+from\s+)?["']([^"']+)["']$"#).unwrap()
+});
+static CALL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^@?([a-zA-Z_][a-zA-Z0-9_]*(?:\.[a-zA-Z_][a-zA-Z0-9_]*)*)\((.*)\)$").unwrap()
+});
+static SECTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[([a-zA-Z_][a-zA-Z0-9_]*)\]$").unwrap());
+
+/// A parsed `fn name(params) = body` declaration
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDef {
+    pub name: String,
+    pub params: Vec<String>,
+    pub body: String,
+    /// Section the function was declared in; `None` for top-level functions
+    pub scope: Option<String>,
+}
+
+impl FunctionDef {
+    /// Parse a declaration line. Returns `None` if the line is not a `fn`.
+    pub fn parse(line: &str, scope: Option<&str>) -> Option<Result<Self, HlxError>> {
+        let cap = FN_DECL.captures(line.trim())?;
+        let name = cap[1].to_string();
+        let mut params = Vec::new();
+        for param in cap[2].split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            if !is_identifier(param) {
+                return Some(Err(HlxError::invalid_input(
+                    format!("Invalid parameter `{}` in function `{}`", param, name),
+                    "Parameters must be plain identifiers",
+                )));
+            }
+            if params.iter().any(|p| p == param) {
+                return Some(Err(HlxError::invalid_input(
+                    format!("Duplicate parameter `{}` in function `{}`", param, name),
+                    "Give each parameter a distinct name",
+                )));
+            }
+            params.push(param.to_string());
+        }
+        Some(Ok(Self {
+            name,
+            params,
+            body: cap[3].trim().trim_end_matches(';').trim().to_string(),
+            scope: scope.map(String::from),
+        }))
+    }
+
+    /// Substitute `args` for the parameters in the body
+    pub fn bind(&self, args: &[Value]) -> Result<String, HlxError> {
+        if args.len() != self.params.len() {
+            return Err(HlxError::invalid_parameters(
+                &self.name,
+                &format!("expected {} argument(s), got {}", self.params.len(), args.len()),
+            ));
+        }
+        let bindings: HashMap<&str, String> = self
+            .params
+            .iter()
+            .map(String::as_str)
+            .zip(args.iter().map(to_literal))
+            .collect();
+        Ok(substitute(&self.body, &bindings))
+    }
+}
+
+/// A parsed `import ["names" from] "file"` line
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportDecl {
+    pub path: String,
+    /// `None` imports every top-level function
+    pub names: Option<Vec<String>>,
+}
+
+impl ImportDecl {
+    pub fn parse(line: &str) -> Option<Self> {
+        let cap = IMPORT_DECL.captures(line.trim())?;
+        Some(Self {
+            path: cap[2].to_string(),
+            names: cap
+                .get(1)
+                .map(|m| m.as_str().split(',').map(|n| n.trim().to_string()).collect()),
+        })
+    }
+}
+
+/// Functions visible to an [`OperatorEngine`](crate::ops::engine::OperatorEngine),
+/// keyed by declaring section and name
+pub struct FunctionRegistry {
+    functions: RwLock<HashMap<(Option<String>, String), FunctionDef>>,
+    /// Built-in operator names that functions may not redefine
+    reserved: RwLock<HashSet<String>>,
+    max_depth: AtomicUsize,
+}
+
+impl Default for FunctionRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FunctionRegistry {
+    pub fn new() -> Self {
+        Self {
+            functions: RwLock::new(HashMap::new()),
+            reserved: RwLock::new(HashSet::new()),
+            max_depth: AtomicUsize::new(DEFAULT_MAX_DEPTH),
+        }
+    }
+
+    pub fn set_max_depth(&self, depth: usize) {
+        self.max_depth.store(depth, Ordering::SeqCst);
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth.load(Ordering::SeqCst)
+    }
+
+    /// Reserve built-in operator names so no function can shadow them
+    pub fn reserve<I: IntoIterator<Item = String>>(&self, names: I) {
+        if let Ok(mut reserved) = self.reserved.write() {
+            reserved.extend(names);
+        }
+    }
+
+    /// Declare (or redeclare) a function in its scope. Fails if the name
+    /// belongs to a built-in operator.
+    pub fn define(&self, def: FunctionDef) -> Result<(), HlxError> {
+        let reserved = self.reserved
+            .read()
+            .map_err(|_| HlxError::validation_error("RwLock poisoned", "Check concurrency"))?;
+        if reserved.contains(&def.name) {
+            return Err(HlxError::invalid_input(
+                format!("Function `{}` would shadow the built-in operator of the same name", def.name),
+                "Rename the function; built-in operators can't be redefined",
+            ));
+        }
+        drop(reserved);
+        let mut functions = self.functions
+            .write()
+            .map_err(|_| HlxError::validation_error("RwLock poisoned", "Check concurrency"))?;
+        functions.insert((def.scope.clone(), def.name.clone()), def);
+        Ok(())
+    }
+
+    /// Resolve `name` from inside `scope`: the section's own functions shadow
+    /// top-level ones.
+    pub fn lookup(&self, name: &str, scope: Option<&str>) -> Option<FunctionDef> {
+        let functions = self.functions.read().ok()?;
+        scope
+            .and_then(|s| functions.get(&(Some(s.to_string()), name.to_string())))
+            .or_else(|| functions.get(&(None, name.to_string())))
+            .cloned()
+    }
+
+    /// All declared functions, sorted by scope then name
+    pub fn list(&self) -> Vec<FunctionDef> {
+        let mut defs: Vec<FunctionDef> = self
+            .functions
+            .read()
+            .map(|f| f.values().cloned().collect())
+            .unwrap_or_default();
+        defs.sort_by(|a, b| (&a.scope, &a.name).cmp(&(&b.scope, &b.name)));
+        defs
+    }
+
+    /// Descriptors for the declared functions. Bodies are not analysed, so
+    /// functions are reported as impure.
+    pub fn descriptors(&self) -> Vec<OperatorDescriptor> {
+        self.list()
+            .into_iter()
+            .map(|def| {
+                let scope = def.scope.as_ref().map(|s| format!(" (in [{}])", s)).unwrap_or_default();
+                let summary = format!("fn {}({}) = {}{}", def.name, def.params.join(", "), def.body, scope);
+                def.params.iter().fold(
+                    OperatorDescriptor::new(def.name.clone(), OperatorCategory::Function, summary),
+                    |op, p| op.param(p, ValueType::Any, "Function argument"),
+                )
+                .impure()
+            })
+            .collect()
+    }
+
+    /// Import the top-level functions of another Helix file, optionally
+    /// restricted to `names`. Returns the names that were imported.
+    ///
+    /// `import` lines in that file are resolved first, relative to it, and
+    /// the functions they bring in count as its own; an import cycle is an
+    /// error.
+    pub fn import_file(&self, path: &Path, names: Option<&[String]>) -> Result<Vec<String>, HlxError> {
+        self.import_nested(path, names, &mut Vec::new())
+    }
+
+    /// `chain` holds the files currently being imported, outermost first
+    fn import_nested(
+        &self,
+        path: &Path,
+        names: Option<&[String]>,
+        chain: &mut Vec<PathBuf>,
+    ) -> Result<Vec<String>, HlxError> {
+        let source = fs::read_to_string(path).map_err(|e| HlxError::io_error(
+            format!("Failed to import {}: {}", path.display(), e),
+            "Check the import path; relative paths resolve against the importing file",
+        ))?;
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if chain.contains(&canonical) {
+            let cycle: Vec<String> = chain
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(HlxError::invalid_input(
+                format!("Import cycle: {}", cycle.join(" -> ")),
+                "Move the shared functions into a file that imports neither",
+            ));
+        }
+        chain.push(canonical);
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut exported = HashMap::new();
+        // Already defined by a nested import; re-exported under the same name
+        let mut reexported = HashSet::new();
+        for line in source.lines() {
+            let line = line.trim();
+            if SECTION.is_match(line) {
+                break;
+            }
+            if let Some(def) = FunctionDef::parse(line, None) {
+                let def = def?;
+                reexported.remove(&def.name);
+                exported.insert(def.name.clone(), def);
+            } else if let Some(import) = ImportDecl::parse(line) {
+                let nested = self.import_nested(&base.join(&import.path), import.names.as_deref(), chain)?;
+                for name in nested {
+                    exported.remove(&name);
+                    reexported.insert(name);
+                }
+            }
+        }
+        chain.pop();
+
+        let selected: Vec<String> = match names {
+            Some(names) => {
+                if let Some(missing) = names
+                    .iter()
+                    .find(|n| !exported.contains_key(*n) && !reexported.contains(*n))
+                {
+                    return Err(HlxError::invalid_input(
+                        format!("{} does not define a top-level function `{}`", path.display(), missing),
+                        "Only functions declared before the first section can be imported",
+                    ));
+                }
+                names.to_vec()
+            }
+            None => exported.keys().chain(reexported.iter()).cloned().collect(),
+        };
+        let mut imported = Vec::new();
+        for name in selected {
+            if let Some(def) = exported.remove(&name) {
+                self.define(def)?;
+            }
+            imported.push(name);
+        }
+        imported.sort();
+        Ok(imported)
+    }
+
+    /// Run `call` as a call of `name`, one level deeper than the evaluation
+    /// it is made from. Fails without running it once the recursion limit is
+    /// reached.
+    pub async fn enter<F: Future>(&self, name: &str, call: F) -> Result<F::Output, HlxError> {
+        let depth = CALL_DEPTH.try_with(|depth| *depth).unwrap_or(0) + 1;
+        let max = self.max_depth();
+        if depth > max {
+            return Err(HlxError::execution_error(
+                format!("Recursion limit of {} exceeded calling `{}`", max, name),
+                "Check the function for unbounded recursion",
+            ));
+        }
+        Ok(CALL_DEPTH.scope(depth, call).await)
+    }
+
+    /// Map operator-style JSON parameters onto a function's arguments:
+    /// `{"args": [..]}` positionally, otherwise by parameter name.
+    pub fn args_from_params(def: &FunctionDef, params: &HashMap<String, Value>) -> Result<Vec<Value>, HlxError> {
+        if let Some(Value::Array(args)) = params.get("args") {
+            return Ok(args.clone());
+        }
+        def.params
+            .iter()
+            .map(|p| params.get(p).cloned().ok_or_else(|| HlxError::invalid_parameters(
+                &def.name,
+                &format!("missing argument `{}`", p),
+            )))
+            .collect()
+    }
+}
+
+/// Split a call's argument list on top-level commas, respecting quotes and
+/// nested brackets.
+pub fn split_args(args: &str) -> Vec<&str> {
+    let mut out = Vec::new();
+    let mut depth = 0i32;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut start = 0;
+    for (i, c) in args.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                out.push(args[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = args[start..].trim();
+    if !last.is_empty() || !out.is_empty() {
+        out.push(last);
+    }
+    out
+}
+
+/// Split a body of the form `name(args)` / `@name(args)` into its parts
+pub fn parse_call(body: &str) -> Option<(&str, &str)> {
+    let cap = CALL.captures(body.trim())?;
+    Some((cap.get(1)?.as_str(), cap.get(2)?.as_str()))
+}
+
+/// Parse a JSON literal, falling back to the raw text as a string
+pub fn literal_value(raw: &str) -> Value {
+    let raw = raw.trim();
+    match serde_json::from_str::<serde_json::Value>(raw) {
+        Ok(json) => json_to_value(&json),
+        Err(_) => Value::String(raw.to_string()),
+    }
+}
+
+/// Source literal for an argument; whole numbers are written without `.0`
+fn to_literal(value: &Value) -> String {
+    match value {
+        Value::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => format!("{}", *n as i64),
+        other => value_to_json(other).to_string(),
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Replace bare parameter identifiers outside string literals. Identifiers
+/// that are operator names (`@x`), variables (`$x`), member accesses (`a.x`)
+/// or calls (`x(`) are left alone.
+fn substitute(body: &str, bindings: &HashMap<&str, String>) -> String {
+    let mut out = String::with_capacity(body.len());
+    let chars: Vec<char> = body.chars().collect();
+    let mut quote: Option<char> = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if let Some(q) = quote {
+            out.push(c);
+            if c == '\\' && i + 1 < chars.len() {
+                out.push(chars[i + 1]);
+                i += 2;
+                continue;
+            }
+            if c == q {
+                quote = None;
+            }
+            i += 1;
+            continue;
+        }
+        if c == '"' || c == '\'' {
+            quote = Some(c);
+            out.push(c);
+            i += 1;
+            continue;
+        }
+        if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            let prev = if start > 0 { Some(chars[start - 1]) } else { None };
+            let next = chars[i..].iter().find(|c| !c.is_whitespace());
+            let bound = bindings.get(word.as_str()).filter(|_| {
+                !matches!(prev, Some('@') | Some('$') | Some('.'))
+                    && !matches!(next, Some('('))
+            });
+            match bound {
+                Some(value) => out.push_str(value),
+                None => out.push_str(&word),
+            }
+            continue;
+        }
+        out.push(c);
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_and_bind() {
+        let def = FunctionDef::parse(r#"fn greet(name, punct) = "Hello " + name + punct"#, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(def.params, vec!["name", "punct"]);
+        let body = def
+            .bind(&[Value::String("Ada".to_string()), Value::String("!".to_string())])
+            .unwrap();
+        assert_eq!(body, r#""Hello " + "Ada" + "!""#);
+        assert!(def.bind(&[]).is_err());
+        assert!(FunctionDef::parse("key = value", None).is_none());
+    }
+
+    #[test]
+    fn test_substitution_skips_strings_and_calls() {
+        let def = FunctionDef::parse(r#"fn f(name) = @name({"name": name, "s": "name"})"#, None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            def.bind(&[Value::Number(1.0)]).unwrap(),
+            r#"@name({"name": 1, "s": "name"})"#
+        );
+    }
+
+    #[test]
+    fn test_lexical_scope_lookup() {
+        let registry = FunctionRegistry::new();
+        registry.define(FunctionDef::parse("fn f() = 1", None).unwrap().unwrap()).unwrap();
+        registry.define(FunctionDef::parse("fn f() = 2", Some("app")).unwrap().unwrap()).unwrap();
+        assert_eq!(registry.lookup("f", Some("app")).unwrap().body, "2");
+        assert_eq!(registry.lookup("f", Some("other")).unwrap().body, "1");
+        assert_eq!(registry.lookup("f", None).unwrap().body, "1");
+    }
+
+    #[test]
+    fn test_import_only_top_level_functions() {
+        let dir = TempDir::new().unwrap();
+        let lib = dir.path().join("lib.hlx");
+        fs::write(&lib, "fn a() = 1\nfn b(x) = x\n[section]\nfn hidden() = 3\n").unwrap();
+
+        let registry = FunctionRegistry::new();
+        assert_eq!(registry.import_file(&lib, None).unwrap(), vec!["a", "b"]);
+        assert!(registry.lookup("hidden", None).is_none());
+
+        let only = FunctionRegistry::new();
+        only.import_file(&lib, Some(&["b".to_string()])).unwrap();
+        assert!(only.lookup("a", None).is_none());
+        assert!(only.import_file(&lib, Some(&["hidden".to_string()])).is_err());
+
+        let decl = ImportDecl::parse(r#"import a, b from "lib.hlx""#).unwrap();
+        assert_eq!(decl.names, Some(vec!["a".to_string(), "b".to_string()]));
+    }
+
+    #[test]
+    fn test_nested_imports_resolve_relative_to_the_imported_file() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("lib")).unwrap();
+        fs::write(dir.path().join("lib").join("util.hlx"), "fn helper(x) = x\nfn unused() = 0\n").unwrap();
+        fs::write(
+            dir.path().join("lib").join("text.hlx"),
+            "import helper from \"util.hlx\"\nfn slug(x) = helper(x)\n",
+        )
+        .unwrap();
+
+        let registry = FunctionRegistry::new();
+        let lib = dir.path().join("lib").join("text.hlx");
+        assert_eq!(registry.import_file(&lib, None).unwrap(), vec!["helper", "slug"]);
+        assert!(registry.lookup("unused", None).is_none());
+
+        // A selective import still defines what the selected function calls
+        let only = FunctionRegistry::new();
+        assert_eq!(only.import_file(&lib, Some(&["slug".to_string()])).unwrap(), vec!["slug"]);
+        assert!(only.lookup("helper", None).is_some());
+    }
+
+    #[test]
+    fn test_import_cycles_are_rejected() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.hlx"), "import \"b.hlx\"\nfn a() = 1\n").unwrap();
+        fs::write(dir.path().join("b.hlx"), "import \"a.hlx\"\nfn b() = 2\n").unwrap();
+
+        let registry = FunctionRegistry::new();
+        let err = registry.import_file(&dir.path().join("a.hlx"), None).unwrap_err();
+        assert!(err.to_string().contains("Import cycle"), "{}", err);
+        assert!(err.to_string().contains("a.hlx -> "), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_recursion_limit() {
+        let registry = FunctionRegistry::new();
+        registry.set_max_depth(2);
+        let two = registry.enter("f", registry.enter("f", async { 2 })).await.unwrap();
+        assert_eq!(two.unwrap(), 2);
+        let three = registry
+            .enter("f", registry.enter("f", registry.enter("f", async { 3 })))
+            .await
+            .unwrap();
+        assert!(three.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_evaluations_have_separate_depths() {
+        let registry = FunctionRegistry::new();
+        registry.set_max_depth(2);
+        // Both evaluations sit at the limit at the same time
+        let evaluation = || registry.enter("f", registry.enter("f", async {
+            tokio::task::yield_now().await;
+        }));
+        let (first, second) = tokio::join!(evaluation(), evaluation());
+        assert!(first.unwrap().is_ok());
+        assert!(second.unwrap().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_functions_cannot_shadow_builtins() {
+        let engine = crate::ops::engine::OperatorEngine::new().await.unwrap();
+        for line in ["fn lower(s) = s", "fn var(name) = name", "fn string(s) = s"] {
+            let def = FunctionDef::parse(line, None).unwrap().unwrap();
+            assert!(engine.functions().define(def).is_err(), "{}", line);
+        }
+        assert!(engine.functions().lookup("lower", None).is_none());
+        let value = engine.execute_operator("lower", r#"{"input": "ABC"}"#).await.unwrap();
+        assert_eq!(value.get("result"), Some(&Value::String("abc".to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_engine_calls_functions_like_operators() {
+        let engine = crate::ops::engine::OperatorEngine::new().await.unwrap();
+        for line in [
+            r#"fn lowercase(s) = @string({"operation": "lower", "input": s})"#,
+            "fn slug(name) = lowercase(name)",
+        ] {
+            engine.functions().define(FunctionDef::parse(line, None).unwrap().unwrap()).unwrap();
+        }
+        let value = engine.execute_operator("@slug", r#"{"args": ["Hello World"]}"#).await.unwrap();
+        assert_eq!(value, Value::String("hello world".to_string()));
+        let value = engine.execute_operator("slug", r#"{"name": "ABC"}"#).await.unwrap();
+        assert_eq!(value, Value::String("abc".to_string()));
+    }
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(split_args(r#""a, b", [1, 2], @f(x, y)"#), vec![r#""a, b""#, "[1, 2]", "@f(x, y)"]);
+        assert!(split_args("  ").is_empty());
+    }
+}
@@ -28,16 +28,21 @@
 //! - @calc: Complex calculations with meval
 //! - @min: Minimum value from array
 //! - @max: Maximum value from array
+//! - @convert: Unit conversion of quantities such as `16GiB` or `1.5h`
 
 use crate::dna::hel::error::HlxError;
 use crate::ops::utils::{json_to_value, value_to_json};
 use crate::ops::OperatorTrait;
+use crate::ops::args::Args;
+use crate::ops::hermetic::Hermetic;
+use crate::ops::meta::{OperatorCategory as Cat, OperatorDescriptor as Op, ValueType as T};
+use crate::dna::atp::quantity::Unit;
 use crate::dna::atp::value::Value;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::{Arc, RwLock};
 
-use chrono::{Local, Utc, TimeZone, Datelike, Timelike, DateTime};
+use chrono::{Local, Utc, TimeZone, DateTime, FixedOffset};
 use chrono_tz::Tz;
 use regex::Regex;
 use base64::{engine::general_purpose, Engine as _};
@@ -60,6 +65,8 @@ pub struct ExecutionContext {
     pub params: HashMap<String, String>,
     /// URL query parameters
     pub query: HashMap<String, String>,
+    /// Fixed clock, seed and environment for reproducible evaluation
+    pub hermetic: Option<Arc<Hermetic>>,
 }
 
 impl Default for ExecutionContext {
@@ -70,6 +77,7 @@ impl Default for ExecutionContext {
             cookies: HashMap::new(),
             params: HashMap::new(),
             query: HashMap::new(),
+            hermetic: None,
         }
     }
 }
@@ -89,15 +97,24 @@ pub struct FundamentalOperators {
     variables: Arc<RwLock<HashMap<String, Value>>>,
     /// Execution context – carries request, session, cookies, etc.
     context: Arc<ExecutionContext>,
+    /// Declared signatures, used to bind and type-check arguments
+    signatures: HashMap<String, Op>,
 }
 
 impl FundamentalOperators {
     /// Construct a new instance **with** an execution context.
     pub async fn new_with_context(context: ExecutionContext) -> Result<Self, HlxError> {
-        Ok(Self {
+        let mut operators = Self {
             variables: Arc::new(RwLock::new(HashMap::new())),
             context: Arc::new(context),
-        })
+            signatures: HashMap::new(),
+        };
+        operators.signatures = operators
+            .descriptors()
+            .into_iter()
+            .map(|d| (d.name.clone(), d))
+            .collect();
+        Ok(operators)
     }
 
     /// Legacy constructor for backward compatibility (creates empty context)
@@ -113,6 +130,30 @@ impl FundamentalOperators {
         Ok(vars.get(name).cloned().unwrap_or(Value::Null))
     }
 
+    /// Current time in the host timezone, or the hermetic clock in UTC
+    fn now(&self) -> DateTime<FixedOffset> {
+        match &self.context.hermetic {
+            Some(hermetic) => hermetic.now().fixed_offset(),
+            None => Local::now().fixed_offset(),
+        }
+    }
+
+    /// `ts` seconds since the epoch, in the same timezone as [`Self::now`]
+    fn at_timestamp(&self, ts: i64) -> Option<DateTime<FixedOffset>> {
+        match &self.context.hermetic {
+            Some(_) => Utc.timestamp_opt(ts, 0).single().map(|dt| dt.fixed_offset()),
+            None => Local.timestamp_opt(ts, 0).single().map(|dt| dt.fixed_offset()),
+        }
+    }
+
+    /// Environment variable from the hermetic snapshot or the process
+    fn env_var(&self, name: &str) -> Option<String> {
+        match &self.context.hermetic {
+            Some(hermetic) => hermetic.env_var(name).map(String::from),
+            None => std::env::var(name).ok(),
+        }
+    }
+
     /// Set a variable value in the global variables store
     pub fn set_variable(&self, name: String, value: Value) -> Result<(), HlxError> {
         let mut vars = self.variables.write()
@@ -127,19 +168,10 @@ impl FundamentalOperators {
     /// Parameters:
     ///   * `name` (String) – variable name (required)
     ///   * `value` (any Value) – if present the variable is set, otherwise it is read.
-    async fn var_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn var_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
-        let name = params_map
-            .get("name")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| {
-                HlxError::invalid_parameters(
-                    "var",
-                    "Missing required parameter `name`",
-                )
-            })?
-            .to_string();
+        let name = args.str("name")?.to_string();
 
         if let Some(val) = params_map.get("value") {
             // ---- SET ----
@@ -179,17 +211,14 @@ impl FundamentalOperators {
     }
 
     /// @env – Read an OS environment variable, optionally providing a default.
-    async fn env_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn env_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
-        let var = params_map
-            .get("var")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("env", "Missing required `var`"))?;
+        let var = args.str("var")?;
 
-        match std::env::var(var) {
-            Ok(v) => Ok(Value::String(v)),
-            Err(_) => {
+        match self.env_var(var) {
+            Some(v) => Ok(Value::String(v)),
+            None => {
                 if let Some(default) = params_map.get("default").and_then(|v| v.as_string()) {
                     Ok(Value::String(default.to_string()))
                 } else {
@@ -205,8 +234,8 @@ impl FundamentalOperators {
     /// @request – Pull data from the current request.
     /// Parameters:
     ///   * `field` (String, optional) – one of `method`, `url`, `headers`, `body`, `all`.
-    async fn request_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn request_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
         let field = params_map
             .get("field")
             .and_then(|v| v.as_string())
@@ -254,8 +283,8 @@ impl FundamentalOperators {
     ///   * `action` (String, optional) – `get` (default) or `set`.
     ///   * `key` (String, required for both actions).
     ///   * `value` (any Value, required for `set`).
-    async fn session_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn session_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
         let action = params_map
             .get("action")
             .and_then(|v| v.as_string())
@@ -263,12 +292,7 @@ impl FundamentalOperators {
 
         match action {
             "get" => {
-                let key = params_map
-                    .get("key")
-                    .and_then(|v| v.as_string())
-                    .ok_or_else(|| {
-                        HlxError::invalid_parameters("session", "`key` required for get")
-                    })?;
+                let key = args.str("key")?;
 
                 let sess = self
                     .context
@@ -284,17 +308,8 @@ impl FundamentalOperators {
                 }))
             }
             "set" => {
-                let key = params_map
-                    .get("key")
-                    .and_then(|v| v.as_string())
-                    .ok_or_else(|| {
-                        HlxError::invalid_parameters("session", "`key` required for set")
-                    })?;
-                let value = params_map
-                    .get("value")
-                    .ok_or_else(|| {
-                        HlxError::invalid_parameters("session", "`value` required for set")
-                    })?;
+                let key = args.str("key")?;
+                let value = args.value("value")?;
 
                 let mut sess = self
                     .context
@@ -320,8 +335,8 @@ impl FundamentalOperators {
     }
 
     /// @cookie – Read a cookie from the shared context.
-    async fn cookie_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn cookie_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
         let name = params_map
             .get("name")
             .and_then(|v| v.as_string())
@@ -343,8 +358,8 @@ impl FundamentalOperators {
     }
 
     /// @header – Retrieve a request header.
-    async fn header_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn header_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
         let name = params_map
             .get("name")
             .and_then(|v| v.as_string())
@@ -365,12 +380,8 @@ impl FundamentalOperators {
     }
 
     /// @param – Pull a route‑parameter from the context.
-    async fn param_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
-        let name = params_map
-            .get("name")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("param", "`name` required"))?;
+    async fn param_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let name = args.str("name")?;
 
         let val = self
             .context
@@ -388,12 +399,8 @@ impl FundamentalOperators {
     }
 
     /// @query – Pull a URL‑query parameter from the shared context.
-    async fn query_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
-        let name = params_map
-            .get("name")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("query", "`name` required"))?;
+    async fn query_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let name = args.str("name")?;
 
         let val = self
             .context
@@ -411,50 +418,44 @@ impl FundamentalOperators {
     }
 
     // Date and Time Operations
-    async fn date_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn date_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let format = params_map.get("format")
             .and_then(|v| v.as_string())
             .unwrap_or("%Y-%m-%d");
 
-        use chrono::{Local, Datelike, Timelike};
-
-        let now = Local::now();
+        let now = self.now();
         let formatted = now.format(format).to_string();
 
         Ok(Value::String(formatted))
     }
 
-    async fn time_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn time_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let format = params_map.get("format")
             .and_then(|v| v.as_string())
             .unwrap_or("%H:%M:%S");
 
-        use chrono::Local;
-
-        let now = Local::now();
+        let now = self.now();
         let formatted = now.format(format).to_string();
 
         Ok(Value::String(formatted))
     }
 
-    async fn timestamp_operator(&self, _params: &str) -> Result<Value, HlxError> {
-        use chrono::Utc;
-        let timestamp = Utc::now().timestamp();
+    async fn timestamp_operator(&self, _args: &Args) -> Result<Value, HlxError> {
+        let timestamp = self.now().timestamp();
         Ok(Value::Number(timestamp as f64))
     }
 
-    async fn now_operator(&self, _params: &str) -> Result<Value, HlxError> {
-        use chrono::{Utc, Local};
-        let now = Local::now().to_rfc3339();
+    async fn now_operator(&self, _args: &Args) -> Result<Value, HlxError> {
+        let now = self.now().to_rfc3339();
         Ok(Value::String(now))
     }
 
-    async fn format_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn format_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let input = params_map.get("input")
             .and_then(|v| v.as_string())
@@ -464,20 +465,15 @@ impl FundamentalOperators {
             .and_then(|v| v.as_string())
             .unwrap_or("%Y-%m-%d %H:%M:%S");
 
-        use chrono::{Local, Utc, TimeZone};
-
         let datetime = if input == "now" {
-            Local::now()
+            self.now()
         } else if let Ok(ts) = input.parse::<i64>() {
-            match Local.timestamp_opt(ts, 0) {
-                chrono::LocalResult::Single(dt) => dt,
-                _ => Local::now(),
-            }
+            self.at_timestamp(ts).unwrap_or_else(|| self.now())
         } else {
             // Try to parse as ISO string
             match chrono::DateTime::parse_from_rfc3339(input) {
-                Ok(dt) => dt.with_timezone(&Local),
-                Err(_) => Local::now(),
+                Ok(dt) => dt.with_timezone(&self.now().timezone()),
+                Err(_) => self.now(),
             }
         };
 
@@ -485,8 +481,8 @@ impl FundamentalOperators {
         Ok(Value::String(result))
     }
 
-    async fn timezone_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn timezone_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let tz = params_map.get("tz")
             .and_then(|v| v.as_string())
@@ -496,19 +492,15 @@ impl FundamentalOperators {
             .and_then(|v| v.as_string())
             .unwrap_or("now");
 
-        use chrono::{Local, Utc, TimeZone};
-
+        let now = self.now().with_timezone(&Utc);
         let datetime = if input == "now" {
-            Utc::now()
+            now
         } else if let Ok(ts) = input.parse::<i64>() {
-            match Utc.timestamp_opt(ts, 0) {
-                chrono::LocalResult::Single(dt) => dt,
-                _ => Utc::now(),
-            }
+            Utc.timestamp_opt(ts, 0).single().unwrap_or(now)
         } else {
             match chrono::DateTime::parse_from_rfc3339(input) {
                 Ok(dt) => dt.with_timezone(&Utc),
-                Err(_) => Utc::now(),
+                Err(_) => now,
             }
         };
 
@@ -519,16 +511,12 @@ impl FundamentalOperators {
     }
 
     // String and Data Processing
-    async fn string_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn string_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
-        let input = params_map.get("input")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("string", "Missing 'input' parameter"))?;
+        let input = args.str("input")?;
 
-        let operation = params_map.get("operation")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("string", "Missing 'operation' parameter"))?;
+        let operation = args.str("operation")?;
 
         match operation {
             "upper" => Ok(Value::String(input.to_uppercase())),
@@ -553,20 +541,14 @@ impl FundamentalOperators {
         }
     }
 
-    async fn regex_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn regex_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
-        let input = params_map.get("input")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("regex", "Missing 'input' parameter"))?;
+        let input = args.str("input")?;
 
-        let pattern = params_map.get("pattern")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("regex", "Missing 'pattern' parameter"))?;
+        let pattern = args.str("pattern")?;
 
-        let operation = params_map.get("operation")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("regex", "Missing 'operation' parameter"))?;
+        let operation = args.str("operation")?;
 
         let re = regex::Regex::new(pattern)
             .map_err(|e| HlxError::validation_error(format!("Invalid regex pattern: {}", e), "Check regex syntax"))?;
@@ -592,18 +574,12 @@ impl FundamentalOperators {
         }
     }
 
-    async fn json_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
-
-        let operation = params_map.get("operation")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("json", "Missing 'operation' parameter"))?;
+    async fn json_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let operation = args.str("operation")?;
 
         match operation {
             "parse" => {
-                let input = params_map.get("input")
-                    .and_then(|v| v.as_string())
-                    .ok_or_else(|| HlxError::invalid_parameters("json", "Missing 'input' parameter for parse"))?;
+                let input = args.str("input")?;
 
                 let parsed: serde_json::Value = serde_json::from_str(input)
                     .map_err(|e| HlxError::json_error(format!("JSON parse error: {}", e), "Provide valid JSON"))?;
@@ -611,8 +587,7 @@ impl FundamentalOperators {
                 Ok(json_to_value(&parsed))
             },
             "stringify" => {
-                let input = params_map.get("input")
-                    .ok_or_else(|| HlxError::invalid_parameters("json", "Missing 'input' parameter for stringify"))?;
+                let input = args.value("input")?;
 
                 let json_value = value_to_json(input);
                 let json_str = serde_json::to_string(&json_value)
@@ -624,16 +599,10 @@ impl FundamentalOperators {
         }
     }
 
-    async fn base64_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
-
-        let input = params_map.get("input")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("base64", "Missing 'input' parameter"))?;
+    async fn base64_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let input = args.str("input")?;
 
-        let operation = params_map.get("operation")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("base64", "Missing 'operation' parameter"))?;
+        let operation = args.str("operation")?;
 
         match operation {
             "encode" => {
@@ -652,16 +621,10 @@ impl FundamentalOperators {
         }
     }
 
-    async fn url_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
-
-        let input = params_map.get("input")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("url", "Missing 'input' parameter"))?;
+    async fn url_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let input = args.str("input")?;
 
-        let operation = params_map.get("operation")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("url", "Missing 'operation' parameter"))?;
+        let operation = args.str("operation")?;
 
         match operation {
             "encode" => Ok(Value::String(urlencoding::encode(input).to_string())),
@@ -674,16 +637,10 @@ impl FundamentalOperators {
         }
     }
 
-    async fn hash_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
-
-        let input = params_map.get("input")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("hash", "Missing 'input' parameter"))?;
+    async fn hash_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let input = args.str("input")?;
 
-        let algorithm = params_map.get("algorithm")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("hash", "Missing 'algorithm' parameter"))?;
+        let algorithm = args.opt_str("algorithm").unwrap_or("sha256");
 
         match algorithm {
             "sha256" => {
@@ -699,15 +656,21 @@ impl FundamentalOperators {
         }
     }
 
-    async fn uuid_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn uuid_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let version = params_map.get("version")
             .and_then(|v| v.as_string())
             .unwrap_or("v4");
 
         match version {
-            "v4" => Ok(Value::String(uuid::Uuid::new_v4().to_string())),
+            "v4" => {
+                let id = match &self.context.hermetic {
+                    Some(hermetic) => hermetic.uuid(),
+                    None => Uuid::new_v4(),
+                };
+                Ok(Value::String(id.to_string()))
+            }
             _ => Err(HlxError::invalid_parameters("uuid", "Only v4 is supported")),
         }
     }
@@ -715,16 +678,12 @@ impl FundamentalOperators {
     /// @if - Conditional evaluation.
     /// Parameters: condition (Bool) - The condition to evaluate; then (Value) - Value if true; else (Value, optional) - Value if false.
     /// Example: @if condition=true then="yes" else="no"
-    async fn if_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn if_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
-        let condition = params_map.get("condition")
-            .and_then(|v| v.as_boolean())
-            .ok_or_else(|| HlxError::invalid_parameters("if", "Missing 'condition' parameter"))?;
+        let condition = args.bool("condition")?;
 
-        let then_value = params_map.get("then")
-            .cloned()
-            .ok_or_else(|| HlxError::invalid_parameters("if", "Missing 'then' parameter"))?;
+        let then_value = args.value("then")?.clone();
 
         let else_value = params_map.get("else").cloned().unwrap_or(Value::Null);
 
@@ -734,15 +693,10 @@ impl FundamentalOperators {
     /// @switch - Switch statement evaluation.
     /// Parameters: value (Value) - The value to match against; cases (Array) - Array of objects with 'match' and 'result' fields.
     /// Example: @switch value="a" cases=[{"match":"a","result":"apple"}]
-    async fn switch_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
-
-        let value = params_map.get("value")
-            .ok_or_else(|| HlxError::invalid_parameters("switch", "Missing 'value' parameter"))?;
+    async fn switch_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let value = args.value("value")?;
 
-        let cases = params_map.get("cases")
-            .and_then(|v| v.as_array())
-            .ok_or_else(|| HlxError::invalid_parameters("switch", "Missing 'cases' parameter"))?;
+        let cases = args.array("cases")?;
 
         for case in cases {
             if let Value::Object(case_obj) = case {
@@ -758,8 +712,8 @@ impl FundamentalOperators {
         Ok(Value::Null)
     }
 
-    async fn case_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn case_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let value = params_map.get("value")
             .and_then(|v| v.as_string())
@@ -782,8 +736,8 @@ impl FundamentalOperators {
         }))
     }
 
-    async fn default_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn default_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let value = params_map.get("value")
             .and_then(|v| v.as_string())
@@ -808,8 +762,8 @@ impl FundamentalOperators {
         }))
     }
 
-    async fn and_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn and_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let a = params_map.get("a")
             .and_then(|v| v.as_boolean())
@@ -830,8 +784,8 @@ impl FundamentalOperators {
         }))
     }
 
-    async fn or_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn or_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let a = params_map.get("a")
             .and_then(|v| v.as_boolean())
@@ -852,8 +806,8 @@ impl FundamentalOperators {
         }))
     }
 
-    async fn not_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn not_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let value = params_map.get("value")
             .and_then(|v| v.as_boolean())
@@ -873,20 +827,12 @@ impl FundamentalOperators {
     /// @math - Basic mathematical operations.
     /// Parameters: operation (String) - "add", "sub", "mul", "div", "mod", "pow"; a (Number); b (Number).
     /// Example: @math operation="add" a=5 b=3
-    async fn math_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn math_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let operation = args.str("operation")?;
 
-        let operation = params_map.get("operation")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("math", "Missing 'operation' parameter"))?;
+        let a = args.number("a")?;
 
-        let a = params_map.get("a")
-            .and_then(|v| v.as_number())
-            .ok_or_else(|| HlxError::invalid_parameters("math", "Missing 'a' parameter"))?;
-
-        let b = params_map.get("b")
-            .and_then(|v| v.as_number())
-            .ok_or_else(|| HlxError::invalid_parameters("math", "Missing 'b' parameter"))?;
+        let b = args.number("b")?;
 
         match operation {
             "add" => Ok(Value::Number(a + b)),
@@ -914,12 +860,8 @@ impl FundamentalOperators {
     /// @calc - Complex mathematical expression evaluation.
     /// Parameters: expression (String) - Mathematical expression to evaluate.
     /// Example: @calc expression="2 * (3 + 4) / 2"
-    async fn calc_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
-
-        let expression = params_map.get("expression")
-            .and_then(|v| v.as_string())
-            .ok_or_else(|| HlxError::invalid_parameters("calc", "Missing 'expression' parameter"))?;
+    async fn calc_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let expression = args.str("expression")?;
 
         let result = meval::eval_str(expression)
             .map_err(|e| HlxError::validation_error(
@@ -930,8 +872,8 @@ impl FundamentalOperators {
         Ok(Value::Number(result))
     }
 
-    async fn min_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn min_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let empty_vec: Vec<Value> = vec![];
         let values = params_map.get("values")
@@ -952,8 +894,8 @@ impl FundamentalOperators {
         }))
     }
 
-    async fn max_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn max_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let empty_vec: Vec<Value> = vec![];
         let values = params_map.get("values")
@@ -974,8 +916,8 @@ impl FundamentalOperators {
         }))
     }
 
-    async fn avg_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn avg_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let empty_vec: Vec<Value> = vec![];
         let values = params_map.get("values")
@@ -1000,8 +942,8 @@ impl FundamentalOperators {
         }))
     }
 
-    async fn sum_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn sum_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let empty_vec: Vec<Value> = vec![];
         let values = params_map.get("values")
@@ -1022,8 +964,8 @@ impl FundamentalOperators {
         }))
     }
 
-    async fn round_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn round_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let value = params_map.get("value")
             .and_then(|v| v.as_number())
@@ -1045,9 +987,24 @@ impl FundamentalOperators {
         }))
     }
 
+    /// @convert - Express a quantity in another unit of the same dimension.
+    /// Parameters: value (Quantity), unit (String) - Target unit symbol.
+    /// Example: @convert(16GiB, "MB")
+    async fn convert_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let value = args.quantity("value")?;
+        let unit = args.str("unit")?;
+        let unit: Unit = unit
+            .parse()
+            .map_err(|e: String| HlxError::invalid_parameters("convert", &e))?;
+        value
+            .convert_to(unit)
+            .map(Value::Quantity)
+            .map_err(|e| HlxError::invalid_parameters("convert", &e))
+    }
+
     // Array and Collection Operations
-    async fn array_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn array_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let operation = params_map.get("operation")
             .and_then(|v| v.as_string())
@@ -1081,8 +1038,8 @@ impl FundamentalOperators {
         }
     }
 
-    async fn map_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn map_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let empty_vec: Vec<Value> = vec![];
         let array = params_map.get("array")
@@ -1130,8 +1087,8 @@ impl FundamentalOperators {
         }))
     }
 
-    async fn filter_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn filter_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let empty_vec: Vec<Value> = vec![];
         let array = params_map.get("array")
@@ -1179,8 +1136,8 @@ impl FundamentalOperators {
         }))
     }
 
-    async fn sort_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn sort_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let empty_vec: Vec<Value> = vec![];
         let array = params_map.get("array")
@@ -1222,8 +1179,8 @@ impl FundamentalOperators {
         }))
     }
 
-    async fn join_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn join_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let empty_vec: Vec<Value> = vec![];
         let array = params_map.get("array")
@@ -1250,8 +1207,8 @@ impl FundamentalOperators {
         }))
     }
 
-    async fn split_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn split_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let input = params_map.get("input")
             .and_then(|v| v.as_string())
@@ -1276,8 +1233,8 @@ impl FundamentalOperators {
         }))
     }
 
-    async fn length_operator(&self, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::dna::ops::utils::parse_params(params)?;
+    async fn length_operator(&self, args: &Args) -> Result<Value, HlxError> {
+        let params_map = args.values();
 
         let input = params_map.get("input")
             .cloned()
@@ -1304,67 +1261,286 @@ impl OperatorTrait for FundamentalOperators {
     async fn execute(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
         // Support both @ prefixed and non-prefixed operators
         let clean_operator = operator.strip_prefix('@').unwrap_or(operator);
+        let signature = self.signatures.get(clean_operator).ok_or_else(|| {
+            HlxError::unknown_error(format!("Unknown fundamental operator: @{}", clean_operator), "Check the operator name")
+        })?;
+        let args = &Args::parse(signature, params)?;
 
         match clean_operator {
             // Variable and Environment Access
-            "var" => self.var_operator(params).await,
-            "env" => self.env_operator(params).await,
+            "var" => self.var_operator(args).await,
+            "env" => self.env_operator(args).await,
 
             // HTTP and Request Data
-            "request" => self.request_operator(params).await,
-            "session" => self.session_operator(params).await,
-            "cookie" => self.cookie_operator(params).await,
-            "header" => self.header_operator(params).await,
-            "param" => self.param_operator(params).await,
-            "query" => self.query_operator(params).await,
+            "request" => self.request_operator(args).await,
+            "session" => self.session_operator(args).await,
+            "cookie" => self.cookie_operator(args).await,
+            "header" => self.header_operator(args).await,
+            "param" => self.param_operator(args).await,
+            "query" => self.query_operator(args).await,
 
             // Date and Time
-            "date" => self.date_operator(params).await,
-            "time" => self.time_operator(params).await,
-            "timestamp" => self.timestamp_operator(params).await,
-            "now" => self.now_operator(params).await,
-            "format" => self.format_operator(params).await,
-            "timezone" => self.timezone_operator(params).await,
+            "date" => self.date_operator(args).await,
+            "time" => self.time_operator(args).await,
+            "timestamp" => self.timestamp_operator(args).await,
+            "now" => self.now_operator(args).await,
+            "format" => self.format_operator(args).await,
+            "timezone" => self.timezone_operator(args).await,
 
             // String and Data Processing
-            "string" => self.string_operator(params).await,
-            "regex" => self.regex_operator(params).await,
-            "json" => self.json_operator(params).await,
-            "base64" => self.base64_operator(params).await,
-            "url" => self.url_operator(params).await,
-            "hash" => self.hash_operator(params).await,
-            "uuid" => self.uuid_operator(params).await,
+            "string" => self.string_operator(args).await,
+            "regex" => self.regex_operator(args).await,
+            "json" => self.json_operator(args).await,
+            "base64" => self.base64_operator(args).await,
+            "url" => self.url_operator(args).await,
+            "hash" => self.hash_operator(args).await,
+            "uuid" => self.uuid_operator(args).await,
 
             // Conditional and Logic
-            "if" => self.if_operator(params).await,
-            "switch" => self.switch_operator(params).await,
-            "case" => self.case_operator(params).await,
-            "default" => self.default_operator(params).await,
-            "and" => self.and_operator(params).await,
-            "or" => self.or_operator(params).await,
-            "not" => self.not_operator(params).await,
+            "if" => self.if_operator(args).await,
+            "switch" => self.switch_operator(args).await,
+            "case" => self.case_operator(args).await,
+            "default" => self.default_operator(args).await,
+            "and" => self.and_operator(args).await,
+            "or" => self.or_operator(args).await,
+            "not" => self.not_operator(args).await,
 
             // Math and Calculations
-            "math" => self.math_operator(params).await,
-            "calc" => self.calc_operator(params).await,
-            "min" => self.min_operator(params).await,
-            "max" => self.max_operator(params).await,
-            "avg" => self.avg_operator(params).await,
-            "sum" => self.sum_operator(params).await,
-            "round" => self.round_operator(params).await,
+            "math" => self.math_operator(args).await,
+            "calc" => self.calc_operator(args).await,
+            "min" => self.min_operator(args).await,
+            "max" => self.max_operator(args).await,
+            "avg" => self.avg_operator(args).await,
+            "sum" => self.sum_operator(args).await,
+            "round" => self.round_operator(args).await,
+            "convert" => self.convert_operator(args).await,
 
             // Array and Collections
-            "array" => self.array_operator(params).await,
-            "map" => self.map_operator(params).await,
-            "filter" => self.filter_operator(params).await,
-            "sort" => self.sort_operator(params).await,
-            "join" => self.join_operator(params).await,
-            "split" => self.split_operator(params).await,
-            "length" => self.length_operator(params).await,
+            "array" => self.array_operator(args).await,
+            "map" => self.map_operator(args).await,
+            "filter" => self.filter_operator(args).await,
+            "sort" => self.sort_operator(args).await,
+            "join" => self.join_operator(args).await,
+            "split" => self.split_operator(args).await,
+            "length" => self.length_operator(args).await,
 
             _ => Err(HlxError::unknown_error(format!("Unknown fundamental operator: @{}", clean_operator), "Check the operator name")),
         }
     }
+
+    fn descriptors(&self) -> Vec<Op> {
+        vec![
+            // Variable and Environment Access
+            Op::new("var", Cat::Variables, "Read or set a global variable persisted across executions")
+                .param("name", T::String, "Variable name")
+                .optional("value", T::Any, "Value to store; omit to read")
+                .returns(T::Object)
+                .impure()
+                .example(r#"@var({"name": "build", "value": 42})"#),
+            Op::new("env", Cat::Variables, "Read an environment variable")
+                .param("var", T::String, "Environment variable name")
+                .optional("default", T::Any, "Value when the variable is unset")
+                .returns(T::String)
+                .nondeterministic()
+                .example(r#"@env({"var": "HOME", "default": "/tmp"})"#),
+
+            // HTTP and Request Data
+            Op::new("request", Cat::Request, "Read data from the current HTTP request")
+                .optional("field", T::String, "Request field")
+                .choices(&["method", "url", "headers", "body", "all"])
+                .returns(T::Any)
+                .nondeterministic(),
+            Op::new("session", Cat::Request, "Get or set a session value")
+                .param("action", T::String, "Session action")
+                .choices(&["get", "set"])
+                .param("key", T::String, "Session key")
+                .optional("value", T::Any, "Value to set")
+                .returns(T::Object)
+                .impure(),
+            Op::new("cookie", Cat::Request, "Read a request cookie")
+                .param("name", T::String, "Cookie name")
+                .returns(T::Object)
+                .nondeterministic(),
+            Op::new("header", Cat::Request, "Read a request header")
+                .param("name", T::String, "Header name")
+                .returns(T::Object)
+                .nondeterministic(),
+            Op::new("param", Cat::Request, "Read a route parameter")
+                .param("name", T::String, "Parameter name")
+                .returns(T::Object)
+                .nondeterministic(),
+            Op::new("query", Cat::Request, "Read a URL query parameter")
+                .param("name", T::String, "Query parameter name")
+                .returns(T::Object)
+                .nondeterministic(),
+
+            // Date and Time
+            Op::new("date", Cat::DateTime, "Current local date formatted with strftime")
+                .optional("format", T::String, "strftime format")
+                .returns(T::String)
+                .nondeterministic()
+                .example(r#"@date({"format": "%Y-%m-%d"})"#),
+            Op::new("time", Cat::DateTime, "Current local time formatted with strftime")
+                .optional("format", T::String, "strftime format")
+                .returns(T::String)
+                .nondeterministic(),
+            Op::new("timestamp", Cat::DateTime, "Current Unix timestamp in seconds")
+                .returns(T::Number)
+                .nondeterministic(),
+            Op::new("now", Cat::DateTime, "Current UTC time as RFC 3339")
+                .returns(T::String)
+                .nondeterministic(),
+            Op::new("format", Cat::DateTime, "Format a timestamp or date string, defaulting to now")
+                .optional("input", T::Any, "Timestamp or date string")
+                .optional("format", T::String, "strftime format")
+                .returns(T::String)
+                .nondeterministic(),
+            Op::new("timezone", Cat::DateTime, "Convert a time, defaulting to now, into a timezone")
+                .param("tz", T::String, "IANA timezone name")
+                .optional("input", T::Any, "Timestamp or date string")
+                .returns(T::String)
+                .nondeterministic()
+                .example(r#"@timezone({"tz": "Europe/Paris"})"#),
+
+            // String and Data Processing
+            Op::new("string", Cat::String, "Basic string transformations")
+                .param("input", T::String, "Input text")
+                .param("operation", T::String, "Transformation")
+                .choices(&["upper", "lower", "capitalize", "reverse", "length", "trim", "substring"])
+                .optional("start", T::Number, "Substring start")
+                .optional("len", T::Number, "Substring length")
+                .returns(T::Any)
+                .example(r#"@string({"input": "Helix", "operation": "upper"})"#),
+            Op::new("regex", Cat::String, "Regular expression matching and replacement")
+                .param("input", T::String, "Text to search")
+                .param("pattern", T::String, "Regular expression")
+                .param("operation", T::String, "What to do with the pattern")
+                .choices(&["match", "find", "replace", "captures"])
+                .optional("replacement", T::String, "Replacement text for `replace`")
+                .returns(T::Any)
+                .example(r#"@regex({"input": "a1b2", "pattern": "[0-9]", "operation": "replace", "replacement": ""})"#),
+            Op::new("json", Cat::Encoding, "Parse or serialize JSON")
+                .param("operation", T::String, "Direction")
+                .choices(&["parse", "stringify", "encode", "decode"])
+                .param("input", T::Any, "Value or JSON text")
+                .returns(T::Any),
+            Op::new("base64", Cat::Encoding, "Base64 encode or decode")
+                .param("input", T::String, "Input text")
+                .param("operation", T::String, "Direction")
+                .choices(&["encode", "decode"])
+                .returns(T::String),
+            Op::new("url", Cat::Encoding, "URL percent-encode or decode")
+                .param("input", T::String, "Input text")
+                .param("operation", T::String, "Direction")
+                .choices(&["encode", "decode"])
+                .returns(T::String),
+            Op::new("hash", Cat::Encoding, "Hex digest of a string")
+                .param("input", T::String, "Input text")
+                .optional("algorithm", T::String, "Hash algorithm")
+                .choices(&["sha256", "md5"])
+                .returns(T::String),
+            Op::new("uuid", Cat::Encoding, "Generate a random UUID")
+                .optional("version", T::String, "UUID version")
+                .choices(&["v4"])
+                .returns(T::String)
+                .nondeterministic(),
+
+            // Conditional and Logic
+            Op::new("if", Cat::Conditional, "Choose between two values")
+                .param("condition", T::Bool, "Condition")
+                .param("then", T::Any, "Value when true")
+                .optional("else", T::Any, "Value when false"),
+            Op::new("switch", Cat::Conditional, "Return the result of the first matching case")
+                .param("value", T::Any, "Value to match")
+                .param("cases", T::Array, "Objects with `match` and `result`"),
+            Op::new("case", Cat::Conditional, "Test a value against a match")
+                .param("value", T::Any, "Value to test")
+                .param("match", T::Any, "Expected value")
+                .returns(T::Object),
+            Op::new("default", Cat::Conditional, "Fall back to a default for null or empty values")
+                .param("value", T::Any, "Value to check")
+                .param("default", T::Any, "Fallback")
+                .returns(T::Object),
+            Op::new("and", Cat::Logic, "Logical AND")
+                .param("a", T::Bool, "Left operand")
+                .param("b", T::Bool, "Right operand")
+                .returns(T::Object),
+            Op::new("or", Cat::Logic, "Logical OR")
+                .param("a", T::Bool, "Left operand")
+                .param("b", T::Bool, "Right operand")
+                .returns(T::Object),
+            Op::new("not", Cat::Logic, "Logical NOT")
+                .param("value", T::Bool, "Operand")
+                .returns(T::Object),
+
+            // Math and Calculations
+            Op::new("math", Cat::Math, "Binary arithmetic")
+                .param("operation", T::String, "Arithmetic operation")
+                .choices(&["add", "sub", "mul", "div", "mod", "pow"])
+                .param("a", T::Number, "Left operand")
+                .param("b", T::Number, "Right operand")
+                .returns(T::Number)
+                .example(r#"@math({"operation": "mul", "a": 6, "b": 7})"#),
+            Op::new("calc", Cat::Math, "Evaluate an arithmetic expression")
+                .param("expression", T::String, "Expression such as `2 * (3 + 4)`")
+                .returns(T::Number),
+            Op::new("min", Cat::Math, "Smallest number in an array")
+                .param("values", T::Array, "Numbers")
+                .returns(T::Object),
+            Op::new("max", Cat::Math, "Largest number in an array")
+                .param("values", T::Array, "Numbers")
+                .returns(T::Object),
+            Op::new("avg", Cat::Math, "Mean of an array of numbers")
+                .param("values", T::Array, "Numbers")
+                .returns(T::Object),
+            Op::new("sum", Cat::Math, "Sum of an array of numbers")
+                .param("values", T::Array, "Numbers")
+                .returns(T::Object),
+            Op::new("round", Cat::Math, "Round to a number of decimals")
+                .param("value", T::Number, "Number to round")
+                .optional("decimals", T::Number, "Decimal places")
+                .returns(T::Object),
+            Op::new("convert", Cat::Math, "Convert a quantity to another unit of the same dimension")
+                .param("value", T::Quantity, "Quantity such as 16GiB, 8k or 1.5h")
+                .param("unit", T::String, "Target unit, e.g. MB, MiB, k, ms or h")
+                .returns(T::Quantity)
+                .example(r#"@convert(16GiB, "MB")"#),
+
+            // Array and Collections
+            Op::new("array", Cat::Collections, "Create or modify an array")
+                .param("operation", T::String, "Array operation")
+                .choices(&["create", "push", "pop"])
+                .optional("items", T::Array, "Existing items")
+                .optional("item", T::Any, "Item to push")
+                .returns(T::Any),
+            Op::new("map", Cat::Collections, "Transform every array item")
+                .param("array", T::Array, "Items")
+                .param("transform", T::String, "Transformation")
+                .choices(&["upper", "lower", "double"])
+                .returns(T::Object),
+            Op::new("filter", Cat::Collections, "Keep array items matching a condition")
+                .param("array", T::Array, "Items")
+                .param("condition", T::String, "Condition")
+                .choices(&["not_empty", "positive", "negative"])
+                .returns(T::Object),
+            Op::new("split", Cat::Collections, "Split a string into an array")
+                .param("input", T::String, "Input text")
+                .optional("separator", T::String, "Separator")
+                .returns(T::Object),
+            Op::new("sort", Cat::Collections, "Sort an array")
+                .param("array", T::Array, "Items")
+                .optional("order", T::String, "Sort order")
+                .choices(&["asc", "desc"])
+                .returns(T::Object),
+            Op::new("join", Cat::Collections, "Join array items into a string")
+                .param("array", T::Array, "Items")
+                .optional("separator", T::String, "Separator")
+                .returns(T::Object),
+            Op::new("length", Cat::Collections, "Length of a string, array or object")
+                .param("input", T::Any, "Value to measure")
+                .returns(T::Object),
+        ]
+    }
 }
 
 /// Clean registry API for fundamental operators
@@ -1392,6 +1568,11 @@ impl OperatorRegistry {
         self.core.execute(op, params).await
     }
 
+    /// Descriptors for every fundamental operator
+    pub fn descriptors(&self) -> Vec<Op> {
+        self.core.descriptors()
+    }
+
     /// Get access to the underlying context for inspection/modification
     pub fn context(&self) -> &Arc<ExecutionContext> {
         &self.core.context
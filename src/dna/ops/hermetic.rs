@@ -0,0 +1,229 @@
+//! Hermetic Evaluation - reproducible clock, randomness and environment
+//!
+//! In hermetic mode operators never read the host: `@now`, `@date`, `@time`,
+//! `@timestamp`, `@format` and `@timezone` see a fixed clock, `@uuid` draws
+//! from a seeded generator, and `@env` / `$VAR` resolve only against an
+//! explicit snapshot. Attach a [`Hermetic`] to an `ExecutionContext` (or
+//! build the engine with `OperatorEngine::new_hermetic`) to enable it.
+//!
+//! [`nondeterministic_calls`] lists the call sites that would differ between
+//! builds without hermetic mode; `hlx lint --hermetic` reports them.
+
+use crate::dna::hel::error::HlxError;
+use crate::ops::meta::OperatorDescriptor;
+use chrono::{DateTime, TimeZone, Utc};
+use once_cell::sync::Lazy;
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Environment variable holding the clock, per reproducible-builds.org
+pub const SOURCE_DATE_EPOCH: &str = "SOURCE_DATE_EPOCH";
+
+/// Fixed inputs for reproducible evaluation
+#[derive(Debug)]
+pub struct Hermetic {
+    clock: DateTime<Utc>,
+    seed: u64,
+    rng: Mutex<StdRng>,
+    env: BTreeMap<String, String>,
+}
+
+impl Hermetic {
+    /// Clock at `epoch` seconds, seed 0 and an empty environment
+    pub fn new(epoch: i64) -> Result<Self, HlxError> {
+        let clock = Utc.timestamp_opt(epoch, 0).single().ok_or_else(|| {
+            HlxError::config_validation(
+                SOURCE_DATE_EPOCH,
+                epoch.to_string(),
+                "Use a Unix timestamp in seconds",
+            )
+        })?;
+        Ok(Self {
+            clock,
+            seed: 0,
+            rng: Mutex::new(StdRng::seed_from_u64(0)),
+            env: BTreeMap::new(),
+        })
+    }
+
+    /// Clock from `SOURCE_DATE_EPOCH`, or the Unix epoch when it is unset
+    pub fn from_source_date_epoch() -> Result<Self, HlxError> {
+        match std::env::var(SOURCE_DATE_EPOCH) {
+            Ok(raw) => {
+                let epoch = raw.trim().parse::<i64>().map_err(|_| {
+                    HlxError::config_validation(SOURCE_DATE_EPOCH, &raw, "Use a Unix timestamp in seconds")
+                })?;
+                Self::new(epoch)
+            }
+            Err(_) => Self::new(0),
+        }
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self.rng = Mutex::new(StdRng::seed_from_u64(seed));
+        self
+    }
+
+    pub fn with_env_var(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.insert(name.into(), value.into());
+        self
+    }
+
+    /// Add `KEY=VALUE` entries, e.g. from repeated `--env` flags
+    pub fn with_env_pairs<I, S>(mut self, pairs: I) -> Result<Self, HlxError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for pair in pairs {
+            let pair = pair.as_ref();
+            let (name, value) = pair.split_once('=').ok_or_else(|| {
+                HlxError::invalid_input(
+                    format!("Invalid environment entry `{}`", pair),
+                    "Use KEY=VALUE",
+                )
+            })?;
+            self.env.insert(name.to_string(), value.to_string());
+        }
+        Ok(self)
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock
+    }
+
+    pub fn epoch(&self) -> i64 {
+        self.clock.timestamp()
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Next version 4 UUID from the seeded generator
+    pub fn uuid(&self) -> Uuid {
+        let mut bytes = [0u8; 16];
+        match self.rng.lock() {
+            Ok(mut rng) => rng.fill_bytes(&mut bytes),
+            Err(poisoned) => poisoned.into_inner().fill_bytes(&mut bytes),
+        }
+        uuid::Builder::from_random_bytes(bytes).into_uuid()
+    }
+
+    pub fn env_var(&self, name: &str) -> Option<&str> {
+        self.env.get(name).map(String::as_str)
+    }
+
+    /// The environment snapshot
+    pub fn env(&self) -> &BTreeMap<String, String> {
+        &self.env
+    }
+}
+
+/// A call whose result depends on the clock, randomness, the environment or
+/// external state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallSite {
+    pub line: usize,
+    pub column: usize,
+    /// `@name` for operators, `$NAME` for environment variables
+    pub call: String,
+    /// What the call depends on, from the operator's summary
+    pub reason: String,
+}
+
+static OPERATOR_CALL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"@([a-zA-Z_][a-zA-Z0-9_]*(?:\.[a-zA-Z_][a-zA-Z0-9_]*)*)\s*\(").unwrap());
+static ENV_VAR: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$([a-zA-Z_][a-zA-Z0-9_]*)").unwrap());
+
+/// Every call in `source` to an operator `descriptors` marks nondeterministic,
+/// plus every `$VAR` environment reference, in source order. Comment lines
+/// (`#` or `//`) are skipped.
+pub fn nondeterministic_calls(source: &str, descriptors: &[OperatorDescriptor]) -> Vec<CallSite> {
+    let mut sites = Vec::new();
+    for (index, line) in source.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('#') || trimmed.starts_with("//") {
+            continue;
+        }
+        for cap in OPERATOR_CALL.captures_iter(line) {
+            let name = &cap[1];
+            let Some(descriptor) = descriptors.iter().find(|d| d.name == name) else {
+                continue;
+            };
+            if descriptor.deterministic {
+                continue;
+            }
+            sites.push(CallSite {
+                line: index + 1,
+                column: cap.get(0).unwrap().start() + 1,
+                call: format!("@{}", name),
+                reason: descriptor.summary.clone(),
+            });
+        }
+        for cap in ENV_VAR.captures_iter(line) {
+            sites.push(CallSite {
+                line: index + 1,
+                column: cap.get(0).unwrap().start() + 1,
+                call: cap[0].to_string(),
+                reason: "Read an environment variable".to_string(),
+            });
+        }
+    }
+    sites.sort_by_key(|s| (s.line, s.column));
+    sites
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ops::engine::OperatorEngine;
+    use crate::dna::atp::value::Value;
+
+    #[tokio::test]
+    async fn test_hermetic_operators_are_reproducible() {
+        let run = || async {
+            let hermetic = Hermetic::new(1_700_000_000)
+                .unwrap()
+                .with_seed(7)
+                .with_env_var("STAGE", "prod");
+            let engine = OperatorEngine::new_hermetic(hermetic).await.unwrap();
+            let mut out = Vec::new();
+            for (op, params) in [
+                ("@timestamp", ""),
+                ("@now", ""),
+                ("@date", r#"format = "%Y-%m-%d""#),
+                ("@uuid", ""),
+                ("@uuid", ""),
+                ("@env", r#"var = "STAGE""#),
+                ("@env", r#"var = "HOME", default = "none""#),
+            ] {
+                out.push(engine.execute_operator(op, params).await.unwrap());
+            }
+            out
+        };
+        let first = run().await;
+        assert_eq!(first, run().await);
+        assert_eq!(first[0], Value::Number(1_700_000_000.0));
+        assert_eq!(first[2], Value::String("2023-11-14".into()));
+        assert_ne!(first[3], first[4]);
+        assert_eq!(first[5], Value::String("prod".into()));
+        // The host environment is never consulted
+        assert_eq!(first[6], Value::String("none".into()));
+    }
+
+    #[tokio::test]
+    async fn test_lint_lists_nondeterministic_call_sites() {
+        let engine = OperatorEngine::new().await.unwrap();
+        let source = "# @now() in a comment\nbuild {\n    id = @uuid()\n    name = @string(\"a\", \"upper\")\n    home = $HOME\n}\n";
+        let sites = nondeterministic_calls(source, &engine.descriptors());
+        let calls: Vec<(usize, &str)> = sites.iter().map(|s| (s.line, s.call.as_str())).collect();
+        assert_eq!(calls, vec![(3, "@uuid"), (5, "$HOME")]);
+        assert_eq!(sites[0].column, 10);
+    }
+}
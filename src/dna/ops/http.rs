@@ -0,0 +1,524 @@
+//! HTTP Operators - @http.get / @http.json against allowlisted hosts
+//!
+//! - `@http.get("https://registry.example.com/latest")`: response body as a string
+//! - `@http.json("https://registry.example.com/models", "$.latest.version")`:
+//!   parsed JSON, optionally narrowed by the same path queries as `@file.json`
+//!
+//! Only hosts listed in the project's `http_allow` array may be fetched
+//! (`"*.example.com"` matches subdomains, `"127.0.0.1:8080"` pins a port);
+//! `http_timeout` sets the per-request timeout in seconds. Redirects are
+//! followed only while every hop stays on an allowed host. Responses are
+//! cached on disk keyed by URL and revalidated with `If-None-Match` when the
+//! server sent an ETag. In offline mode (`hlx --offline`, or `HLX_OFFLINE=1`)
+//! requests are served from the cache only, and a miss is an error.
+
+use crate::dna::atp::types::{ProjectConfig, Value as ConfigValue};
+use crate::dna::atp::value::Value;
+use crate::dna::hel::error::HlxError;
+use crate::ops::OperatorTrait;
+use crate::ops::meta::{OperatorCategory, OperatorDescriptor, ValueType};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// Environment variable that turns on offline mode for every engine
+pub const OFFLINE_ENV: &str = "HLX_OFFLINE";
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_REDIRECTS: usize = 10;
+
+/// Hosts, timeout, cache location and offline switch for `@http`
+#[derive(Debug, Clone)]
+pub struct HttpPolicy {
+    pub allow: Vec<String>,
+    pub timeout: Duration,
+    pub offline: bool,
+    /// Defaults to `~/.dna/hlx/http-cache`
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Default for HttpPolicy {
+    fn default() -> Self {
+        Self {
+            allow: Vec::new(),
+            timeout: DEFAULT_TIMEOUT,
+            offline: matches!(
+                std::env::var(OFFLINE_ENV).as_deref(),
+                Ok("1") | Ok("true") | Ok("yes")
+            ),
+            cache_dir: None,
+        }
+    }
+}
+
+/// Host and optional port of an `http(s)://` URL
+pub fn authority(url: &str) -> Option<(String, Option<u16>)> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let authority = authority.rsplit('@').next()?;
+    let (host, port) = match authority.rsplit_once(':') {
+        // `[::1]` alone has colons but no port
+        Some((host, port)) if !port.ends_with(']') => (host, Some(port.parse().ok()?)),
+        _ => (authority, None),
+    };
+    let host = host.trim_matches(['[', ']']);
+    if host.is_empty() {
+        return None;
+    }
+    Some((host.to_lowercase(), port))
+}
+
+/// Whether `url` is covered by an `http_allow` entry
+pub fn host_allowed(url: &str, allow: &[String]) -> bool {
+    let Some((host, port)) = authority(url) else {
+        return false;
+    };
+    allow.iter().any(|entry| {
+        let entry = entry.trim().to_lowercase();
+        let (pattern, entry_port) = match entry.rsplit_once(':') {
+            Some((pattern, p)) => match p.parse::<u16>() {
+                Ok(p) => (pattern.to_string(), Some(p)),
+                Err(_) => (entry.clone(), None),
+            },
+            None => (entry.clone(), None),
+        };
+        if entry_port.is_some() && entry_port != port {
+            return false;
+        }
+        match pattern.strip_prefix("*.") {
+            Some(domain) => host.ends_with(&format!(".{}", domain)),
+            None => host == pattern,
+        }
+    })
+}
+
+/// A cached response body and the validator it was served with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedResponse {
+    url: String,
+    etag: Option<String>,
+    fetched_at: u64,
+    body: String,
+}
+
+/// `@http.*` operators sharing one policy
+pub struct HttpOperators {
+    policy: RwLock<HttpPolicy>,
+}
+
+impl HttpOperators {
+    pub async fn new() -> Result<Self, HlxError> {
+        Ok(Self {
+            policy: RwLock::new(HttpPolicy::default()),
+        })
+    }
+
+    pub fn policy(&self) -> HttpPolicy {
+        match self.policy.read() {
+            Ok(policy) => policy.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut HttpPolicy)) {
+        match self.policy.write() {
+            Ok(mut policy) => apply(&mut policy),
+            Err(poisoned) => apply(&mut poisoned.into_inner()),
+        }
+    }
+
+    pub fn allow_hosts<I, S>(&self, hosts: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let hosts: Vec<String> = hosts.into_iter().map(Into::into).collect();
+        self.update(|policy| policy.allow.extend(hosts));
+    }
+
+    pub fn set_offline(&self, offline: bool) {
+        self.update(|policy| policy.offline = offline);
+    }
+
+    pub fn set_timeout(&self, timeout: Duration) {
+        self.update(|policy| policy.timeout = timeout);
+    }
+
+    pub fn set_cache_dir(&self, dir: impl Into<PathBuf>) {
+        let dir = dir.into();
+        self.update(|policy| policy.cache_dir = Some(dir));
+    }
+
+    /// Apply `http_allow` and `http_timeout` from a `project` block
+    pub fn configure(&self, project: &ProjectConfig) -> Result<(), HlxError> {
+        if let Some(allow) = project.metadata.get("http_allow") {
+            let hosts = match allow {
+                ConfigValue::Array(items) => items
+                    .iter()
+                    .map(|item| item.as_string().map(String::from))
+                    .collect::<Option<Vec<_>>>(),
+                ConfigValue::String(host) => Some(vec![host.clone()]),
+                _ => None,
+            }
+            .ok_or_else(|| HlxError::config_validation(
+                format!("project.{}.http_allow", project.name),
+                "",
+                "Use an array of host names, e.g. [\"registry.example.com\"]",
+            ))?;
+            self.allow_hosts(hosts);
+        }
+        if let Some(timeout) = project.metadata.get("http_timeout") {
+            let seconds = timeout.as_number().filter(|s| *s > 0.0).ok_or_else(|| {
+                HlxError::config_validation(
+                    format!("project.{}.http_timeout", project.name),
+                    "",
+                    "Use a positive number of seconds",
+                )
+            })?;
+            self.set_timeout(Duration::from_secs_f64(seconds));
+        }
+        Ok(())
+    }
+
+    fn cache_path(policy: &HttpPolicy, url: &str) -> PathBuf {
+        let dir = policy.cache_dir.clone().unwrap_or_else(|| {
+            crate::dna::atp::ops::get_or_create_helix_dir()
+                .map(|dir| dir.join("http-cache"))
+                .unwrap_or_else(|_| std::env::temp_dir().join("hlx-http-cache"))
+        });
+        let digest = Sha256::digest(url.as_bytes());
+        let key: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        dir.join(format!("{}.json", key))
+    }
+
+    fn load_cached(path: &PathBuf, url: &str) -> Option<CachedResponse> {
+        let raw = fs::read_to_string(path).ok()?;
+        serde_json::from_str::<CachedResponse>(&raw)
+            .ok()
+            .filter(|cached| cached.url == url)
+    }
+
+    fn store(path: &PathBuf, response: &CachedResponse) -> Result<(), HlxError> {
+        let io = |e: std::io::Error| HlxError::io_error(
+            format!("Failed to write HTTP cache {}: {}", path.display(), e),
+            "Check permissions on the cache directory",
+        );
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(io)?;
+        }
+        let json = serde_json::to_string(response).map_err(|e| HlxError::execution_error(
+            format!("Failed to encode HTTP cache entry: {}", e),
+            "This is a bug; please report it",
+        ))?;
+        fs::write(path, json).map_err(io)
+    }
+
+    /// Body of `url`, from the cache when offline or when the server answers
+    /// `304 Not Modified`
+    pub async fn fetch(&self, url: &str, headers: &HashMap<String, String>) -> Result<String, HlxError> {
+        let policy = self.policy();
+        if authority(url).is_none() {
+            return Err(HlxError::invalid_parameters("http", &format!("`{}` is not an http(s) URL", url)));
+        }
+        if !host_allowed(url, &policy.allow) {
+            return Err(HlxError::invalid_input(
+                format!("Host of {} is not in the project's http_allow list", url),
+                "Add the host to `http_allow` in the project block",
+            ));
+        }
+        let path = Self::cache_path(&policy, url);
+        let cached = Self::load_cached(&path, url);
+        if policy.offline {
+            return cached.map(|c| c.body).ok_or_else(|| HlxError::execution_error(
+                format!("Offline mode: no cached response for {}", url),
+                "Run once without --offline to populate the cache",
+            ));
+        }
+        self.fetch_online(&policy, url, headers, &path, cached).await
+    }
+
+    #[cfg(feature = "reqwest")]
+    async fn fetch_online(
+        &self,
+        policy: &HttpPolicy,
+        url: &str,
+        headers: &HashMap<String, String>,
+        path: &PathBuf,
+        cached: Option<CachedResponse>,
+    ) -> Result<String, HlxError> {
+        let request_error = |e: reqwest::Error| {
+            if e.is_redirect() {
+                let reason = std::error::Error::source(&e).map(|s| s.to_string()).unwrap_or_default();
+                return HlxError::invalid_input(
+                    format!("Request to {} was redirected: {}", url, reason),
+                    "Add the redirect target's host to `http_allow`, or fetch the final URL directly",
+                );
+            }
+            HlxError::execution_error(
+                format!("Request to {} failed: {}", url, e),
+                "Check the URL and network, or use --offline to serve from cache",
+            )
+        };
+        let allow = policy.allow.clone();
+        let redirects = reqwest::redirect::Policy::custom(move |attempt| {
+            if attempt.previous().len() >= MAX_REDIRECTS {
+                attempt.error(format!("more than {} redirects", MAX_REDIRECTS))
+            } else if host_allowed(attempt.url().as_str(), &allow) {
+                attempt.follow()
+            } else {
+                let target = format!("{} is not in the project's http_allow list", attempt.url());
+                attempt.error(target)
+            }
+        });
+        let client = reqwest::Client::builder()
+            .timeout(policy.timeout)
+            .redirect(redirects)
+            .build()
+            .map_err(request_error)?;
+        let mut request = client.get(url);
+        for (name, value) in headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        if let Some(etag) = cached.as_ref().and_then(|c| c.etag.as_deref()) {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        let response = request.send().await.map_err(request_error)?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                return Ok(cached.body);
+            }
+        }
+        if !status.is_success() {
+            return Err(HlxError::execution_error(
+                format!("GET {} returned {}", url, status),
+                "Check the URL and any required headers",
+            ));
+        }
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+        let body = response.text().await.map_err(request_error)?;
+        let fetched_at = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self::store(path, &CachedResponse {
+            url: url.to_string(),
+            etag,
+            fetched_at,
+            body: body.clone(),
+        })?;
+        Ok(body)
+    }
+
+    #[cfg(not(feature = "reqwest"))]
+    async fn fetch_online(
+        &self,
+        _policy: &HttpPolicy,
+        _url: &str,
+        _headers: &HashMap<String, String>,
+        _path: &PathBuf,
+        _cached: Option<CachedResponse>,
+    ) -> Result<String, HlxError> {
+        Err(HlxError::feature_error("reqwest", "@http requires the cli feature (reqwest)"))
+    }
+
+    pub async fn execute(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
+        self.execute_impl(operator, params).await
+    }
+
+    async fn execute_impl(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
+        let name = format!("http.{}", operator);
+        let params_map = crate::ops::args::bind_params(&self.descriptors(), &name, params)?;
+        let url = params_map
+            .get("url")
+            .and_then(|v| v.as_string())
+            .ok_or_else(|| HlxError::invalid_parameters(&name, "Missing required parameter `url`"))?;
+        let headers: HashMap<String, String> = match params_map.get("headers") {
+            Some(Value::Object(map)) => map.iter().map(|(k, v)| (k.clone(), v.to_string())).collect(),
+            _ => HashMap::new(),
+        };
+        match operator {
+            "get" => Ok(Value::String(self.fetch(url, &headers).await?)),
+            "json" => {
+                let body = self.fetch(url, &headers).await?;
+                let document: serde_json::Value = serde_json::from_str(&body).map_err(|e| {
+                    HlxError::invalid_input(
+                        format!("Response from {} is not JSON: {}", url, e),
+                        "Use @http.get for non-JSON responses",
+                    )
+                })?;
+                let selected = match params_map.get("query").and_then(|v| v.as_string()) {
+                    Some(query) => crate::ops::file::select(&document, query)?,
+                    None => document,
+                };
+                Ok(crate::ops::utils::json_to_value(&selected))
+            }
+            _ => Err(HlxError::unknown_operator(format!("@{}", name))),
+        }
+    }
+}
+
+#[async_trait]
+impl OperatorTrait for HttpOperators {
+    async fn execute(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
+        self.execute_impl(operator, params).await
+    }
+
+    fn descriptors(&self) -> Vec<OperatorDescriptor> {
+        vec![
+            OperatorDescriptor::new("http.get", OperatorCategory::Request, "Fetch a URL from an allowlisted host")
+                .param("url", ValueType::String, "http(s) URL; its host must be in `http_allow`")
+                .optional("headers", ValueType::Object, "Extra request headers")
+                .returns(ValueType::String)
+                .nondeterministic()
+                .example(r#"@http.get("https://registry.example.com/latest")"#),
+            OperatorDescriptor::new("http.json", OperatorCategory::Request, "Fetch and parse JSON from an allowlisted host")
+                .param("url", ValueType::String, "http(s) URL; its host must be in `http_allow`")
+                .optional("query", ValueType::String, "JSONPath-style query such as `$.latest.version`")
+                .optional("headers", ValueType::Object, "Extra request headers")
+                .nondeterministic()
+                .example(r#"@http.json("https://registry.example.com/models", "$.latest.version")"#),
+        ]
+    }
+}
+
+#[cfg(all(test, feature = "reqwest"))]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    /// Serves `body` with ETag `"v1"` and answers a matching `If-None-Match`
+    /// with 304. Returns the base URL and counters of (requests, 304s).
+    fn stub_server(body: &'static str) -> (String, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let not_modified = Arc::new(AtomicUsize::new(0));
+        let (h, n) = (Arc::clone(&hits), Arc::clone(&not_modified));
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => break,
+                        Ok(read) => request.extend_from_slice(&buf[..read]),
+                    }
+                }
+                h.fetch_add(1, Ordering::SeqCst);
+                let request = String::from_utf8_lossy(&request).to_lowercase();
+                let response = if request.contains("if-none-match: \"v1\"") {
+                    n.fetch_add(1, Ordering::SeqCst);
+                    "HTTP/1.1 304 Not Modified\r\nETag: \"v1\"\r\nConnection: close\r\n\r\n".to_string()
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    )
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        (format!("http://{}", addr), hits, not_modified)
+    }
+
+    /// Answers every request with a 302 to `location`
+    fn redirect_server(location: String) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { break };
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let response = format!(
+                    "HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    location
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    async fn operators(cache: &TempDir) -> HttpOperators {
+        let ops = HttpOperators::new().await.unwrap();
+        ops.set_offline(false);
+        ops.set_cache_dir(cache.path());
+        ops.allow_hosts(["127.0.0.1"]);
+        ops
+    }
+
+    #[tokio::test]
+    async fn test_fetch_caches_and_revalidates_with_etag() {
+        let (base, hits, not_modified) = stub_server(r#"{"latest": {"version": "2.1.0"}}"#);
+        let cache = TempDir::new().unwrap();
+        let ops = operators(&cache).await;
+        let url = format!("{}/models", base);
+        let params = format!(r#""{}", "$.latest.version""#, url);
+
+        assert_eq!(ops.execute("json", &params).await.unwrap(), Value::String("2.1.0".into()));
+        assert_eq!(ops.execute("json", &params).await.unwrap(), Value::String("2.1.0".into()));
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        assert_eq!(not_modified.load(Ordering::SeqCst), 1);
+
+        ops.set_offline(true);
+        assert_eq!(ops.execute("json", &params).await.unwrap(), Value::String("2.1.0".into()));
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+        let err = ops.execute("get", &format!(r#""{}/other""#, base)).await.unwrap_err();
+        assert!(err.to_string().contains("Offline mode"));
+    }
+
+    #[tokio::test]
+    async fn test_hosts_outside_allowlist_are_rejected() {
+        let (base, hits, _) = stub_server("ok");
+        let cache = TempDir::new().unwrap();
+        let ops = HttpOperators::new().await.unwrap();
+        ops.set_cache_dir(cache.path());
+        let err = ops.execute("get", &format!(r#""{}/""#, base)).await.unwrap_err();
+        assert!(err.to_string().contains("http_allow"));
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+        let allow = vec!["*.example.com".to_string(), "localhost:8080".to_string()];
+        assert!(host_allowed("https://registry.example.com/x", &allow));
+        assert!(!host_allowed("https://example.com.evil.io/x", &allow));
+        assert!(host_allowed("http://localhost:8080/", &allow));
+        assert!(!host_allowed("http://localhost:9090/", &allow));
+    }
+
+    #[tokio::test]
+    async fn test_redirects_must_stay_on_allowed_hosts() {
+        let (target, hits, _) = stub_server("secret");
+        let cache = TempDir::new().unwrap();
+        let ops = operators(&cache).await;
+
+        // `localhost` resolves to the same server but isn't on the allowlist
+        let outside = target.replace("127.0.0.1", "localhost");
+        let base = redirect_server(format!("{}/internal", outside));
+        let err = ops.execute("get", &format!(r#""{}/start""#, base)).await.unwrap_err();
+        assert!(err.to_string().contains("http_allow"), "{}", err);
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+        let base = redirect_server(format!("{}/internal", target));
+        let body = ops.execute("get", &format!(r#""{}/start""#, base)).await.unwrap();
+        assert_eq!(body, Value::String("secret".into()));
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+}
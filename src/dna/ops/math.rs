@@ -2,40 +2,84 @@
 //!
 //! This module provides a complete calculator that can parse and evaluate
 //! a custom DSL with variables, arithmetic operations, and reference-with-modifier syntax.
+//!
+//! Numbers are `f64`: integers, decimals and scientific notation (`3e-4`).
+//! Operators are `+ - * x / % ^ **` with the usual precedence (`^` binds
+//! right to left), and the functions are `min`, `max`, `abs`, `floor`,
+//! `ceil`, `round`, `sqrt`, `exp`, `ln`, `log`, `log2`, `log10` and `pow`;
+//! `pi` and `e` are available unless a variable shadows them. The grammar
+//! lives in `ulator.pest`.
 
 use crate::dna::hel::error::HlxError;
-use crate::ops::eval::{run_program as eval_run_program, Env};
+pub use crate::ops::eval::{eval_expr, run_program, Env};
 // use crate::ops::parse_program as parse_calculator_program;
 // use crate::dna::atp::ops::{Rule, CalcParser};
 use crate::dna::atp::value::Value;
 use anyhow;
 use std::collections::HashMap;
 use async_trait::async_trait;
+use pest::Parser;
+use pest::iterators::{Pair, Pairs};
 
 // AST definitions for the calculator DSL
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Expr {
-    /// Integer literal (e.g., 42)
-    Number(i64),
+    /// Numeric literal (e.g., 42, 0.5, 3e-4)
+    Number(f64),
     /// Variable reference (e.g., a, b, c)
     Var(String),
     /// Multiplication (e.g., a x b)
     Mul(Box<Expr>, Box<Expr>),
+    /// Division (e.g., a / b)
+    Div(Box<Expr>, Box<Expr>),
+    /// Remainder (e.g., a % b)
+    Mod(Box<Expr>, Box<Expr>),
+    /// Exponentiation (e.g., a ^ b or a ** b)
+    Pow(Box<Expr>, Box<Expr>),
     /// Addition (e.g., a + b)
     Add(Box<Expr>, Box<Expr>),
     /// Subtraction (e.g., a - b)
     Sub(Box<Expr>, Box<Expr>),
+    /// Negation (e.g., -a)
+    Neg(Box<Expr>),
+    /// Function call (e.g., max(a, b))
+    Call {
+        name: String,
+        args: Vec<Expr>,
+    },
     /// Reference with optional modifier (e.g., @c or @c #4)
     Ref {
         /// Variable name to reference
         var: String,
         /// Optional modifier value (number after #)
-        modifier: Option<i64>,
+        modifier: Option<f64>,
     },
 }
 
+impl std::fmt::Display for Expr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expr::Number(n) => write!(f, "{}", n),
+            Expr::Var(name) => write!(f, "{}", name),
+            Expr::Mul(l, r) => write!(f, "({} * {})", l, r),
+            Expr::Div(l, r) => write!(f, "({} / {})", l, r),
+            Expr::Mod(l, r) => write!(f, "({} % {})", l, r),
+            Expr::Pow(l, r) => write!(f, "({} ^ {})", l, r),
+            Expr::Add(l, r) => write!(f, "({} + {})", l, r),
+            Expr::Sub(l, r) => write!(f, "({} - {})", l, r),
+            Expr::Neg(e) => write!(f, "-{}", e),
+            Expr::Call { name, args } => {
+                let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+                write!(f, "{}({})", name, args.join(", "))
+            }
+            Expr::Ref { var, modifier: Some(m) } => write!(f, "@{} #{}", var, m),
+            Expr::Ref { var, modifier: None } => write!(f, "@{}", var),
+        }
+    }
+}
+
 /// A single assignment statement (e.g., `a = 2`)
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Assign {
     /// Variable name being assigned
     pub name: String,
@@ -64,7 +108,7 @@ impl MathOperators {
     async fn execute_impl(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
         match operator {
             "calc" => {
-                let parsed_params = crate::ops::utils::parse_params(params)?;
+                let parsed_params = crate::ops::args::bind_params(&crate::ops::OperatorTrait::descriptors(self), operator, params)?;
                 let source = parsed_params.get("source")
                     .ok_or_else(|| HlxError::invalid_input("Missing 'source' parameter", "Check the source parameter"))?
                     .to_string();
@@ -75,12 +119,12 @@ impl MathOperators {
                 // Convert the environment to a Value
                 let mut result_obj = HashMap::new();
                 for (key, value) in result.env {
-                    result_obj.insert(key, Value::Number(value as f64));
+                    result_obj.insert(key, Value::Number(value));
                 }
                 Ok(Value::Object(result_obj))
             }
             "eval" => {
-                let parsed_params = crate::ops::utils::parse_params(params)?;
+                let parsed_params = crate::ops::args::bind_params(&crate::ops::OperatorTrait::descriptors(self), operator, params)?;
                 let expression = parsed_params.get("expression")
                     .ok_or_else(|| HlxError::invalid_input("Missing 'expression' parameter", "Check the expression parameter"))?
                     .to_string();
@@ -90,7 +134,7 @@ impl MathOperators {
                     .map_err(|e| HlxError::execution_error(format!("Evaluation error: {}", e), "Check expression syntax"))?;
 
                 if let Some(value) = result.env.get("result") {
-                    Ok(Value::Number(*value as f64))
+                    Ok(Value::Number(*value))
                 } else {
                     Ok(Value::Number(0.0))
                 }
@@ -105,6 +149,19 @@ impl crate::ops::OperatorTrait for MathOperators {
     async fn execute(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
         self.execute_impl(operator, params).await
     }
+
+    fn descriptors(&self) -> Vec<crate::ops::meta::OperatorDescriptor> {
+        use crate::ops::meta::{OperatorCategory as Cat, OperatorDescriptor as Op, ValueType as T};
+        vec![
+            Op::new("calc", Cat::Math, "Run a calculator program and return its variables")
+                .param("source", T::String, "Calculator program")
+                .returns(T::Object)
+                .example(r#"@calc({"source": "reproducibility { a = 2 b = a * 3 }"})"#),
+            Op::new("eval", Cat::Math, "Evaluate one calculator expression")
+                .param("expression", T::String, "Expression")
+                .returns(T::Number),
+        ]
+    }
 }
 
 /// Calculator engine that parses and evaluates DSL programs
@@ -116,6 +173,12 @@ pub struct CalcResult {
     pub env: Env,
 }
 
+impl Default for Calculator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Calculator {
     /// Create a new calculator instance
     pub fn new() -> Self {
@@ -126,7 +189,7 @@ impl Calculator {
     ///
     /// # Example
     /// ```
-    /// use helix::operators::math::Calculator;
+    /// use helix::dna::ops::math::Calculator;
     ///
     /// let calc = Calculator::new();
     /// let src = r#"
@@ -135,21 +198,23 @@ impl Calculator {
     ///     b = 2
     ///     c = a x b
     ///     d = @c #4
+    ///     lr = 3e-4 * sqrt(c)
     /// }
     /// "#;
     ///
     /// let result = calc.evaluate(src).unwrap();
-    /// assert_eq!(result.env["a"], 2);
-    /// assert_eq!(result.env["b"], 2);
-    /// assert_eq!(result.env["c"], 4); // 2 * 2
-    /// assert_eq!(result.env["d"], 0); // 4 % 4
+    /// assert_eq!(result.env["a"], 2.0);
+    /// assert_eq!(result.env["b"], 2.0);
+    /// assert_eq!(result.env["c"], 4.0); // 2 * 2
+    /// assert_eq!(result.env["d"], 0.0); // 4 % 4
+    /// assert_eq!(result.env["lr"], 6e-4);
     /// ```
     pub fn evaluate(&self, source: &str) -> anyhow::Result<CalcResult> {
         // Parse the program
         let assignments = parse_program(source)?;
 
         // Evaluate the assignments
-        let env = eval_run_program(&assignments)?;
+        let env = run_program(&assignments)?;
 
         Ok(CalcResult { env })
     }
@@ -160,157 +225,110 @@ impl Calculator {
     }
 }
 
+#[derive(pest_derive::Parser)]
+#[grammar = "dna/ops/ulator.pest"]
+struct CalcParser;
+
 /// Parse a calculator DSL program into a list of assignments
 pub fn parse_program(source: &str) -> anyhow::Result<Vec<Assign>> {
-    // Simple manual parser for the calculator DSL
+    let program = CalcParser::parse(Rule::program, source)
+        .map_err(|e| anyhow::anyhow!("{}", e))?
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("empty program"))?;
     let mut assignments = Vec::new();
-
-    for line in source.lines() {
-        let line = line.trim();
-        if line.is_empty() || line.starts_with("//") {
-            continue;
-        }
-
-        if let Some((name, expr_str)) = line.split_once('=') {
-            let name = name.trim().to_string();
-            let expr_str = expr_str.trim();
-
-            let expr = parse_expression(expr_str)?;
-            assignments.push(Assign {
-                name,
-                value: expr,
-            });
+    for block in program.into_inner().filter(|p| p.as_rule() == Rule::reproducibility) {
+        for statement in block.into_inner() {
+            let mut inner = statement.into_inner();
+            let name = next_pair(&mut inner)?.as_str().to_string();
+            let value = parse_expr(next_pair(&mut inner)?)?;
+            assignments.push(Assign { name, value });
         }
     }
-
     Ok(assignments)
 }
 
-/// Parse a mathematical expression
-fn parse_expression(expr_str: &str) -> anyhow::Result<Expr> {
-    parse_add_sub(expr_str)
+fn next_pair<'a>(pairs: &mut Pairs<'a, Rule>) -> anyhow::Result<Pair<'a, Rule>> {
+    pairs.next().ok_or_else(|| anyhow::anyhow!("malformed calculator syntax tree"))
 }
 
-/// Parse addition and subtraction (lowest precedence)
-fn parse_add_sub(expr_str: &str) -> anyhow::Result<Expr> {
-    let mut result = parse_mul_div(expr_str)?;
-
-    let expr_chars: Vec<char> = expr_str.chars().collect();
-    let mut i = 0;
-
-    while i < expr_chars.len() {
-        match expr_chars[i] {
-            '+' => {
-                i += 1;
-                let right = parse_mul_div(&expr_str[i..])?;
-                result = Expr::Add(Box::new(result), Box::new(right));
-            }
-            '-' => {
-                i += 1;
-                let right = parse_mul_div(&expr_str[i..])?;
-                result = Expr::Sub(Box::new(result), Box::new(right));
-            }
-            _ => break,
-        }
-    }
-
-    Ok(result)
+fn parse_number(text: &str) -> anyhow::Result<f64> {
+    text.replace('_', "")
+        .parse::<f64>()
+        .map_err(|e| anyhow::anyhow!("invalid number `{}`: {}", text, e))
 }
 
-/// Parse multiplication and division (higher precedence)
-fn parse_mul_div(expr_str: &str) -> anyhow::Result<Expr> {
-    let mut result = parse_factor(expr_str)?;
-
-    let expr_chars: Vec<char> = expr_str.chars().collect();
-    let mut i = 0;
-
-    while i < expr_chars.len() {
-        match expr_chars[i] {
-            'x' | '*' => {
-                i += 1;
-                let right = parse_factor(&expr_str[i..])?;
-                result = Expr::Mul(Box::new(result), Box::new(right));
-            }
-            _ => break,
-        }
+/// Fold `first (op operand)*` left to right
+fn fold_binary(pair: Pair<Rule>) -> anyhow::Result<Expr> {
+    let mut inner = pair.into_inner();
+    let mut acc = parse_expr(next_pair(&mut inner)?)?;
+    while let Some(op) = inner.next() {
+        let right = Box::new(parse_expr(next_pair(&mut inner)?)?);
+        let left = Box::new(acc);
+        acc = match op.as_str() {
+            "+" => Expr::Add(left, right),
+            "-" => Expr::Sub(left, right),
+            "*" | "x" => Expr::Mul(left, right),
+            "/" => Expr::Div(left, right),
+            "%" => Expr::Mod(left, right),
+            other => return Err(anyhow::anyhow!("unknown operator `{}`", other)),
+        };
     }
-
-    Ok(result)
+    Ok(acc)
 }
 
-/// Parse factors (highest precedence)
-fn parse_factor(expr_str: &str) -> anyhow::Result<Expr> {
-    let expr_str = expr_str.trim();
-
-    // Handle parentheses
-    if expr_str.starts_with('(') && expr_str.ends_with(')') {
-        let inner = &expr_str[1..expr_str.len()-1];
-        return parse_add_sub(inner);
-    }
-
-    // Handle references (@variable #modifier)
-    if expr_str.starts_with('@') {
-        if let Some(hash_pos) = expr_str.find('#') {
-            let var = expr_str[1..hash_pos].to_string();
-            let modifier_str = &expr_str[hash_pos+1..];
-            let modifier = modifier_str.parse::<i64>()?;
-            return Ok(Expr::Ref {
-                var,
-                modifier: Some(modifier),
-            });
-        } else {
-            let var = expr_str[1..].to_string();
-            return Ok(Expr::Ref {
-                var,
-                modifier: None,
-            });
+fn parse_expr(pair: Pair<Rule>) -> anyhow::Result<Expr> {
+    match pair.as_rule() {
+        Rule::expr | Rule::term => fold_binary(pair),
+        Rule::power => {
+            let mut inner = pair.into_inner();
+            let base = parse_expr(next_pair(&mut inner)?)?;
+            match inner.next() {
+                Some(_pow_op) => {
+                    let exponent = parse_expr(next_pair(&mut inner)?)?;
+                    Ok(Expr::Pow(Box::new(base), Box::new(exponent)))
+                }
+                None => Ok(base),
+            }
         }
-    }
-
-    // Handle numbers
-    if let Ok(num) = expr_str.parse::<i64>() {
-        return Ok(Expr::Number(num));
-    }
-
-    // Handle variables
-    Ok(Expr::Var(expr_str.to_string()))
-}
-
-
-/// Evaluate an expression in the given environment
-pub fn eval_expr(expr: &Expr, env: &Env) -> i64 {
-    match expr {
-        Expr::Number(n) => *n,
-        Expr::Var(name) => env.get(name).copied().unwrap_or(0),
-        Expr::Add(left, right) => eval_expr(left, env) + eval_expr(right, env),
-        Expr::Sub(left, right) => eval_expr(left, env) - eval_expr(right, env),
-        Expr::Mul(left, right) => eval_expr(left, env) * eval_expr(right, env),
-        Expr::Ref { var, modifier } => {
-            let value = env.get(var).copied().unwrap_or(0);
-            match modifier {
-                Some(mod_val) => value % mod_val,
-                None => value,
+        Rule::unary => {
+            let mut negations = 0;
+            let mut operand = None;
+            for part in pair.into_inner() {
+                match part.as_rule() {
+                    Rule::neg => negations += 1,
+                    _ => operand = Some(parse_expr(part)?),
+                }
             }
+            let mut expr = operand.ok_or_else(|| anyhow::anyhow!("missing operand"))?;
+            for _ in 0..negations {
+                expr = match expr {
+                    Expr::Number(n) => Expr::Number(-n),
+                    other => Expr::Neg(Box::new(other)),
+                };
+            }
+            Ok(expr)
         }
+        Rule::number => Ok(Expr::Number(parse_number(pair.as_str())?)),
+        Rule::identifier => Ok(Expr::Var(pair.as_str().to_string())),
+        Rule::call => {
+            let mut inner = pair.into_inner();
+            let name = next_pair(&mut inner)?.as_str().to_string();
+            let args = inner.map(parse_expr).collect::<anyhow::Result<Vec<_>>>()?;
+            Ok(Expr::Call { name, args })
+        }
+        Rule::reference => {
+            let mut inner = pair.into_inner();
+            let var = next_pair(&mut inner)?.as_str().to_string();
+            let modifier = inner
+                .next()
+                .map(|m| parse_number(&m.as_str().split_whitespace().collect::<String>()))
+                .transpose()?;
+            Ok(Expr::Ref { var, modifier })
+        }
+        other => Err(anyhow::anyhow!("unexpected rule: {:?}", other)),
     }
 }
 
-/// Run a program (list of assignments) and return the final environment
-pub fn run_program(assignments: &[Assign]) -> anyhow::Result<Env> {
-    let mut env = Env::new();
-    
-    for assignment in assignments {
-        let value = eval_expr(&assignment.value, &env);
-        env.insert(assignment.name.clone(), value);
-    }
-    
-    Ok(env)
-}
-
-// Note: eval_expr and run_program are imported from the eval module
-
-// CalcParser is now defined in atp/ops.rs
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -327,9 +345,9 @@ mod tests {
         "#;
         
         let result = calc.evaluate(src).unwrap();
-        assert_eq!(result.env["a"], 2);
-        assert_eq!(result.env["b"], 3);
-        assert_eq!(result.env["c"], 6);
+        assert_eq!(result.env["a"], 2.0);
+        assert_eq!(result.env["b"], 3.0);
+        assert_eq!(result.env["c"], 6.0);
     }
 
     #[test]
@@ -345,10 +363,10 @@ mod tests {
         "#;
         
         let result = calc.evaluate(src).unwrap();
-        assert_eq!(result.env["a"], 10);
-        assert_eq!(result.env["b"], 3);
-        assert_eq!(result.env["c"], 30);
-        assert_eq!(result.env["d"], 2); // 30 % 4 = 2
+        assert_eq!(result.env["a"], 10.0);
+        assert_eq!(result.env["b"], 3.0);
+        assert_eq!(result.env["c"], 30.0);
+        assert_eq!(result.env["d"], 2.0); // 30 % 4 = 2
     }
 
     #[test]
@@ -363,8 +381,110 @@ mod tests {
         "#;
         
         let result = calc.evaluate(src).unwrap();
-        assert_eq!(result.env["x"], 5);
-        assert_eq!(result.env["y"], 3);
-        assert_eq!(result.env["z"], 16); // (5+3) * (5-3) = 8 * 2 = 16
+        assert_eq!(result.env["x"], 5.0);
+        assert_eq!(result.env["y"], 3.0);
+        assert_eq!(result.env["z"], 16.0); // (5+3) * (5-3) = 8 * 2 = 16
+    }
+
+    #[test]
+    fn test_floats_and_scientific_notation() {
+        let src = r#"
+            reproducibility {
+                lr = 3e-4
+                warmup = 0.1
+                steps = 1_000
+                scaled = lr x steps
+                frac = .5 + 1.5E+1
+            }
+        "#;
+        let result = Calculator::new().evaluate(src).unwrap();
+        assert_eq!(result.env["lr"], 0.0003);
+        assert_eq!(result.env["warmup"], 0.1);
+        assert!((result.env["scaled"] - 0.3).abs() < 1e-12);
+        assert_eq!(result.env["frac"], 15.5);
+    }
+
+    #[test]
+    fn test_division_modulo_power() {
+        let src = r#"
+            reproducibility {
+                a = 7 / 2
+                b = 7 % 3
+                c = 2 ^ 3 ^ 2
+                d = 2 ** 10
+                e = -2 ^ 2
+                f = 10 - 4 / 2 * 3
+                g = 2 ^ -1
+                h = (-2) ^ 2
+            }
+        "#;
+        let result = Calculator::new().evaluate(src).unwrap();
+        assert_eq!(result.env["a"], 3.5);
+        assert_eq!(result.env["b"], 1.0);
+        assert_eq!(result.env["c"], 512.0);
+        assert_eq!(result.env["d"], 1024.0);
+        assert_eq!(result.env["e"], -4.0); // -(2 ^ 2)
+        assert_eq!(result.env["f"], 4.0);
+        assert_eq!(result.env["g"], 0.5);
+        assert_eq!(result.env["h"], 4.0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_functions() {
+        let src = r#"
+            reproducibility {
+                hidden = 768
+                heads = 12
+                head_dim = hidden / heads
+                batch = max(8, min(64, 4096 / head_dim))
+                layers = ceil(log2(hidden))
+                scale = 1 / sqrt(head_dim)
+                area = round(pi * 10)
+            }
+        "#;
+        let result = Calculator::new().evaluate(src).unwrap();
+        assert_eq!(result.env["head_dim"], 64.0);
+        assert_eq!(result.env["batch"], 64.0);
+        assert_eq!(result.env["layers"], 10.0);
+        assert_eq!(result.env["scale"], 0.125);
+        assert_eq!(result.env["area"], 31.0);
+    }
+
+    #[test]
+    fn test_variables_carry_across_blocks() {
+        let src = r#"
+            reproducibility {
+                base = 4
+                // comments are ignored
+            }
+            reproducibility {
+                x1 = base x 2
+                e = 1
+                y = e + x1
+            }
+        "#;
+        let result = Calculator::new().evaluate(src).unwrap();
+        assert_eq!(result.env["x1"], 8.0);
+        // A variable shadows the `e` constant
+        assert_eq!(result.env["y"], 9.0);
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        let calc = Calculator::new();
+        let err = |body: &str| {
+            calc.evaluate(&format!("reproducibility {{ {} }}", body))
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default()
+        };
+        assert!(err("a = 1 / 0").contains("division by zero"));
+        assert!(err("a = 5 b = @a #0").contains("division by zero"));
+        assert!(err("a = 10 ^ 400").contains("overflow"));
+        assert!(err("a = sqrt(-1)").contains("domain error"));
+        assert!(err("a = log2(0)").contains("domain error"));
+        assert!(err("a = b + 1").contains("variable `b` not defined"));
+        assert!(err("a = median(1, 2)").contains("unknown function `median`"));
+        assert!(err("a = 1 +").contains("expected"));
+    }
+}
@@ -0,0 +1,286 @@
+//! Persistent Operator Cache - memoized results across parses
+//!
+//! Operators whose descriptor is marked `memoize` keep their results under
+//! `~/.dna/hlx/eval-cache`, one JSON file per call. An entry is keyed by the
+//! operator name, its bound arguments and the input files the call reads, and
+//! records a fingerprint of each input. It is reused only while every input's
+//! fingerprint is unchanged, so editing a JSON file or writing to a SQLite
+//! database invalidates the results derived from it.
+//!
+//! Plain files are fingerprinted by a SHA-256 of their contents. SQLite
+//! databases are fingerprinted without reading them: the header's change
+//! counter plus the size and modification time of the database and of its
+//! `-wal` file, so commits still sitting in the write-ahead log count too.
+//!
+//! Set `HLX_NO_CACHE=1` to bypass the cache. `hlx cache` lists and prunes it.
+
+use crate::dna::atp::value::Value;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Disables the cache when set to anything but `0` or empty
+pub const NO_CACHE_ENV: &str = "HLX_NO_CACHE";
+
+/// One memoized operator call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MemoEntry {
+    pub operator: String,
+    pub args: String,
+    /// [`fingerprint`] of each input file when the value was computed
+    pub inputs: BTreeMap<PathBuf, String>,
+    pub value: Value,
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+}
+
+impl MemoEntry {
+    /// Inputs that changed or disappeared since the value was computed
+    pub fn stale_inputs(&self) -> Vec<&Path> {
+        self.inputs
+            .iter()
+            .filter(|(path, stamp)| fingerprint(path).ok().as_ref() != Some(*stamp))
+            .map(|(path, _)| path.as_path())
+            .collect()
+    }
+
+    pub fn is_fresh(&self) -> bool {
+        self.stale_inputs().is_empty()
+    }
+}
+
+/// Hex SHA-256 of a file's contents
+pub fn hash_file(path: &Path) -> io::Result<String> {
+    let digest = Sha256::digest(fs::read(path)?);
+    Ok(digest.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Identifies the current state of an input file: its SHA-256 for plain
+/// files, a cheap change stamp for SQLite databases
+pub fn fingerprint(path: &Path) -> io::Result<String> {
+    let Some(change_counter) = sqlite_change_counter(path) else {
+        return hash_file(path);
+    };
+    let mut wal = path.as_os_str().to_owned();
+    wal.push("-wal");
+    let wal = file_stamp(Path::new(&wal)).unwrap_or_else(|_| "none".to_string());
+    Ok(format!("sqlite:{}:{};wal:{}", change_counter, file_stamp(path)?, wal))
+}
+
+/// The file change counter from a SQLite database header, or `None` when
+/// `path` isn't a SQLite database
+fn sqlite_change_counter(path: &Path) -> Option<u32> {
+    let mut header = [0u8; 28];
+    fs::File::open(path).ok()?.read_exact(&mut header).ok()?;
+    if &header[..16] != b"SQLite format 3\0" {
+        return None;
+    }
+    Some(u32::from_be_bytes([header[24], header[25], header[26], header[27]]))
+}
+
+/// Size and modification time of a file
+fn file_stamp(path: &Path) -> io::Result<String> {
+    let metadata = fs::metadata(path)?;
+    let modified = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    Ok(format!("{}@{}", metadata.len(), modified))
+}
+
+/// Memoized operator results stored as JSON files in one directory
+#[derive(Debug, Clone)]
+pub struct EvalCache {
+    dir: PathBuf,
+}
+
+impl EvalCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// `~/.dna/hlx/eval-cache`
+    pub fn default_dir() -> io::Result<PathBuf> {
+        crate::dna::atp::ops::get_or_create_helix_dir().map(|dir| dir.join("eval-cache"))
+    }
+
+    /// The cache in the default directory, or `None` when `HLX_NO_CACHE` is
+    /// set or there is no home directory
+    pub fn from_env() -> Option<Self> {
+        let disabled = std::env::var(NO_CACHE_ENV)
+            .map(|v| !v.is_empty() && v != "0")
+            .unwrap_or(false);
+        if disabled {
+            return None;
+        }
+        Self::default_dir().ok().map(Self::new)
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry_path(&self, operator: &str, args: &str, inputs: &[PathBuf]) -> PathBuf {
+        let mut hasher = Sha256::new();
+        hasher.update(operator.as_bytes());
+        hasher.update([0]);
+        hasher.update(args.as_bytes());
+        for input in inputs {
+            hasher.update([0]);
+            hasher.update(input.to_string_lossy().as_bytes());
+        }
+        let key: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// The cached value for this call, if its inputs are unchanged
+    pub fn get(&self, operator: &str, args: &str, inputs: &[PathBuf]) -> Option<Value> {
+        let raw = fs::read_to_string(self.entry_path(operator, args, inputs)).ok()?;
+        let entry: MemoEntry = serde_json::from_str(&raw).ok()?;
+        let same_call = entry.operator == operator
+            && entry.args == args
+            && entry.inputs.keys().eq(inputs.iter());
+        if same_call && entry.is_fresh() {
+            Some(entry.value)
+        } else {
+            None
+        }
+    }
+
+    /// Record `value` for this call. Calls whose inputs can't be read are
+    /// not cached.
+    pub fn put(&self, operator: &str, args: &str, inputs: &[PathBuf], value: &Value) -> io::Result<()> {
+        let mut stamps = BTreeMap::new();
+        for input in inputs {
+            match fingerprint(input) {
+                Ok(stamp) => stamps.insert(input.clone(), stamp),
+                Err(_) => return Ok(()),
+            };
+        }
+        let entry = MemoEntry {
+            operator: operator.to_string(),
+            args: args.to_string(),
+            inputs: stamps,
+            value: value.clone(),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        };
+        fs::create_dir_all(&self.dir)?;
+        let path = self.entry_path(operator, args, inputs);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_vec(&entry)?)?;
+        fs::rename(&tmp, &path)
+    }
+
+    /// Cached value, or the result of `compute`, which is then cached
+    pub fn get_or_insert_with<E>(
+        &self,
+        operator: &str,
+        args: &str,
+        inputs: &[PathBuf],
+        compute: impl FnOnce() -> Result<Value, E>,
+    ) -> Result<Value, E> {
+        if let Some(value) = self.get(operator, args, inputs) {
+            return Ok(value);
+        }
+        let value = compute()?;
+        let _ = self.put(operator, args, inputs, &value);
+        Ok(value)
+    }
+
+    /// Every readable entry with its file, sorted by operator then arguments
+    pub fn entries(&self) -> Vec<(PathBuf, MemoEntry)> {
+        let mut entries: Vec<_> = fs::read_dir(&self.dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| {
+                let entry = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+                Some((path, entry))
+            })
+            .collect();
+        entries.sort_by(|(_, a): &(PathBuf, MemoEntry), (_, b)| {
+            (&a.operator, &a.args).cmp(&(&b.operator, &b.args))
+        });
+        entries
+    }
+
+    /// Remove entries whose inputs changed, plus unreadable and partially
+    /// written files. Returns the number of files removed and bytes freed.
+    pub fn prune(&self) -> io::Result<(usize, u64)> {
+        self.remove_where(|path| {
+            let entry = fs::read_to_string(path)
+                .ok()
+                .and_then(|raw| serde_json::from_str::<MemoEntry>(&raw).ok());
+            entry.is_none_or(|entry| !entry.is_fresh())
+        })
+    }
+
+    /// Remove every entry
+    pub fn clear(&self) -> io::Result<(usize, u64)> {
+        self.remove_where(|_| true)
+    }
+
+    fn remove_where(&self, remove: impl Fn(&Path) -> bool) -> io::Result<(usize, u64)> {
+        let mut removed = 0;
+        let mut freed = 0;
+        let listing = match fs::read_dir(&self.dir) {
+            Ok(listing) => listing,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((0, 0)),
+            Err(e) => return Err(e),
+        };
+        for entry in listing {
+            let path = entry?.path();
+            if path.is_file() && remove(&path) {
+                freed += fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+                fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok((removed, freed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_entries_invalidate_when_inputs_change() {
+        let dir = TempDir::new().unwrap();
+        let cache = EvalCache::new(dir.path().join("cache"));
+        let input = dir.path().join("model.json");
+        fs::write(&input, r#"{"layers": 12}"#).unwrap();
+        let inputs = vec![input.clone()];
+
+        let mut calls = 0;
+        let mut eval = |cache: &EvalCache| {
+            cache.get_or_insert_with("file.json", "\"model.json\"", &inputs, || {
+                calls += 1;
+                Ok::<_, ()>(Value::Number(calls as f64))
+            })
+        };
+        assert_eq!(eval(&cache), Ok(Value::Number(1.0)));
+        assert_eq!(eval(&cache), Ok(Value::Number(1.0)));
+        assert_eq!(cache.entries().len(), 1);
+        assert!(cache.entries()[0].1.is_fresh());
+
+        fs::write(&input, r#"{"layers": 24}"#).unwrap();
+        assert_eq!(cache.entries()[0].1.stale_inputs(), vec![input.as_path()]);
+        assert_eq!(cache.prune().unwrap().0, 1);
+        assert_eq!(eval(&cache), Ok(Value::Number(2.0)));
+        assert!(cache.get("file.json", "\"other.json\"", &inputs).is_none());
+        assert_eq!(cache.clear().unwrap().0, 1);
+        assert!(cache.entries().is_empty());
+    }
+}
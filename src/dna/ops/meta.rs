@@ -0,0 +1,287 @@
+//! Operator Metadata - descriptors for listing, docs and completion
+//!
+//! Every operator set reports an [`OperatorDescriptor`] per operator through
+//! [`OperatorTrait::descriptors`](crate::ops::OperatorTrait::descriptors).
+//! `OperatorEngine` merges them (plus plugins and user functions) so the CLI
+//! (`hlx ops list`, `hlx ops describe @regex`) and editor tooling read one source.
+
+use serde::Serialize;
+use std::fmt;
+use std::str::FromStr;
+
+/// Grouping used by `hlx ops list`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OperatorCategory {
+    Variables,
+    Request,
+    DateTime,
+    String,
+    Encoding,
+    Conditional,
+    Logic,
+    Math,
+    Collections,
+    Data,
+    Plugin,
+    Function,
+}
+
+impl OperatorCategory {
+    pub const ALL: [OperatorCategory; 12] = [
+        Self::Variables,
+        Self::Request,
+        Self::DateTime,
+        Self::String,
+        Self::Encoding,
+        Self::Conditional,
+        Self::Logic,
+        Self::Math,
+        Self::Collections,
+        Self::Data,
+        Self::Plugin,
+        Self::Function,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Variables => "variables",
+            Self::Request => "request",
+            Self::DateTime => "datetime",
+            Self::String => "string",
+            Self::Encoding => "encoding",
+            Self::Conditional => "conditional",
+            Self::Logic => "logic",
+            Self::Math => "math",
+            Self::Collections => "collections",
+            Self::Data => "data",
+            Self::Plugin => "plugin",
+            Self::Function => "function",
+        }
+    }
+}
+
+impl fmt::Display for OperatorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OperatorCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|c| c.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!(
+                "unknown category `{}` (expected one of: {})",
+                s,
+                Self::ALL.iter().map(|c| c.as_str()).collect::<Vec<_>>().join(", ")
+            ))
+    }
+}
+
+/// Shape of a parameter or return value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ValueType {
+    String,
+    Number,
+    Bool,
+    Array,
+    Object,
+    Null,
+    /// A number with a unit, such as `16GiB` or `1.5h`
+    Quantity,
+    Any,
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::String => "string",
+            Self::Number => "number",
+            Self::Bool => "bool",
+            Self::Array => "array",
+            Self::Object => "object",
+            Self::Null => "null",
+            Self::Quantity => "quantity",
+            Self::Any => "any",
+        };
+        f.write_str(name)
+    }
+}
+
+/// One key of an operator's JSON parameter object
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ParamSpec {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: ValueType,
+    pub required: bool,
+    pub description: String,
+    /// Accepted values, for enum-like parameters such as `operation`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<String>,
+}
+
+/// Name, parameter schema and behaviour of an operator
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OperatorDescriptor {
+    /// Name without the leading `@`, e.g. `regex` or `db.get`
+    pub name: String,
+    pub category: OperatorCategory,
+    pub summary: String,
+    pub params: Vec<ParamSpec>,
+    pub returns: ValueType,
+    /// No side effects (no writes to variables, sessions or storage)
+    pub pure: bool,
+    /// Same parameters always produce the same result
+    pub deterministic: bool,
+    /// Results are kept in the persistent evaluation cache, keyed by the
+    /// input files the call reads (see [`crate::ops::memo`])
+    pub memoize: bool,
+    pub examples: Vec<String>,
+}
+
+impl OperatorDescriptor {
+    /// A pure, deterministic operator with no parameters returning `any`
+    pub fn new(name: impl Into<String>, category: OperatorCategory, summary: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            category,
+            summary: summary.into(),
+            params: Vec::new(),
+            returns: ValueType::Any,
+            pure: true,
+            deterministic: true,
+            memoize: false,
+            examples: Vec::new(),
+        }
+    }
+
+    pub fn param(self, name: &str, ty: ValueType, description: &str) -> Self {
+        self.push_param(name, ty, true, description)
+    }
+
+    pub fn optional(self, name: &str, ty: ValueType, description: &str) -> Self {
+        self.push_param(name, ty, false, description)
+    }
+
+    /// Restrict the most recently added parameter to `choices`
+    pub fn choices(mut self, choices: &[&str]) -> Self {
+        if let Some(param) = self.params.last_mut() {
+            param.choices = choices.iter().map(|c| c.to_string()).collect();
+        }
+        self
+    }
+
+    pub fn returns(mut self, ty: ValueType) -> Self {
+        self.returns = ty;
+        self
+    }
+
+    /// Has side effects; implies nondeterministic
+    pub fn impure(mut self) -> Self {
+        self.pure = false;
+        self.deterministic = false;
+        self
+    }
+
+    /// Reads the clock, environment, request or storage
+    pub fn nondeterministic(mut self) -> Self {
+        self.deterministic = false;
+        self
+    }
+
+    /// Expensive and determined by its arguments and input files
+    pub fn memoize(mut self) -> Self {
+        self.memoize = true;
+        self
+    }
+
+    pub fn example(mut self, example: &str) -> Self {
+        self.examples.push(example.to_string());
+        self
+    }
+
+    fn push_param(mut self, name: &str, ty: ValueType, required: bool, description: &str) -> Self {
+        self.params.push(ParamSpec {
+            name: name.to_string(),
+            ty,
+            required,
+            description: description.to_string(),
+            choices: Vec::new(),
+        });
+        self
+    }
+
+    /// `@name(required, optional?)`
+    pub fn signature(&self) -> String {
+        let params: Vec<String> = self
+            .params
+            .iter()
+            .map(|p| if p.required { p.name.clone() } else { format!("{}?", p.name) })
+            .collect();
+        format!("@{}({})", self.name, params.join(", "))
+    }
+
+    /// Insert text for editor completion: the call with a JSON skeleton of
+    /// the required parameters
+    pub fn snippet(&self) -> String {
+        let required: Vec<String> = self
+            .params
+            .iter()
+            .filter(|p| p.required)
+            .map(|p| format!("\"{}\": ", p.name))
+            .collect();
+        if required.is_empty() {
+            format!("@{}()", self.name)
+        } else {
+            format!("@{}({{{}}})", self.name, required.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_descriptor_builder() {
+        let d = OperatorDescriptor::new("regex", OperatorCategory::String, "Regular expressions")
+            .param("input", ValueType::String, "Text to search")
+            .param("operation", ValueType::String, "What to do")
+            .choices(&["match", "replace"])
+            .optional("replacement", ValueType::String, "Replacement text")
+            .nondeterministic();
+        assert_eq!(d.signature(), "@regex(input, operation, replacement?)");
+        assert_eq!(d.snippet(), r#"@regex({"input": , "operation": })"#);
+        assert_eq!(d.params[1].choices, vec!["match", "replace"]);
+        assert!(d.pure && !d.deterministic);
+        assert_eq!("DateTime".parse::<OperatorCategory>(), Ok(OperatorCategory::DateTime));
+        assert!("nope".parse::<OperatorCategory>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_engine_describes_routed_operators() {
+        let engine = crate::ops::engine::OperatorEngine::new().await.unwrap();
+        for name in ["var", "regex", "if", "concat", "calc", "db.get", "sql.query"] {
+            assert!(engine.describe(&format!("@{}", name)).is_some(), "missing descriptor for @{}", name);
+        }
+        // Bare `calc` dispatches to the calculator, so its descriptor wins.
+        assert_eq!(engine.describe("calc").unwrap().params[0].name, "source");
+        let db: Vec<String> = engine.complete("@db.").into_iter().map(|d| d.name).collect();
+        assert_eq!(db, vec!["db.get", "db.put"]);
+
+        engine
+            .functions()
+            .define(crate::ops::functions::FunctionDef::parse("fn slug(name) = name", None).unwrap().unwrap())
+            .unwrap();
+        let slug = engine.describe("@slug").unwrap();
+        assert_eq!(slug.category, OperatorCategory::Function);
+        assert_eq!(slug.signature(), "@slug(name)");
+    }
+}
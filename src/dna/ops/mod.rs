@@ -11,12 +11,33 @@ pub mod math;
 pub mod eval;
 pub mod utils;
 pub mod engine;
+pub mod db;
+pub mod query;
+pub mod functions;
+pub mod plugin;
+pub mod meta;
+pub mod args;
+pub mod hermetic;
+pub mod file;
+pub mod http;
+pub mod memo;
 
 pub use eval::{run_program, Env};
 
 #[async_trait]
 pub trait OperatorTrait: Send + Sync {
     async fn execute(&self, operator: &str, params: &str) -> Result<Value, HlxError>;
+
+    /// Metadata for every operator this set handles
+    fn descriptors(&self) -> Vec<meta::OperatorDescriptor> {
+        Vec::new()
+    }
+
+    /// Files a memoized operator call reads, used to key and invalidate its
+    /// cache entry. `None` means this call must not be cached.
+    fn inputs(&self, _operator: &str, _params: &str) -> Option<Vec<std::path::PathBuf>> {
+        None
+    }
 }
 
 
@@ -0,0 +1,518 @@
+//! WebAssembly Operator Plugins - sandboxed `@plugin.op` operators
+//!
+//! A `plugin` block whose `source` points at a `.wasm` module adds operators
+//! without touching `FundamentalOperators`:
+//! ```text
+//! plugin "textkit" {
+//!     source = "plugins/textkit.wasm"
+//!     config { fuel = 1000000, max_memory_mb = 8 }
+//! }
+//! value = @textkit.reverse({"input": "abc"})
+//! ```
+//!
+//! ABI (version 1), all strings UTF-8 JSON in linear memory:
+//! - `memory`: exported linear memory
+//! - `hlx_alloc(len: i32) -> i32`: reserve `len` bytes for host input
+//! - `hlx_op_<name>(ptr: i32, len: i32) -> i64`: run operator `<name>` on the
+//!   JSON parameters at `ptr..ptr+len`; returns `(out_ptr << 32) | out_len`
+//!   of its JSON result. A result of `{"error": "..."}` fails the call.
+//! - `hlx_abi_version() -> i32` (optional): must return 1
+//! - `hlx_configure(ptr: i32, len: i32) -> i32` (optional): receives the
+//!   plugin's `config` block once after loading; non-zero rejects it
+//!
+//! Modules run on an interpreter with per-call fuel and a memory cap, and are
+//! given no imports, so they have no filesystem, network, clock or
+//! environment access. Modules that import anything fail to load.
+
+use crate::dna::atp::types::{self, PluginConfig};
+use crate::dna::atp::value::Value;
+use crate::dna::hel::error::HlxError;
+use crate::ops::meta::{OperatorCategory, OperatorDescriptor};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Plugin ABI version understood by this host
+pub const ABI_VERSION: i32 = 1;
+/// Fuel granted to each operator call unless the plugin sets `fuel`
+pub const DEFAULT_FUEL: u64 = 10_000_000;
+/// Linear memory cap unless the plugin sets `max_memory_mb`
+pub const DEFAULT_MAX_MEMORY_MB: u64 = 16;
+/// Namespaces of built-in operator families, which a plugin may not take
+pub const RESERVED_NAMESPACES: &[&str] = &["db", "sql", "file", "http"];
+const OP_EXPORT_PREFIX: &str = "hlx_op_";
+
+/// Built-in operators are dispatched before plugins, so a plugin named
+/// after one of their namespaces would never be called
+fn check_namespace(name: &str) -> Result<(), HlxError> {
+    if RESERVED_NAMESPACES.contains(&name) {
+        return Err(HlxError::validation_error(
+            format!("Plugin name `{}` is reserved for the built-in @{}.* operators", name, name),
+            "Rename the plugin block",
+        ));
+    }
+    Ok(())
+}
+
+/// Loaded plugins by namespace
+pub struct PluginHost {
+    plugins: RwLock<HashMap<String, Arc<Mutex<WasmPlugin>>>>,
+}
+
+impl Default for PluginHost {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PluginHost {
+    pub fn new() -> Self {
+        Self {
+            plugins: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Load the module named by a `plugin` block; relative sources resolve
+    /// against `base_dir`. Returns the operators it exports.
+    pub fn load(&self, config: &PluginConfig, base_dir: Option<&Path>) -> Result<Vec<String>, HlxError> {
+        check_namespace(&config.name)?;
+        let source = Path::new(&config.source);
+        let path = match base_dir {
+            Some(base) if source.is_relative() => base.join(source),
+            _ => source.to_path_buf(),
+        };
+        let bytes = std::fs::read(&path).map_err(|e| HlxError::io_error(
+            format!("Failed to read plugin `{}` from {}: {}", config.name, path.display(), e),
+            "Point the plugin `source` at a compiled .wasm module",
+        ))?;
+        self.load_bytes(config, &bytes)
+    }
+
+    /// Load a plugin from module bytes, replacing any plugin of the same name
+    pub fn load_bytes(&self, config: &PluginConfig, bytes: &[u8]) -> Result<Vec<String>, HlxError> {
+        check_namespace(&config.name)?;
+        let plugin = WasmPlugin::instantiate(config, bytes)?;
+        let operators = plugin.operators();
+        let mut plugins = self.plugins
+            .write()
+            .map_err(|_| HlxError::validation_error("RwLock poisoned", "Check concurrency"))?;
+        plugins.insert(config.name.clone(), Arc::new(Mutex::new(plugin)));
+        Ok(operators)
+    }
+
+    pub fn is_loaded(&self, namespace: &str) -> bool {
+        self.plugins
+            .read()
+            .map(|p| p.contains_key(namespace))
+            .unwrap_or(false)
+    }
+
+    /// Names of the loaded plugins, sorted
+    pub fn namespaces(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .plugins
+            .read()
+            .map(|p| p.keys().cloned().collect())
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+
+    /// Operators exported by a loaded plugin
+    pub fn operators(&self, namespace: &str) -> Option<Vec<String>> {
+        let plugin = self.plugins.read().ok()?.get(namespace).cloned()?;
+        let plugin = plugin.lock().ok()?;
+        Some(plugin.operators())
+    }
+
+    /// Descriptors for every loaded plugin operator. Plugins have no imports,
+    /// so their operators are pure and deterministic.
+    pub fn descriptors(&self) -> Vec<OperatorDescriptor> {
+        self.namespaces()
+            .into_iter()
+            .flat_map(|ns| {
+                self.operators(&ns)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(move |op| OperatorDescriptor::new(
+                        format!("{}.{}", ns, op),
+                        OperatorCategory::Plugin,
+                        format!("`{}` from WebAssembly plugin `{}`", op, ns),
+                    ))
+            })
+            .collect()
+    }
+
+    /// Run `namespace.operator` with JSON `params`
+    pub fn execute(&self, namespace: &str, operator: &str, params: &str) -> Result<Value, HlxError> {
+        let plugin = self.plugins
+            .read()
+            .map_err(|_| HlxError::validation_error("RwLock poisoned", "Check concurrency"))?
+            .get(namespace)
+            .cloned()
+            .ok_or_else(|| HlxError::unknown_operator(format!("@{}.{}", namespace, operator)))?;
+        let mut plugin = plugin
+            .lock()
+            .map_err(|_| HlxError::validation_error("Mutex poisoned", "Reload the plugin"))?;
+        // Plugins receive a JSON object; named `key = value` arguments are converted
+        let params = match params.trim() {
+            "" => "{}".to_string(),
+            p if serde_json::from_str::<serde_json::Value>(p).is_ok() => p.to_string(),
+            p => {
+                let map = crate::ops::utils::parse_params(p)?;
+                crate::ops::utils::value_to_json(&Value::Object(map)).to_string()
+            }
+        };
+        plugin.call(operator, &params)
+    }
+}
+
+/// Plugin `config` block as JSON for `hlx_configure`
+fn config_to_json(value: &types::Value) -> serde_json::Value {
+    match value {
+        types::Value::String(s) | types::Value::Reference(s) | types::Value::Identifier(s) => {
+            serde_json::Value::String(s.clone())
+        }
+        types::Value::Number(n) => serde_json::Number::from_f64(*n)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        types::Value::Bool(b) => serde_json::Value::Bool(*b),
+        types::Value::Null => serde_json::Value::Null,
+        types::Value::Array(items) => serde_json::Value::Array(items.iter().map(config_to_json).collect()),
+        types::Value::Object(map) => serde_json::Value::Object(
+            map.iter().map(|(k, v)| (k.clone(), config_to_json(v))).collect(),
+        ),
+        types::Value::Duration(d) => serde_json::Value::String(format!("{} {:?}", d.value, d.unit)),
+        types::Value::Quantity(q) => serde_json::Value::String(q.to_string()),
+    }
+}
+
+/// Numeric limit from the plugin `config` block
+fn config_limit(config: &PluginConfig, key: &str, default: u64) -> Result<u64, HlxError> {
+    match config.config.get(key) {
+        None => Ok(default),
+        Some(types::Value::Number(n)) if *n >= 1.0 => Ok(*n as u64),
+        Some(other) => Err(HlxError::config_validation(
+            format!("plugin.{}.{}", config.name, key),
+            other.to_string(),
+            format!("`{}` must be a positive number", key),
+        )),
+    }
+}
+
+#[cfg(feature = "plugins")]
+struct PluginState {
+    limits: wasmi::StoreLimits,
+}
+
+#[cfg(feature = "plugins")]
+struct WasmPlugin {
+    name: String,
+    store: wasmi::Store<PluginState>,
+    instance: wasmi::Instance,
+    memory: wasmi::Memory,
+    operators: Vec<String>,
+    fuel: u64,
+}
+
+#[cfg(feature = "plugins")]
+impl WasmPlugin {
+    fn instantiate(config: &PluginConfig, bytes: &[u8]) -> Result<Self, HlxError> {
+        use wasmi::{Config, Engine, ExternType, Linker, Module, Store, StoreLimitsBuilder};
+        use wasmi::core::ValType;
+
+        let fuel = config_limit(config, "fuel", DEFAULT_FUEL)?;
+        let max_memory = config_limit(config, "max_memory_mb", DEFAULT_MAX_MEMORY_MB)? as usize * 1024 * 1024;
+        let load_error = |e: wasmi::Error| HlxError::execution_error(
+            format!("Failed to load plugin `{}`: {}", config.name, e),
+            "Plugins get no imports and must stay within `max_memory_mb`",
+        );
+
+        let mut engine_config = Config::default();
+        engine_config.consume_fuel(true);
+        let engine = Engine::new(&engine_config);
+        let module = Module::new(&engine, bytes).map_err(load_error)?;
+        let mut store = Store::new(&engine, PluginState {
+            limits: StoreLimitsBuilder::new()
+                .memory_size(max_memory)
+                .instances(1)
+                .trap_on_grow_failure(true)
+                .build(),
+        });
+        store.limiter(|state| &mut state.limits);
+        store.set_fuel(fuel).map_err(|e| load_error(e.into()))?;
+
+        // An empty linker is the sandbox: any import fails instantiation.
+        let linker = Linker::<PluginState>::new(&engine);
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(load_error)?;
+        let memory = instance.get_memory(&store, "memory").ok_or_else(|| HlxError::execution_error(
+            format!("Plugin `{}` does not export `memory`", config.name),
+            "Export the module's linear memory as `memory`",
+        ))?;
+
+        let mut operators: Vec<String> = module
+            .exports()
+            .filter_map(|export| {
+                let name = export.name().strip_prefix(OP_EXPORT_PREFIX)?;
+                match export.ty() {
+                    ExternType::Func(ty)
+                        if ty.params() == [ValType::I32, ValType::I32]
+                            && ty.results() == [ValType::I64] => Some(name.to_string()),
+                    _ => None,
+                }
+            })
+            .collect();
+        operators.sort();
+
+        let mut plugin = Self {
+            name: config.name.clone(),
+            store,
+            instance,
+            memory,
+            operators,
+            fuel,
+        };
+        plugin.check_abi_version()?;
+        plugin.configure(config)?;
+        Ok(plugin)
+    }
+
+    fn operators(&self) -> Vec<String> {
+        self.operators.clone()
+    }
+
+    fn check_abi_version(&mut self) -> Result<(), HlxError> {
+        let Ok(version) = self.instance.get_typed_func::<(), i32>(&self.store, "hlx_abi_version") else {
+            return Ok(());
+        };
+        self.refuel()?;
+        let version = version.call(&mut self.store, ()).map_err(|e| self.trap("hlx_abi_version", e))?;
+        if version != ABI_VERSION {
+            return Err(HlxError::execution_error(
+                format!("Plugin `{}` targets ABI version {}, host supports {}", self.name, version, ABI_VERSION),
+                "Rebuild the plugin against the current plugin ABI",
+            ));
+        }
+        Ok(())
+    }
+
+    fn configure(&mut self, config: &PluginConfig) -> Result<(), HlxError> {
+        let Ok(configure) = self.instance.get_typed_func::<(i32, i32), i32>(&self.store, "hlx_configure") else {
+            return Ok(());
+        };
+        let json = serde_json::Value::Object(
+            config.config.iter().map(|(k, v)| (k.clone(), config_to_json(v))).collect(),
+        )
+        .to_string();
+        self.refuel()?;
+        let (ptr, len) = self.write_input(&json)?;
+        let status = configure
+            .call(&mut self.store, (ptr, len))
+            .map_err(|e| self.trap("hlx_configure", e))?;
+        if status != 0 {
+            return Err(HlxError::config_validation(
+                format!("plugin.{}.config", config.name),
+                json,
+                format!("Plugin rejected its configuration (status {})", status),
+            ));
+        }
+        Ok(())
+    }
+
+    fn call(&mut self, operator: &str, params: &str) -> Result<Value, HlxError> {
+        if !self.operators.iter().any(|op| op == operator) {
+            return Err(HlxError::unknown_operator(format!("@{}.{}", self.name, operator)));
+        }
+        let export = format!("{}{}", OP_EXPORT_PREFIX, operator);
+        let func = self.instance
+            .get_typed_func::<(i32, i32), i64>(&self.store, &export)
+            .map_err(|e| self.trap(&export, e))?;
+        self.refuel()?;
+        let (ptr, len) = self.write_input(params)?;
+        let packed = func.call(&mut self.store, (ptr, len)).map_err(|e| self.trap(&export, e))?;
+
+        let out_ptr = (packed as u64 >> 32) as usize;
+        let out_len = (packed as u64 & 0xFFFF_FFFF) as usize;
+        let mut out = vec![0u8; out_len];
+        self.memory.read(&self.store, out_ptr, &mut out).map_err(|e| HlxError::execution_error(
+            format!("Plugin `{}` returned an out-of-bounds result: {}", self.name, e),
+            "Return `(ptr << 32) | len` of the JSON result",
+        ))?;
+        let json: serde_json::Value = serde_json::from_slice(&out).map_err(|e| HlxError::execution_error(
+            format!("Plugin `{}` returned invalid JSON from `{}`: {}", self.name, operator, e),
+            "Plugin operators must return UTF-8 JSON",
+        ))?;
+        if let serde_json::Value::Object(map) = &json {
+            if let (1, Some(error)) = (map.len(), map.get("error")) {
+                return Err(HlxError::execution_error(
+                    format!("@{}.{} failed: {}", self.name, operator, error.as_str().unwrap_or(&error.to_string())),
+                    "Check the operator's parameters",
+                ));
+            }
+        }
+        Ok(crate::ops::utils::json_to_value(&json))
+    }
+
+    /// Each call starts with a full fuel budget
+    fn refuel(&mut self) -> Result<(), HlxError> {
+        self.store.set_fuel(self.fuel).map_err(|e| HlxError::execution_error(
+            format!("Failed to set fuel for plugin `{}`: {}", self.name, e),
+            "Plugins require fuel metering",
+        ))
+    }
+
+    fn write_input(&mut self, input: &str) -> Result<(i32, i32), HlxError> {
+        let alloc = self.instance
+            .get_typed_func::<i32, i32>(&self.store, "hlx_alloc")
+            .map_err(|e| self.trap("hlx_alloc", e))?;
+        let len = i32::try_from(input.len()).map_err(|_| HlxError::invalid_input(
+            format!("Input for plugin `{}` is too large", self.name),
+            "Pass smaller parameters",
+        ))?;
+        let ptr = alloc.call(&mut self.store, len).map_err(|e| self.trap("hlx_alloc", e))?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, input.as_bytes())
+            .map_err(|e| HlxError::execution_error(
+                format!("Plugin `{}` allocated an invalid input buffer: {}", self.name, e),
+                "`hlx_alloc` must return a pointer into exported memory",
+            ))?;
+        Ok((ptr, len))
+    }
+
+    fn trap(&self, export: &str, e: wasmi::Error) -> HlxError {
+        match e.as_trap_code() {
+            Some(wasmi::core::TrapCode::OutOfFuel) => HlxError::execution_error(
+                format!("Plugin `{}` ran out of fuel in `{}`", self.name, export),
+                "Raise the plugin's `fuel` limit or check for runaway loops",
+            ),
+            Some(wasmi::core::TrapCode::GrowthOperationLimited) => HlxError::execution_error(
+                format!("Plugin `{}` exceeded its memory limit in `{}`", self.name, export),
+                "Raise the plugin's `max_memory_mb` limit",
+            ),
+            _ => HlxError::execution_error(
+                format!("Plugin `{}` failed in `{}`: {}", self.name, export, e),
+                "Check that the module implements the Helix plugin ABI",
+            ),
+        }
+    }
+}
+
+#[cfg(not(feature = "plugins"))]
+struct WasmPlugin;
+
+#[cfg(not(feature = "plugins"))]
+impl WasmPlugin {
+    fn instantiate(config: &PluginConfig, _bytes: &[u8]) -> Result<Self, HlxError> {
+        config_limit(config, "fuel", DEFAULT_FUEL)?;
+        Err(HlxError::feature_error("plugins", "WebAssembly plugins require the plugins feature"))
+    }
+
+    fn operators(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn call(&mut self, _operator: &str, _params: &str) -> Result<Value, HlxError> {
+        Err(HlxError::feature_error("plugins", "WebAssembly plugins require the plugins feature"))
+    }
+}
+
+#[cfg(all(test, feature = "plugins"))]
+mod tests {
+    use super::*;
+
+    /// Echo plugin: `echo` returns its input, `fail` reports an error,
+    /// `spin` never terminates and `grow` allocates without bound.
+    const ECHO_WAT: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (global $next (mut i32) (i32.const 1024))
+          (func (export "hlx_abi_version") (result i32) (i32.const 1))
+          (func (export "hlx_alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            (local.set $ptr (global.get $next))
+            (global.set $next (i32.add (global.get $next) (local.get $len)))
+            (local.get $ptr))
+          (func (export "hlx_op_echo") (param $ptr i32) (param $len i32) (result i64)
+            (i64.or
+              (i64.shl (i64.extend_i32_u (local.get $ptr)) (i64.const 32))
+              (i64.extend_i32_u (local.get $len))))
+          (data (i32.const 16) "{\"error\":\"nope\"}")
+          (func (export "hlx_op_fail") (param i32 i32) (result i64)
+            (i64.or (i64.shl (i64.const 16) (i64.const 32)) (i64.const 16)))
+          (func (export "hlx_op_spin") (param i32 i32) (result i64)
+            (loop $l (br $l))
+            (i64.const 0))
+          (func (export "hlx_op_grow") (param i32 i32) (result i64)
+            (drop (memory.grow (i32.const 1024)))
+            (i64.const 0)))
+    "#;
+
+    fn plugin(name: &str) -> PluginConfig {
+        let mut config = HashMap::new();
+        config.insert("fuel".to_string(), types::Value::Number(100_000.0));
+        config.insert("max_memory_mb".to_string(), types::Value::Number(2.0));
+        PluginConfig {
+            name: name.to_string(),
+            source: format!("{}.wasm", name),
+            version: "latest".to_string(),
+            config,
+        }
+    }
+
+    #[test]
+    fn test_plugin_operators_round_trip_json() {
+        let host = PluginHost::new();
+        let wasm = wat::parse_str(ECHO_WAT).unwrap();
+        let ops = host.load_bytes(&plugin("echo"), &wasm).unwrap();
+        assert_eq!(ops, vec!["echo", "fail", "grow", "spin"]);
+
+        let value = host.execute("echo", "echo", r#"{"input": "abc"}"#).unwrap();
+        assert_eq!(value.get("input"), Some(&Value::String("abc".to_string())));
+        let err = host.execute("echo", "fail", "{}").unwrap_err();
+        assert!(err.to_string().contains("nope"));
+        assert!(host.execute("echo", "missing", "{}").is_err());
+    }
+
+    #[test]
+    fn test_plugin_limits() {
+        let host = PluginHost::new();
+        let wasm = wat::parse_str(ECHO_WAT).unwrap();
+        host.load_bytes(&plugin("echo"), &wasm).unwrap();
+        assert!(host.execute("echo", "spin", "{}").unwrap_err().to_string().contains("fuel"));
+        assert!(host.execute("echo", "grow", "{}").unwrap_err().to_string().contains("memory limit"));
+        // Limits are per call; the plugin stays usable.
+        assert!(host.execute("echo", "echo", "{}").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_engine_routes_plugin_namespace() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("echo.wasm"), wat::parse_str(ECHO_WAT).unwrap()).unwrap();
+        let engine = crate::ops::engine::OperatorEngine::new().await.unwrap();
+        engine.load_plugin(&plugin("echo"), Some(dir.path())).unwrap();
+        let value = engine.execute_operator("@echo.echo", r#"{"n": 1}"#).await.unwrap();
+        assert_eq!(value.get("n"), Some(&Value::Number(1.0)));
+    }
+
+    #[test]
+    fn test_reserved_namespaces_are_rejected() {
+        let host = PluginHost::new();
+        let wasm = wat::parse_str(ECHO_WAT).unwrap();
+        for name in RESERVED_NAMESPACES {
+            let err = host.load_bytes(&plugin(name), &wasm).unwrap_err();
+            assert!(err.to_string().contains("reserved"), "{}", err);
+            assert!(!host.is_loaded(name));
+        }
+    }
+
+    #[test]
+    fn test_plugin_imports_are_rejected() {
+        let host = PluginHost::new();
+        let wasm = wat::parse_str(r#"(module (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32))) (memory (export "memory") 1))"#).unwrap();
+        assert!(host.load_bytes(&plugin("fs"), &wasm).is_err());
+        assert!(!host.is_loaded("fs"));
+    }
+}
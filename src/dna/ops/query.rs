@@ -0,0 +1,472 @@
+//! SQL Query Operator - @query against SQLite
+//!
+//! `@query("SELECT ...")` runs against the SQLite file of a declared database:
+//! - `@query("SELECT ...")`: the database named by `database.default`
+//! - `@query("name:SELECT ...")`: a specific declared database
+//! - `@query("SELECT ... WHERE id = ?", [3])`: positional parameter binding
+//! - `@query("SELECT ... WHERE id = :id", {"id": 3})`: named parameter binding
+//!
+//! Rows come back as a `Value::Array` of `Value::Object` keyed by column name.
+//! Connections are opened read-only unless the database declares
+//! `writable = true`, in which case statements without result columns return
+//! `{"rows_affected", "last_insert_rowid"}`.
+
+use crate::dna::atp::types::DatabaseConfig;
+use crate::dna::atp::value::Value;
+use crate::dna::hel::error::HlxError;
+use crate::ops::OperatorTrait;
+use crate::ops::meta::{OperatorCategory, OperatorDescriptor, ValueType};
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+#[cfg(feature = "sqlite")]
+use rusqlite::{types::ValueRef, Connection, OpenFlags, Statement};
+
+/// Resolved SQLite file plus whether statements may modify it
+#[derive(Debug, Clone, PartialEq)]
+pub struct SqlTarget {
+    pub path: PathBuf,
+    pub writable: bool,
+}
+
+impl SqlTarget {
+    /// Target for a `database` declaration; requires `path`, and honours the
+    /// `writable = true` property.
+    pub fn from_config(config: &DatabaseConfig) -> Result<Self, HlxError> {
+        let path = config.path.as_ref().ok_or_else(|| HlxError::config_validation(
+            format!("database.{}.path", config.name),
+            "",
+            "Set `path` to the SQLite file this database should query",
+        ))?;
+        let writable = matches!(
+            config.properties.get("writable"),
+            Some(crate::dna::atp::types::Value::Bool(true))
+        );
+        Ok(Self {
+            path: PathBuf::from(path),
+            writable,
+        })
+    }
+}
+
+/// Split the cross-database `name:SQL` form into its database name and SQL.
+/// Returns `(None, sql)` when no database prefix is present.
+pub fn split_database_prefix(query: &str) -> (Option<&str>, &str) {
+    let trimmed = query.trim_start();
+    if let Some((name, sql)) = trimmed.split_once(':') {
+        let name = name.trim();
+        let is_identifier = name
+            .chars()
+            .next()
+            .map(|c| c.is_ascii_alphabetic() || c == '_')
+            .unwrap_or(false)
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if is_identifier {
+            return (Some(name), sql.trim());
+        }
+    }
+    (None, query.trim())
+}
+
+/// Whether a `database.default` value names a SQLite file directly
+fn looks_like_sqlite_file(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    lower.ends_with(".db") || lower.ends_with(".sqlite") || lower.ends_with(".sqlite3")
+}
+
+/// Run one SQL statement against `target`, binding `params` (an array for
+/// positional `?` parameters, an object for named ones, or `Null`).
+pub fn run_query(target: &SqlTarget, sql: &str, params: &Value) -> Result<Value, HlxError> {
+    #[cfg(feature = "sqlite")]
+    {
+        run_sqlite(target, sql, params)
+    }
+    #[cfg(not(feature = "sqlite"))]
+    {
+        let _ = (target, sql, params);
+        Err(HlxError::feature_error("sqlite", "@query requires the sqlite feature"))
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn run_sqlite(target: &SqlTarget, sql: &str, params: &Value) -> Result<Value, HlxError> {
+    let flags = if target.writable {
+        OpenFlags::SQLITE_OPEN_READ_WRITE
+            | OpenFlags::SQLITE_OPEN_CREATE
+            | OpenFlags::SQLITE_OPEN_URI
+            | OpenFlags::SQLITE_OPEN_NO_MUTEX
+    } else {
+        OpenFlags::SQLITE_OPEN_READ_ONLY
+            | OpenFlags::SQLITE_OPEN_URI
+            | OpenFlags::SQLITE_OPEN_NO_MUTEX
+    };
+    let conn = Connection::open_with_flags(&target.path, flags).map_err(|e| {
+        HlxError::io_error(
+            format!("Failed to open SQLite database {}: {}", target.path.display(), e),
+            "Check the database `path`; read-only databases must already exist",
+        )
+    })?;
+    let mut stmt = conn.prepare(sql).map_err(|e| HlxError::execution_error(
+        format!("Invalid SQL: {}", e),
+        "Check the query syntax and referenced tables",
+    ))?;
+    if !target.writable && !stmt.readonly() {
+        return Err(HlxError::invalid_input(
+            format!("Query would modify read-only database {}: {}", target.path.display(), sql),
+            "Declare the database with `writable = true` to allow writes",
+        ));
+    }
+    bind_params(&mut stmt, params)?;
+
+    let sql_error = |e: rusqlite::Error| HlxError::execution_error(
+        format!("Query failed: {}", e),
+        "Check the query and its parameters",
+    );
+    if stmt.column_count() == 0 {
+        let affected = stmt.raw_execute().map_err(sql_error)?;
+        return Ok(Value::Object({
+            let mut map = HashMap::new();
+            map.insert("rows_affected".to_string(), Value::Number(affected as f64));
+            map.insert(
+                "last_insert_rowid".to_string(),
+                Value::Number(conn.last_insert_rowid() as f64),
+            );
+            map
+        }));
+    }
+
+    let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+    let mut rows = stmt.raw_query();
+    let mut out = Vec::new();
+    while let Some(row) = rows.next().map_err(sql_error)? {
+        let mut obj = HashMap::new();
+        for (i, column) in columns.iter().enumerate() {
+            let cell = row.get_ref(i).map_err(sql_error)?;
+            obj.insert(column.clone(), sql_to_value(cell));
+        }
+        out.push(Value::Object(obj));
+    }
+    Ok(Value::Array(out))
+}
+
+#[cfg(feature = "sqlite")]
+fn bind_params(stmt: &mut Statement<'_>, params: &Value) -> Result<(), HlxError> {
+    let expected = stmt.parameter_count();
+    match params {
+        Value::Null => {
+            if expected > 0 {
+                return Err(HlxError::invalid_parameters(
+                    "query",
+                    &format!("Query expects {} parameter(s) but none were given", expected),
+                ));
+            }
+        }
+        Value::Array(values) => {
+            if values.len() != expected {
+                return Err(HlxError::invalid_parameters(
+                    "query",
+                    &format!("Query expects {} parameter(s), got {}", expected, values.len()),
+                ));
+            }
+            for (i, value) in values.iter().enumerate() {
+                stmt.raw_bind_parameter(i + 1, value_to_sql(value))
+                    .map_err(|e| HlxError::invalid_parameters("query", &e.to_string()))?;
+            }
+        }
+        Value::Object(values) => {
+            for (name, value) in values {
+                let index = [":", "@", "$"]
+                    .iter()
+                    .find_map(|prefix| {
+                        stmt.parameter_index(&format!("{}{}", prefix, name.trim_start_matches([':', '@', '$'])))
+                            .ok()
+                            .flatten()
+                    })
+                    .ok_or_else(|| HlxError::invalid_parameters(
+                        "query",
+                        &format!("Query has no parameter named `{}`", name),
+                    ))?;
+                stmt.raw_bind_parameter(index, value_to_sql(value))
+                    .map_err(|e| HlxError::invalid_parameters("query", &e.to_string()))?;
+            }
+        }
+        other => {
+            return Err(HlxError::invalid_parameters(
+                "query",
+                &format!("Parameters must be an array or object, got {}", other),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Helix value -> SQLite value. Whole numbers bind as INTEGER, booleans as
+/// 0/1, and arrays/objects as JSON text.
+#[cfg(feature = "sqlite")]
+fn value_to_sql(value: &Value) -> rusqlite::types::Value {
+    use rusqlite::types::Value as SqlValue;
+    match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(*b as i64),
+        Value::Number(n) if n.fract() == 0.0 && n.abs() < 9_007_199_254_740_992.0 => {
+            SqlValue::Integer(*n as i64)
+        }
+        Value::Number(n) => SqlValue::Real(*n),
+        Value::String(s) | Value::Identifier(s) => SqlValue::Text(s.clone()),
+        Value::Array(_) | Value::Object(_) => {
+            SqlValue::Text(crate::ops::utils::value_to_json(value).to_string())
+        }
+        other => SqlValue::Text(other.to_string()),
+    }
+}
+
+/// SQLite cell -> Helix value. BLOBs are returned base64-encoded.
+#[cfg(feature = "sqlite")]
+fn sql_to_value(cell: ValueRef<'_>) -> Value {
+    use base64::{engine::general_purpose, Engine as _};
+    match cell {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(i) => Value::Number(i as f64),
+        ValueRef::Real(f) => Value::Number(f),
+        ValueRef::Text(t) => Value::String(String::from_utf8_lossy(t).to_string()),
+        ValueRef::Blob(b) => Value::String(general_purpose::STANDARD.encode(b)),
+    }
+}
+
+/// Declared SQL databases, shared by the operator engine and `@query`
+pub struct SqlOperators {
+    databases: RwLock<HashMap<String, DatabaseConfig>>,
+}
+
+impl SqlOperators {
+    pub async fn new() -> Result<Self, HlxError> {
+        Ok(Self {
+            databases: RwLock::new(HashMap::new()),
+        })
+    }
+
+    /// Make a `database` declaration addressable by name from `@query`
+    pub fn register(&self, config: &DatabaseConfig) -> Result<(), HlxError> {
+        let mut databases = self.databases
+            .write()
+            .map_err(|_| HlxError::validation_error("RwLock poisoned", "Check concurrency"))?;
+        databases.insert(config.name.clone(), config.clone());
+        Ok(())
+    }
+
+    /// Resolve a database name to its SQLite file. A name that is itself a
+    /// `.db`/`.sqlite` path is used directly (read-only).
+    pub fn target(&self, name: &str) -> Result<SqlTarget, HlxError> {
+        let databases = self.databases
+            .read()
+            .map_err(|_| HlxError::validation_error("RwLock poisoned", "Check concurrency"))?;
+        if let Some(config) = databases.get(name) {
+            return SqlTarget::from_config(config);
+        }
+        if looks_like_sqlite_file(name) {
+            return Ok(SqlTarget {
+                path: Path::new(name).to_path_buf(),
+                writable: false,
+            });
+        }
+        Err(HlxError::invalid_parameters(
+            "query",
+            &format!("Database `{}` is not declared", name),
+        ))
+    }
+
+    pub async fn execute(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
+        self.execute_impl(operator, params).await
+    }
+
+    async fn execute_impl(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
+        let params_map = crate::ops::args::bind_params(&self.descriptors(), &format!("sql.{}", operator), params)?;
+        match operator {
+            "query" => self.query_operator(&params_map).await,
+            _ => Err(HlxError::unknown_operator(format!("@sql.{}", operator))),
+        }
+    }
+
+    /// @sql.query – Run SQL against a declared database.
+    /// Parameters:
+    ///   * `sql` (String) – statement, optionally prefixed with `name:` (required)
+    ///   * `db` (String) – database name, defaults to `default`
+    ///   * `params` (Array | Object) – positional or named bind parameters
+    async fn query_operator(&self, params: &HashMap<String, Value>) -> Result<Value, HlxError> {
+        let (target, sql) = self.query_target(params)?;
+        run_query(&target, sql, params.get("params").unwrap_or(&Value::Null))
+    }
+
+    /// The database a bound `sql.query` call runs against, and its statement
+    /// with any `name:` prefix removed
+    fn query_target<'a>(&self, params: &'a HashMap<String, Value>) -> Result<(SqlTarget, &'a str), HlxError> {
+        let query = params
+            .get("sql")
+            .and_then(|v| v.as_string())
+            .ok_or_else(|| HlxError::invalid_parameters("sql.query", "Missing required parameter `sql`"))?;
+        let (prefix, sql) = split_database_prefix(query);
+        let db_name = prefix
+            .or_else(|| params.get("db").and_then(|v| v.as_string()))
+            .unwrap_or("default");
+        Ok((self.target(db_name)?, sql))
+    }
+}
+
+#[async_trait]
+impl OperatorTrait for SqlOperators {
+    async fn execute(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
+        self.execute_impl(operator, params).await
+    }
+
+    /// Only queries against read-only databases are cached; a writable one
+    /// may be changed by the statement itself.
+    fn inputs(&self, operator: &str, params: &str) -> Option<Vec<PathBuf>> {
+        if operator != "query" {
+            return None;
+        }
+        let params_map = crate::ops::args::bind_params(&self.descriptors(), "sql.query", params).ok()?;
+        let (target, _) = self.query_target(&params_map).ok()?;
+        if target.writable {
+            return None;
+        }
+        Some(vec![target.path.canonicalize().unwrap_or(target.path)])
+    }
+
+    fn descriptors(&self) -> Vec<OperatorDescriptor> {
+        vec![
+            OperatorDescriptor::new("sql.query", OperatorCategory::Data, "Run SQL against a declared SQLite database")
+                .param("sql", ValueType::String, "Statement, optionally prefixed with `name:`")
+                .optional("db", ValueType::String, "Declared database name, `default` if omitted")
+                .optional("params", ValueType::Any, "Positional array or named object of bindings")
+                .impure()
+                .memoize()
+                .example(r#"@sql.query({"sql": "SELECT * FROM runs WHERE id = ?", "params": [1]})"#),
+        ]
+    }
+}
+
+#[cfg(all(test, feature = "sqlite"))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn seeded(dir: &TempDir) -> SqlTarget {
+        let target = SqlTarget {
+            path: dir.path().join("runs.db"),
+            writable: true,
+        };
+        run_query(&target, "CREATE TABLE runs (id INTEGER, model TEXT, loss REAL)", &Value::Null).unwrap();
+        for (id, model, loss) in [(1, "small", 0.5), (2, "large", 0.25)] {
+            run_query(
+                &target,
+                "INSERT INTO runs (id, model, loss) VALUES (?, ?, ?)",
+                &Value::Array(vec![
+                    Value::Number(id as f64),
+                    Value::String(model.to_string()),
+                    Value::Number(loss),
+                ]),
+            )
+            .unwrap();
+        }
+        SqlTarget { writable: false, ..target }
+    }
+
+    #[test]
+    fn test_select_returns_rows_as_objects() {
+        let dir = TempDir::new().unwrap();
+        let target = seeded(&dir);
+        let rows = run_query(&target, "SELECT id, model, loss FROM runs ORDER BY id", &Value::Null).unwrap();
+        let rows = rows.as_array().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get("model"), Some(&Value::String("small".to_string())));
+        assert_eq!(rows[1].get("loss"), Some(&Value::Number(0.25)));
+    }
+
+    #[test]
+    fn test_positional_and_named_binding() {
+        let dir = TempDir::new().unwrap();
+        let target = seeded(&dir);
+        let rows = run_query(
+            &target,
+            "SELECT model FROM runs WHERE id = ?",
+            &Value::Array(vec![Value::Number(2.0)]),
+        )
+        .unwrap();
+        assert_eq!(rows.as_array().unwrap()[0].get("model"), Some(&Value::String("large".to_string())));
+
+        let mut named = HashMap::new();
+        named.insert("model".to_string(), Value::String("small".to_string()));
+        let rows = run_query(&target, "SELECT id FROM runs WHERE model = :model", &Value::Object(named)).unwrap();
+        assert_eq!(rows.as_array().unwrap()[0].get("id"), Some(&Value::Number(1.0)));
+
+        assert!(run_query(&target, "SELECT id FROM runs WHERE id = ?", &Value::Null).is_err());
+    }
+
+    #[test]
+    fn test_writes_require_opt_in() {
+        let dir = TempDir::new().unwrap();
+        let target = seeded(&dir);
+        let err = run_query(&target, "DELETE FROM runs", &Value::Null).unwrap_err();
+        assert!(err.to_string().contains("read-only"));
+        let rows = run_query(&target, "SELECT COUNT(*) AS n FROM runs", &Value::Null).unwrap();
+        assert_eq!(rows.as_array().unwrap()[0].get("n"), Some(&Value::Number(2.0)));
+    }
+
+    #[test]
+    fn test_split_database_prefix() {
+        assert_eq!(split_database_prefix("analytics:SELECT 1"), (Some("analytics"), "SELECT 1"));
+        assert_eq!(split_database_prefix("SELECT * FROM t WHERE id = :id"), (None, "SELECT * FROM t WHERE id = :id"));
+    }
+
+    #[test]
+    fn test_memoized_query_sees_commits_in_wal() {
+        use crate::dna::ops::memo::{hash_file, EvalCache};
+
+        let dir = TempDir::new().unwrap();
+        let target = seeded(&dir);
+        let writer = rusqlite::Connection::open(&target.path).unwrap();
+        let mode: String = writer.query_row("PRAGMA journal_mode=WAL", [], |row| row.get(0)).unwrap();
+        assert_eq!(mode, "wal");
+        writer.execute_batch("PRAGMA wal_autocheckpoint=0").unwrap();
+
+        let cache = EvalCache::new(dir.path().join("cache"));
+        let inputs = vec![target.path.clone()];
+        let sql = "SELECT COUNT(*) AS n FROM runs";
+        let count = || {
+            let rows = cache.get_or_insert_with("sql.query", sql, &inputs, || run_query(&target, sql, &Value::Null))?;
+            Ok::<_, HlxError>(rows.as_array().unwrap()[0].get("n").cloned())
+        };
+        assert_eq!(count().unwrap(), Some(Value::Number(2.0)));
+
+        // The commit only reaches the -wal file; the database file is untouched
+        let before = hash_file(&target.path).unwrap();
+        writer.execute("INSERT INTO runs (id, model, loss) VALUES (3, 'tiny', 0.75)", []).unwrap();
+        assert_eq!(hash_file(&target.path).unwrap(), before);
+        assert_eq!(count().unwrap(), Some(Value::Number(3.0)));
+    }
+
+    #[tokio::test]
+    async fn test_sql_operator_selects_declared_database() {
+        let dir = TempDir::new().unwrap();
+        let target = seeded(&dir);
+        let ops = SqlOperators::new().await.unwrap();
+        ops.register(&DatabaseConfig {
+            name: "metrics".to_string(),
+            path: Some(target.path.to_string_lossy().to_string()),
+            shards: None,
+            compression: None,
+            cache_size: None,
+            vector_index: None,
+            properties: HashMap::new(),
+        })
+        .unwrap();
+
+        let rows = ops
+            .execute("query", r#"{"sql": "metrics:SELECT model FROM runs WHERE id = ?", "params": [1]}"#)
+            .await
+            .unwrap();
+        assert_eq!(rows.as_array().unwrap()[0].get("model"), Some(&Value::String("small".to_string())));
+        assert!(ops.execute("query", r#"{"sql": "other:SELECT 1"}"#).await.is_err());
+    }
+}
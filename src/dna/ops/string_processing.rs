@@ -33,7 +33,7 @@ impl StringOperators {
     }
 
     async fn execute_impl(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
-        let params_map = crate::ops::utils::parse_params(params)?;
+        let params_map = crate::ops::args::bind_params(&self.descriptors(), operator, params)?;
 
         match operator {
             "concat" => self.concat_operator(&params_map).await,
@@ -54,6 +54,47 @@ impl crate::ops::OperatorTrait for StringOperators {
     async fn execute(&self, operator: &str, params: &str) -> Result<Value, HlxError> {
         self.execute_impl(operator, params).await
     }
+
+    fn descriptors(&self) -> Vec<crate::ops::meta::OperatorDescriptor> {
+        use crate::ops::meta::{OperatorCategory as Cat, OperatorDescriptor as Op, ValueType as T};
+        vec![
+            Op::new("concat", Cat::String, "Join strings with an optional separator")
+                .param("strings", T::Array, "Strings to join")
+                .optional("separator", T::String, "Separator")
+                .returns(T::Object)
+                .example(r#"@concat({"strings": ["a", "b"], "separator": "-"})"#),
+            Op::new("split", Cat::String, "Split a string on a delimiter")
+                .param("input", T::String, "Input text")
+                .optional("delimiter", T::String, "Delimiter")
+                .returns(T::Object),
+            Op::new("replace", Cat::String, "Replace every occurrence of a substring")
+                .param("input", T::String, "Input text")
+                .param("from", T::String, "Text to find")
+                .param("to", T::String, "Replacement")
+                .returns(T::Object),
+            Op::new("trim", Cat::String, "Trim whitespace")
+                .param("input", T::String, "Input text")
+                .optional("mode", T::String, "Which side to trim")
+                .choices(&["left", "right", "both"])
+                .returns(T::Object),
+            Op::new("upper", Cat::String, "Uppercase a string")
+                .param("input", T::String, "Input text")
+                .returns(T::Object),
+            Op::new("lower", Cat::String, "Lowercase a string")
+                .param("input", T::String, "Input text")
+                .returns(T::Object),
+            Op::new("hash", Cat::Encoding, "Hex digest of a string")
+                .param("input", T::String, "Input text")
+                .optional("algorithm", T::String, "Hash algorithm")
+                .choices(&["sha256", "md5"])
+                .returns(T::Object),
+            Op::new("format", Cat::String, "Fill `{name}` placeholders in a template")
+                .param("template", T::String, "Template text")
+                .param("variables", T::Object, "Placeholder values")
+                .returns(T::Object)
+                .example(r#"@format({"template": "Hi {name}", "variables": {"name": "Ada"}})"#),
+        ]
+    }
 }
 
 impl StringOperators {
@@ -17,7 +17,7 @@ pub fn parse_params(params: &str) -> Result<HashMap<String, Value>, HlxError> {
             }
             Ok(map)
         }
-        _ => Err(HlxError::invalid_parameters("unknown", params)),
+        _ => crate::ops::args::Args::unchecked("unknown", params).map(|args| args.into_values()),
     }
 }
 pub fn json_value_to_value(json_value: &JsonValue) -> Value {
@@ -80,9 +80,9 @@ pub fn value_to_json_value(value: &Value) -> JsonValue {
         }
         Value::Null => JsonValue::Null,
         Value::Duration(d) => JsonValue::String(format!("{} {:?}", d.value, d.unit)),
+        Value::Quantity(q) => JsonValue::String(q.to_string()),
         Value::Reference(r) => JsonValue::String(format!("@{}", r)),
         Value::Identifier(i) => JsonValue::String(i.clone()),
-        _ => JsonValue::Null,
     }
 }
 
@@ -106,6 +106,7 @@ match value {
     }
     Value::Null => serde_json::Value::Null,
     Value::Duration(d) => serde_json::Value::String(format!("{} {:?}", d.value, d.unit)),
+    Value::Quantity(q) => serde_json::Value::String(q.to_string()),
     Value::Reference(r) => serde_json::Value::String(format!("@{}", r)),
     Value::Identifier(i) => serde_json::Value::String(i.clone()),
 }
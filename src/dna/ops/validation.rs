@@ -517,6 +517,7 @@ impl SchemaValidator {
             Value::Object(_) => ValueType::Object,
             Value::Null => ValueType::Null,
             Value::Duration(_) => ValueType::String,
+            Value::Quantity(_) => ValueType::String,
             Value::Reference(_) => ValueType::String,
             Value::Identifier(_) => ValueType::String,
         }
@@ -8,6 +8,7 @@ use std::collections::HashMap;
 use std::sync::Arc;
 use serde::{Serialize, Deserialize};
 pub use crate::dna::hel::error::HlxError;
+use crate::dna::hel::limits::ReadLimits;
 
 /// Helix Data Format (.helix files) Magic Header
 pub const HELIX_DATA_MAGIC: &[u8; 4] = b"HLX\x01";
@@ -277,6 +278,7 @@ fn extract_preview_rows(batch: &RecordBatch, count: usize) -> Vec<serde_json::Va
 pub struct HlxReader<R: std::io::Read + Seek> {
     reader: R,
     header: Option<HlxHeader>,
+    limits: ReadLimits,
 }
 
 impl<R: std::io::Read + Seek> HlxReader<R> {
@@ -284,9 +286,16 @@ impl<R: std::io::Read + Seek> HlxReader<R> {
         Self {
             reader,
             header: None,
+            limits: ReadLimits::default(),
         }
     }
 
+    /// Bound the header and footer sizes and the nesting of their JSON
+    pub fn with_limits(mut self, limits: ReadLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
     /// Read and validate the header
     pub fn read_header(&mut self) -> Result<&HlxHeader, HlxError> {
         if self.header.is_some() {
@@ -306,12 +315,15 @@ impl<R: std::io::Read + Seek> HlxReader<R> {
         // Read header length
         let mut header_len_bytes = [0u8; 4];
         self.reader.read_exact(&mut header_len_bytes)?;
-        let header_len = u32::from_le_bytes(header_len_bytes) as usize;
+        let header_len = u32::from_le_bytes(header_len_bytes) as u64;
 
         // Read header JSON
-        let mut header_bytes = vec![0u8; header_len];
-        self.reader.read_exact(&mut header_bytes)?;
+        let header_bytes = self.limits.read_section("header", &mut self.reader, header_len)?;
         let header: HlxHeader = HlxHeader::from_json_bytes(&header_bytes)?;
+        self.limits.check_strings("schema", header.fields.len())?;
+        for value in header.metadata.values().chain(header.preview_rows.iter().flatten()) {
+            self.limits.check_json_depth(value)?;
+        }
 
         self.header = Some(header);
         Ok(self.header.as_ref().unwrap())
@@ -351,12 +363,17 @@ impl<R: std::io::Read + Seek> HlxReader<R> {
             return Ok(None); // No footer
         }
 
-        let footer_len = u32::from_le_bytes(footer_header[4..8].try_into().unwrap()) as usize;
+        let footer_len = u32::from_le_bytes(footer_header[4..8].try_into().unwrap()) as u64;
+        if footer_len > file_size - 8 {
+            return Err(HlxError::validation_error(
+                format!("Footer of {} bytes is larger than the file", footer_len),
+                "File may be truncated or corrupted"
+            ));
+        }
 
         // Read footer content
-        self.reader.seek(SeekFrom::End(-8 - footer_len as i64))?;
-        let mut footer_bytes = vec![0u8; footer_len];
-        self.reader.read_exact(&mut footer_bytes)?;
+        self.reader.seek(SeekFrom::Start(file_size - 8 - footer_len))?;
+        let footer_bytes = self.limits.read_section("footer", &mut self.reader, footer_len)?;
 
         let footer_jsonl = String::from_utf8(footer_bytes)
             .map_err(|_| HlxError::validation_error("Invalid UTF-8 in footer", ""))?;
@@ -366,6 +383,10 @@ impl<R: std::io::Read + Seek> HlxReader<R> {
             .filter(|line| !line.trim().is_empty())
             .map(|line| serde_json::from_str(line).unwrap_or(serde_json::Value::Null))
             .collect();
+        self.limits.check_strings("preview", rows.len())?;
+        for row in &rows {
+            self.limits.check_json_depth(row)?;
+        }
 
         Ok(Some(rows))
     }
@@ -489,4 +510,33 @@ mod tests {
         // For now, just test that the writer can be created
         assert!(writer.header.is_none());
     }
+
+    #[test]
+    fn test_oversized_lengths_fail_with_typed_errors() {
+        use crate::dna::hel::limits::LimitError;
+        let fixtures = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/malformed");
+        let huge_header = std::fs::read(fixtures.join("data_huge_header.helix")).unwrap();
+        assert!(matches!(
+            HlxReader::new(Cursor::new(huge_header)).read_header(),
+            Err(HlxError::LimitExceeded(LimitError::SectionTooLarge { .. }))
+        ));
+        let footer_overflow = std::fs::read(fixtures.join("data_footer_overflow.helix")).unwrap();
+        assert!(matches!(
+            HlxReader::new(Cursor::new(footer_overflow)).get_preview(),
+            Err(HlxError::ValidationError { .. })
+        ));
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn fuzz_helix_reader_never_panics(
+            bytes in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..256)
+        ) {
+            let bytes = [&HELIX_DATA_MAGIC[..], &bytes].concat();
+            let _ = HlxReader::new(Cursor::new(&bytes)).read_header().map(|header| header.row_count);
+            let _ = HlxReader::new(Cursor::new(&bytes)).get_preview();
+        }
+    }
 }
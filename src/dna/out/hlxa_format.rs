@@ -0,0 +1,223 @@
+//! HLXA Config Format (.hlxa files) - Archived configuration
+//! The payload is the archived form of a `HelixConfig`, laid out so that a
+//! memory-mapped file can be read in place once it has been validated,
+//! without deserializing into owned maps and strings
+
+use std::fs::File;
+use std::path::Path;
+use memmap2::Mmap;
+use rkyv::rancor::Error as RkyvError;
+use rkyv::util::AlignedVec;
+use crate::dna::hel::error::HlxError;
+pub use crate::dna::atp::types::{ArchivedHelixConfig, HelixConfig};
+
+/// HLXA Format Magic Header
+pub const HLXA_MAGIC: &[u8; 4] = b"HLXA";
+pub const HLXA_VERSION: u32 = 1;
+/// Magic, version and payload length. Sixteen bytes keeps the payload of a
+/// page-aligned mapping aligned for the archive.
+pub const HLXA_HEADER_SIZE: usize = 16;
+/// Alignment the payload needs to be accessed in place
+pub const HLXA_ALIGNMENT: usize = 16;
+
+/// Archive `config` into the bytes of a `.hlxa` file
+pub fn to_bytes(config: &HelixConfig) -> Result<AlignedVec<HLXA_ALIGNMENT>, HlxError> {
+    let payload = rkyv::to_bytes::<RkyvError>(config)
+        .map_err(|e| HlxError::serialization_error(
+            format!("Failed to archive config: {}", e),
+            "Check that the configuration contains only archivable values",
+        ))?;
+    let mut bytes = AlignedVec::<HLXA_ALIGNMENT>::with_capacity(HLXA_HEADER_SIZE + payload.len());
+    bytes.extend_from_slice(HLXA_MAGIC);
+    bytes.extend_from_slice(&HLXA_VERSION.to_le_bytes());
+    bytes.extend_from_slice(&(payload.len() as u64).to_le_bytes());
+    bytes.extend_from_slice(&payload);
+    Ok(bytes)
+}
+
+/// Validate the bytes of a `.hlxa` file and view them as a config.
+/// Nothing is copied; `bytes` must start at a 16-byte aligned address, which
+/// memory maps and the output of [`to_bytes`] always do.
+pub fn access(bytes: &[u8]) -> Result<&ArchivedHelixConfig, HlxError> {
+    let payload = payload(bytes)?;
+    rkyv::access::<ArchivedHelixConfig, RkyvError>(payload)
+        .map_err(|e| HlxError::deserialization_error(
+            format!("Invalid archived config: {}", e),
+            "The file is corrupted or was not written by this version of helix",
+        ))
+}
+
+/// Deserialize an archived config into an owned `HelixConfig`
+pub fn deserialize(config: &ArchivedHelixConfig) -> Result<HelixConfig, HlxError> {
+    rkyv::deserialize::<HelixConfig, RkyvError>(config)
+        .map_err(|e| HlxError::deserialization_error(
+            format!("Failed to deserialize archived config: {}", e),
+            "The file is corrupted or was not written by this version of helix",
+        ))
+}
+
+pub fn write_file(config: &HelixConfig, path: &Path) -> Result<(), HlxError> {
+    let bytes = to_bytes(config)?;
+    std::fs::write(path, &bytes[..])
+        .map_err(|e| HlxError::io_error(
+            format!("Failed to write {}: {}", path.display(), e),
+            "Check that the output directory exists and is writable",
+        ))
+}
+
+fn payload(bytes: &[u8]) -> Result<&[u8], HlxError> {
+    if bytes.len() < HLXA_HEADER_SIZE || &bytes[0..4] != HLXA_MAGIC {
+        return Err(HlxError::deserialization_error(
+            "Not an HLXA file",
+            "Write the file with hlxa_format::write_file",
+        ));
+    }
+    let version = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+    if version != HLXA_VERSION {
+        return Err(HlxError::deserialization_error(
+            format!("Unsupported HLXA version {}", version),
+            format!("This version of helix reads version {}", HLXA_VERSION),
+        ));
+    }
+    let len = u64::from_le_bytes(bytes[8..16].try_into().unwrap());
+    let payload = &bytes[HLXA_HEADER_SIZE..];
+    if len != payload.len() as u64 {
+        return Err(HlxError::deserialization_error(
+            format!("HLXA payload is {} bytes, header says {}", payload.len(), len),
+            "The file is truncated or corrupted",
+        ));
+    }
+    if !(payload.as_ptr() as usize).is_multiple_of(HLXA_ALIGNMENT) {
+        return Err(HlxError::deserialization_error(
+            "HLXA payload is not aligned",
+            "Read the file into an aligned buffer or open it with HlxaFile",
+        ));
+    }
+    Ok(payload)
+}
+
+/// A memory-mapped `.hlxa` file. The archive is validated once when the
+/// file is opened; [`HlxaFile::config`] then reads the mapping in place.
+/// Use [`access`] on a buffer read into memory when the file may change.
+pub struct HlxaFile {
+    mmap: Mmap,
+}
+
+impl HlxaFile {
+    /// Map and validate the archive at `path`.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be truncated or modified, by this or any other
+    /// process, while the returned `HlxaFile` is alive. Later reads trust the
+    /// bytes validated here, so a change underneath the mapping is
+    /// undefined behavior.
+    pub unsafe fn open(path: &Path) -> Result<Self, HlxError> {
+        let io_error = |e: std::io::Error| HlxError::io_error(
+            format!("Failed to map {}: {}", path.display(), e),
+            "Check that the file exists and is readable",
+        );
+        let file = File::open(path).map_err(io_error)?;
+        // SAFETY: the caller keeps the file unchanged while it is mapped
+        let mmap = unsafe { Mmap::map(&file) }.map_err(io_error)?;
+        access(&mmap)?;
+        Ok(Self { mmap })
+    }
+
+    pub fn config(&self) -> &ArchivedHelixConfig {
+        // SAFETY: the payload was validated by `access` in `open`, and the
+        // contract of `open` keeps it unchanged since
+        unsafe {
+            rkyv::access_unchecked::<ArchivedHelixConfig>(&self.mmap[HLXA_HEADER_SIZE..])
+        }
+    }
+
+    pub fn deserialize(&self) -> Result<HelixConfig, HlxError> {
+        deserialize(self.config())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dna::atp::types::{AgentConfig, Duration, TimeUnit, Value};
+    use crate::dna::atp::quantity::{Quantity, Unit};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn sample_config() -> HelixConfig {
+        let mut config = HelixConfig::default();
+        config.agents.insert("researcher".to_string(), AgentConfig {
+            name: "researcher".to_string(),
+            model: "gpt-4".to_string(),
+            role: "Research".to_string(),
+            temperature: Some(0.5),
+            max_tokens: Some(4096),
+            capabilities: vec!["search".to_string(), "summarize".to_string()],
+            backstory: None,
+            tools: vec![],
+            constraints: vec![],
+        });
+        let mut nested = HashMap::new();
+        nested.insert("window".to_string(), Value::Duration(Duration {
+            value: 30,
+            unit: TimeUnit::Minutes,
+        }));
+        nested.insert("memory".to_string(), Value::Quantity(Quantity::new(16.0, Unit::GiB)));
+        let mut section = HashMap::new();
+        section.insert("limits".to_string(), Value::Object(nested));
+        section.insert("hosts".to_string(), Value::Array(vec![
+            Value::String("a".to_string()),
+            Value::Null,
+        ]));
+        config.sections.insert("cache".to_string(), section);
+        config
+    }
+
+    #[test]
+    fn test_archived_config_reads_in_place() {
+        let bytes = to_bytes(&sample_config()).unwrap();
+        let archived = access(&bytes).unwrap();
+        let agent = archived.agents.get("researcher").unwrap();
+        assert_eq!(agent.model.as_str(), "gpt-4");
+        assert_eq!(agent.max_tokens.as_ref().map(|t| t.to_native()), Some(4096));
+        assert_eq!(agent.capabilities[1].as_str(), "summarize");
+        assert_eq!(archived.sections.get("cache").unwrap().len(), 2);
+
+        let owned = deserialize(archived).unwrap();
+        let limits = &owned.sections["cache"]["limits"];
+        match limits {
+            Value::Object(map) => {
+                assert!(matches!(&map["memory"], Value::Quantity(q) if *q == Quantity::new(16.0, Unit::GiB)));
+                assert!(matches!(&map["window"], Value::Duration(d) if d.value == 30));
+            }
+            other => panic!("expected an object, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_mapped_file_is_validated_on_open() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.hlxa");
+        write_file(&sample_config(), &path).unwrap();
+        // SAFETY: the temporary file is not modified while it is mapped
+        let file = unsafe { HlxaFile::open(&path) }.unwrap();
+        assert_eq!(file.config().agents.len(), 1);
+        assert_eq!(file.deserialize().unwrap().agents["researcher"].role, "Research");
+        drop(file);
+
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes.truncate(bytes.len() - 1);
+        std::fs::write(&path, &bytes).unwrap();
+        // SAFETY: as above
+        assert!(unsafe { HlxaFile::open(&path) }.is_err());
+
+        let mut bytes = to_bytes(&sample_config()).unwrap();
+        bytes[0] = b'X';
+        assert!(access(&bytes).is_err());
+        let mut bytes = to_bytes(&sample_config()).unwrap();
+        let len = bytes.len();
+        bytes[len - 4..].copy_from_slice(&[0xff; 4]);
+        assert!(access(&bytes).is_err());
+    }
+}
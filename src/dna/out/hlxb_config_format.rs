@@ -1,8 +1,11 @@
 use std::collections::HashMap;
 use std::io::{Read, Write, Seek, SeekFrom};
 use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
 use crate::dna::hel::error::HlxError;
+use crate::dna::hel::limits::{ReadError, ReadLimits};
 pub use crate::dna::atp::types::{AgentConfig, WorkflowConfig, CrewConfig, ContextConfig, HelixConfig};
+use crate::dna::atp::types::Value;
 #[cfg(feature = "zstd")]
 use zstd::{Encoder, Decoder};
 #[cfg(feature = "lz4_flex")]
@@ -10,7 +13,7 @@ use lz4_flex::{compress_prepend_size, decompress_size_prepended};
 #[cfg(feature = "flate2")]
 use flate2::{Compression, write::GzEncoder, read::GzDecoder};
 #[cfg(feature = "bincode")]
-use bincode::{serialize, deserialize};
+use bincode::serialize;
 #[cfg(feature = "crc32fast")]
 use crc32fast::Hasher as Crc32Hasher;
 
@@ -19,7 +22,9 @@ use crc32fast::Hasher as Crc32Hasher;
 
 /// HLXB Format Magic Header
 pub const HLXB_MAGIC: &[u8; 4] = b"HLXB";
-pub const HLXB_VERSION: u8 = 1;
+/// Version 2 writes one section per named entry and records an index of
+/// them, whose offset follows the section count in the header
+pub const HLXB_VERSION: u8 = 2;
 
 /// HLXB Config structure (same as HLX but optimized for binary)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +52,9 @@ pub struct HlxbHeader {
     pub version: u8,
     pub created_at: u64,
     pub section_count: u32,
+    /// Offset of the index section; `None` for version 1 files and files
+    /// written without any named entries
+    pub index_offset: Option<u64>,
 }
 
 /// Section type identifiers
@@ -58,6 +66,36 @@ pub enum SectionType {
     Crews = 0x03,
     Contexts = 0x04,
     Metadata = 0x05,
+    Sections = 0x06,
+    Index = 0x07,
+}
+
+impl SectionType {
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0x01 => Some(SectionType::Agents),
+            0x02 => Some(SectionType::Workflows),
+            0x03 => Some(SectionType::Crews),
+            0x04 => Some(SectionType::Contexts),
+            0x05 => Some(SectionType::Metadata),
+            0x06 => Some(SectionType::Sections),
+            0x07 => Some(SectionType::Index),
+            _ => None,
+        }
+    }
+
+    /// Prefix used for entries of this type, as in `agents/researcher`
+    pub fn name(&self) -> &'static str {
+        match self {
+            SectionType::Agents => "agents",
+            SectionType::Workflows => "workflows",
+            SectionType::Crews => "crews",
+            SectionType::Contexts => "contexts",
+            SectionType::Metadata => "metadata",
+            SectionType::Sections => "sections",
+            SectionType::Index => "index",
+        }
+    }
 }
 
 /// Compression algorithms supported
@@ -232,6 +270,58 @@ impl CompressionManager {
         }
     }
 
+    /// Decompress data from an untrusted source, refusing output beyond
+    /// what `limits` allow for its size
+    pub fn decompress_within(data: &[u8], algorithm: CompressionAlgorithm, limits: &ReadLimits) -> Result<Vec<u8>, HlxError> {
+        let compressed = data.len() as u64;
+        let bounded = |result: Result<Vec<u8>, ReadError>, name: &str| {
+            result.map_err(|e| match e {
+                ReadError::Io(e) => HlxError::decompression_error(
+                    format!("{} decompression failed: {}", name, e),
+                    "Data may be corrupted or compressed with different settings"
+                ),
+                other => other.into(),
+            })
+        };
+        match algorithm {
+            CompressionAlgorithm::None => Ok(data.to_vec()),
+            CompressionAlgorithm::Lz4 => {
+                // The output size is prepended; check it before allocating
+                let declared = data
+                    .get(..4)
+                    .map(|size| u32::from_le_bytes([size[0], size[1], size[2], size[3]]) as u64)
+                    .unwrap_or(0);
+                limits.check_decompressed("lz4", compressed, declared)?;
+                Self::decompress(data, algorithm)
+            }
+            CompressionAlgorithm::Zstd => {
+                #[cfg(feature = "zstd")]
+                {
+                    let decoder = Decoder::new(data)
+                        .map_err(|e| HlxError::decompression_error(
+                            format!("Failed to create ZSTD decoder: {}", e),
+                            "Data may be corrupted"
+                        ))?;
+                    bounded(limits.decompress("zstd", decoder, compressed), "ZSTD")
+                }
+                #[cfg(not(feature = "zstd"))]
+                {
+                    Err(HlxError::feature_error("zstd", "ZSTD decompression requires zstd feature"))
+                }
+            }
+            CompressionAlgorithm::Gzip => {
+                #[cfg(feature = "flate2")]
+                {
+                    bounded(limits.decompress("gzip", GzDecoder::new(data), compressed), "GZIP")
+                }
+                #[cfg(not(feature = "flate2"))]
+                {
+                    Err(HlxError::feature_error("flate2", "GZIP decompression requires flate2 feature"))
+                }
+            }
+        }
+    }
+
     /// Benchmark compression algorithms for given data and return the best one
     pub fn benchmark_and_select(data: &[u8]) -> CompressionAlgorithm {
         if data.len() < 1024 {
@@ -273,21 +363,52 @@ impl CompressionManager {
 /// Section header for each data section
 #[derive(Debug)]
 pub struct SectionHeader {
-    pub section_type: SectionType,
+    /// `None` for types added after this build; their data is skipped
+    pub section_type: Option<SectionType>,
     pub uncompressed_size: u64,
     pub compressed_size: u64,
     pub crc32_checksum: u32,
 }
 
+/// Location of one named entry, such as `agents/researcher`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Raw [`SectionType`] byte
+    pub section_type: u8,
+    pub name: String,
+    /// Offset of the section header holding the entry
+    pub offset: u64,
+    pub compressed_size: u64,
+    pub uncompressed_size: u64,
+}
+
+impl IndexEntry {
+    pub fn section_type(&self) -> Option<SectionType> {
+        SectionType::from_byte(self.section_type)
+    }
+
+    /// `agents/researcher`, or just the name for unknown section types
+    pub fn path(&self) -> String {
+        match self.section_type() {
+            Some(section_type) => format!("{}/{}", section_type.name(), self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Size of the header up to and including the section count
+const SECTION_COUNT_END: u64 = 4 + 1 + 8 + 4;
+
 /// HLXB Writer for creating binary config files
 pub struct HlxbWriter<W: Write + Seek> {
     writer: W,
     section_count: u32,
+    index: Vec<IndexEntry>,
 }
 
 impl<W: Write + Seek> HlxbWriter<W> {
     pub fn new(writer: W) -> Self {
-        Self { writer, section_count: 0 }
+        Self { writer, section_count: 0, index: Vec::new() }
     }
 
     pub fn write_header(&mut self) -> Result<(), HlxError> {
@@ -307,11 +428,15 @@ impl<W: Write + Seek> HlxbWriter<W> {
         // Write placeholder for section count (will be updated in finalize)
         self.writer.write_all(&0u32.to_le_bytes())?;
 
+        // Write placeholder for the index offset (will be updated in finalize)
+        self.writer.write_all(&0u64.to_le_bytes())?;
+
         Ok(())
     }
 
-    /// Write a section with compression and checksum
-    fn write_section<T: Serialize>(&mut self, section_type: SectionType, data: &T) -> Result<(), HlxError> {
+    /// Write a section with compression and checksum, indexing it under
+    /// `name` if one is given
+    fn write_section<T: Serialize>(&mut self, section_type: SectionType, name: Option<&str>, data: &T) -> Result<(), HlxError> {
         #[cfg(not(feature = "bincode"))]
         return Err(HlxError::feature_error("bincode", "Binary serialization requires bincode feature"));
 
@@ -360,6 +485,16 @@ impl<W: Write + Seek> HlxbWriter<W> {
                 hasher.finalize()
             };
 
+            if let Some(name) = name {
+                self.index.push(IndexEntry {
+                    section_type: section_type as u8,
+                    name: name.to_string(),
+                    offset: self.writer.stream_position()?,
+                    compressed_size,
+                    uncompressed_size,
+                });
+            }
+
             // Write section header
             self.writer.write_all(&[section_type as u8])?;
             self.writer.write_all(&uncompressed_size.to_le_bytes())?;
@@ -375,33 +510,59 @@ impl<W: Write + Seek> HlxbWriter<W> {
         }
     }
 
+    /// Write each entry as its own single-entry section so readers can
+    /// seek straight to it
+    fn write_entries<T: Serialize>(&mut self, section_type: SectionType, entries: &HashMap<String, T>) -> Result<(), HlxError> {
+        let mut names: Vec<&String> = entries.keys().collect();
+        names.sort();
+        for name in names {
+            let entry: HashMap<&str, &T> = HashMap::from([(name.as_str(), &entries[name])]);
+            self.write_section(section_type, Some(name), &entry)?;
+        }
+        Ok(())
+    }
+
     pub fn write_agents(&mut self, agents: &HashMap<String, AgentConfig>) -> Result<(), HlxError> {
-        self.write_section(SectionType::Agents, agents)
+        self.write_entries(SectionType::Agents, agents)
     }
 
     pub fn write_workflows(&mut self, workflows: &HashMap<String, WorkflowConfig>) -> Result<(), HlxError> {
-        self.write_section(SectionType::Workflows, workflows)
+        self.write_entries(SectionType::Workflows, workflows)
     }
 
     pub fn write_crews(&mut self, crews: &HashMap<String, CrewConfig>) -> Result<(), HlxError> {
-        self.write_section(SectionType::Crews, crews)
+        self.write_entries(SectionType::Crews, crews)
     }
 
     pub fn write_contexts(&mut self, contexts: &HashMap<String, ContextConfig>) -> Result<(), HlxError> {
-        self.write_section(SectionType::Contexts, contexts)
+        self.write_entries(SectionType::Contexts, contexts)
+    }
+
+    pub fn write_sections(&mut self, sections: &HashMap<String, HashMap<String, Value>>) -> Result<(), HlxError> {
+        self.write_entries(SectionType::Sections, sections)
     }
 
     pub fn write_metadata(&mut self, metadata: &HashMap<String, serde_json::Value>) -> Result<(), HlxError> {
-        self.write_section(SectionType::Metadata, metadata)
+        self.write_section(SectionType::Metadata, None, metadata)
     }
 
     pub fn finalize(&mut self) -> Result<(), HlxError> {
+        // Write the index last, once every entry's offset is known
+        let mut index_offset = 0u64;
+        if !self.index.is_empty() {
+            index_offset = self.writer.stream_position()?;
+            let index = std::mem::take(&mut self.index);
+            self.write_section(SectionType::Index, None, &index)?;
+            self.index = index;
+        }
+
         // Seek back to the section count position in header (after magic + version + timestamp)
         let section_count_pos = HLXB_MAGIC.len() + 1 + 8; // magic(4) + version(1) + timestamp(8)
         self.writer.seek(SeekFrom::Start(section_count_pos as u64))?;
 
-        // Write the actual section count
+        // Write the actual section count and the index offset after it
         self.writer.write_all(&self.section_count.to_le_bytes())?;
+        self.writer.write_all(&index_offset.to_le_bytes())?;
 
         Ok(())
     }
@@ -410,11 +571,20 @@ impl<W: Write + Seek> HlxbWriter<W> {
 /// HLXB Reader for parsing binary config files
 pub struct HlxbReader<R: Read + Seek> {
     reader: R,
+    index: Option<Vec<IndexEntry>>,
+    limits: ReadLimits,
 }
 
 impl<R: Read + Seek> HlxbReader<R> {
     pub fn new(reader: R) -> Self {
-        Self { reader }
+        Self { reader, index: None, limits: ReadLimits::default() }
+    }
+
+    /// Bound section sizes, decompression and nesting; the defaults suit
+    /// any config a person would write
+    pub fn with_limits(mut self, limits: ReadLimits) -> Self {
+        self.limits = limits;
+        self
     }
 
     pub fn read_header(&mut self) -> Result<HlxbHeader, HlxError> {
@@ -434,8 +604,8 @@ impl<R: Read + Seek> HlxbReader<R> {
         let mut version = [0u8; 1];
         self.reader.read_exact(&mut version)?;
 
-        // Validate version
-        if version[0] != HLXB_VERSION {
+        // Validate version; every earlier version is still readable
+        if version[0] == 0 || version[0] > HLXB_VERSION {
             return Err(HlxError::validation_error(
                 format!("Unsupported HLXB version: {} (expected {})", version[0], HLXB_VERSION),
                 "File was created with an incompatible version of Helix"
@@ -452,11 +622,20 @@ impl<R: Read + Seek> HlxbReader<R> {
         self.reader.read_exact(&mut section_count_bytes)?;
         let section_count = u32::from_le_bytes(section_count_bytes);
 
+        // Read index offset, added in version 2
+        let mut index_offset = None;
+        if version[0] >= 2 {
+            let mut index_offset_bytes = [0u8; 8];
+            self.reader.read_exact(&mut index_offset_bytes)?;
+            index_offset = Some(u64::from_le_bytes(index_offset_bytes)).filter(|&offset| offset != 0);
+        }
+
         Ok(HlxbHeader {
             magic,
             version: version[0],
             created_at,
             section_count,
+            index_offset,
         })
     }
 
@@ -465,17 +644,7 @@ impl<R: Read + Seek> HlxbReader<R> {
         let mut section_type_byte = [0u8; 1];
         self.reader.read_exact(&mut section_type_byte)?;
 
-        let section_type = match section_type_byte[0] {
-            0x01 => SectionType::Agents,
-            0x02 => SectionType::Workflows,
-            0x03 => SectionType::Crews,
-            0x04 => SectionType::Contexts,
-            0x05 => SectionType::Metadata,
-            _ => return Err(HlxError::validation_error(
-                format!("Unknown section type: 0x{:02x}", section_type_byte[0]),
-                "File contains an unsupported section type"
-            )),
-        };
+        let section_type = SectionType::from_byte(section_type_byte[0]);
 
         let mut uncompressed_size_bytes = [0u8; 8];
         self.reader.read_exact(&mut uncompressed_size_bytes)?;
@@ -489,6 +658,11 @@ impl<R: Read + Seek> HlxbReader<R> {
         self.reader.read_exact(&mut checksum_bytes)?;
         let crc32_checksum = u32::from_le_bytes(checksum_bytes);
 
+        // Refuse sizes no reader should allocate before trusting them
+        let name = section_type.map_or("unknown", |t| t.name());
+        self.limits.check_section(name, compressed_size)?;
+        self.limits.check_decompressed(name, compressed_size, uncompressed_size)?;
+
         Ok(SectionHeader {
             section_type,
             uncompressed_size,
@@ -499,9 +673,10 @@ impl<R: Read + Seek> HlxbReader<R> {
 
     /// Read and decompress section data
     fn read_section_data(&mut self, header: &SectionHeader) -> Result<Vec<u8>, HlxError> {
+        let name = header.section_type.map_or("unknown", |t| t.name());
+
         // Read compressed data
-        let mut compressed_data = vec![0u8; header.compressed_size as usize];
-        self.reader.read_exact(&mut compressed_data)?;
+        let compressed_data = self.limits.read_section(name, &mut self.reader, header.compressed_size)?;
 
         // Verify checksum
         #[cfg(feature = "crc32fast")]
@@ -522,18 +697,20 @@ impl<R: Read + Seek> HlxbReader<R> {
         let decompressed_data = compressed_data;
         #[cfg(feature = "zstd")]
         let decompressed_data = {
-            let mut decoder = Decoder::new(&compressed_data[..])
+            let decoder = Decoder::new(&compressed_data[..])
                 .map_err(|e| HlxError::decompression_error(
                     format!("Failed to create ZSTD decoder: {}", e),
                     "File may be corrupted"
                 ))?;
-            let mut decompressed = Vec::new();
-            decoder.read_to_end(&mut decompressed)
-                .map_err(|e| HlxError::decompression_error(
-                    format!("Failed to decompress data: {}", e),
-                    "File may be corrupted or compressed with incompatible settings"
-                ))?;
-            decompressed
+            self.limits
+                .decompress(name, decoder, header.compressed_size)
+                .map_err(|e| match e {
+                    ReadError::Io(e) => HlxError::decompression_error(
+                        format!("Failed to decompress data: {}", e),
+                        "File may be corrupted or compressed with incompatible settings"
+                    ),
+                    other => other.into(),
+                })?
         };
 
         // Verify decompressed size
@@ -553,62 +730,195 @@ impl<R: Read + Seek> HlxbReader<R> {
 
         #[cfg(feature = "bincode")]
         {
+            // The index lookups may have moved the reader
+            self.reader.seek(SeekFrom::Start(0))?;
             let header = self.read_header()?;
 
             let mut config = HelixConfig::default();
 
             for _ in 0..header.section_count {
                 let section_header = self.read_section_header()?;
+                let Some(section_type) = section_header.section_type else {
+                    // Written by a newer version; sizes are known, so step over it
+                    self.reader.seek(SeekFrom::Current(section_header.compressed_size as i64))?;
+                    continue;
+                };
                 let section_data = self.read_section_data(&section_header)?;
 
-                match section_header.section_type {
+                match section_type {
                     SectionType::Agents => {
-                        let agents: HashMap<String, AgentConfig> = deserialize(&section_data)
-                            .map_err(|e| HlxError::deserialization_error(
-                                format!("Failed to deserialize agents section: {}", e),
-                                "Check agents configuration"
-                            ))?;
-                        config.agents = agents;
+                        let agents: HashMap<String, AgentConfig> = self.decode_section("agents", &section_data)?;
+                        config.agents.extend(agents);
                     }
                     SectionType::Workflows => {
-                        let workflows: HashMap<String, WorkflowConfig> = deserialize(&section_data)
-                            .map_err(|e| HlxError::deserialization_error(
-                                format!("Failed to deserialize workflows section: {}", e),
-                                "Check workflows configuration"
-                            ))?;
-                        config.workflows = workflows;
+                        let workflows: HashMap<String, WorkflowConfig> = self.decode_section("workflows", &section_data)?;
+                        config.workflows.extend(workflows);
                     }
                     SectionType::Crews => {
-                        let crews: HashMap<String, CrewConfig> = deserialize(&section_data)
-                            .map_err(|e| HlxError::deserialization_error(
-                                format!("Failed to deserialize crews section: {}", e),
-                                "Check crews configuration"
-                            ))?;
-                        config.crews = crews;
+                        let crews: HashMap<String, CrewConfig> = self.decode_section("crews", &section_data)?;
+                        config.crews.extend(crews);
                     }
                     SectionType::Contexts => {
-                        let contexts: HashMap<String, ContextConfig> = deserialize(&section_data)
-                            .map_err(|e| HlxError::deserialization_error(
-                                format!("Failed to deserialize contexts section: {}", e),
-                                "Check contexts configuration"
-                            ))?;
-                        config.contexts = contexts;
+                        let contexts: HashMap<String, ContextConfig> = self.decode_section("contexts", &section_data)?;
+                        config.contexts.extend(contexts);
+                    }
+                    SectionType::Sections => {
+                        let sections: HashMap<String, HashMap<String, Value>> = self.decode_section("sections", &section_data)?;
+                        config.sections.extend(sections);
                     }
                     SectionType::Metadata => {
                         // HelixConfig doesn't have a metadata field, skip for now
                         // TODO: Add metadata support to HelixConfig if needed
-                        let _metadata: HashMap<String, serde_json::Value> = deserialize(&section_data)
-                            .map_err(|e| HlxError::deserialization_error(
-                                format!("Failed to deserialize metadata section: {}", e),
-                                "Check metadata configuration"
-                            ))?;
+                        let _metadata: HashMap<String, serde_json::Value> = self.decode_section("metadata", &section_data)?;
                     }
+                    // Only needed for partial loading
+                    SectionType::Index => {}
                 }
             }
 
             Ok(config)
         }
     }
+
+    /// Where each named entry lives in the file. Version 1 files have no
+    /// stored index, so theirs is rebuilt by scanning every section once.
+    pub fn read_index(&mut self) -> Result<&[IndexEntry], HlxError> {
+        if self.index.is_none() {
+            self.reader.seek(SeekFrom::Start(0))?;
+            let header = self.read_header()?;
+            let index = match header.index_offset {
+                Some(offset) => self.read_stored_index(offset)?,
+                None => self.scan_index(header.section_count)?,
+            };
+            self.index = Some(index);
+        }
+        Ok(self.index.as_deref().unwrap_or_default())
+    }
+
+    /// Load a single agent without reading the rest of the file
+    pub fn get_agent(&mut self, name: &str) -> Result<Option<AgentConfig>, HlxError> {
+        self.read_entry(SectionType::Agents, name)
+    }
+
+    /// Load a single named section without reading the rest of the file
+    pub fn get_section(&mut self, name: &str) -> Result<Option<HashMap<String, Value>>, HlxError> {
+        self.read_entry(SectionType::Sections, name)
+    }
+
+    pub fn get_workflow(&mut self, name: &str) -> Result<Option<WorkflowConfig>, HlxError> {
+        self.read_entry(SectionType::Workflows, name)
+    }
+
+    pub fn get_crew(&mut self, name: &str) -> Result<Option<CrewConfig>, HlxError> {
+        self.read_entry(SectionType::Crews, name)
+    }
+
+    pub fn get_context(&mut self, name: &str) -> Result<Option<ContextConfig>, HlxError> {
+        self.read_entry(SectionType::Contexts, name)
+    }
+
+    fn read_entry<T: DeserializeOwned>(&mut self, section_type: SectionType, name: &str) -> Result<Option<T>, HlxError> {
+        let offset = self
+            .read_index()?
+            .iter()
+            .find(|entry| entry.section_type == section_type as u8 && entry.name == name)
+            .map(|entry| entry.offset);
+        let Some(offset) = offset else {
+            return Ok(None);
+        };
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let mut entries: HashMap<String, T> = self.read_indexed_section(section_type)?;
+        Ok(entries.remove(name))
+    }
+
+    /// Read and decode the section starting at the current position,
+    /// which the index says is of type `expected`
+    fn read_indexed_section<T: DeserializeOwned>(&mut self, expected: SectionType) -> Result<T, HlxError> {
+        #[cfg(not(feature = "bincode"))]
+        return Err(HlxError::feature_error("bincode", "Binary deserialization requires bincode feature"));
+
+        #[cfg(feature = "bincode")]
+        {
+            let section_header = self.read_section_header()?;
+            if section_header.section_type.map(|t| t as u8) != Some(expected as u8) {
+                return Err(HlxError::validation_error(
+                    format!("Index points at a {:?} section, expected {:?}", section_header.section_type, expected),
+                    "File may be corrupted"
+                ));
+            }
+            let section_data = self.read_section_data(&section_header)?;
+            self.decode_section(expected.name(), &section_data)
+        }
+    }
+
+    /// Decode a section's bincode within the reader's limits
+    fn decode_section<T: DeserializeOwned>(&self, name: &str, data: &[u8]) -> Result<T, HlxError> {
+        self.limits.decode(data).map_err(|e| match e {
+            ReadError::Limit(limit) => HlxError::LimitExceeded(limit),
+            other => HlxError::deserialization_error(
+                format!("Failed to deserialize {} section: {}", name, other),
+                "File may be corrupted"
+            ),
+        })
+    }
+
+    fn read_stored_index(&mut self, offset: u64) -> Result<Vec<IndexEntry>, HlxError> {
+        if offset < SECTION_COUNT_END {
+            return Err(HlxError::validation_error(
+                format!("Index offset {} points into the header", offset),
+                "File may be corrupted"
+            ));
+        }
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let index: Vec<IndexEntry> = self.read_indexed_section(SectionType::Index)?;
+        self.limits.check_strings("index", index.len())?;
+        Ok(index)
+    }
+
+    fn scan_index(&mut self, section_count: u32) -> Result<Vec<IndexEntry>, HlxError> {
+        let mut index = Vec::new();
+        for _ in 0..section_count {
+            let offset = self.reader.stream_position()?;
+            let section_header = self.read_section_header()?;
+            let names = match section_header.section_type {
+                Some(SectionType::Agents) => self.section_names::<AgentConfig>(&section_header)?,
+                Some(SectionType::Workflows) => self.section_names::<WorkflowConfig>(&section_header)?,
+                Some(SectionType::Crews) => self.section_names::<CrewConfig>(&section_header)?,
+                Some(SectionType::Contexts) => self.section_names::<ContextConfig>(&section_header)?,
+                Some(SectionType::Sections) => self.section_names::<HashMap<String, Value>>(&section_header)?,
+                _ => {
+                    self.reader.seek(SeekFrom::Current(section_header.compressed_size as i64))?;
+                    continue;
+                }
+            };
+            self.limits.check_strings("index", index.len() + names.len())?;
+            for name in names {
+                index.push(IndexEntry {
+                    section_type: section_header.section_type.map_or(0, |t| t as u8),
+                    name,
+                    offset,
+                    compressed_size: section_header.compressed_size,
+                    uncompressed_size: section_header.uncompressed_size,
+                });
+            }
+        }
+        Ok(index)
+    }
+
+    fn section_names<T: DeserializeOwned>(&mut self, section_header: &SectionHeader) -> Result<Vec<String>, HlxError> {
+        #[cfg(not(feature = "bincode"))]
+        return Err(HlxError::feature_error("bincode", "Binary deserialization requires bincode feature"));
+
+        #[cfg(feature = "bincode")]
+        {
+            let section_data = self.read_section_data(section_header)?;
+            let name = section_header.section_type.map_or("unknown", |t| t.name());
+            let entries: HashMap<String, T> = self.decode_section(name, &section_data)?;
+            let mut names: Vec<String> = entries.into_keys().collect();
+            names.sort();
+            Ok(names)
+        }
+    }
 }
 
 /// HLXB Config Reader/Writer
@@ -647,6 +957,7 @@ impl HlxbConfigHandler {
         hlxb_writer.write_workflows(&config.workflows)?;
         hlxb_writer.write_crews(&config.crews)?;
         hlxb_writer.write_contexts(&config.contexts)?;
+        hlxb_writer.write_sections(&config.sections)?;
 
         hlxb_writer.finalize()
     }
@@ -673,25 +984,25 @@ impl HlxbConfigHandler {
         // Read version
         let mut version = [0u8; 1];
         reader.read_exact(&mut version)?;
-        if version[0] != HLXB_VERSION {
+        if version[0] == 0 || version[0] > HLXB_VERSION {
             return Err(HlxError::validation_error(
                 format!("Unsupported HLXB version: {}", version[0]),
-                "Only version 1 is supported"
+                format!("Versions 1 to {} are supported", HLXB_VERSION)
             ));
         }
 
         // Read data length
         let mut len_bytes = [0u8; 8];
         reader.read_exact(&mut len_bytes)?;
-        let data_len = u64::from_le_bytes(len_bytes) as usize;
+        let data_len = u64::from_le_bytes(len_bytes);
 
         // Read compressed data
-        let mut compressed_data = vec![0u8; data_len];
-        reader.read_exact(&mut compressed_data)?;
+        let limits = ReadLimits::default();
+        let compressed_data = limits.read_section("config", reader, data_len)?;
 
         // Select decompression algorithm based on data characteristics
         let algorithm = CompressionAlgorithm::select_best(&compressed_data);
-        let decompressed_data = CompressionManager::decompress(&compressed_data, algorithm)?;
+        let decompressed_data = CompressionManager::decompress_within(&compressed_data, algorithm, &limits)?;
 
         // Convert to string
         let json_data = String::from_utf8(decompressed_data)
@@ -772,6 +1083,127 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_reader_skips_sections_from_newer_writers() {
+        let mut agents = HashMap::new();
+        agents.insert("assistant".to_string(), AgentConfig {
+            name: "assistant".to_string(),
+            model: "gpt-4".to_string(),
+            role: "helper".to_string(),
+            temperature: None,
+            max_tokens: None,
+            capabilities: Vec::new(),
+            backstory: None,
+            tools: Vec::new(),
+            constraints: Vec::new(),
+        });
+        let mut cursor = Cursor::new(Vec::new());
+        {
+            let mut writer = HlxbWriter::new(&mut cursor);
+            writer.write_header().unwrap();
+            // A section type this build doesn't know, as a later version might add
+            writer.writer.write_all(&[0x7f]).unwrap();
+            writer.writer.write_all(&3u64.to_le_bytes()).unwrap();
+            writer.writer.write_all(&3u64.to_le_bytes()).unwrap();
+            writer.writer.write_all(&0u32.to_le_bytes()).unwrap();
+            writer.writer.write_all(b"new").unwrap();
+            writer.section_count += 1;
+            writer.write_agents(&agents).unwrap();
+            writer.finalize().unwrap();
+        }
+        cursor.set_position(0);
+        let config = HlxbConfigHandler::read_helix_config(&mut cursor).unwrap();
+        assert_eq!(config.agents["assistant"].model, "gpt-4");
+    }
+
+    fn agent(name: &str, model: &str) -> AgentConfig {
+        AgentConfig {
+            name: name.to_string(),
+            model: model.to_string(),
+            role: "helper".to_string(),
+            temperature: None,
+            max_tokens: None,
+            capabilities: Vec::new(),
+            backstory: None,
+            tools: Vec::new(),
+            constraints: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_index_loads_single_entries() {
+        let mut config = HelixConfig::default();
+        config.agents.insert("researcher".to_string(), agent("researcher", "gpt-4"));
+        config.agents.insert("writer".to_string(), agent("writer", "claude-3"));
+        let mut database = HashMap::new();
+        database.insert("host".to_string(), Value::String("localhost".to_string()));
+        config.sections.insert("database".to_string(), database);
+        let mut cursor = Cursor::new(Vec::new());
+        HlxbConfigHandler::write_helix_config(&config, &mut cursor).unwrap();
+
+        let mut reader = HlxbReader::new(Cursor::new(cursor.get_ref().clone()));
+        let paths: Vec<String> = reader.read_index().unwrap().iter().map(IndexEntry::path).collect();
+        assert_eq!(paths, ["agents/researcher", "agents/writer", "sections/database"]);
+        let writer_offset = reader.read_index().unwrap()[1].offset as usize;
+        assert_eq!(reader.get_agent("researcher").unwrap().unwrap().model, "gpt-4");
+        assert!(reader.get_agent("editor").unwrap().is_none());
+        let section = reader.get_section("database").unwrap().unwrap();
+        assert_eq!(section["host"].as_string(), Some("localhost"));
+
+        // Damage the writer agent; the researcher is still readable on its own
+        let mut bytes = cursor.into_inner();
+        bytes[writer_offset] = 0x04;
+        let mut reader = HlxbReader::new(Cursor::new(bytes.clone()));
+        assert_eq!(reader.get_agent("researcher").unwrap().unwrap().name, "researcher");
+        assert!(reader.get_agent("writer").is_err());
+        bytes[writer_offset] = SectionType::Agents as u8;
+        let full = HlxbConfigHandler::read_helix_config(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(full.agents.len(), 2);
+        assert_eq!(full.sections.len(), 1);
+    }
+
+    #[test]
+    fn test_version_1_files_are_indexed_by_scanning() {
+        let mut agents = HashMap::new();
+        agents.insert("researcher".to_string(), agent("researcher", "gpt-4"));
+        agents.insert("writer".to_string(), agent("writer", "claude-3"));
+        let mut cursor = Cursor::new(Vec::new());
+        {
+            // Version 1 layout: no index offset, one section holding every agent
+            let mut writer = HlxbWriter::new(&mut cursor);
+            writer.writer.write_all(HLXB_MAGIC).unwrap();
+            writer.writer.write_all(&[1]).unwrap();
+            writer.writer.write_all(&0u64.to_le_bytes()).unwrap();
+            writer.writer.write_all(&1u32.to_le_bytes()).unwrap();
+            writer.write_section(SectionType::Agents, None, &agents).unwrap();
+        }
+        let mut reader = HlxbReader::new(Cursor::new(cursor.into_inner()));
+        let index = reader.read_index().unwrap();
+        assert_eq!(index.len(), 2);
+        assert_eq!(index[0].offset, index[1].offset);
+        assert_eq!(reader.get_agent("writer").unwrap().unwrap().model, "claude-3");
+        assert_eq!(reader.read_config().unwrap().agents.len(), 2);
+    }
+
+    #[test]
+    fn test_reads_version_1_fixture() {
+        // Written from `fixture.hlx` by the last version 1 writer; never regenerated
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/hlxb/config_v1.hlxb");
+        let bytes = std::fs::read(path).unwrap();
+        assert_eq!(bytes[4], 1);
+        let config = HlxbConfigHandler::read_helix_config(&mut Cursor::new(bytes.clone())).unwrap();
+        assert_eq!(config.agents.len(), 2);
+        assert_eq!(config.agents["researcher"].model, "gpt-4");
+        assert_eq!(config.agents["researcher"].temperature, Some(0.5));
+        assert_eq!(config.workflows["publish"].steps.len(), 2);
+        assert!(config.contexts.contains_key("production"));
+        let mut reader = HlxbReader::new(Cursor::new(bytes));
+        assert_eq!(reader.read_header().unwrap().version, 1);
+        assert_eq!(reader.get_agent("writer").unwrap().unwrap().model, "claude");
+        assert!(reader.get_workflow("publish").unwrap().is_some());
+    }
+
     #[test]
     fn test_compression_algorithms() {
         let test_data = b"Hello, World! This is a test string for compression algorithms.";
@@ -877,6 +1309,71 @@ mod tests {
 
         println!("✅ Compressed HLXB config roundtrip test passed");
     }
+
+    fn malformed(name: &str) -> Vec<u8> {
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/malformed")
+            .join(name);
+        std::fs::read(path).unwrap()
+    }
+
+    #[test]
+    fn test_malformed_configs_fail_with_typed_errors() {
+        use crate::dna::hel::limits::LimitError;
+        let read = |name| HlxbReader::new(Cursor::new(malformed(name))).read_config();
+        assert!(matches!(
+            read("config_huge_section.hlxb"),
+            Err(HlxError::LimitExceeded(LimitError::SectionTooLarge { .. }))
+        ));
+        assert!(matches!(
+            read("config_zip_bomb.hlxb"),
+            Err(HlxError::LimitExceeded(LimitError::DecompressionRatio { .. }))
+        ));
+        assert!(matches!(
+            read("config_deep_nesting.hlxb"),
+            Err(HlxError::LimitExceeded(LimitError::TooDeep { .. }))
+        ));
+        assert!(matches!(read("config_truncated.hlxb"), Err(HlxError::Io { .. })));
+        let mut reader = HlxbReader::new(Cursor::new(malformed("config_bad_index_offset.hlxb")));
+        assert!(matches!(reader.get_agent("agent"), Err(HlxError::ValidationError { .. })));
+
+        let tight = ReadLimits { max_section_size: 16, ..ReadLimits::default() };
+        let mut config = HelixConfig::default();
+        config.agents.insert("agent".to_string(), agent("agent", "gpt-4"));
+        let mut valid = Cursor::new(Vec::new());
+        HlxbConfigHandler::write_helix_config(&config, &mut valid).unwrap();
+        valid.set_position(0);
+        assert!(HlxbReader::new(&mut valid).with_limits(tight).read_config().is_err());
+        valid.set_position(0);
+        assert!(HlxbReader::new(&mut valid).read_config().is_ok());
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn fuzz_hlxb_reader_never_panics(bytes in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..512)) {
+            let _ = HlxbReader::new(Cursor::new(&bytes)).read_config();
+            let _ = HlxbReader::new(Cursor::new(&bytes)).get_agent("agent");
+        }
+
+        #[test]
+        fn fuzz_hlxb_reader_never_panics_on_mutations(
+            edits in proptest::collection::vec((proptest::prelude::any::<proptest::sample::Index>(), proptest::prelude::any::<u8>()), 1..8)
+        ) {
+            let mut config = HelixConfig::default();
+            config.agents.insert("agent".to_string(), agent("agent", "gpt-4"));
+            let mut cursor = Cursor::new(Vec::new());
+            HlxbConfigHandler::write_helix_config(&config, &mut cursor).unwrap();
+            let mut bytes = cursor.into_inner();
+            for (index, byte) in &edits {
+                let at = index.index(bytes.len());
+                bytes[at] = *byte;
+            }
+            let _ = HlxbReader::new(Cursor::new(&bytes)).read_config();
+            let _ = HlxbReader::new(Cursor::new(&bytes)).read_index().map(|index| index.len());
+        }
+    }
 }
 
 impl Default for HlxbConfig {
@@ -8,6 +8,7 @@ use std::io::{Write, Seek, SeekFrom};
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 pub use crate::dna::hel::error::HlxError;
+use crate::dna::hel::limits::ReadLimits;
 pub use crate::dna::atp::output::OutputConfig;
 pub use crate::dna::atp::output::DataWriter;
 
@@ -291,11 +292,18 @@ impl<W: Write + Seek> HlxcWriter<W> {
 /// HLXC Format Reader for preview functionality
 pub struct HlxcReader<R: std::io::Read + Seek> {
     reader: R,
+    limits: ReadLimits,
 }
 
 impl<R: std::io::Read + Seek> HlxcReader<R> {
     pub fn new(reader: R) -> Self {
-        Self { reader }
+        Self { reader, limits: ReadLimits::default() }
+    }
+
+    /// Bound the header and footer sizes and the nesting of their JSON
+    pub fn with_limits(mut self, limits: ReadLimits) -> Self {
+        self.limits = limits;
+        self
     }
 
     /// Read and validate the header
@@ -327,12 +335,15 @@ impl<R: std::io::Read + Seek> HlxcReader<R> {
         // Read header length
         let mut header_len_bytes = [0u8; 4];
         self.reader.read_exact(&mut header_len_bytes)?;
-        let header_len = u32::from_le_bytes(header_len_bytes) as usize;
+        let header_len = u32::from_le_bytes(header_len_bytes) as u64;
 
         // Read header JSON
-        let mut header_bytes = vec![0u8; header_len];
-        self.reader.read_exact(&mut header_bytes)?;
+        let header_bytes = self.limits.read_section("header", &mut self.reader, header_len)?;
         let header: HlxcHeader = HlxcHeader::from_json_bytes(&header_bytes)?;
+        self.limits.check_strings("schema", header.fields.len())?;
+        for value in header.metadata.iter().flat_map(|metadata| metadata.values()) {
+            self.limits.check_json_depth(value)?;
+        }
 
         Ok(header)
     }
@@ -372,16 +383,21 @@ impl<R: std::io::Read + Seek> HlxcReader<R> {
             return Ok(None); // No footer
         }
 
-        let footer_len = u32::from_le_bytes(footer_header[0..4].try_into().unwrap()) as usize;
+        let footer_len = u32::from_le_bytes(footer_header[0..4].try_into().unwrap()) as u64;
         eprintln!("DEBUG Reader: Footer length: {}", footer_len);
+        if footer_len > file_size - 8 {
+            return Err(HlxError::validation_error(
+                format!("Footer of {} bytes is larger than the file", footer_len),
+                "File may be truncated or corrupted"
+            ));
+        }
 
         // Now seek back to read the footer content
         // Footer content starts at (end - 8 - footer_len)
-        let content_start = file_size - 8 - footer_len as u64;
+        let content_start = file_size - 8 - footer_len;
         eprintln!("DEBUG Reader: Content should start at: {}", content_start);
         self.reader.seek(SeekFrom::Start(content_start))?;
-        let mut footer_bytes = vec![0u8; footer_len];
-        self.reader.read_exact(&mut footer_bytes)?;
+        let footer_bytes = self.limits.read_section("footer", &mut self.reader, footer_len)?;
 
         let footer_jsonl = String::from_utf8(footer_bytes)
             .map_err(|_| HlxError::validation_error("Invalid UTF-8 in footer", ""))?;
@@ -391,6 +407,10 @@ impl<R: std::io::Read + Seek> HlxcReader<R> {
             .filter(|line| !line.trim().is_empty())
             .map(|line| serde_json::from_str(line).unwrap_or(serde_json::Value::Null))
             .collect();
+        self.limits.check_strings("preview", rows.len())?;
+        for row in &rows {
+            self.limits.check_json_depth(row)?;
+        }
 
         Ok(Some(rows))
     }
@@ -424,7 +444,7 @@ impl<R: std::io::Read + Seek> HlxcReader<R> {
 
 #[cfg(test)]
 mod tests {
-
+    use super::*;
     use std::io::Cursor;
     use arrow::array::StringArray;
 
@@ -475,6 +495,35 @@ mod tests {
         assert_eq!(read_header.fields.len(), 1);
         assert_eq!(read_header.fields[0].name, "test");
     }
+
+    #[test]
+    fn test_oversized_lengths_fail_with_typed_errors() {
+        use crate::dna::hel::limits::LimitError;
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures/malformed/columnar_oversized.hlxc");
+        let bytes = std::fs::read(path).unwrap();
+        assert!(matches!(
+            HlxcReader::new(Cursor::new(&bytes)).read_header(),
+            Err(HlxError::LimitExceeded(LimitError::SectionTooLarge { .. }))
+        ));
+        assert!(matches!(
+            HlxcReader::new(Cursor::new(&bytes)).get_preview(),
+            Err(HlxError::ValidationError { .. })
+        ));
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(256))]
+
+        #[test]
+        fn fuzz_hlxc_reader_never_panics(
+            bytes in proptest::collection::vec(proptest::prelude::any::<u8>(), 0..256)
+        ) {
+            let bytes = [&HLXC_MAGIC[..], &bytes].concat();
+            let _ = HlxcReader::new(Cursor::new(&bytes)).read_header();
+            let _ = HlxcReader::new(Cursor::new(&bytes)).get_preview();
+        }
+    }
 }
 
 /// Create Arrow schema from field definitions
@@ -1,4 +1,7 @@
 pub mod helix_format;
+#[cfg(all(feature = "rkyv", feature = "memmap2"))]
+pub mod hlxa_format;
 pub mod hlx_config_format;
 pub mod hlxb_config_format;
-pub mod hlxc_format;
\ No newline at end of file
+pub mod hlxc_format;
+pub mod parquet_format;
\ No newline at end of file
@@ -0,0 +1,181 @@
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::{Compression, ZstdLevel};
+use parquet::file::properties::WriterProperties;
+pub use crate::dna::hel::error::HlxError;
+pub use crate::dna::atp::output::{CompressionAlgorithm, CompressionConfig, OutputConfig};
+pub use crate::dna::atp::output::DataWriter;
+
+/// Parquet codec for an output compression setting. Only zstd has levels;
+/// LZ4 is written as LZ4_RAW, the framing current readers expect.
+pub fn parquet_compression(config: &CompressionConfig) -> Result<Compression, HlxError> {
+    if !config.enabled {
+        return Ok(Compression::UNCOMPRESSED);
+    }
+    match config.algorithm {
+        CompressionAlgorithm::Zstd => {
+            let level = ZstdLevel::try_new(config.level as i32)
+                .map_err(|e| HlxError::validation_error(
+                    format!("Invalid zstd level {} for Parquet: {}", config.level, e),
+                    "Use a zstd level between 1 and 22",
+                ))?;
+            Ok(Compression::ZSTD(level))
+        }
+        CompressionAlgorithm::Lz4 => Ok(Compression::LZ4_RAW),
+        CompressionAlgorithm::Snappy => Ok(Compression::SNAPPY),
+    }
+}
+
+fn parquet_error(e: parquet::errors::ParquetError) -> HlxError {
+    HlxError::serialization_error(
+        format!("Failed to write Parquet: {}", e),
+        "Check that every batch has the schema of the first one",
+    )
+}
+
+/// Parquet Data Writer implementation for the OutputManager. The file's
+/// schema is taken from the first batch written to it.
+pub struct ParquetDataWriter {
+    writer: Option<ArrowWriter<std::fs::File>>,
+    config: OutputConfig,
+    batch_count: usize,
+}
+
+impl ParquetDataWriter {
+    pub fn new(config: OutputConfig) -> Self {
+        Self {
+            writer: None,
+            config,
+            batch_count: 0,
+        }
+    }
+
+    fn ensure_writer(&mut self, batch: &RecordBatch) -> Result<(), HlxError> {
+        if self.writer.is_none() {
+            let filename = format!("output_{:04}.parquet", self.batch_count);
+            let filepath = self.config.output_dir.join(filename);
+            std::fs::create_dir_all(&self.config.output_dir)?;
+
+            let properties = WriterProperties::builder()
+                .set_compression(parquet_compression(&self.config.compression)?)
+                .set_max_row_group_size(self.config.row_group_size.max(1))
+                .build();
+            let file = std::fs::File::create(filepath)?;
+            let writer = ArrowWriter::try_new(file, batch.schema(), Some(properties))
+                .map_err(parquet_error)?;
+
+            self.writer = Some(writer);
+        }
+        Ok(())
+    }
+}
+
+impl DataWriter for ParquetDataWriter {
+    fn write_batch(&mut self, batch: RecordBatch) -> Result<(), HlxError> {
+        self.ensure_writer(&batch)?;
+
+        if let Some(writer) = &mut self.writer {
+            writer.write(&batch).map_err(parquet_error)?;
+        }
+
+        Ok(())
+    }
+
+    fn finalize(&mut self) -> Result<(), HlxError> {
+        if let Some(writer) = self.writer.take() {
+            writer.close().map_err(parquet_error)?;
+            self.batch_count += 1;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dna::atp::output::{OutputFormat, OutputManager};
+    use crate::dna::atp::types::Value;
+    use arrow::array::{Array, BooleanArray, Float64Array, StringArray};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn config(dir: &TempDir, compression: CompressionConfig) -> OutputConfig {
+        OutputConfig {
+            output_dir: dir.path().to_path_buf(),
+            formats: vec![OutputFormat::Parquet],
+            compression,
+            batch_size: 4,
+            row_group_size: 5,
+            ..OutputConfig::default()
+        }
+    }
+
+    fn write_rows(config: OutputConfig, rows: usize) {
+        let mut manager = OutputManager::new(config);
+        for i in 0..rows {
+            let mut row = HashMap::new();
+            row.insert("name".to_string(), Value::String(format!("row-{}", i)));
+            row.insert("score".to_string(), Value::Number(i as f64 * 1.5));
+            row.insert("even".to_string(), Value::Bool(i % 2 == 0));
+            manager.add_row(row).unwrap();
+        }
+        manager.finalize_all().unwrap();
+    }
+
+    #[test]
+    fn test_parquet_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        write_rows(config(&dir, CompressionConfig::default()), 11);
+
+        let file = std::fs::File::open(dir.path().join("output_0000.parquet")).unwrap();
+        let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+        let metadata = builder.metadata().clone();
+        let schema = builder.schema().clone();
+        let batches: Vec<RecordBatch> = builder.build().unwrap().map(Result::unwrap).collect();
+
+        // Batches of 4 are regrouped into row groups of at most 5
+        let sizes: Vec<i64> = metadata.row_groups().iter().map(|group| group.num_rows()).collect();
+        assert_eq!(sizes, [5, 5, 1]);
+        assert!(matches!(metadata.row_group(0).column(0).compression(), Compression::ZSTD(_)));
+
+        let rows: usize = batches.iter().map(RecordBatch::num_rows).sum();
+        assert_eq!(rows, 11);
+        let batch = arrow::compute::concat_batches(&schema, &batches).unwrap();
+        let column = |name: &str| batch.column(schema.index_of(name).unwrap()).clone();
+        let names = column("name");
+        let names = names.as_any().downcast_ref::<StringArray>().unwrap();
+        let scores = column("score");
+        let scores = scores.as_any().downcast_ref::<Float64Array>().unwrap();
+        let even = column("even");
+        let even = even.as_any().downcast_ref::<BooleanArray>().unwrap();
+        for i in 0..11 {
+            assert_eq!(names.value(i), format!("row-{}", i));
+            assert_eq!(scores.value(i), i as f64 * 1.5);
+            assert_eq!(even.value(i), i % 2 == 0);
+        }
+    }
+
+    #[test]
+    fn test_parquet_compression_follows_config() {
+        let cases = [
+            (CompressionAlgorithm::Lz4, true, Compression::LZ4_RAW),
+            (CompressionAlgorithm::Snappy, true, Compression::SNAPPY),
+            (CompressionAlgorithm::Zstd, false, Compression::UNCOMPRESSED),
+        ];
+        for (algorithm, enabled, expected) in cases {
+            let dir = TempDir::new().unwrap();
+            let compression = CompressionConfig { enabled, algorithm, level: 4 };
+            write_rows(config(&dir, compression), 3);
+
+            let file = std::fs::File::open(dir.path().join("output_0000.parquet")).unwrap();
+            let builder = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+            assert_eq!(builder.metadata().row_group(0).column(0).compression(), expected);
+            let rows: usize = builder.build().unwrap().map(|batch| batch.unwrap().num_rows()).sum();
+            assert_eq!(rows, 3);
+        }
+
+        let bad_level = CompressionConfig { enabled: true, algorithm: CompressionAlgorithm::Zstd, level: 99 };
+        assert!(parquet_compression(&bad_level).is_err());
+    }
+}
@@ -17,10 +17,10 @@ fn t01_simple_addition() -> Result<()> {
     "#;
     let calc = Calculator::new();
     let result = calc.evaluate(src.trim())?;
-    assert_eq!(result.env["a"], 10);
-    assert_eq!(result.env["b"], 7);
-    assert_eq!(result.env["c"], 17);
-    assert_eq!(result.env["d"], 17);
+    assert_eq!(result.env["a"], 10.0);
+    assert_eq!(result.env["b"], 7.0);
+    assert_eq!(result.env["c"], 17.0);
+    assert_eq!(result.env["d"], 17.0);
     Ok(())
 }
 #[test]
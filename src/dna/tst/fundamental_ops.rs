@@ -17,6 +17,7 @@ mod tests {
             cookies,
             params,
             query,
+            hermetic: None,
         };
         let registry = OperatorRegistry::new_with_context(context).await.unwrap();
         let result = registry
@@ -67,6 +68,7 @@ mod tests {
             cookies: HashMap::new(),
             params: HashMap::new(),
             query: HashMap::new(),
+            hermetic: None,
         };
         let registry = OperatorRegistry::new_with_context(context).await.unwrap();
         let result = registry.execute("@request", r#"field="method""#).await.unwrap();
@@ -106,6 +108,7 @@ mod tests {
             cookies,
             params: HashMap::new(),
             query: HashMap::new(),
+            hermetic: None,
         };
         let registry = OperatorRegistry::new_with_context(context).await.unwrap();
         let result = registry.execute("@cookie", r#"name="theme""#).await.unwrap();
@@ -126,6 +129,7 @@ mod tests {
             cookies: HashMap::new(),
             params,
             query: HashMap::new(),
+            hermetic: None,
         };
         let registry = OperatorRegistry::new_with_context(context).await.unwrap();
         let result = registry.execute("@param", r#"name="id""#).await.unwrap();
@@ -146,6 +150,7 @@ mod tests {
             cookies: HashMap::new(),
             params: HashMap::new(),
             query,
+            hermetic: None,
         };
         let registry = OperatorRegistry::new_with_context(context).await.unwrap();
         let result = registry.execute("@query", r#"name="page""#).await.unwrap();
@@ -44,8 +44,10 @@ pub use crate::dna::atp::types::{
     TrainingSample, AlgorithmFormat,
 };
 pub use crate::dna::out::hlxb_config_format::{
-    HlxbWriter, HlxbReader, HlxbHeader, HLXB_MAGIC, HLXB_VERSION,
+    HlxbWriter, HlxbReader, HlxbHeader, IndexEntry, HLXB_MAGIC, HLXB_VERSION,
 };
+#[cfg(all(feature = "rkyv", feature = "memmap2"))]
+pub use crate::dna::out::hlxa_format::{HlxaFile, ArchivedHelixConfig, HLXA_MAGIC, HLXA_VERSION};
 pub use crate::dna::atp::ast::{
     HelixAst, Declaration, Expression, Statement, AgentDecl, WorkflowDecl, MemoryDecl,
     ContextDecl, CrewDecl, PipelineDecl,
@@ -96,21 +98,40 @@ type ConfigResult<T> = Result<T, NapiStringError>;
 #[cfg(not(feature = "js"))]
 type ConfigResult<T> = Result<T, String>;
 pub fn parse(source: &str) -> std::result::Result<HelixAst, ParseError> {
-    parse_with_locations(source).or_else(|_| parse_legacy(source))
+    parse_with_locations(source).or_else(|_| parse_legacy(source, None))
+}
+/// Parse without reading the process environment: `$VAR` and `@env`
+/// resolve only against `env`
+pub fn parse_hermetic(
+    source: &str,
+    env: &std::collections::BTreeMap<String, String>,
+) -> std::result::Result<HelixAst, Box<ParseError>> {
+    let env: std::collections::HashMap<String, String> = env
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect();
+    parse_with_locations_in(source, Some(&env))
+        .or_else(|_| parse_legacy(source, Some(&env)).map_err(Box::new))
 }
 pub fn parse_with_locations(source: &str) -> std::result::Result<HelixAst, ParseError> {
+    parse_with_locations_in(source, None).map_err(|e| *e)
+}
+fn parse_with_locations_in(
+    source: &str,
+    hermetic_env: Option<&std::collections::HashMap<String, String>>,
+) -> std::result::Result<HelixAst, Box<ParseError>> {
     use crate::dna::atp::lexer::{tokenize_with_locations, SourceMap};
     let tokens_with_loc = match tokenize_with_locations(source) {
         Ok(tokens) => tokens,
         Err(e) => {
-            return Err(ParseError {
+            return Err(Box::new(ParseError {
                 message: format!("Lexer error: {}", e),
                 location: None,
                 token_index: 0,
                 expected: None,
                 found: String::new(),
                 context: String::new(),
-            });
+            }));
         }
     };
     let source_map = SourceMap {
@@ -118,21 +139,27 @@ pub fn parse_with_locations(source: &str) -> std::result::Result<HelixAst, Parse
         source: source.to_string(),
     };
     let mut parser = Parser::new_with_source_map(source_map);
+    if let Some(env) = hermetic_env {
+        parser.set_hermetic_env(env.clone());
+    }
     match parser.parse() {
         Ok(ast) => Ok(ast),
         Err(msg) => {
-            Err(ParseError {
+            Err(Box::new(ParseError {
                 message: msg,
                 location: None,
                 token_index: 0,
                 expected: None,
                 found: String::new(),
                 context: String::new(),
-            })
+            }))
         }
     }
 }
-fn parse_legacy(source: &str) -> std::result::Result<HelixAst, ParseError> {
+fn parse_legacy(
+    source: &str,
+    hermetic_env: Option<&std::collections::HashMap<String, String>>,
+) -> std::result::Result<HelixAst, ParseError> {
     let tokens = match crate::dna::atp::lexer::tokenize(source) {
         Ok(tokens) => tokens,
         Err(e) => {
@@ -147,6 +174,9 @@ fn parse_legacy(source: &str) -> std::result::Result<HelixAst, ParseError> {
         }
     };
     let mut parser = Parser::new(tokens);
+    if let Some(env) = hermetic_env {
+        parser.set_hermetic_env(env.clone());
+    }
     match parser.parse() {
         Ok(ast) => Ok(ast),
         Err(msg) => {